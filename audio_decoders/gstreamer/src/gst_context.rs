@@ -2,8 +2,8 @@
 mod tests;
 
 use core::audio_system::audio::{
-    AudioCodec, EncodedAudioBuffer, EncodedAudioHeader, RawAudioBuffer, RawAudioFormat,
-    TimestampedRawAudioBuffer,
+    AudioCodec, ChannelLayout, EncodedAudioBuffer, EncodedAudioHeader, RawAudioBuffer,
+    RawAudioFormat, TimestampedRawAudioBuffer,
 };
 use core::util::ClockTime;
 
@@ -31,10 +31,15 @@ impl GstContext {
     pub fn new(audio_info: EncodedAudioHeader) -> Self {
         let pipeline = gst::Pipeline::new(Some("gst_audio_decoder_pipeline"));
 
-        let src_caps = gst::Caps::builder(mime_from_codec(audio_info.codec))
+        let mut src_caps = gst::Caps::builder(mime_from_codec(audio_info.codec))
             .field("rate", audio_info.sample_rate)
-            .field("channels", 1)
-            .build();
+            .field("channels", i32::from(audio_info.channels));
+        if audio_info.codec == AudioCodec::AacLc {
+            src_caps = src_caps
+                .field("mpegversion", 4)
+                .field("stream-format", "adts");
+        }
+        let src_caps = src_caps.build();
         let src = gst_app::AppSrc::builder()
             .name("src")
             .caps(&src_caps)
@@ -56,9 +61,13 @@ impl GstContext {
             .build()
             .unwrap();
 
-        let sink_caps = gst::Caps::builder("audio/x-raw")
-            .field("channels", 1)
-            .build();
+        let mut sink_caps = gst::Caps::builder("audio/x-raw")
+            .field("channels", i32::from(audio_info.channels))
+            .field("layout", "interleaved");
+        if let Some(mask) = channel_mask_from_layout(audio_info.channel_layout) {
+            sink_caps = sink_caps.field("channel-mask", gst::Bitmask::new(mask));
+        }
+        let sink_caps = sink_caps.build();
         let sink = gst_app::AppSink::builder()
             .name("sink")
             .caps(&sink_caps)
@@ -139,24 +148,55 @@ impl GstContext {
 fn mime_from_codec(codec: AudioCodec) -> &'static str {
     match codec {
         AudioCodec::Opus => "audio/x-opus",
-        AudioCodec::Unspecified => panic!("Unsupported audio codec"),
+        AudioCodec::Flac => "audio/x-flac",
+        AudioCodec::AacLc => "audio/mpeg",
+        AudioCodec::Vorbis => "audio/x-vorbis",
+        AudioCodec::Mulaw => "audio/x-mulaw",
+        AudioCodec::Alaw => "audio/x-alaw",
+        _ => panic!("Unsupported audio codec"),
     }
 }
 
 fn parser_name_from_codec(codec: AudioCodec) -> &'static str {
     match codec {
         AudioCodec::Opus => "opusparse",
-        AudioCodec::Unspecified => panic!("Unsupported audio codec"),
+        AudioCodec::Flac => "flacparse",
+        AudioCodec::AacLc => "aacparse",
+        AudioCodec::Vorbis => "vorbisparse",
+        // G.711 is a plain stream of bytes, so there is nothing to parse.
+        AudioCodec::Mulaw | AudioCodec::Alaw => "identity",
+        _ => panic!("Unsupported audio codec"),
     }
 }
 
 fn decoder_name_from_codec(codec: AudioCodec) -> &'static str {
     match codec {
         AudioCodec::Opus => "opusdec",
-        AudioCodec::Unspecified => panic!("Unsupported audio codec"),
+        AudioCodec::Flac => "flacdec",
+        AudioCodec::AacLc => "avdec_aac",
+        AudioCodec::Vorbis => "vorbisdec",
+        AudioCodec::Mulaw => "mulawdec",
+        AudioCodec::Alaw => "alawdec",
+        _ => panic!("Unsupported audio codec"),
     }
 }
 
+fn channel_mask_from_layout(layout: ChannelLayout) -> Option<u64> {
+    match layout {
+        ChannelLayout::Surround => Some(0x7),
+        ChannelLayout::Quad => Some(0x33),
+        ChannelLayout::Surround51 => Some(0x3f),
+        ChannelLayout::Surround71 => Some(0xc3f),
+        _ => None,
+    }
+}
+
+fn raw_audio_channels_from_caps(caps: &gst::CapsRef) -> Option<u8> {
+    caps.iter()
+        .find_map(|structure| structure.get::<i32>("channels").ok())
+        .and_then(|channels| u8::try_from(channels).ok())
+}
+
 fn raw_audio_format_from_caps(caps: &gst::CapsRef) -> Option<RawAudioFormat> {
     for structure in caps.iter() {
         let Ok(str_format) = structure.get::<&str>("format") else {
@@ -186,11 +226,17 @@ fn raw_audio_buffer_from_sample(
 ) -> Option<RawAudioBuffer> {
     let caps = sample.caps()?;
     let format = raw_audio_format_from_caps(caps)?;
+    let channels = raw_audio_channels_from_caps(caps).unwrap_or(audio_info.channels);
 
     let buffer = sample.buffer()?;
     let data = buffer.map_readable().ok()?.as_slice().to_vec();
 
-    Some(RawAudioBuffer::new(data, format, audio_info.sample_rate))
+    let mut raw = RawAudioBuffer::new(data, format, channels, audio_info.sample_rate);
+    if channels == audio_info.channels {
+        raw = raw.with_channel_layout(audio_info.channel_layout);
+    }
+
+    Some(raw)
 }
 
 fn timestamps_from_sample(sample: &gst::Sample) -> Option<ClockTime> {
@@ -1,8 +1,20 @@
 use super::*;
 
+use core::audio_system::audio::EncodedAudioFlags;
+
 const OPUS_DATA: &'static str = include_str!("test.opus.data");
 const RAW_DATA: &'static str = include_str!("test.raw.data");
 
+// The fixtures below encode a mono 440 Hz tone which is regenerated by `tone()`.
+const FLAC_DATA: &str = include_str!("test.flac.data");
+const AAC_DATA: &str = include_str!("test.aac.data");
+const VORBIS_DATA: &str = include_str!("test.vorbis.data");
+const MULAW_DATA: &str = include_str!("test.mulaw.data");
+const ALAW_DATA: &str = include_str!("test.alaw.data");
+
+const TONE_FREQUENCY: f64 = 440.0;
+const TONE_AMPLITUDE: f64 = 16384.0;
+
 #[test]
 fn test_decode_opus() {
     gst::init().unwrap();
@@ -10,6 +22,8 @@ fn test_decode_opus() {
     let header = EncodedAudioHeader {
         codec: AudioCodec::Opus,
         sample_rate: 48000,
+        channels: 1,
+        channel_layout: ChannelLayout::Mono,
     };
 
     let ctx = GstContext::new(header);
@@ -18,6 +32,9 @@ fn test_decode_opus() {
     for data in opus_buffers {
         let encoded_audio = EncodedAudioBuffer {
             header,
+            sequence_number: None,
+            flags: EncodedAudioFlags::default(),
+            extensions: Vec::new(),
             start_ts: Some(ClockTime::from_secs(10)),
             data,
         };
@@ -39,3 +56,139 @@ fn test_decode_opus() {
     let decoded_audio_json = serde_json::to_string(&decoded_audio).unwrap();
     assert_eq!(decoded_audio_json, RAW_DATA);
 }
+
+fn tone(sample_rate: u32, no_samples: usize) -> Vec<f64> {
+    (0..no_samples)
+        .map(|i| {
+            let phase = 2.0 * std::f64::consts::PI * TONE_FREQUENCY * i as f64;
+            (TONE_AMPLITUDE * (phase / f64::from(sample_rate)).sin()).round()
+        })
+        .collect()
+}
+
+fn to_samples(data: &[u8], format: RawAudioFormat) -> Vec<f64> {
+    match format {
+        RawAudioFormat::S16LE => data
+            .chunks_exact(2)
+            .map(|s| f64::from(i16::from_le_bytes([s[0], s[1]])))
+            .collect(),
+        RawAudioFormat::S32LE => data
+            .chunks_exact(4)
+            .map(|s| f64::from(i32::from_le_bytes([s[0], s[1], s[2], s[3]])) / 65536.0)
+            .collect(),
+        RawAudioFormat::F32LE => data
+            .chunks_exact(4)
+            .map(|s| f64::from(f32::from_le_bytes([s[0], s[1], s[2], s[3]])) * 32768.0)
+            .collect(),
+        _ => panic!("Unexpected raw audio format: {format:?}"),
+    }
+}
+
+fn decode(codec: AudioCodec, sample_rate: u32, fixture: &str) -> Vec<f64> {
+    gst::init().unwrap();
+
+    let header = EncodedAudioHeader {
+        codec,
+        sample_rate,
+        channels: 1,
+        channel_layout: ChannelLayout::Mono,
+    };
+
+    let ctx = GstContext::new(header);
+
+    let encoded_buffers: Vec<Vec<u8>> = serde_json::from_str(fixture).unwrap();
+    for data in encoded_buffers {
+        let encoded_audio = EncodedAudioBuffer {
+            header,
+            sequence_number: None,
+            flags: EncodedAudioFlags::default(),
+            extensions: Vec::new(),
+            start_ts: Some(ClockTime::from_secs(10)),
+            data,
+        };
+        ctx.push(encoded_audio);
+    }
+    ctx.push_eos();
+
+    let mut decoded_samples = vec![];
+    while !ctx.is_eos() {
+        if ctx.is_playing_failed() {
+            break;
+        }
+
+        let Some(audio) = ctx.pull() else { continue };
+
+        assert_eq!(audio.channels(), 1);
+        assert_eq!(audio.sample_rate(), sample_rate);
+        decoded_samples.extend(to_samples(audio.as_slice(), audio.format()));
+    }
+
+    decoded_samples
+}
+
+// Lossy codecs add priming and padding, so only the tone itself is compared.
+fn assert_tone(samples: &[f64], sample_rate: u32) {
+    const SILENCE_THRESHOLD: f64 = TONE_AMPLITUDE / 20.0;
+
+    let first = samples
+        .iter()
+        .position(|s| s.abs() > SILENCE_THRESHOLD)
+        .unwrap();
+    let last = samples
+        .iter()
+        .rposition(|s| s.abs() > SILENCE_THRESHOLD)
+        .unwrap();
+    let samples = &samples[first..=last];
+    let no_samples = samples.len() as f64;
+
+    let (mut re, mut im) = (0.0, 0.0);
+    for (i, sample) in samples.iter().enumerate() {
+        let phase = 2.0 * std::f64::consts::PI * TONE_FREQUENCY * i as f64 / f64::from(sample_rate);
+        re += sample * phase.cos();
+        im += sample * phase.sin();
+    }
+    let tone_power = 2.0 * (re * re + im * im) / (no_samples * no_samples);
+    let power = samples.iter().map(|s| s * s).sum::<f64>() / no_samples;
+
+    assert!(tone_power / power > 0.9);
+    assert!((power.sqrt() - TONE_AMPLITUDE / 2f64.sqrt()).abs() < 0.2 * TONE_AMPLITUDE);
+}
+
+#[test]
+fn test_decode_flac() {
+    let decoded_samples = decode(AudioCodec::Flac, 48000, FLAC_DATA);
+
+    assert_eq!(decoded_samples, tone(48000, 12000));
+}
+
+#[test]
+fn test_decode_aac_lc() {
+    let decoded_samples = decode(AudioCodec::AacLc, 48000, AAC_DATA);
+
+    assert_tone(&decoded_samples, 48000);
+}
+
+#[test]
+fn test_decode_vorbis() {
+    let decoded_samples = decode(AudioCodec::Vorbis, 48000, VORBIS_DATA);
+
+    assert_tone(&decoded_samples, 48000);
+}
+
+#[test]
+fn test_decode_g711() {
+    const TOLERANCE: f64 = 600.0;
+
+    for (codec, fixture) in [
+        (AudioCodec::Mulaw, MULAW_DATA),
+        (AudioCodec::Alaw, ALAW_DATA),
+    ] {
+        let decoded_samples = decode(codec, 8000, fixture);
+        let expected_samples = tone(8000, 2000);
+
+        assert_eq!(decoded_samples.len(), expected_samples.len());
+        for (decoded, expected) in decoded_samples.into_iter().zip(expected_samples) {
+            assert!((decoded - expected).abs() <= TOLERANCE);
+        }
+    }
+}
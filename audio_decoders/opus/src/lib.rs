@@ -0,0 +1,211 @@
+#[cfg(test)]
+mod tests;
+
+mod opus_context;
+
+pub use opus_context::OpusContext;
+
+use core::audio_system::audio::{
+    EncodedAudioBuffer, EncodedAudioHeader, TimestampedRawAudioBuffer,
+};
+use core::audio_system::element::{AudioFilter, AudioSink, AudioSource, AudioSystemElementMessage};
+use core::audio_system::pipeline::audio_decoder::{AudioDecoder, AudioDecoderInfo};
+use core::error;
+use core::mueue::*;
+use core::util::{ClockTime, Element, ElementBuilder, Runnable};
+
+/// Longer losses are left to the loss concealer of the audio system.
+const MAX_CONCEALED_PACKETS: u32 = 5;
+
+pub struct OpusDecoder {
+    send: MessageSender<AudioSystemElementMessage>,
+
+    input: Option<MessageReceiver<EncodedAudioBuffer>>,
+    output: Option<MessageSender<TimestampedRawAudioBuffer>>,
+
+    context: Option<OpusContext>,
+
+    expected_sequence_number: Option<u32>,
+    expected_ts: Option<ClockTime>,
+}
+
+impl OpusDecoder {
+    pub fn new(send: MessageSender<AudioSystemElementMessage>) -> Self {
+        Self {
+            send,
+
+            input: None,
+            output: None,
+
+            context: None,
+
+            expected_sequence_number: None,
+            expected_ts: None,
+        }
+    }
+
+    fn update_audio_info(&mut self, info: EncodedAudioHeader) -> error::Result<()> {
+        if self.context.as_ref().map(OpusContext::audio_info) == Some(info) {
+            return Ok(());
+        }
+
+        self.context = None;
+        self.expected_sequence_number = None;
+        self.expected_ts = None;
+        self.context = Some(OpusContext::new(info)?);
+
+        Ok(())
+    }
+
+    fn decode(&mut self, audio: EncodedAudioBuffer) -> error::Result<()> {
+        self.update_audio_info(audio.header)?;
+
+        if !audio.flags.discontinuity {
+            self.conceal_lost_packets(audio.sequence_number)?;
+        }
+        self.expected_sequence_number = audio.sequence_number.map(|seq| seq.wrapping_add(1));
+
+        let Some(context) = self.context.as_mut() else {
+            return Ok(());
+        };
+        let audio = context.decode(audio)?;
+        self.release(audio);
+
+        Ok(())
+    }
+
+    // The decoder predicts the missing packets better than the generic loss concealer.
+    fn conceal_lost_packets(&mut self, sequence_number: Option<u32>) -> error::Result<()> {
+        let (Some(sequence_number), Some(expected_sequence_number)) =
+            (sequence_number, self.expected_sequence_number)
+        else {
+            return Ok(());
+        };
+
+        let no_lost_packets = sequence_number.wrapping_sub(expected_sequence_number);
+        if no_lost_packets == 0 || no_lost_packets > MAX_CONCEALED_PACKETS {
+            return Ok(());
+        }
+
+        for _ in 0..no_lost_packets {
+            let Some(context) = self.context.as_mut() else {
+                return Ok(());
+            };
+            let audio = context.conceal(self.expected_ts)?;
+
+            let _ = self.send.send(AudioSystemElementMessage::LossConcealed {
+                duration: audio.duration(),
+            });
+            self.release(audio);
+        }
+
+        Ok(())
+    }
+
+    fn release(&mut self, audio: TimestampedRawAudioBuffer) {
+        self.expected_ts = audio.stop();
+
+        if let Some(output) = self.output.as_ref() {
+            let _ = output.send(audio);
+        }
+    }
+}
+
+impl Runnable for OpusDecoder {
+    fn update(&mut self) -> error::Result<()> {
+        let Some(input) = self.input.clone() else {
+            return Ok(());
+        };
+
+        while let Some(audio) = input.recv() {
+            if let Err(err) = self.decode(audio) {
+                let _ = self.send.send(AudioSystemElementMessage::Error(err));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn on_stop(&mut self) {
+        if let Some(context) = self.context.as_mut() {
+            context.reset();
+        }
+        self.expected_sequence_number = None;
+        self.expected_ts = None;
+    }
+}
+
+impl Element for OpusDecoder {
+    type Message = AudioSystemElementMessage;
+
+    fn sender(&self) -> MessageSender<Self::Message> {
+        self.send.clone()
+    }
+
+    fn connect(&mut self, send: MessageSender<Self::Message>) {
+        self.send = send;
+    }
+}
+
+impl AudioSink<EncodedAudioBuffer> for OpusDecoder {
+    fn input(&self) -> Option<MessageReceiver<EncodedAudioBuffer>> {
+        self.input.clone()
+    }
+
+    fn set_input(&mut self, input: MessageReceiver<EncodedAudioBuffer>) {
+        self.input = Some(input);
+    }
+
+    fn unset_input(&mut self) {
+        self.input = None;
+    }
+}
+
+impl AudioSource<TimestampedRawAudioBuffer> for OpusDecoder {
+    fn output(&self) -> Option<MessageSender<TimestampedRawAudioBuffer>> {
+        self.output.clone()
+    }
+
+    fn set_output(&mut self, output: MessageSender<TimestampedRawAudioBuffer>) {
+        self.output = Some(output);
+    }
+
+    fn unset_output(&mut self) {
+        self.output = None;
+    }
+}
+
+impl AudioFilter<EncodedAudioBuffer, TimestampedRawAudioBuffer> for OpusDecoder {}
+
+impl AudioDecoder for OpusDecoder {
+    fn info(&self) -> AudioDecoderInfo {
+        AudioDecoderInfo {
+            name: "Opus Audio Decoder".to_string(),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct OpusDecoderBuilder {
+    send: Option<MessageSender<AudioSystemElementMessage>>,
+}
+
+impl OpusDecoderBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl ElementBuilder for OpusDecoderBuilder {
+    type Element = dyn AudioDecoder;
+
+    fn set_sender(&mut self, send: MessageSender<AudioSystemElementMessage>) {
+        self.send = Some(send);
+    }
+
+    fn build(self: Box<Self>) -> error::Result<Box<Self::Element>> {
+        let send = self.send.expect("A sender wasn't provided");
+
+        Ok(Box::new(OpusDecoder::new(send)))
+    }
+}
@@ -0,0 +1,110 @@
+#[cfg(test)]
+mod tests;
+
+use core::audio_system::audio::{
+    AudioCodec, EncodedAudioBuffer, EncodedAudioHeader, RawAudioBuffer, RawAudioFormat,
+    TimestampedRawAudioBuffer,
+};
+use core::error;
+use core::util::ClockTime;
+
+const SUPPORTED_SAMPLE_RATES: [u32; 5] = [8000, 12000, 16000, 24000, 48000];
+const FALLBACK_SAMPLE_RATE: u32 = 48000;
+const MAX_CHANNELS: u8 = 2;
+
+pub struct OpusContext {
+    audio_info: EncodedAudioHeader,
+    sample_rate: u32,
+
+    decoder: opus_decoder::OpusDecoder,
+    pcm: Vec<i16>,
+}
+
+impl OpusContext {
+    pub fn new(audio_info: EncodedAudioHeader) -> error::Result<Self> {
+        if audio_info.codec != AudioCodec::Opus {
+            return Err(error::Error::AudioDecodingFailed(format!(
+                "Unsupported audio codec: {:?}",
+                audio_info.codec
+            )));
+        }
+        if !(1..=MAX_CHANNELS).contains(&audio_info.channels) {
+            return Err(error::Error::AudioDecodingFailed(format!(
+                "Unsupported number of channels: {}",
+                audio_info.channels
+            )));
+        }
+
+        // Opus can be decoded only at a handful of rates, the rest is up to the resizer.
+        let sample_rate = if SUPPORTED_SAMPLE_RATES.contains(&audio_info.sample_rate) {
+            audio_info.sample_rate
+        } else {
+            FALLBACK_SAMPLE_RATE
+        };
+
+        let channels = usize::from(audio_info.channels);
+        let decoder = opus_decoder::OpusDecoder::new(sample_rate, channels).map_err(to_error)?;
+        let pcm = vec![0; decoder.max_frame_size_per_channel() * channels];
+
+        Ok(Self {
+            audio_info,
+            sample_rate,
+
+            decoder,
+            pcm,
+        })
+    }
+
+    pub fn audio_info(&self) -> EncodedAudioHeader {
+        self.audio_info
+    }
+
+    pub fn decode(
+        &mut self,
+        buffer: EncodedAudioBuffer,
+    ) -> error::Result<TimestampedRawAudioBuffer> {
+        self.decode_packet(&buffer.data, buffer.start_ts)
+    }
+
+    /// Synthesizes the audio of a lost packet from the state of the decoder.
+    pub fn conceal(
+        &mut self,
+        start_ts: Option<ClockTime>,
+    ) -> error::Result<TimestampedRawAudioBuffer> {
+        self.decode_packet(&[], start_ts)
+    }
+
+    fn decode_packet(
+        &mut self,
+        packet: &[u8],
+        start_ts: Option<ClockTime>,
+    ) -> error::Result<TimestampedRawAudioBuffer> {
+        let no_frames = self
+            .decoder
+            .decode(packet, &mut self.pcm, false)
+            .map_err(to_error)?;
+        let no_samples = no_frames * usize::from(self.audio_info.channels);
+
+        let data = self.pcm[..no_samples]
+            .iter()
+            .flat_map(|sample| sample.to_le_bytes())
+            .collect();
+        let raw = RawAudioBuffer::new(
+            data,
+            RawAudioFormat::S16LE,
+            self.audio_info.channels,
+            self.sample_rate,
+        )
+        .with_channel_layout(self.audio_info.channel_layout);
+
+        Ok(TimestampedRawAudioBuffer::new(raw, start_ts))
+    }
+
+    pub fn reset(&mut self) {
+        self.decoder.reset();
+    }
+}
+
+fn to_error(err: opus_decoder::OpusError) -> error::Error {
+    error::Error::AudioDecodingFailed(err.to_string())
+}
@@ -0,0 +1,123 @@
+use super::*;
+
+use core::audio_system::audio::{ChannelLayout, EncodedAudioFlags};
+use core::util::ClockTime;
+
+const OPUS_DATA: &str = include_str!("../../../gstreamer/src/gst_context/test.opus.data");
+const RAW_DATA: &str = include_str!("../../../gstreamer/src/gst_context/test.raw.data");
+
+const TOLERANCE: i32 = 2;
+
+fn to_samples(data: &[u8]) -> Vec<i16> {
+    data.chunks_exact(2)
+        .map(|sample| i16::from_le_bytes([sample[0], sample[1]]))
+        .collect()
+}
+
+#[test]
+fn test_decode_opus() {
+    // The reference was decoded by GStreamer into interleaved stereo.
+    let header = EncodedAudioHeader {
+        codec: AudioCodec::Opus,
+        sample_rate: 48000,
+        channels: 2,
+        channel_layout: ChannelLayout::Stereo,
+    };
+
+    let mut ctx = OpusContext::new(header).unwrap();
+
+    let opus_buffers: Vec<Vec<u8>> = serde_json::from_str(OPUS_DATA).unwrap();
+    let mut decoded_audio = vec![];
+    for data in opus_buffers {
+        let encoded_audio = EncodedAudioBuffer {
+            header,
+            sequence_number: None,
+            flags: EncodedAudioFlags::default(),
+            extensions: Vec::new(),
+            start_ts: Some(ClockTime::from_secs(10)),
+            data,
+        };
+        let audio = ctx.decode(encoded_audio).unwrap();
+
+        assert_eq!(audio.format(), RawAudioFormat::S16LE);
+        assert_eq!(audio.channels(), 2);
+        assert_eq!(audio.start(), Some(ClockTime::from_secs(10)));
+
+        decoded_audio.extend_from_slice(audio.as_slice());
+    }
+
+    let raw_audio: Vec<u8> = serde_json::from_str(RAW_DATA).unwrap();
+    let decoded_samples = to_samples(&decoded_audio);
+    let raw_samples = to_samples(&raw_audio);
+
+    assert_eq!(decoded_samples.len(), raw_samples.len());
+    for (decoded, raw) in decoded_samples.into_iter().zip(raw_samples) {
+        assert!((i32::from(decoded) - i32::from(raw)).abs() <= TOLERANCE);
+    }
+}
+
+#[test]
+fn test_unsupported_header() {
+    let header = EncodedAudioHeader {
+        codec: AudioCodec::Opus,
+        sample_rate: 48000,
+        channels: 6,
+        channel_layout: ChannelLayout::Surround51,
+    };
+
+    assert!(OpusContext::new(header).is_err());
+}
+
+#[test]
+fn test_fallback_sample_rate() {
+    let header = EncodedAudioHeader {
+        codec: AudioCodec::Opus,
+        sample_rate: 44100,
+        channels: 1,
+        channel_layout: ChannelLayout::Mono,
+    };
+
+    let mut ctx = OpusContext::new(header).unwrap();
+
+    let opus_buffers: Vec<Vec<u8>> = serde_json::from_str(OPUS_DATA).unwrap();
+    let encoded_audio = EncodedAudioBuffer {
+        header,
+        sequence_number: None,
+        flags: EncodedAudioFlags::default(),
+        extensions: Vec::new(),
+        start_ts: None,
+        data: opus_buffers[0].clone(),
+    };
+    let audio = ctx.decode(encoded_audio).unwrap();
+
+    assert_eq!(audio.sample_rate(), FALLBACK_SAMPLE_RATE);
+    assert_eq!(audio.no_samples(), 960);
+}
+
+#[test]
+fn test_conceal() {
+    let header = EncodedAudioHeader {
+        codec: AudioCodec::Opus,
+        sample_rate: 48000,
+        channels: 2,
+        channel_layout: ChannelLayout::Stereo,
+    };
+
+    let mut ctx = OpusContext::new(header).unwrap();
+
+    let opus_buffers: Vec<Vec<u8>> = serde_json::from_str(OPUS_DATA).unwrap();
+    let encoded_audio = EncodedAudioBuffer {
+        header,
+        sequence_number: None,
+        flags: EncodedAudioFlags::default(),
+        extensions: Vec::new(),
+        start_ts: Some(ClockTime::ZERO),
+        data: opus_buffers[0].clone(),
+    };
+    let audio = ctx.decode(encoded_audio).unwrap();
+
+    let concealed_audio = ctx.conceal(audio.stop()).unwrap();
+    assert_eq!(concealed_audio.no_samples(), audio.no_samples());
+    assert_eq!(concealed_audio.channels(), 2);
+    assert_eq!(concealed_audio.start(), audio.stop());
+}
@@ -0,0 +1,42 @@
+use super::*;
+
+use core::audio_system::audio::AudioCodec;
+use core::test_util::encoded_audio;
+
+const OPUS_DATA: &str = include_str!("../../gstreamer/src/gst_context/test.opus.data");
+
+fn opus_packet(data: Vec<u8>, sequence_number: u32) -> EncodedAudioBuffer {
+    EncodedAudioBuffer {
+        sequence_number: Some(sequence_number),
+        start_ts: Some(ClockTime::from_millis(20) * u64::from(sequence_number)),
+        ..encoded_audio(AudioCodec::Opus, 2, data)
+    }
+}
+
+#[test]
+fn test_conceal_lost_packets() {
+    let (send, notification_recv) = unidirectional_queue();
+    let mut dec = OpusDecoder::new(send);
+
+    let in_send = dec.create_input();
+    let out_recv = dec.create_output();
+
+    let opus_buffers: Vec<Vec<u8>> = serde_json::from_str(OPUS_DATA).unwrap();
+    let _ = in_send.send(opus_packet(opus_buffers[0].clone(), 0));
+    let _ = in_send.send(opus_packet(opus_buffers[3].clone(), 3));
+    let _ = dec.update();
+
+    let start_tss: Vec<_> = out_recv.iter().map(|audio| audio.start()).collect();
+    assert_eq!(
+        start_tss,
+        (0..4)
+            .map(|no_packet| Some(ClockTime::from_millis(20) * no_packet))
+            .collect::<Vec<_>>()
+    );
+
+    let no_concealments = notification_recv
+        .iter()
+        .filter(|msg| matches!(msg, AudioSystemElementMessage::LossConcealed { .. }))
+        .count();
+    assert_eq!(no_concealments, 2);
+}
@@ -1,3 +1,6 @@
+#[cfg(test)]
+mod tests;
+
 use mueue::*;
 
 use crate::{
@@ -5,8 +8,11 @@ use crate::{
     util::{vec_truncate_front, ClockTime},
 };
 
-const NO_AUDIO_HEADER_BYTES: usize = 5;
-const NO_CLOCK_TIME_BYTES: usize = 8;
+/// Set in the first byte of every muxed audio header except version 0.
+const MUXED_AUDIO_VERSION_MARKER: u8 = 0x80;
+pub const MUXED_AUDIO_VERSION: u8 = 1;
+
+const NO_EXTENSION_HEADER_BYTES: usize = 3;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct MuxedAudioBuffer(pub Vec<u8>);
@@ -18,6 +24,53 @@ pub enum AudioCodec {
     #[default]
     Unspecified,
     Opus,
+
+    PcmU8,
+
+    PcmS16LE,
+    PcmS16BE,
+
+    PcmS24LE,
+    PcmS24BE,
+
+    PcmS32LE,
+    PcmS32BE,
+
+    PcmF32LE,
+    PcmF32BE,
+
+    Flac,
+    AacLc,
+    Vorbis,
+
+    Mulaw,
+    Alaw,
+}
+
+impl AudioCodec {
+    /// The sample format of the PCM codecs, which are sent without compression.
+    pub const fn raw_audio_format(self) -> Option<RawAudioFormat> {
+        use AudioCodec::*;
+
+        let format = match self {
+            PcmU8 => RawAudioFormat::U8,
+            PcmS16LE => RawAudioFormat::S16LE,
+            PcmS16BE => RawAudioFormat::S16BE,
+            PcmS24LE => RawAudioFormat::S24LE,
+            PcmS24BE => RawAudioFormat::S24BE,
+            PcmS32LE => RawAudioFormat::S32LE,
+            PcmS32BE => RawAudioFormat::S32BE,
+            PcmF32LE => RawAudioFormat::F32LE,
+            PcmF32BE => RawAudioFormat::F32BE,
+            Unspecified | Opus | Flac | AacLc | Vorbis | Mulaw | Alaw => return None,
+        };
+
+        Some(format)
+    }
+
+    pub const fn is_pcm(self) -> bool {
+        self.raw_audio_format().is_some()
+    }
 }
 
 impl TryFrom<u8> for AudioCodec {
@@ -27,6 +80,20 @@ impl TryFrom<u8> for AudioCodec {
         let var = match value {
             0 => Self::Unspecified,
             1 => Self::Opus,
+            2 => Self::PcmU8,
+            3 => Self::PcmS16LE,
+            4 => Self::PcmS16BE,
+            5 => Self::PcmS24LE,
+            6 => Self::PcmS24BE,
+            7 => Self::PcmS32LE,
+            8 => Self::PcmS32BE,
+            9 => Self::PcmF32LE,
+            10 => Self::PcmF32BE,
+            11 => Self::Flac,
+            12 => Self::AacLc,
+            13 => Self::Vorbis,
+            14 => Self::Mulaw,
+            15 => Self::Alaw,
             _ => return Err(error::Error::IntToEnumCastFailed),
         };
         debug_assert_eq!(var as u8, value);
@@ -43,67 +110,332 @@ impl TryFrom<&u8> for AudioCodec {
     }
 }
 
+#[repr(u8)]
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize,
+)]
+#[serde(tag = "type")]
+pub enum ChannelLayout {
+    #[default]
+    Unspecified,
+    Mono,
+    Stereo,
+    Surround,
+    Quad,
+    Surround51,
+    Surround71,
+}
+
+impl ChannelLayout {
+    pub const fn no_channels(self) -> u8 {
+        use ChannelLayout::*;
+
+        match self {
+            Mono => 1,
+            Stereo => 2,
+            Surround => 3,
+            Quad => 4,
+            Surround51 => 6,
+            Surround71 => 8,
+            Unspecified => 0,
+        }
+    }
+
+    pub const fn from_no_channels(channels: u8) -> Self {
+        use ChannelLayout::*;
+
+        match channels {
+            1 => Mono,
+            2 => Stereo,
+            3 => Surround,
+            4 => Quad,
+            6 => Surround51,
+            8 => Surround71,
+            _ => Unspecified,
+        }
+    }
+}
+
+impl TryFrom<u8> for ChannelLayout {
+    type Error = error::Error;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        let var = match value {
+            0 => Self::Unspecified,
+            1 => Self::Mono,
+            2 => Self::Stereo,
+            3 => Self::Surround,
+            4 => Self::Quad,
+            5 => Self::Surround51,
+            6 => Self::Surround71,
+            _ => return Err(error::Error::IntToEnumCastFailed),
+        };
+        debug_assert_eq!(var as u8, value);
+
+        Ok(var)
+    }
+}
+
+impl TryFrom<&u8> for ChannelLayout {
+    type Error = error::Error;
+
+    fn try_from(value: &u8) -> Result<Self, Self::Error> {
+        Self::try_from(*value)
+    }
+}
+
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub struct EncodedAudioHeader {
     pub codec: AudioCodec,
     pub sample_rate: u32,
+    pub channels: u8,
+    pub channel_layout: ChannelLayout,
 }
 
-impl TryFrom<&[u8]> for EncodedAudioHeader {
-    type Error = error::Error;
+impl EncodedAudioHeader {
+    fn validate(self) -> error::Result<Self> {
+        let is_layout_consistent = self.channel_layout == ChannelLayout::Unspecified
+            || self.channel_layout.no_channels() == self.channels;
+        if self.channels == 0 || !is_layout_consistent {
+            return Err(error::Error::EncodedAudioHeaderParseFailed);
+        }
 
-    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
-        let codec: AudioCodec = value
-            .get(0)
-            .ok_or(error::Error::EncodedAudioHeaderParseFailed)?
-            .try_into()?;
+        Ok(self)
+    }
+}
 
-        let sample_rate_bytes = value
-            .get(1..NO_AUDIO_HEADER_BYTES)
-            .ok_or(error::Error::EncodedAudioHeaderParseFailed)?
-            .try_into()
-            .expect("Failed to parse slice");
-        let sample_rate = u32::from_be_bytes(sample_rate_bytes);
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EncodedAudioFlags {
+    pub silence: bool,
+    pub dtx: bool,
+    pub discontinuity: bool,
+}
+
+impl EncodedAudioFlags {
+    const SILENCE: u8 = 1 << 0;
+    const DTX: u8 = 1 << 1;
+    const DISCONTINUITY: u8 = 1 << 2;
 
-        Ok(Self { sample_rate, codec })
+    /// Unknown bits are ignored so that newer senders can add flags.
+    pub const fn from_bits(bits: u8) -> Self {
+        Self {
+            silence: bits & Self::SILENCE != 0,
+            dtx: bits & Self::DTX != 0,
+            discontinuity: bits & Self::DISCONTINUITY != 0,
+        }
     }
+
+    pub const fn bits(self) -> u8 {
+        let mut bits = 0;
+        if self.silence {
+            bits |= Self::SILENCE;
+        }
+        if self.dtx {
+            bits |= Self::DTX;
+        }
+        if self.discontinuity {
+            bits |= Self::DISCONTINUITY;
+        }
+
+        bits
+    }
+}
+
+/// An optional type-length-value entry of the muxed audio header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncodedAudioExtension {
+    pub kind: u8,
+    pub data: Vec<u8>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct EncodedAudioBuffer {
     pub header: EncodedAudioHeader,
+    pub sequence_number: Option<u32>,
+    pub flags: EncodedAudioFlags,
+    pub extensions: Vec<EncodedAudioExtension>,
     pub start_ts: Option<ClockTime>,
     pub data: Vec<u8>,
 }
 
-impl TryFrom<MuxedAudioBuffer> for EncodedAudioBuffer {
-    type Error = error::Error;
+impl EncodedAudioBuffer {
+    /// Muxes the buffer with the latest version of the header. The extensions
+    /// may take up to `u16::MAX` bytes in total, including their headers.
+    pub fn to_muxed(&self) -> error::Result<MuxedAudioBuffer> {
+        let mut muxed = vec![MUXED_AUDIO_VERSION_MARKER | MUXED_AUDIO_VERSION];
+
+        muxed.push(self.header.codec as u8);
+        muxed.extend_from_slice(&self.header.sample_rate.to_be_bytes());
+        muxed.push(self.header.channels);
+        muxed.push(self.header.channel_layout as u8);
+        muxed.push(self.flags.bits());
+        muxed.extend_from_slice(&self.sequence_number.unwrap_or(0).to_be_bytes());
+        muxed.extend_from_slice(
+            &self
+                .start_ts
+                .unwrap_or(ClockTime::ZERO)
+                .as_nanos()
+                .to_be_bytes(),
+        );
+
+        let extensions_len: usize = self
+            .extensions
+            .iter()
+            .map(|ext| NO_EXTENSION_HEADER_BYTES + ext.data.len())
+            .sum();
+        let extensions_len =
+            u16::try_from(extensions_len).map_err(|_| error::Error::EncodedAudioTooLong)?;
+        muxed.extend_from_slice(&extensions_len.to_be_bytes());
+        for ext in self.extensions.iter() {
+            // Every extension is shorter than all of them together.
+            muxed.push(ext.kind);
+            muxed.extend_from_slice(&(ext.data.len() as u16).to_be_bytes());
+            muxed.extend_from_slice(&ext.data);
+        }
 
-    fn try_from(mut buf: MuxedAudioBuffer) -> Result<Self, Self::Error> {
-        let header: EncodedAudioHeader = buf.0.as_slice().try_into()?;
+        let data_len =
+            u32::try_from(self.data.len()).map_err(|_| error::Error::EncodedAudioTooLong)?;
+        muxed.extend_from_slice(&data_len.to_be_bytes());
+        muxed.extend_from_slice(&self.data);
 
-        let start_ts_bytes = buf
-            .0
-            .get(NO_AUDIO_HEADER_BYTES..NO_AUDIO_HEADER_BYTES + NO_CLOCK_TIME_BYTES)
-            .ok_or(error::Error::EncodedAudioHeaderParseFailed)?
-            .try_into()
-            .expect("Failed to parse slice");
-        let start_ts_nanos = u64::from_be_bytes(start_ts_bytes);
-        let start_ts = ClockTime::from_nanos(start_ts_nanos);
+        Ok(MuxedAudioBuffer(muxed))
+    }
 
-        vec_truncate_front(&mut buf.0, NO_AUDIO_HEADER_BYTES + NO_CLOCK_TIME_BYTES);
-        let data = buf.0;
+    fn parse_v0(mut reader: ByteReader) -> error::Result<Self> {
+        let header = EncodedAudioHeader {
+            codec: reader.read_u8()?.try_into()?,
+            sample_rate: reader.read_u32()?,
+            channels: 1,
+            channel_layout: ChannelLayout::Mono,
+        };
+        let start_ts = ClockTime::from_nanos(reader.read_u64()?);
 
         Ok(Self {
             header,
+            sequence_number: None,
+            flags: EncodedAudioFlags::default(),
+            extensions: Vec::new(),
+            start_ts: Some(start_ts),
+            data: reader.read_rest().to_vec(),
+        })
+    }
+
+    fn parse_v1(mut reader: ByteReader) -> error::Result<Self> {
+        let header = EncodedAudioHeader {
+            codec: reader.read_u8()?.try_into()?,
+            sample_rate: reader.read_u32()?,
+            channels: reader.read_u8()?,
+            channel_layout: reader.read_u8()?.try_into()?,
+        }
+        .validate()?;
+        let flags = EncodedAudioFlags::from_bits(reader.read_u8()?);
+        let sequence_number = reader.read_u32()?;
+        let start_ts = ClockTime::from_nanos(reader.read_u64()?);
+
+        let extensions_len = usize::from(reader.read_u16()?);
+        let mut extensions_reader = ByteReader::new(reader.read_bytes(extensions_len)?);
+        let mut extensions = Vec::new();
+        while !extensions_reader.is_empty() {
+            let kind = extensions_reader.read_u8()?;
+            let len = usize::from(extensions_reader.read_u16()?);
+            let data = extensions_reader.read_bytes(len)?.to_vec();
+
+            extensions.push(EncodedAudioExtension { kind, data });
+        }
+
+        let data_len = reader.read_u32()? as usize;
+        let data = reader.read_bytes(data_len)?.to_vec();
+
+        Ok(Self {
+            header,
+            sequence_number: Some(sequence_number),
+            flags,
+            extensions,
             start_ts: Some(start_ts),
             data,
         })
     }
 }
 
+impl TryFrom<MuxedAudioBuffer> for EncodedAudioBuffer {
+    type Error = error::Error;
+
+    fn try_from(buf: MuxedAudioBuffer) -> Result<Self, Self::Error> {
+        let first_byte = *buf
+            .0
+            .first()
+            .ok_or(error::Error::EncodedAudioHeaderParseFailed)?;
+
+        // Version 0 has no version byte and starts with the codec, which never has the
+        // marker bit set.
+        if first_byte & MUXED_AUDIO_VERSION_MARKER == 0 {
+            return Self::parse_v0(ByteReader::new(&buf.0));
+        }
+
+        let mut reader = ByteReader::new(&buf.0);
+        reader.read_u8()?;
+
+        match first_byte & !MUXED_AUDIO_VERSION_MARKER {
+            1 => Self::parse_v1(reader),
+            _ => Err(error::Error::EncodedAudioHeaderParseFailed),
+        }
+    }
+}
+
 impl Message for EncodedAudioBuffer {}
 
+struct ByteReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> ByteReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    fn read_bytes(&mut self, len: usize) -> error::Result<&'a [u8]> {
+        if len > self.data.len() {
+            return Err(error::Error::EncodedAudioHeaderParseFailed);
+        }
+
+        let (bytes, rest) = self.data.split_at(len);
+        self.data = rest;
+
+        Ok(bytes)
+    }
+
+    fn read_array<const N: usize>(&mut self) -> error::Result<[u8; N]> {
+        Ok(self
+            .read_bytes(N)?
+            .try_into()
+            .expect("Failed to parse slice"))
+    }
+
+    fn read_u8(&mut self) -> error::Result<u8> {
+        self.read_array().map(u8::from_be_bytes)
+    }
+
+    fn read_u16(&mut self) -> error::Result<u16> {
+        self.read_array().map(u16::from_be_bytes)
+    }
+
+    fn read_u32(&mut self) -> error::Result<u32> {
+        self.read_array().map(u32::from_be_bytes)
+    }
+
+    fn read_u64(&mut self) -> error::Result<u64> {
+        self.read_array().map(u64::from_be_bytes)
+    }
+
+    fn read_rest(&mut self) -> &'a [u8] {
+        std::mem::take(&mut self.data)
+    }
+}
+
 #[repr(i8)]
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub enum RawAudioFormat {
@@ -137,24 +469,147 @@ impl RawAudioFormat {
             Unspecified => 0,
         }
     }
+
+    pub const fn bit_depth(self) -> u32 {
+        self.no_bytes() as u32 * u8::BITS
+    }
+
+    pub const fn is_float(self) -> bool {
+        matches!(self, Self::F32LE | Self::F32BE)
+    }
+
+    /// Reads one sample and scales it to the range from -1.0 to 1.0.
+    pub fn read_normalized_sample(self, bytes: &[u8]) -> f64 {
+        use RawAudioFormat::*;
+
+        const S16_SCALE: f64 = (1 << 15) as f64;
+        const S24_SCALE: f64 = (1 << 23) as f64;
+        const S32_SCALE: f64 = (1u64 << 31) as f64;
+
+        match self {
+            U8 => (f64::from(bytes[0]) - 128.0) / 128.0,
+            S16LE => f64::from(i16::from_le_bytes([bytes[0], bytes[1]])) / S16_SCALE,
+            S16BE => f64::from(i16::from_be_bytes([bytes[0], bytes[1]])) / S16_SCALE,
+            S24LE => {
+                let sample = i32::from_le_bytes([0, bytes[0], bytes[1], bytes[2]]) >> 8;
+                f64::from(sample) / S24_SCALE
+            }
+            S24BE => {
+                let sample = i32::from_be_bytes([bytes[0], bytes[1], bytes[2], 0]) >> 8;
+                f64::from(sample) / S24_SCALE
+            }
+            S32LE => f64::from(i32::from_le_bytes(read_4_bytes(bytes))) / S32_SCALE,
+            S32BE => f64::from(i32::from_be_bytes(read_4_bytes(bytes))) / S32_SCALE,
+            F32LE => f64::from(f32::from_le_bytes(read_4_bytes(bytes))),
+            F32BE => f64::from(f32::from_be_bytes(read_4_bytes(bytes))),
+            Unspecified => 0.0,
+        }
+    }
+
+    /// Writes one sample. The integer formats clip it to the range from -1.0 to
+    /// 1.0, while the float ones keep the headroom above it.
+    pub fn write_normalized_sample(self, sample: f64, dst: &mut [u8]) {
+        use RawAudioFormat::*;
+
+        let sample = if sample.is_nan() { 0.0 } else { sample };
+        let to_int = |bits: u32| {
+            let max = ((1u64 << (bits - 1)) - 1) as f64;
+            (sample * (max + 1.0)).round().clamp(-max - 1.0, max) as i32
+        };
+
+        match self {
+            U8 => dst[0] = (to_int(8) + 128) as u8,
+            S16LE => dst[..2].copy_from_slice(&(to_int(16) as i16).to_le_bytes()),
+            S16BE => dst[..2].copy_from_slice(&(to_int(16) as i16).to_be_bytes()),
+            S24LE => dst[..3].copy_from_slice(&to_int(24).to_le_bytes()[..3]),
+            S24BE => dst[..3].copy_from_slice(&to_int(24).to_be_bytes()[1..]),
+            S32LE => dst[..4].copy_from_slice(&to_int(32).to_le_bytes()),
+            S32BE => dst[..4].copy_from_slice(&to_int(32).to_be_bytes()),
+            F32LE => dst[..4].copy_from_slice(&(sample as f32).to_le_bytes()),
+            F32BE => dst[..4].copy_from_slice(&(sample as f32).to_be_bytes()),
+            Unspecified => {}
+        }
+    }
+}
+
+fn read_4_bytes(bytes: &[u8]) -> [u8; 4] {
+    bytes[..4].try_into().expect("The byte slice is too short")
+}
+
+/// Maps the interleaved normalized samples to the other number of channels. The
+/// mono audio is spread to all of the channels and all of the channels are
+/// averaged into the mono one. Otherwise, the channels are kept in order and the
+/// missing ones are silent.
+pub fn map_normalized_channels(samples: &[f64], channels: u8, target_channels: u8) -> Vec<f64> {
+    let channels = usize::from(channels);
+    let target_channels = usize::from(target_channels);
+    if channels == target_channels || channels == 0 {
+        return samples.to_vec();
+    }
+
+    if target_channels == 1 {
+        return samples
+            .chunks_exact(channels)
+            .map(|frame| frame.iter().sum::<f64>() / channels as f64)
+            .collect();
+    }
+
+    samples
+        .chunks_exact(channels)
+        .flat_map(|frame| {
+            (0..target_channels).map(move |channel| match channels {
+                1 => frame[0],
+                _ => frame.get(channel).copied().unwrap_or(0.0),
+            })
+        })
+        .collect()
+}
+
+/// Everything which describes the raw audio except its data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RawAudioInfo {
+    pub format: RawAudioFormat,
+    pub channels: u8,
+    pub channel_layout: ChannelLayout,
+    pub sample_rate: u32,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct RawAudioBuffer {
     data: Vec<u8>,
     format: RawAudioFormat,
+    channels: u8,
+    channel_layout: ChannelLayout,
     sample_rate: u32,
 }
 
 impl RawAudioBuffer {
-    pub const fn new(data: Vec<u8>, format: RawAudioFormat, sample_rate: u32) -> Self {
+    pub const fn new(
+        data: Vec<u8>,
+        format: RawAudioFormat,
+        channels: u8,
+        sample_rate: u32,
+    ) -> Self {
         Self {
             data,
             format,
+            channels,
+            channel_layout: ChannelLayout::from_no_channels(channels),
             sample_rate,
         }
     }
 
+    pub fn with_channel_layout(mut self, channel_layout: ChannelLayout) -> Self {
+        debug_assert!(
+            channel_layout == ChannelLayout::Unspecified
+                || channel_layout.no_channels() == self.channels,
+            "The channel layout doesn't match the number of channels"
+        );
+
+        self.channel_layout = channel_layout;
+        self
+    }
+
     pub fn len(&self) -> usize {
         self.as_slice().len()
     }
@@ -179,12 +634,38 @@ impl RawAudioBuffer {
         self.format
     }
 
+    pub fn channels(&self) -> u8 {
+        self.channels
+    }
+
+    pub fn channel_layout(&self) -> ChannelLayout {
+        self.channel_layout
+    }
+
     pub fn sample_rate(&self) -> u32 {
         self.sample_rate
     }
 
+    pub fn info(&self) -> RawAudioInfo {
+        RawAudioInfo {
+            format: self.format,
+            channels: self.channels,
+            channel_layout: self.channel_layout,
+            sample_rate: self.sample_rate,
+        }
+    }
+
+    /// The number of bytes occupied by one sample of every channel.
+    pub fn frame_size(&self) -> usize {
+        self.format().no_bytes() * self.channels() as usize
+    }
+
+    /// The number of frames, i.e. the number of samples in each channel.
     pub fn no_samples(&self) -> usize {
-        self.as_slice().len() / self.format().no_bytes()
+        self.as_slice()
+            .len()
+            .checked_div(self.frame_size())
+            .unwrap_or(0)
     }
 
     pub fn duration(&self) -> ClockTime {
@@ -195,35 +676,76 @@ impl RawAudioBuffer {
     }
 
     pub fn truncate_front(&mut self, no_samples: usize) {
-        let no_bytes = self.format().no_bytes();
+        let frame_size = self.frame_size();
 
-        vec_truncate_front(self.as_vec_mut(), no_samples as usize * no_bytes);
+        vec_truncate_front(self.as_vec_mut(), no_samples * frame_size);
     }
 
     pub fn truncate_duration_front(&mut self, cut_dur: ClockTime) {
         let sample_rate = self.sample_rate();
         let format = self.format();
+        let channels = self.channels();
 
-        vec_truncate_front(self.as_vec_mut(), cut_dur.to_no_bytes(sample_rate, format));
+        vec_truncate_front(
+            self.as_vec_mut(),
+            cut_dur.to_no_bytes(sample_rate, format, channels),
+        );
     }
 
     pub fn truncate(&mut self, no_samples: usize) {
-        let no_bytes = self.format().no_bytes();
+        let frame_size = self.frame_size();
 
-        self.data.truncate(no_samples as usize * no_bytes);
+        self.data.truncate(no_samples * frame_size);
     }
 
     pub fn truncate_duration(&mut self, cut_dur: ClockTime) {
         let sample_rate = self.sample_rate();
         let format = self.format();
+        let channels = self.channels();
 
-        self.data.truncate(cut_dur.to_no_bytes(sample_rate, format));
+        self.data
+            .truncate(cut_dur.to_no_bytes(sample_rate, format, channels));
     }
 
-    pub fn split_at_timestamp(mut self, ts: ClockTime) -> (Self, Self) {
-        let bytes = ts.to_no_bytes(self.sample_rate(), self.format());
+    /// Interleaved samples of every channel scaled to the range from -1.0 to 1.0.
+    pub fn to_normalized_samples(&self) -> Vec<f64> {
+        let no_bytes = self.format().no_bytes();
+        if no_bytes == 0 {
+            return Vec::new();
+        }
+
+        self.as_slice()
+            .chunks_exact(no_bytes)
+            .map(|bytes| self.format().read_normalized_sample(bytes))
+            .collect()
+    }
+
+    pub fn from_normalized_samples(
+        samples: &[f64],
+        format: RawAudioFormat,
+        channels: u8,
+        sample_rate: u32,
+    ) -> Self {
+        let mut data = vec![0; samples.len() * format.no_bytes()];
+        if format.no_bytes() != 0 {
+            for (sample, dst) in samples.iter().zip(data.chunks_exact_mut(format.no_bytes())) {
+                format.write_normalized_sample(*sample, dst);
+            }
+        }
 
-        let new_buf = Self::new(self.data[bytes..].to_vec(), self.format, self.sample_rate);
+        Self::new(data, format, channels, sample_rate)
+    }
+
+    pub fn split_at_timestamp(mut self, ts: ClockTime) -> (Self, Self) {
+        let bytes = ts.to_no_bytes(self.sample_rate(), self.format(), self.channels());
+
+        let new_buf = Self::new(
+            self.data[bytes..].to_vec(),
+            self.format,
+            self.channels,
+            self.sample_rate,
+        )
+        .with_channel_layout(self.channel_layout);
         self.data.truncate(bytes);
 
         (self, new_buf)
@@ -248,7 +770,7 @@ impl TimestampedRawAudioBuffer {
 
     pub const fn null() -> Self {
         Self {
-            raw: RawAudioBuffer::new(Vec::new(), RawAudioFormat::Unspecified, 0),
+            raw: RawAudioBuffer::new(Vec::new(), RawAudioFormat::Unspecified, 0, 0),
             start: None,
         }
     }
@@ -257,6 +779,10 @@ impl TimestampedRawAudioBuffer {
         self.raw.as_slice()
     }
 
+    pub fn as_raw(&self) -> &RawAudioBuffer {
+        &self.raw
+    }
+
     pub fn into_raw(self) -> RawAudioBuffer {
         self.raw
     }
@@ -269,10 +795,22 @@ impl TimestampedRawAudioBuffer {
         self.raw.format()
     }
 
+    pub fn channels(&self) -> u8 {
+        self.raw.channels()
+    }
+
+    pub fn channel_layout(&self) -> ChannelLayout {
+        self.raw.channel_layout()
+    }
+
     pub fn sample_rate(&self) -> u32 {
         self.raw.sample_rate()
     }
 
+    pub fn info(&self) -> RawAudioInfo {
+        self.raw.info()
+    }
+
     pub fn start(&self) -> Option<ClockTime> {
         self.start
     }
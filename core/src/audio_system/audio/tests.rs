@@ -0,0 +1,33 @@
+use super::*;
+
+#[test]
+fn test_map_normalized_channels() {
+    assert_eq!(
+        map_normalized_channels(&[0.1, 0.2], 1, 2),
+        vec![0.1, 0.1, 0.2, 0.2]
+    );
+    assert_eq!(
+        map_normalized_channels(&[0.1, 0.2, 0.3, 0.4], 2, 2),
+        vec![0.1, 0.2, 0.3, 0.4]
+    );
+    assert_eq!(
+        map_normalized_channels(&[0.1, 0.2, 0.3, 0.4], 2, 3),
+        vec![0.1, 0.2, 0.0, 0.3, 0.4, 0.0]
+    );
+    assert_eq!(
+        map_normalized_channels(&[0.1, 0.2, 0.3, 0.4, 0.5, 0.6], 3, 2),
+        vec![0.1, 0.2, 0.4, 0.5]
+    );
+}
+
+#[test]
+fn test_downmix_normalized_channels_to_mono() {
+    let mono = map_normalized_channels(&[0.1, 0.3, 0.5, 0.4, 0.5, 0.6, -0.5, 0.5, 0.0], 3, 1);
+
+    assert_eq!(mono.len(), 3);
+    for (sample, expected) in mono.iter().zip([0.3, 0.5, 0.0]) {
+        assert!((sample - expected).abs() < 1e-12);
+    }
+    // The audio which is only in the second channel isn't lost.
+    assert_eq!(map_normalized_channels(&[0.0, 0.8], 2, 1), vec![0.4]);
+}
@@ -6,6 +6,29 @@ use mueue::*;
 #[non_exhaustive]
 pub enum AudioSystemElementMessage {
     Error(error::Error),
+    /// A gap in the audio stream was filled with the synthesized audio.
+    LossConcealed {
+        duration: ClockTime,
+    },
+    /// The automatic gain control changed its linear gain.
+    AgcGainChanged {
+        gain: f64,
+    },
+    /// The voice activity detector noticed the start or end of the speech.
+    SpeakingChanged {
+        speaking: bool,
+    },
+    /// The volume of the virtual microphone was changed, possibly by another
+    /// application.
+    VirtualMicrophoneVolumeChanged {
+        volume: f64,
+        muted: bool,
+    },
+    /// An application started or stopped recording from the virtual
+    /// microphone.
+    VirtualMicrophoneInUseChanged {
+        in_use: bool,
+    },
 }
 
 impl Message for AudioSystemElementMessage {}
@@ -3,14 +3,24 @@ pub mod element;
 pub mod pipeline;
 pub mod queue;
 
+#[cfg(test)]
+mod tests;
+
+use audio::{EncodedAudioBuffer, RawAudioBuffer, TimestampedRawAudioBuffer};
 use element::*;
 use pipeline::*;
-use pipeline::{audio_decoder::*, resizer::*, sync::*, virtual_microphone::*};
+use pipeline::{
+    agc::*, audio_decoder::*, converter::*, dynamics::*, equalizer::*, file_writer::*,
+    filter_chain::*, gain::*, jitter_buffer::*, loss_concealer::*, noise_gate::*,
+    noise_suppressor::*, pcm_decoder::*, resampler::*, resizer::*, sync::*, tee::*,
+    virtual_microphone::*,
+};
 
 use crate::util::*;
 use crate::*;
 
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::Arc;
 
 use mueue::{unidirectional_queue, Message, MessageEndpoint, MessageReceiver, MessageSender};
@@ -20,13 +30,69 @@ use self::pipeline::demuxer::AudioDemuxer;
 pub type AudioSystemEndpoint = MessageEndpoint<AudioSystemControlMessage, AudioSystemMessage>;
 
 #[non_exhaustive]
-pub enum AudioSystemMessage {}
+pub enum AudioSystemMessage {
+    Error(error::Error),
+    /// See [`AudioSystemElementMessage::AgcGainChanged`].
+    AgcGainChanged {
+        gain: f64,
+    },
+    /// See [`AudioSystemElementMessage::SpeakingChanged`].
+    SpeakingChanged {
+        speaking: bool,
+    },
+    /// See [`AudioSystemElementMessage::VirtualMicrophoneVolumeChanged`].
+    VirtualMicrophoneVolumeChanged {
+        volume: f64,
+        muted: bool,
+    },
+    /// See [`AudioSystemElementMessage::VirtualMicrophoneInUseChanged`].
+    VirtualMicrophoneInUseChanged {
+        in_use: bool,
+    },
+}
 
 impl Message for AudioSystemMessage {}
 
 #[non_exhaustive]
 pub enum AudioSystemControlMessage {
-
+    SetResizeMode(ResizeMode),
+    /// Sets the linear gain of the audio.
+    SetGain(f64),
+    SetMuted(bool),
+    SetAgcEnabled(bool),
+    SetAgcParams(AgcParams),
+    /// Sets the strength of the noise suppression from 0.0 to 1.0.
+    SetNoiseSuppressionStrength(f64),
+    SetNoiseSuppressionBypassed(bool),
+    SetNoiseGateEnabled(bool),
+    SetNoiseGateParams(NoiseGateParams),
+    /// Stops passing the audio while the noise gate is closed, the virtual
+    /// microphone is fed with silence instead.
+    SetSilenceSuppression(bool),
+    SetLimiterParams(LimiterParams),
+    SetCompressorEnabled(bool),
+    SetCompressorParams(CompressorParams),
+    SetEqBands(Vec<EqBand>),
+    SetEqPreset(EqPreset),
+    /// Inserts the filter which isn't in the chain at the given position.
+    InsertAudioFilter {
+        index: usize,
+        info: AudioFilterInfo,
+    },
+    RemoveAudioFilter(AudioFilterInfo),
+    /// Records the Opus packets received from the device into an Ogg file and
+    /// the audio played by the virtual microphone into a WAV file. The received
+    /// audio in the other codecs is reported with an error. The recording which
+    /// is in progress is stopped first.
+    StartRecording {
+        received: Option<PathBuf>,
+        played: Option<PathBuf>,
+    },
+    StopRecording,
+    /// Sets the linear volume which the sound system applies to the virtual
+    /// microphone.
+    SetVirtualMicrophoneVolume(f64),
+    SetVirtualMicrophoneMuted(bool),
 }
 
 impl Message for AudioSystemControlMessage {}
@@ -34,12 +100,16 @@ impl Message for AudioSystemControlMessage {}
 #[allow(dead_code)]
 pub struct AudioSystem {
     endpoint: AudioSystemEndpoint,
+    notification_send: MessageSender<AudioSystemElementMessage>,
     notification_recv: MessageReceiver<AudioSystemElementMessage>,
 
     pipeline: AudioPipelineStateMachine,
 
     audio_decs: HashMap<AudioDecoderInfo, Option<Box<dyn AudioDecoder>>>,
     virtual_mics: HashMap<VirtualMicrophoneInfo, Option<Box<dyn VirtualMicrophone>>>,
+    encoded_filters: AudioFilterPool<EncodedAudioBuffer>,
+    decoded_filters: AudioFilterPool<TimestampedRawAudioBuffer>,
+    raw_filters: AudioFilterPool<RawAudioBuffer>,
 }
 
 impl AudioSystem {
@@ -47,33 +117,119 @@ impl AudioSystem {
         end: AudioSystemEndpoint,
         audio_decs_builders: Vec<Box<dyn AudioDecoderBuilder>>,
         virtual_mics_builders: Vec<Box<dyn VirtualMicrophoneBuilder>>,
+        audio_filters_builders: AudioFilterBuilders,
+        pipeline_config: AudioPipelineConfig,
     ) -> Self {
-        let (notification_send, notification_recv) = unidirectional_queue();
+        Self::with_sys_clock(
+            end,
+            audio_decs_builders,
+            virtual_mics_builders,
+            audio_filters_builders,
+            pipeline_config,
+            Arc::new(SystemClock::new()),
+        )
+    }
 
-        let sys_clock = Arc::new(SystemClock::new());
+    fn with_sys_clock(
+        end: AudioSystemEndpoint,
+        audio_decs_builders: Vec<Box<dyn AudioDecoderBuilder>>,
+        virtual_mics_builders: Vec<Box<dyn VirtualMicrophoneBuilder>>,
+        audio_filters_builders: AudioFilterBuilders,
+        pipeline_config: AudioPipelineConfig,
+        sys_clock: Arc<dyn Clock>,
+    ) -> Self {
+        let (notification_send, notification_recv) = unidirectional_queue();
 
         let demux = AudioDemuxer::new(notification_send.clone());
+        let received_tee = AudioTee::new(notification_send.clone());
+        let AudioFilterBuilders {
+            encoded: encoded_filters_builders,
+            decoded: decoded_filters_builders,
+            raw: raw_filters_builders,
+        } = audio_filters_builders;
+        let mut encoded_filters = AudioFilterPool::new(build_audio_filters(
+            encoded_filters_builders,
+            &notification_send,
+        ));
+        let encoded_filter_chain = chain_audio_filters(
+            &mut encoded_filters,
+            &pipeline_config.encoded_filters,
+            "encoded",
+            &notification_send,
+        );
+        let jitter = JitterBuffer::new(notification_send.clone(), sys_clock.clone());
         let mut audio_decs = collect_audio_decs(audio_decs_builders, notification_send.clone());
+        let pcm_dec = PcmDecoder::new(notification_send.clone());
+        let built_in_decoded_filters: [Box<dyn ChainedAudioFilter<_>>; 2] = [
+            Box::new(LossConcealer::new(notification_send.clone())),
+            Box::new(NoiseGate::new(notification_send.clone())),
+        ];
+        let mut decoded_filters = AudioFilterPool::new(built_in_decoded_filters.into_iter().chain(
+            build_audio_filters(decoded_filters_builders, &notification_send),
+        ));
+        let decoded_filter_chain = chain_audio_filters(
+            &mut decoded_filters,
+            &pipeline_config.decoded_filters,
+            "decoded",
+            &notification_send,
+        );
         let sync = Synchronizer::new(notification_send.clone(), sys_clock);
         let resizer = AudioResizer::new(notification_send.clone());
+        let float_conv = AudioConverter::new(notification_send.clone());
+        let resampler = AudioResampler::new(notification_send.clone());
+        let built_in_raw_filters: [Box<dyn ChainedAudioFilter<_>>; 5] = [
+            Box::new(Equalizer::new(notification_send.clone())),
+            Box::new(NoiseSuppressor::new(notification_send.clone())),
+            Box::new(AutomaticGainControl::new(notification_send.clone())),
+            Box::new(AudioGain::new(notification_send.clone())),
+            Box::new(DynamicsProcessor::new(notification_send.clone())),
+        ];
+        let mut raw_filters = AudioFilterPool::new(built_in_raw_filters.into_iter().chain(
+            build_audio_filters(raw_filters_builders, &notification_send),
+        ));
+        let raw_filter_chain = chain_audio_filters(
+            &mut raw_filters,
+            &pipeline_config.raw_filters,
+            "raw",
+            &notification_send,
+        );
+        let conv = AudioConverter::new(notification_send.clone());
+        let played_tee = AudioTee::new(notification_send.clone());
         let mut virtual_mics =
-            collect_virtual_microphones(virtual_mics_builders, notification_send);
+            collect_virtual_microphones(virtual_mics_builders, notification_send.clone());
 
         let mut pipeline = AudioPipeline::new();
         pipeline.set_audio_demuxer(demux);
+        pipeline.set_received_audio_tee(received_tee);
+        pipeline.set_encoded_filter_chain(encoded_filter_chain);
+        pipeline.set_jitter_buffer(jitter);
         pipeline.set_audio_decoder(take_first_audio_decoder(&mut audio_decs));
+        pipeline.set_pcm_decoder(pcm_dec);
+        pipeline.set_decoded_filter_chain(decoded_filter_chain);
         pipeline.set_synchronizer(sync);
         pipeline.set_resizer(resizer);
-        pipeline.set_virtual_microphone(take_first_virtual_microphone(&mut virtual_mics));
+        pipeline.set_float_converter(float_conv);
+        pipeline.set_resampler(resampler);
+        pipeline.set_raw_filter_chain(raw_filter_chain);
+        pipeline.set_converter(conv);
+        pipeline.set_played_audio_tee(played_tee);
+        pipeline.set_virtual_microphone(subscribe_virtual_microphone(
+            take_first_virtual_microphone(&mut virtual_mics),
+            &notification_send,
+        ));
 
         Self {
             endpoint: end,
+            notification_send,
             notification_recv,
 
             pipeline: RunnableStateMachine::new(pipeline),
 
             audio_decs,
             virtual_mics,
+            encoded_filters,
+            decoded_filters,
+            raw_filters,
         }
     }
 
@@ -95,9 +251,100 @@ impl AudioSystem {
 
         let mic = self.virtual_mics.get_mut(&info).and_then(Option::take);
         if let Some(mic) = mic {
+            let mic = subscribe_virtual_microphone(mic, &self.notification_send);
             self.pipeline.runnable_mut().set_virtual_microphone(mic);
         }
     }
+
+    /// The filters which were removed from their chain may be inserted again.
+    /// The filter is inserted into the chain of the section which it fits into.
+    pub fn insert_audio_filter(&mut self, index: usize, info: AudioFilterInfo) {
+        let pipeline = self.pipeline.runnable_mut();
+        if self.encoded_filters.contains(&info) {
+            insert_audio_filter(
+                &mut self.encoded_filters,
+                pipeline.encoded_filter_chain_mut(),
+                index,
+                &info,
+            );
+        } else if self.decoded_filters.contains(&info) {
+            insert_audio_filter(
+                &mut self.decoded_filters,
+                pipeline.decoded_filter_chain_mut(),
+                index,
+                &info,
+            );
+            pipeline.relink_noise_gate_state();
+        } else {
+            insert_audio_filter(
+                &mut self.raw_filters,
+                pipeline.raw_filter_chain_mut(),
+                index,
+                &info,
+            );
+        }
+    }
+
+    pub fn remove_audio_filter(&mut self, info: AudioFilterInfo) {
+        let pipeline = self.pipeline.runnable_mut();
+        if let Some(filter) = pipeline
+            .encoded_filter_chain_mut()
+            .and_then(|chain| chain.remove(&info))
+        {
+            self.encoded_filters.put(filter);
+        } else if let Some(filter) = pipeline
+            .decoded_filter_chain_mut()
+            .and_then(|chain| chain.remove(&info))
+        {
+            self.decoded_filters.put(filter);
+            pipeline.relink_noise_gate_state();
+        } else if let Some(filter) = pipeline
+            .raw_filter_chain_mut()
+            .and_then(|chain| chain.remove(&info))
+        {
+            self.raw_filters.put(filter);
+        }
+    }
+
+    pub fn start_recording(
+        &mut self,
+        received: Option<PathBuf>,
+        played: Option<PathBuf>,
+    ) -> error::Result<()> {
+        self.stop_recording();
+
+        let received_rec = received
+            .map(|path| OggOpusFileWriter::create(self.notification_send.clone(), path))
+            .transpose()?;
+        let played_rec = played
+            .map(|path| WavFileWriter::create(self.notification_send.clone(), path))
+            .transpose()?;
+
+        let pipeline = self.pipeline.runnable_mut();
+        if let Some(received_rec) = received_rec {
+            pipeline.set_received_audio_recorder(received_rec);
+        }
+        if let Some(played_rec) = played_rec {
+            pipeline.set_played_audio_recorder(played_rec);
+        }
+
+        Ok(())
+    }
+
+    /// The files are completed when their writers are dropped. The audio which
+    /// is queued for the writers is written before.
+    pub fn stop_recording(&mut self) {
+        let pipeline = self.pipeline.runnable_mut();
+        if let Some(received_rec) = pipeline.received_audio_recorder_mut() {
+            let _ = received_rec.update();
+        }
+        if let Some(played_rec) = pipeline.played_audio_recorder_mut() {
+            let _ = played_rec.update();
+        }
+
+        pipeline.take_received_audio_recorder();
+        pipeline.take_played_audio_recorder();
+    }
 }
 
 fn collect_audio_decs(
@@ -130,6 +377,63 @@ fn collect_virtual_microphones(
         .collect()
 }
 
+fn subscribe_virtual_microphone(
+    mut virtual_mic: Box<dyn VirtualMicrophone>,
+    notification_sender: &MessageSender<AudioSystemElementMessage>,
+) -> Box<dyn VirtualMicrophone> {
+    if let Err(err) = virtual_mic.subscribe() {
+        let _ = notification_sender.send(AudioSystemElementMessage::Error(err));
+    }
+
+    virtual_mic
+}
+
+fn build_audio_filters<T: Message>(
+    audio_filters_builders: Vec<Box<dyn ChainedAudioFilterBuilder<T>>>,
+    notification_sender: &MessageSender<AudioSystemElementMessage>,
+) -> impl Iterator<Item = Box<dyn ChainedAudioFilter<T>>> + '_ {
+    audio_filters_builders
+        .into_iter()
+        .map(|mut builder| {
+            builder.set_sender(notification_sender.clone());
+            builder
+        })
+        .filter_map(|builder| builder.build().ok())
+}
+
+// The filter which doesn't process the audio of the section is reported, as it
+// can't be linked into the section's chain.
+fn chain_audio_filters<T: Message>(
+    audio_filters: &mut AudioFilterPool<T>,
+    infos: &[AudioFilterInfo],
+    section: &'static str,
+    notification_sender: &MessageSender<AudioSystemElementMessage>,
+) -> AudioFilterChain<T> {
+    let mut chain = AudioFilterChain::new(notification_sender.clone());
+    for info in infos {
+        match audio_filters.take(info) {
+            Some(filter) => chain.push(filter),
+            None => {
+                let err = error::Error::UnknownAudioFilter(info.name.clone(), section);
+                let _ = notification_sender.send(AudioSystemElementMessage::Error(err));
+            }
+        }
+    }
+
+    chain
+}
+
+fn insert_audio_filter<T: Message>(
+    audio_filters: &mut AudioFilterPool<T>,
+    chain: Option<&mut AudioFilterChain<T>>,
+    index: usize,
+    info: &AudioFilterInfo,
+) {
+    if let (Some(chain), Some(filter)) = (chain, audio_filters.take(info)) {
+        chain.insert(index, filter);
+    }
+}
+
 fn take_first_audio_decoder(
     audio_decs: &mut HashMap<AudioDecoderInfo, Option<Box<dyn AudioDecoder>>>,
 ) -> Box<dyn AudioDecoder> {
@@ -183,7 +487,214 @@ impl Runnable for AudioSystem {
     }
 
     fn update(&mut self) -> error::Result<()> {
-        todo!()
+        let endpoint = self.endpoint.clone();
+        for msg in endpoint.iter() {
+            match msg {
+                AudioSystemControlMessage::SetResizeMode(mode) => {
+                    if let Some(resizer) = self.pipeline.runnable_mut().resizer_mut() {
+                        resizer.set_mode(mode);
+                    }
+                }
+                AudioSystemControlMessage::SetGain(gain) if gain.is_finite() && gain >= 0.0 => {
+                    if let Some(audio_gain) = self
+                        .pipeline
+                        .runnable_mut()
+                        .raw_audio_filter_mut::<AudioGain>()
+                    {
+                        audio_gain.set_gain(gain);
+                    }
+                }
+                AudioSystemControlMessage::SetMuted(muted) => {
+                    if let Some(audio_gain) = self
+                        .pipeline
+                        .runnable_mut()
+                        .raw_audio_filter_mut::<AudioGain>()
+                    {
+                        audio_gain.set_muted(muted);
+                    }
+                }
+                AudioSystemControlMessage::SetAgcEnabled(enabled) => {
+                    if let Some(agc) = self
+                        .pipeline
+                        .runnable_mut()
+                        .raw_audio_filter_mut::<AutomaticGainControl>()
+                    {
+                        agc.set_enabled(enabled);
+                    }
+                }
+                AudioSystemControlMessage::SetAgcParams(params) => {
+                    if let Some(agc) = self
+                        .pipeline
+                        .runnable_mut()
+                        .raw_audio_filter_mut::<AutomaticGainControl>()
+                    {
+                        agc.set_params(params);
+                    }
+                }
+                AudioSystemControlMessage::SetNoiseSuppressionStrength(strength) => {
+                    if let Some(ns) = self
+                        .pipeline
+                        .runnable_mut()
+                        .raw_audio_filter_mut::<NoiseSuppressor>()
+                    {
+                        ns.set_strength(strength);
+                    }
+                }
+                AudioSystemControlMessage::SetNoiseSuppressionBypassed(bypassed) => {
+                    if let Some(ns) = self
+                        .pipeline
+                        .runnable_mut()
+                        .raw_audio_filter_mut::<NoiseSuppressor>()
+                    {
+                        ns.set_bypassed(bypassed);
+                    }
+                }
+                AudioSystemControlMessage::SetNoiseGateEnabled(enabled) => {
+                    if let Some(gate) = self
+                        .pipeline
+                        .runnable_mut()
+                        .decoded_audio_filter_mut::<NoiseGate>()
+                    {
+                        gate.set_enabled(enabled);
+                    }
+                }
+                AudioSystemControlMessage::SetNoiseGateParams(params) => {
+                    if let Some(gate) = self
+                        .pipeline
+                        .runnable_mut()
+                        .decoded_audio_filter_mut::<NoiseGate>()
+                    {
+                        gate.set_params(params);
+                    }
+                }
+                AudioSystemControlMessage::SetSilenceSuppression(suppress) => {
+                    if let Some(gate) = self
+                        .pipeline
+                        .runnable_mut()
+                        .decoded_audio_filter_mut::<NoiseGate>()
+                    {
+                        gate.set_suppress_silence(suppress);
+                    }
+                }
+                AudioSystemControlMessage::SetLimiterParams(params) => {
+                    if let Some(dynamics) = self
+                        .pipeline
+                        .runnable_mut()
+                        .raw_audio_filter_mut::<DynamicsProcessor>()
+                    {
+                        dynamics.set_limiter_params(params);
+                    }
+                }
+                AudioSystemControlMessage::SetCompressorEnabled(enabled) => {
+                    if let Some(dynamics) = self
+                        .pipeline
+                        .runnable_mut()
+                        .raw_audio_filter_mut::<DynamicsProcessor>()
+                    {
+                        dynamics.set_compressor_enabled(enabled);
+                    }
+                }
+                AudioSystemControlMessage::SetCompressorParams(params) => {
+                    if let Some(dynamics) = self
+                        .pipeline
+                        .runnable_mut()
+                        .raw_audio_filter_mut::<DynamicsProcessor>()
+                    {
+                        dynamics.set_compressor_params(params);
+                    }
+                }
+                AudioSystemControlMessage::SetEqBands(bands) => {
+                    if let Some(eq) = self
+                        .pipeline
+                        .runnable_mut()
+                        .raw_audio_filter_mut::<Equalizer>()
+                    {
+                        eq.set_bands(bands);
+                    }
+                }
+                AudioSystemControlMessage::SetEqPreset(preset) => {
+                    if let Some(eq) = self
+                        .pipeline
+                        .runnable_mut()
+                        .raw_audio_filter_mut::<Equalizer>()
+                    {
+                        eq.set_preset(preset);
+                    }
+                }
+                AudioSystemControlMessage::InsertAudioFilter { index, info } => {
+                    self.insert_audio_filter(index, info);
+                }
+                AudioSystemControlMessage::RemoveAudioFilter(info) => {
+                    self.remove_audio_filter(info);
+                }
+                AudioSystemControlMessage::StartRecording { received, played } => {
+                    if let Err(err) = self.start_recording(received, played) {
+                        let _ = self.endpoint.send(AudioSystemMessage::Error(err));
+                    }
+                }
+                AudioSystemControlMessage::StopRecording => {
+                    self.stop_recording();
+                }
+                AudioSystemControlMessage::SetVirtualMicrophoneVolume(volume)
+                    if volume.is_finite() && volume >= 0.0 =>
+                {
+                    if let Some(Err(err)) = self
+                        .pipeline
+                        .runnable_mut()
+                        .virtual_microphone_mut()
+                        .map(|mic| mic.set_volume(volume))
+                    {
+                        let _ = self.endpoint.send(AudioSystemMessage::Error(err));
+                    }
+                }
+                AudioSystemControlMessage::SetVirtualMicrophoneMuted(muted) => {
+                    if let Some(Err(err)) = self
+                        .pipeline
+                        .runnable_mut()
+                        .virtual_microphone_mut()
+                        .map(|mic| mic.set_muted(muted))
+                    {
+                        let _ = self.endpoint.send(AudioSystemMessage::Error(err));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(result) = self.pipeline.proceed() {
+            result?;
+        }
+
+        for msg in self.notification_recv.iter() {
+            match msg {
+                AudioSystemElementMessage::Error(err) => {
+                    let _ = self.endpoint.send(AudioSystemMessage::Error(err));
+                }
+                AudioSystemElementMessage::AgcGainChanged { gain } => {
+                    let _ = self
+                        .endpoint
+                        .send(AudioSystemMessage::AgcGainChanged { gain });
+                }
+                AudioSystemElementMessage::SpeakingChanged { speaking } => {
+                    let _ = self
+                        .endpoint
+                        .send(AudioSystemMessage::SpeakingChanged { speaking });
+                }
+                AudioSystemElementMessage::VirtualMicrophoneVolumeChanged { volume, muted } => {
+                    let _ = self
+                        .endpoint
+                        .send(AudioSystemMessage::VirtualMicrophoneVolumeChanged { volume, muted });
+                }
+                AudioSystemElementMessage::VirtualMicrophoneInUseChanged { in_use } => {
+                    let _ = self
+                        .endpoint
+                        .send(AudioSystemMessage::VirtualMicrophoneInUseChanged { in_use });
+                }
+                _ => {}
+            }
+        }
+
+        Ok(())
     }
 }
 
@@ -192,6 +703,8 @@ pub struct AudioSystemBuilder {
 
     audio_decs_builders: Vec<Box<dyn AudioDecoderBuilder>>,
     virtual_mics_builders: Vec<Box<dyn VirtualMicrophoneBuilder>>,
+    audio_filters_builders: AudioFilterBuilders,
+    pipeline_config: AudioPipelineConfig,
 }
 
 impl AudioSystemBuilder {
@@ -201,6 +714,8 @@ impl AudioSystemBuilder {
 
             audio_decs_builders: vec![],
             virtual_mics_builders: vec![],
+            audio_filters_builders: AudioFilterBuilders::default(),
+            pipeline_config: AudioPipelineConfig::default(),
         }
     }
 
@@ -216,6 +731,37 @@ impl AudioSystemBuilder {
         self.virtual_mics_builders.push(Box::new(builder));
         self
     }
+
+    /// The filter is chained only if the pipeline config lists it in the section
+    /// of the audio which it processes.
+    pub fn add_encoded_audio_filter<B>(mut self, builder: B) -> Self
+    where
+        B: ChainedAudioFilterBuilder<EncodedAudioBuffer> + 'static,
+    {
+        self.audio_filters_builders.encoded.push(Box::new(builder));
+        self
+    }
+
+    pub fn add_decoded_audio_filter<B>(mut self, builder: B) -> Self
+    where
+        B: ChainedAudioFilterBuilder<TimestampedRawAudioBuffer> + 'static,
+    {
+        self.audio_filters_builders.decoded.push(Box::new(builder));
+        self
+    }
+
+    pub fn add_audio_filter<B: ChainedAudioFilterBuilder<RawAudioBuffer> + 'static>(
+        mut self,
+        builder: B,
+    ) -> Self {
+        self.audio_filters_builders.raw.push(Box::new(builder));
+        self
+    }
+
+    pub fn set_pipeline_config(mut self, config: AudioPipelineConfig) -> Self {
+        self.pipeline_config = config;
+        self
+    }
 }
 
 impl ComponentBuilder for AudioSystemBuilder {
@@ -230,6 +776,8 @@ impl ComponentBuilder for AudioSystemBuilder {
             end,
             audio_decs_builders,
             virtual_mics_builders,
+            audio_filters_builders,
+            pipeline_config,
         } = *self;
         let end = end.expect("An audio system endpoint wasn't provided");
 
@@ -237,6 +785,8 @@ impl ComponentBuilder for AudioSystemBuilder {
             end,
             audio_decs_builders,
             virtual_mics_builders,
+            audio_filters_builders,
+            pipeline_config,
         )))
     }
 }
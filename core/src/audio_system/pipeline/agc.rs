@@ -0,0 +1,284 @@
+#[cfg(test)]
+mod tests;
+
+use crate::error;
+use crate::util::{ClockTime, Element, Runnable};
+
+use crate::audio_system::audio::RawAudioBuffer;
+use crate::audio_system::element::{
+    AudioFilter, AudioSink, AudioSource, AudioSystemElementMessage,
+};
+
+use super::filter_chain::{AudioFilterInfo, ChainedAudioFilter};
+
+use mueue::*;
+
+/// The gain is recomputed for every block of this duration.
+const BLOCK_DURATION: ClockTime = ClockTime::from_millis(10);
+/// The time constant of the short-term RMS.
+const RMS_DURATION: ClockTime = ClockTime::from_millis(50);
+/// The time constant of the gain when it decreases.
+const ATTACK_DURATION: ClockTime = ClockTime::from_millis(50);
+/// The time constant of the gain when it increases.
+const RELEASE_DURATION: ClockTime = ClockTime::from_millis(500);
+
+/// Smaller changes of the gain aren't reported.
+const REPORT_THRESHOLD_DB: f64 = 0.5;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AgcParams {
+    /// The RMS level in dBFS which the gain brings the audio to.
+    pub target_level: f64,
+    /// The largest gain in dB.
+    pub max_gain: f64,
+    /// The audio which is quieter than this level in dBFS doesn't change the gain.
+    pub noise_floor: f64,
+}
+
+impl Default for AgcParams {
+    fn default() -> Self {
+        Self {
+            target_level: -18.0,
+            max_gain: 24.0,
+            noise_floor: -55.0,
+        }
+    }
+}
+
+/// Brings the short-term RMS level of the audio to the target one by
+/// gradually changing its gain.
+pub struct AutomaticGainControl {
+    send: MessageSender<AudioSystemElementMessage>,
+    input: Option<MessageReceiver<RawAudioBuffer>>,
+    output: Option<MessageSender<RawAudioBuffer>>,
+
+    enabled: bool,
+    params: AgcParams,
+
+    mean_square: f64,
+    gain: f64,
+    reported_gain: f64,
+}
+
+impl AutomaticGainControl {
+    pub fn new(send: MessageSender<AudioSystemElementMessage>) -> Self {
+        Self {
+            send,
+            input: None,
+            output: None,
+
+            enabled: false,
+            params: AgcParams::default(),
+
+            mean_square: 0.0,
+            gain: 1.0,
+            reported_gain: 1.0,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    pub fn params(&self) -> AgcParams {
+        self.params
+    }
+
+    pub fn set_params(&mut self, params: AgcParams) {
+        self.params = params;
+    }
+
+    /// The linear gain which is currently applied.
+    pub fn gain(&self) -> f64 {
+        self.gain
+    }
+
+    fn process(&mut self, mut audio: RawAudioBuffer) -> RawAudioBuffer {
+        // The gain returns to unity smoothly after the control is disabled.
+        if !self.enabled && self.gain == 1.0 {
+            return audio;
+        }
+
+        let format = audio.format();
+        let channels = usize::from(audio.channels());
+        let sample_rate = audio.sample_rate();
+        if format.no_bytes() == 0 || channels == 0 || sample_rate == 0 {
+            return audio;
+        }
+
+        let block_frames = BLOCK_DURATION.to_no_samples(sample_rate).max(1);
+        let rms_coeff = smoothing_coeff(RMS_DURATION, block_frames, sample_rate);
+        let attack_coeff = smoothing_coeff(ATTACK_DURATION, block_frames, sample_rate);
+        let release_coeff = smoothing_coeff(RELEASE_DURATION, block_frames, sample_rate);
+
+        for block in audio
+            .as_slice_mut()
+            .chunks_mut(block_frames * channels * format.no_bytes())
+        {
+            let samples: Vec<f64> = block
+                .chunks_exact(format.no_bytes())
+                .map(|bytes| format.read_normalized_sample(bytes))
+                .collect();
+            if samples.is_empty() {
+                continue;
+            }
+
+            let mean_square =
+                samples.iter().map(|sample| sample * sample).sum::<f64>() / samples.len() as f64;
+            // The pauses are gated by the level of the block, so that neither the
+            // short-term RMS nor the gain follow the noise.
+            let is_pause = power_to_db(mean_square) < self.params.noise_floor;
+            if !is_pause {
+                self.mean_square += (mean_square - self.mean_square) * rms_coeff;
+            }
+
+            let desired_gain = if is_pause && self.enabled {
+                self.gain
+            } else {
+                self.desired_gain()
+            };
+            let coeff = if desired_gain < self.gain {
+                attack_coeff
+            } else {
+                release_coeff
+            };
+            let prev_gain = self.gain;
+            self.gain += (desired_gain - self.gain) * coeff;
+            if (self.gain - 1.0).abs() < 1e-4 && desired_gain == 1.0 {
+                self.gain = 1.0;
+            }
+
+            // The gain changes linearly within the block to avoid zipper noise.
+            let no_frames = samples.len() / channels;
+            for (no_sample, (sample, bytes)) in samples
+                .iter()
+                .zip(block.chunks_exact_mut(format.no_bytes()))
+                .enumerate()
+            {
+                let progress = (no_sample / channels + 1) as f64 / no_frames as f64;
+                let gain = prev_gain + (self.gain - prev_gain) * progress;
+
+                format.write_normalized_sample(sample * gain, bytes);
+            }
+        }
+
+        self.report_gain();
+
+        audio
+    }
+
+    fn desired_gain(&self) -> f64 {
+        if !self.enabled {
+            return 1.0;
+        }
+
+        let level = power_to_db(self.mean_square);
+        let gain = (self.params.target_level - level).min(self.params.max_gain);
+        db_to_gain(gain)
+    }
+
+    fn report_gain(&mut self) {
+        let change = gain_to_db(self.gain) - gain_to_db(self.reported_gain);
+        let returned_to_unity = self.gain == 1.0 && self.reported_gain != 1.0;
+        if change.abs() < REPORT_THRESHOLD_DB && !returned_to_unity {
+            return;
+        }
+
+        self.reported_gain = self.gain;
+        let _ = self
+            .send
+            .send(AudioSystemElementMessage::AgcGainChanged { gain: self.gain });
+    }
+}
+
+fn smoothing_coeff(time_constant: ClockTime, block_frames: usize, sample_rate: u32) -> f64 {
+    let time_constant_frames = time_constant.to_no_samples(sample_rate).max(1) as f64;
+
+    1.0 - (-(block_frames as f64) / time_constant_frames).exp()
+}
+
+fn power_to_db(power: f64) -> f64 {
+    10.0 * power.max(f64::MIN_POSITIVE).log10()
+}
+
+fn gain_to_db(gain: f64) -> f64 {
+    20.0 * gain.max(f64::MIN_POSITIVE).log10()
+}
+
+fn db_to_gain(db: f64) -> f64 {
+    10f64.powf(db / 20.0)
+}
+
+impl Runnable for AutomaticGainControl {
+    fn update(&mut self) -> error::Result<()> {
+        let Some(input) = self.input.clone() else {
+            return Ok(());
+        };
+
+        for audio in input.iter() {
+            let audio = self.process(audio);
+
+            if let Some(output) = self.output.as_ref() {
+                let _ = output.send(audio);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Element for AutomaticGainControl {
+    type Message = AudioSystemElementMessage;
+
+    fn sender(&self) -> MessageSender<Self::Message> {
+        self.send.clone()
+    }
+
+    fn connect(&mut self, send: MessageSender<Self::Message>) {
+        self.send = send;
+    }
+}
+
+impl AudioSink<RawAudioBuffer> for AutomaticGainControl {
+    fn input(&self) -> Option<MessageReceiver<RawAudioBuffer>> {
+        self.input.clone()
+    }
+
+    fn set_input(&mut self, input: MessageReceiver<RawAudioBuffer>) {
+        self.input = Some(input);
+    }
+
+    fn unset_input(&mut self) {
+        self.input = None;
+    }
+}
+
+impl AudioSource<RawAudioBuffer> for AutomaticGainControl {
+    fn output(&self) -> Option<MessageSender<RawAudioBuffer>> {
+        self.output.clone()
+    }
+
+    fn set_output(&mut self, output: MessageSender<RawAudioBuffer>) {
+        self.output = Some(output);
+    }
+
+    fn unset_output(&mut self) {
+        self.output = None;
+    }
+}
+
+impl AudioFilter<RawAudioBuffer, RawAudioBuffer> for AutomaticGainControl {}
+
+impl ChainedAudioFilter<RawAudioBuffer> for AutomaticGainControl {
+    fn info(&self) -> AudioFilterInfo {
+        AudioFilterInfo::new("automatic_gain_control")
+    }
+
+    fn is_active(&self) -> bool {
+        self.enabled || self.gain != 1.0
+    }
+}
@@ -0,0 +1,111 @@
+use super::*;
+
+use crate::audio_system::audio::RawAudioFormat;
+
+use std::f64::consts::PI;
+
+const SAMPLE_RATE: u32 = 48000;
+const FREQUENCY: f64 = 440.0;
+const BUFFER_FRAMES: usize = 960;
+
+/// A tone with the given RMS level in dBFS.
+fn tone(level: f64, no_buf: usize) -> RawAudioBuffer {
+    let amplitude = db_to_gain(level) * 2f64.sqrt();
+    let first_frame = no_buf * BUFFER_FRAMES;
+    let samples: Vec<f64> = (first_frame..first_frame + BUFFER_FRAMES)
+        .map(|n| amplitude * (2.0 * PI * FREQUENCY * n as f64 / SAMPLE_RATE as f64).sin())
+        .collect();
+
+    RawAudioBuffer::from_normalized_samples(&samples, RawAudioFormat::S16LE, 1, SAMPLE_RATE)
+}
+
+fn rms_level(audio: &RawAudioBuffer) -> f64 {
+    let samples = audio.to_normalized_samples();
+    let mean_square =
+        samples.iter().map(|sample| sample * sample).sum::<f64>() / samples.len() as f64;
+
+    power_to_db(mean_square)
+}
+
+/// Processes 3 seconds of the tone and returns the last buffer.
+fn process_tone(agc: &mut AutomaticGainControl, level: f64) -> RawAudioBuffer {
+    let in_send = agc.create_input();
+    let out_recv = agc.create_output();
+
+    for no_buf in 0..150 {
+        let _ = in_send.send(tone(level, no_buf));
+    }
+    let _ = agc.update();
+
+    out_recv.iter().last().unwrap()
+}
+
+#[test]
+fn test_pass_when_disabled() {
+    let (send, notification_recv) = unidirectional_queue();
+    let mut agc = AutomaticGainControl::new(send);
+
+    assert_eq!(process_tone(&mut agc, -40.0), tone(-40.0, 149));
+    assert!(notification_recv.recv().is_none());
+}
+
+#[test]
+fn test_reach_target_level() {
+    let (send, _) = unidirectional_queue();
+    let mut agc = AutomaticGainControl::new(send);
+    agc.set_enabled(true);
+
+    let audio = process_tone(&mut agc, -35.0);
+    assert!((rms_level(&audio) - agc.params().target_level).abs() < 1.0);
+
+    let audio = process_tone(&mut agc, -6.0);
+    assert!((rms_level(&audio) - agc.params().target_level).abs() < 1.0);
+}
+
+#[test]
+fn test_limit_gain() {
+    let (send, _) = unidirectional_queue();
+    let mut agc = AutomaticGainControl::new(send);
+    agc.set_enabled(true);
+    agc.set_params(AgcParams {
+        max_gain: 12.0,
+        ..AgcParams::default()
+    });
+
+    let audio = process_tone(&mut agc, -50.0);
+    assert!((rms_level(&audio) - -38.0).abs() < 1.0);
+    assert!(gain_to_db(agc.gain()) <= 12.0);
+}
+
+#[test]
+fn test_hold_gain_below_noise_floor() {
+    let (send, _) = unidirectional_queue();
+    let mut agc = AutomaticGainControl::new(send);
+    agc.set_enabled(true);
+
+    let _ = process_tone(&mut agc, -30.0);
+    let gain = agc.gain();
+
+    let _ = process_tone(&mut agc, -70.0);
+    assert!((agc.gain() - gain).abs() < gain * 0.01);
+}
+
+#[test]
+fn test_report_gain() {
+    let (send, notification_recv) = unidirectional_queue();
+    let mut agc = AutomaticGainControl::new(send);
+    agc.set_enabled(true);
+
+    let _ = process_tone(&mut agc, -30.0);
+
+    let gains: Vec<f64> = notification_recv
+        .iter()
+        .filter_map(|msg| match msg {
+            AudioSystemElementMessage::AgcGainChanged { gain } => Some(gain),
+            _ => None,
+        })
+        .collect();
+    assert!(gains.len() > 1);
+    assert!(gains.windows(2).all(|pair| pair[1] > pair[0]));
+    assert!(gain_to_db(agc.gain()) - gain_to_db(*gains.last().unwrap()) < REPORT_THRESHOLD_DB);
+}
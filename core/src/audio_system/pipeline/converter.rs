@@ -0,0 +1,264 @@
+#[cfg(test)]
+mod tests;
+
+use crate::error;
+use crate::util::{Element, Runnable};
+
+use crate::audio_system::audio::{map_normalized_channels, RawAudioBuffer, RawAudioFormat};
+use crate::audio_system::element::{
+    AudioFilter, AudioSink, AudioSource, AudioSystemElementMessage,
+};
+use crate::audio_system::pipeline::resampler::SincResampler;
+
+use mueue::*;
+
+const DITHER_SEED: u32 = 0x2545_f491;
+
+/// Converts the samples of the audio to the target format, so that the elements
+/// after it don't have to adapt to the format changes of the stream.
+pub struct AudioConverter {
+    send: MessageSender<AudioSystemElementMessage>,
+    input: Option<MessageReceiver<RawAudioBuffer>>,
+    output: Option<MessageSender<RawAudioBuffer>>,
+
+    target_format: Option<RawAudioFormat>,
+    source_format: Option<RawAudioFormat>,
+    dither: TriangularDither,
+}
+
+impl AudioConverter {
+    pub fn new(send: MessageSender<AudioSystemElementMessage>) -> Self {
+        Self {
+            send,
+            input: None,
+            output: None,
+
+            target_format: None,
+            source_format: None,
+            dither: TriangularDither::new(DITHER_SEED),
+        }
+    }
+
+    pub fn target_format(&self) -> Option<RawAudioFormat> {
+        self.target_format
+    }
+
+    /// The audio passes unchanged if the target format isn't set.
+    pub fn set_target_format(&mut self, target_format: Option<RawAudioFormat>) {
+        self.target_format = target_format.filter(|format| *format != RawAudioFormat::Unspecified);
+    }
+
+    /// The format of the last audio which came in, whether it was converted
+    /// or not.
+    pub fn source_format(&self) -> Option<RawAudioFormat> {
+        self.source_format
+    }
+
+    fn convert(&mut self, audio: RawAudioBuffer) -> RawAudioBuffer {
+        let format = audio.format();
+        if format != RawAudioFormat::Unspecified {
+            self.source_format = Some(format);
+        }
+
+        let Some(target_format) = self.target_format else {
+            return audio;
+        };
+
+        if format == target_format || format == RawAudioFormat::Unspecified {
+            return audio;
+        }
+
+        // The quantization error is decorrelated from the signal when the
+        // precision is reduced.
+        let dither_amplitude = if needs_dither(format, target_format) {
+            1.0 / f64::from(1u32 << (target_format.bit_depth() - 1))
+        } else {
+            0.0
+        };
+
+        let mut data = vec![0; audio.len() / format.no_bytes() * target_format.no_bytes()];
+        for (src, dst) in audio
+            .as_slice()
+            .chunks_exact(format.no_bytes())
+            .zip(data.chunks_exact_mut(target_format.no_bytes()))
+        {
+            let mut sample = format.read_normalized_sample(src);
+            if dither_amplitude != 0.0 {
+                sample += self.dither.next_sample() * dither_amplitude;
+            }
+
+            target_format.write_normalized_sample(sample, dst);
+        }
+
+        RawAudioBuffer::new(data, target_format, audio.channels(), audio.sample_rate())
+            .with_channel_layout(audio.channel_layout())
+    }
+}
+
+fn needs_dither(format: RawAudioFormat, target_format: RawAudioFormat) -> bool {
+    if target_format.is_float() {
+        return false;
+    }
+
+    format.is_float() || format.bit_depth() > target_format.bit_depth()
+}
+
+/// Produces the noise with the triangular distribution from -1.0 to 1.0, which
+/// is the sum of two uniformly distributed values.
+#[derive(Debug, Clone)]
+struct TriangularDither {
+    state: u32,
+}
+
+impl TriangularDither {
+    fn new(seed: u32) -> Self {
+        Self { state: seed.max(1) }
+    }
+
+    fn next_sample(&mut self) -> f64 {
+        self.next_uniform() + self.next_uniform() - 1.0
+    }
+
+    // Xorshift is good enough for the noise and doesn't need any dependencies.
+    fn next_uniform(&mut self) -> f64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 17;
+        self.state ^= self.state << 5;
+
+        f64::from(self.state) / f64::from(u32::MAX)
+    }
+}
+
+impl Runnable for AudioConverter {
+    fn update(&mut self) -> error::Result<()> {
+        let Some(input) = self.input.clone() else {
+            return Ok(());
+        };
+
+        for audio in input.iter() {
+            let audio = self.convert(audio);
+
+            if let Some(output) = self.output.as_ref() {
+                let _ = output.send(audio);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Element for AudioConverter {
+    type Message = AudioSystemElementMessage;
+
+    fn sender(&self) -> MessageSender<Self::Message> {
+        self.send.clone()
+    }
+
+    fn connect(&mut self, send: MessageSender<Self::Message>) {
+        self.send = send;
+    }
+}
+
+impl AudioSink<RawAudioBuffer> for AudioConverter {
+    fn input(&self) -> Option<MessageReceiver<RawAudioBuffer>> {
+        self.input.clone()
+    }
+
+    fn set_input(&mut self, input: MessageReceiver<RawAudioBuffer>) {
+        self.input = Some(input);
+    }
+
+    fn unset_input(&mut self) {
+        self.input = None;
+    }
+}
+
+impl AudioSource<RawAudioBuffer> for AudioConverter {
+    fn output(&self) -> Option<MessageSender<RawAudioBuffer>> {
+        self.output.clone()
+    }
+
+    fn set_output(&mut self, output: MessageSender<RawAudioBuffer>) {
+        self.output = Some(output);
+    }
+
+    fn unset_output(&mut self) {
+        self.output = None;
+    }
+}
+
+impl AudioFilter<RawAudioBuffer, RawAudioBuffer> for AudioConverter {}
+
+/// Adapts the raw audio to the fixed format, number of channels and sample rate
+/// of a virtual microphone, which can't make the pipeline produce them.
+pub struct RawAudioAdapter {
+    format: RawAudioFormat,
+    channels: u8,
+    sample_rate: u32,
+
+    // The resampler continues the stream while its sample rate stays the same.
+    resampler: Option<(u32, SincResampler)>,
+}
+
+impl RawAudioAdapter {
+    pub fn new(format: RawAudioFormat, channels: u8, sample_rate: u32) -> Self {
+        Self {
+            format,
+            channels,
+            sample_rate,
+
+            resampler: None,
+        }
+    }
+
+    /// The audio which matches the target passes unchanged.
+    pub fn adapt(&mut self, audio: RawAudioBuffer) -> RawAudioBuffer {
+        if audio.format() == self.format
+            && audio.channels() == self.channels
+            && audio.sample_rate() == self.sample_rate
+        {
+            return audio;
+        }
+
+        let samples = self.adapt_normalized(&audio);
+
+        RawAudioBuffer::from_normalized_samples(
+            &samples,
+            self.format,
+            self.channels,
+            self.sample_rate,
+        )
+    }
+
+    /// Maps the channels and resamples the audio, but leaves the samples
+    /// normalized instead of writing them in the target format.
+    pub fn adapt_normalized(&mut self, audio: &RawAudioBuffer) -> Vec<f64> {
+        let samples = map_normalized_channels(
+            &audio.to_normalized_samples(),
+            audio.channels(),
+            self.channels,
+        );
+
+        if audio.sample_rate() == self.sample_rate || audio.sample_rate() == 0 {
+            return samples;
+        }
+
+        let resampler = match &mut self.resampler {
+            Some((input_rate, resampler)) if *input_rate == audio.sample_rate() => resampler,
+            resampler => {
+                let ratio = f64::from(self.sample_rate) / f64::from(audio.sample_rate());
+                let new_resampler = SincResampler::new(self.channels, ratio);
+
+                &mut resampler.insert((audio.sample_rate(), new_resampler)).1
+            }
+        };
+
+        resampler.process(&samples)
+    }
+
+    /// Forgets the audio which the resampler holds back, e.g. when the stream
+    /// is stopped.
+    pub fn reset(&mut self) {
+        self.resampler = None;
+    }
+}
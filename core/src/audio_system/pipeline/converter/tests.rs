@@ -0,0 +1,149 @@
+use super::*;
+
+const SAMPLE_RATE: u32 = 48000;
+
+const FORMATS: [RawAudioFormat; 9] = [
+    RawAudioFormat::U8,
+    RawAudioFormat::S16LE,
+    RawAudioFormat::S16BE,
+    RawAudioFormat::S24LE,
+    RawAudioFormat::S24BE,
+    RawAudioFormat::S32LE,
+    RawAudioFormat::S32BE,
+    RawAudioFormat::F32LE,
+    RawAudioFormat::F32BE,
+];
+
+fn ramp(format: RawAudioFormat) -> RawAudioBuffer {
+    let samples: Vec<f64> = (0..200).map(|n| n as f64 / 100.0 - 1.0).collect();
+
+    RawAudioBuffer::from_normalized_samples(&samples, format, 2, SAMPLE_RATE)
+}
+
+fn convert(target_format: RawAudioFormat, audio: RawAudioBuffer) -> RawAudioBuffer {
+    let (send, _) = unidirectional_queue();
+    let mut conv = AudioConverter::new(send);
+    conv.set_target_format(Some(target_format));
+
+    let in_send = conv.create_input();
+    let out_recv = conv.create_output();
+
+    let _ = in_send.send(audio);
+    let _ = conv.update();
+
+    out_recv.recv().unwrap()
+}
+
+#[test]
+fn test_pass_without_target_format() {
+    let (send, _) = unidirectional_queue();
+    let mut conv = AudioConverter::new(send);
+
+    let in_send = conv.create_input();
+    let out_recv = conv.create_output();
+
+    let _ = in_send.send(ramp(RawAudioFormat::S24LE));
+    let _ = conv.update();
+
+    assert_eq!(out_recv.recv().unwrap(), ramp(RawAudioFormat::S24LE));
+}
+
+#[test]
+fn test_convert_all_formats() {
+    for format in FORMATS {
+        for target_format in FORMATS {
+            let audio = convert(target_format, ramp(format));
+            assert_eq!(audio.format(), target_format);
+            assert_eq!(audio.channels(), 2);
+            assert_eq!(audio.sample_rate(), SAMPLE_RATE);
+            assert_eq!(audio.no_samples(), 100);
+
+            // Both the rounding and the dither can't change a sample by more than
+            // two steps of the least precise format.
+            let tolerance = 2.0 / 128.0 + f64::EPSILON;
+            for (expected, converted) in ramp(format)
+                .to_normalized_samples()
+                .iter()
+                .zip(audio.to_normalized_samples())
+            {
+                assert!(
+                    (expected - converted).abs() <= tolerance,
+                    "{format:?} -> {target_format:?}: {expected} != {converted}"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_dither_when_reducing_bit_depth() {
+    // A quarter of the step of S16 is lost without the dither.
+    let level = 0.25 / f64::from(1 << 15);
+    let samples = vec![level; 48000];
+    let audio =
+        RawAudioBuffer::from_normalized_samples(&samples, RawAudioFormat::S32LE, 1, SAMPLE_RATE);
+
+    let converted = convert(RawAudioFormat::S16LE, audio).to_normalized_samples();
+    assert!(converted.iter().any(|sample| *sample != 0.0));
+
+    let mean = converted.iter().sum::<f64>() / converted.len() as f64;
+    assert!((mean - level).abs() < level * 0.2);
+}
+
+#[test]
+fn test_dont_dither_when_keeping_bit_depth() {
+    let audio = ramp(RawAudioFormat::S16LE);
+    let converted = convert(RawAudioFormat::S32BE, audio.clone());
+
+    assert_eq!(
+        converted.to_normalized_samples(),
+        audio.to_normalized_samples()
+    );
+}
+
+#[test]
+fn test_adapt_raw_audio() {
+    let mut adapter = RawAudioAdapter::new(RawAudioFormat::S16LE, 2, SAMPLE_RATE);
+
+    let audio = ramp(RawAudioFormat::S16LE);
+    assert_eq!(adapter.adapt(audio.clone()), audio);
+
+    let mono =
+        RawAudioBuffer::from_normalized_samples(&[0.5; 100], RawAudioFormat::F32LE, 1, SAMPLE_RATE);
+    let adapted = adapter.adapt(mono);
+    assert_eq!(adapted.format(), RawAudioFormat::S16LE);
+    assert_eq!(adapted.channels(), 2);
+    assert_eq!(adapted.no_samples(), 100);
+    assert!(adapted
+        .to_normalized_samples()
+        .iter()
+        .all(|sample| *sample == 0.5));
+}
+
+#[test]
+fn test_adapt_sample_rate() {
+    const NO_BUFFERS: usize = 10;
+    const NO_SAMPLES: usize = 480;
+
+    let mut adapter = RawAudioAdapter::new(RawAudioFormat::F32LE, 1, SAMPLE_RATE);
+
+    // The resampler continues the stream from one buffer to the next.
+    let no_samples: usize = (0..NO_BUFFERS)
+        .map(|_| {
+            let audio = RawAudioBuffer::from_normalized_samples(
+                &[0.5; NO_SAMPLES],
+                RawAudioFormat::S16LE,
+                1,
+                SAMPLE_RATE / 2,
+            );
+            let adapted = adapter.adapt(audio);
+            assert_eq!(adapted.sample_rate(), SAMPLE_RATE);
+
+            adapted.no_samples()
+        })
+        .sum();
+
+    let latency = SincResampler::new(1, 2.0).latency();
+    assert!(no_samples <= NO_BUFFERS * NO_SAMPLES * 2);
+    assert!(no_samples + latency * 2 + 2 >= NO_BUFFERS * NO_SAMPLES * 2);
+}
@@ -1,14 +1,35 @@
 use crate::{
-    audio_system::audio::{AudioCodec, EncodedAudioHeader},
+    audio_system::audio::{
+        AudioCodec, ChannelLayout, EncodedAudioExtension, EncodedAudioFlags, EncodedAudioHeader,
+    },
     util::ClockTime,
 };
 
 use super::*;
 
-#[test]
-fn test_demux() {
-    const TS_IN_NANOS: u64 = 100000000;
+const TS_IN_NANOS: u64 = 100000000;
+
+fn v1_muxed_buf(codec: AudioCodec, channels: u8, channel_layout: ChannelLayout) -> Vec<u8> {
+    let mut data = vec![0x81, codec as u8];
+
+    data.extend_from_slice(&48000u32.to_be_bytes());
+    data.push(channels);
+    data.push(channel_layout as u8);
+    data.push(0b110);
+    data.extend_from_slice(&7u32.to_be_bytes());
+    data.extend_from_slice(&TS_IN_NANOS.to_be_bytes());
+
+    data.extend_from_slice(&5u16.to_be_bytes());
+    data.extend_from_slice(&[1, 0, 2, 24, 42]);
 
+    data.extend_from_slice(&16u32.to_be_bytes());
+    data.extend_from_slice(&[42; 16]);
+
+    data
+}
+
+#[test]
+fn test_demux_v0() {
     let (send, _) = unidirectional_queue();
     let mut demuxer = AudioDemuxer::new(send);
 
@@ -28,10 +49,122 @@ fn test_demux() {
         header: EncodedAudioHeader {
             codec: AudioCodec::Opus,
             sample_rate: 48000,
+            channels: 1,
+            channel_layout: ChannelLayout::Mono,
         },
+        sequence_number: None,
+        flags: EncodedAudioFlags::default(),
+        extensions: Vec::new(),
         start_ts: Some(ClockTime::from_nanos(TS_IN_NANOS)),
         data: vec![42; 16],
     };
 
     assert_eq!(encoded_buf, expected_encoded_buf);
 }
+
+#[test]
+fn test_demux_v1() {
+    let (send, _) = unidirectional_queue();
+    let mut demuxer = AudioDemuxer::new(send);
+
+    let muxed_buf = v1_muxed_buf(AudioCodec::Opus, 2, ChannelLayout::Stereo);
+    demuxer.push(MuxedAudioBuffer(muxed_buf));
+
+    let encoded_buf = demuxer.pull().unwrap();
+    let expected_encoded_buf = EncodedAudioBuffer {
+        header: EncodedAudioHeader {
+            codec: AudioCodec::Opus,
+            sample_rate: 48000,
+            channels: 2,
+            channel_layout: ChannelLayout::Stereo,
+        },
+        sequence_number: Some(7),
+        flags: EncodedAudioFlags {
+            silence: false,
+            dtx: true,
+            discontinuity: true,
+        },
+        extensions: vec![EncodedAudioExtension {
+            kind: 1,
+            data: vec![24, 42],
+        }],
+        start_ts: Some(ClockTime::from_nanos(TS_IN_NANOS)),
+        data: vec![42; 16],
+    };
+
+    assert_eq!(encoded_buf, expected_encoded_buf);
+    assert_eq!(
+        encoded_buf.to_muxed().unwrap().0,
+        v1_muxed_buf(AudioCodec::Opus, 2, ChannelLayout::Stereo)
+    );
+}
+
+#[test]
+fn test_mux_extensions_limit() {
+    let (send, _) = unidirectional_queue();
+    let mut demuxer = AudioDemuxer::new(send);
+
+    let mut encoded_buf = EncodedAudioBuffer {
+        header: EncodedAudioHeader {
+            codec: AudioCodec::Opus,
+            sample_rate: 48000,
+            channels: 1,
+            channel_layout: ChannelLayout::Mono,
+        },
+        sequence_number: Some(7),
+        flags: EncodedAudioFlags::default(),
+        extensions: vec![
+            EncodedAudioExtension {
+                kind: 1,
+                data: vec![24; 60000],
+            },
+            EncodedAudioExtension {
+                kind: 2,
+                data: vec![42; usize::from(u16::MAX) - 60000 - 2 * 3],
+            },
+        ],
+        start_ts: Some(ClockTime::from_nanos(TS_IN_NANOS)),
+        data: vec![42; 16],
+    };
+
+    demuxer.push(encoded_buf.to_muxed().unwrap());
+    assert_eq!(demuxer.pull().unwrap(), encoded_buf);
+
+    encoded_buf.extensions[1].data.push(42);
+    assert!(matches!(
+        encoded_buf.to_muxed(),
+        Err(error::Error::EncodedAudioTooLong)
+    ));
+}
+
+#[test]
+fn test_demux_inconsistent_channels() {
+    let (send, _) = unidirectional_queue();
+    let mut demuxer = AudioDemuxer::new(send);
+
+    let muxed_buf = v1_muxed_buf(AudioCodec::Opus, 1, ChannelLayout::Stereo);
+    demuxer.push(MuxedAudioBuffer(muxed_buf));
+
+    assert!(demuxer.pull().is_none());
+}
+
+#[test]
+fn test_demux_malformed() {
+    let (send, _) = unidirectional_queue();
+    let mut demuxer = AudioDemuxer::new(send);
+
+    let mut unknown_version = v1_muxed_buf(AudioCodec::Opus, 1, ChannelLayout::Mono);
+    unknown_version[0] = 0x82;
+    demuxer.push(MuxedAudioBuffer(unknown_version));
+    assert!(demuxer.pull().is_none());
+
+    let mut truncated_payload = v1_muxed_buf(AudioCodec::Opus, 1, ChannelLayout::Mono);
+    truncated_payload.pop();
+    demuxer.push(MuxedAudioBuffer(truncated_payload));
+    assert!(demuxer.pull().is_none());
+
+    let mut truncated_extension = v1_muxed_buf(AudioCodec::Opus, 1, ChannelLayout::Mono);
+    truncated_extension[22] = 6;
+    demuxer.push(MuxedAudioBuffer(truncated_extension));
+    assert!(demuxer.pull().is_none());
+}
@@ -0,0 +1,358 @@
+#[cfg(test)]
+mod tests;
+
+use crate::error;
+use crate::util::{ClockTime, Element, Runnable};
+
+use crate::audio_system::audio::{RawAudioBuffer, RawAudioInfo};
+use crate::audio_system::element::{
+    AudioFilter, AudioSink, AudioSource, AudioSystemElementMessage,
+};
+
+use super::filter_chain::{AudioFilterInfo, ChainedAudioFilter};
+
+use std::collections::VecDeque;
+
+use mueue::*;
+
+/// The limiter sees the peaks this long before they are played, so it reduces
+/// the gain in advance. It's also the latency of the element.
+const LOOKAHEAD_DURATION: ClockTime = ClockTime::from_millis(5);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LimiterParams {
+    /// The peak level in dBFS which the audio never exceeds.
+    pub threshold: f64,
+    /// How long it takes to restore the gain after the peak.
+    pub release: ClockTime,
+}
+
+impl Default for LimiterParams {
+    fn default() -> Self {
+        Self {
+            threshold: -1.0,
+            release: ClockTime::from_millis(50),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CompressorParams {
+    /// The level in dBFS above which the audio is compressed.
+    pub threshold: f64,
+    /// How many dB above the threshold at the input become one dB at the output.
+    pub ratio: f64,
+    /// The time constant of the level detector when the audio gets louder.
+    pub attack: ClockTime,
+    /// The time constant of the level detector when the audio gets quieter.
+    pub release: ClockTime,
+}
+
+impl Default for CompressorParams {
+    fn default() -> Self {
+        Self {
+            threshold: -20.0,
+            ratio: 4.0,
+            attack: ClockTime::from_millis(10),
+            release: ClockTime::from_millis(100),
+        }
+    }
+}
+
+/// Compresses the audio if enabled and limits its peaks, so that loud bursts
+/// don't clip. The gain of every channel is reduced independently.
+pub struct DynamicsProcessor {
+    send: MessageSender<AudioSystemElementMessage>,
+    input: Option<MessageReceiver<RawAudioBuffer>>,
+    output: Option<MessageSender<RawAudioBuffer>>,
+
+    limiter_params: LimiterParams,
+    compressor_params: CompressorParams,
+    compressor_enabled: bool,
+
+    stream_info: Option<RawAudioInfo>,
+    channel_states: Vec<ChannelState>,
+}
+
+impl DynamicsProcessor {
+    pub fn new(send: MessageSender<AudioSystemElementMessage>) -> Self {
+        Self {
+            send,
+            input: None,
+            output: None,
+
+            limiter_params: LimiterParams::default(),
+            compressor_params: CompressorParams::default(),
+            compressor_enabled: false,
+
+            stream_info: None,
+            channel_states: Vec::new(),
+        }
+    }
+
+    pub fn limiter_params(&self) -> LimiterParams {
+        self.limiter_params
+    }
+
+    /// The threshold of the limiter can't be above 0 dBFS.
+    pub fn set_limiter_params(&mut self, params: LimiterParams) {
+        self.limiter_params = LimiterParams {
+            threshold: params.threshold.min(0.0),
+            ..params
+        };
+    }
+
+    pub fn compressor_params(&self) -> CompressorParams {
+        self.compressor_params
+    }
+
+    /// The ratio is at least 1.0, which doesn't compress the audio.
+    pub fn set_compressor_params(&mut self, params: CompressorParams) {
+        self.compressor_params = CompressorParams {
+            ratio: params.ratio.max(1.0),
+            ..params
+        };
+    }
+
+    pub fn is_compressor_enabled(&self) -> bool {
+        self.compressor_enabled
+    }
+
+    pub fn set_compressor_enabled(&mut self, enabled: bool) {
+        self.compressor_enabled = enabled;
+    }
+
+    fn process(&mut self, audio: RawAudioBuffer) -> RawAudioBuffer {
+        if audio.format().no_bytes() == 0 || audio.channels() == 0 || audio.sample_rate() == 0 {
+            return audio;
+        }
+
+        let stream_info = audio.info();
+        if self.stream_info != Some(stream_info) {
+            let lookahead = LOOKAHEAD_DURATION
+                .to_no_samples(stream_info.sample_rate)
+                .max(1);
+
+            self.stream_info = Some(stream_info);
+            self.channel_states =
+                vec![ChannelState::new(lookahead); usize::from(stream_info.channels)];
+        }
+
+        let sample_rate = stream_info.sample_rate;
+        let limiter_threshold = db_to_gain(self.limiter_params.threshold);
+        let limiter_release = smoothing_coeff(self.limiter_params.release, sample_rate);
+        let compressor = self.compressor_enabled.then(|| Compressor {
+            threshold: db_to_gain(self.compressor_params.threshold),
+            slope: 1.0 - 1.0 / self.compressor_params.ratio,
+            attack: smoothing_coeff(self.compressor_params.attack, sample_rate),
+            release: smoothing_coeff(self.compressor_params.release, sample_rate),
+        });
+
+        let mut samples = audio.to_normalized_samples();
+        for frame in samples.chunks_exact_mut(self.channel_states.len()) {
+            for (sample, state) in frame.iter_mut().zip(self.channel_states.iter_mut()) {
+                if let Some(compressor) = compressor.as_ref() {
+                    *sample = state.compress(*sample, compressor);
+                }
+                *sample = state.limit(*sample, limiter_threshold, limiter_release);
+            }
+        }
+
+        RawAudioBuffer::from_normalized_samples(
+            &samples,
+            stream_info.format,
+            stream_info.channels,
+            stream_info.sample_rate,
+        )
+        .with_channel_layout(stream_info.channel_layout)
+    }
+
+    fn reset(&mut self) {
+        self.stream_info = None;
+        self.channel_states.clear();
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Compressor {
+    threshold: f64,
+    slope: f64,
+    attack: f64,
+    release: f64,
+}
+
+#[derive(Debug, Clone)]
+struct ChannelState {
+    lookahead: usize,
+    no_sample: usize,
+
+    // The samples which wait for the gain computed with the look-ahead.
+    delay: VecDeque<f64>,
+    // The ascending minima of the gains required by the samples in the window.
+    required_gains: VecDeque<(usize, f64)>,
+    // The minima of the required gains, which are averaged to smooth the attack.
+    window_gains: VecDeque<f64>,
+    window_gains_sum: f64,
+    limiter_gain: f64,
+
+    envelope: f64,
+}
+
+impl ChannelState {
+    fn new(lookahead: usize) -> Self {
+        Self {
+            lookahead,
+            no_sample: 0,
+
+            delay: vec![0.0; lookahead].into(),
+            required_gains: VecDeque::new(),
+            window_gains: vec![1.0; lookahead + 1].into(),
+            window_gains_sum: (lookahead + 1) as f64,
+            limiter_gain: 1.0,
+
+            envelope: 0.0,
+        }
+    }
+
+    fn compress(&mut self, sample: f64, compressor: &Compressor) -> f64 {
+        let level = sample.abs();
+        let coeff = if level > self.envelope {
+            compressor.attack
+        } else {
+            compressor.release
+        };
+        self.envelope += (level - self.envelope) * coeff;
+
+        if self.envelope <= compressor.threshold {
+            return sample;
+        }
+
+        // The gain in dB is (threshold - level) * slope.
+        sample * (compressor.threshold / self.envelope).powf(compressor.slope)
+    }
+
+    // Every gain of the window is at most the gain required by the delayed
+    // sample, so is their average, and the peaks never exceed the threshold.
+    fn limit(&mut self, sample: f64, threshold: f64, release: f64) -> f64 {
+        let required_gain = if sample.abs() > threshold {
+            threshold / sample.abs()
+        } else {
+            1.0
+        };
+
+        while self
+            .required_gains
+            .back()
+            .is_some_and(|(_, gain)| *gain >= required_gain)
+        {
+            self.required_gains.pop_back();
+        }
+        self.required_gains
+            .push_back((self.no_sample, required_gain));
+        while self
+            .required_gains
+            .front()
+            .is_some_and(|(no_sample, _)| no_sample + self.lookahead < self.no_sample)
+        {
+            self.required_gains.pop_front();
+        }
+        self.no_sample += 1;
+
+        let window_gain = self.required_gains.front().map_or(1.0, |(_, gain)| *gain);
+        self.window_gains.push_back(window_gain);
+        self.window_gains_sum += window_gain - self.window_gains.pop_front().unwrap_or(1.0);
+
+        let smoothed_gain = (self.window_gains_sum / self.window_gains.len() as f64).min(1.0);
+        self.limiter_gain = if smoothed_gain < self.limiter_gain {
+            smoothed_gain
+        } else {
+            self.limiter_gain + (smoothed_gain - self.limiter_gain) * release
+        };
+
+        self.delay.push_back(sample);
+        let delayed_sample = self.delay.pop_front().unwrap_or(0.0);
+
+        // The running sum may accumulate rounding errors.
+        (delayed_sample * self.limiter_gain).clamp(-threshold, threshold)
+    }
+}
+
+fn smoothing_coeff(time_constant: ClockTime, sample_rate: u32) -> f64 {
+    let time_constant_frames = time_constant.to_no_samples(sample_rate).max(1) as f64;
+
+    1.0 - (-1.0 / time_constant_frames).exp()
+}
+
+fn db_to_gain(db: f64) -> f64 {
+    10f64.powf(db / 20.0)
+}
+
+impl Runnable for DynamicsProcessor {
+    fn update(&mut self) -> error::Result<()> {
+        let Some(input) = self.input.clone() else {
+            return Ok(());
+        };
+
+        for audio in input.iter() {
+            let audio = self.process(audio);
+
+            if let Some(output) = self.output.as_ref() {
+                let _ = output.send(audio);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn on_stop(&mut self) {
+        self.reset();
+    }
+}
+
+impl Element for DynamicsProcessor {
+    type Message = AudioSystemElementMessage;
+
+    fn sender(&self) -> MessageSender<Self::Message> {
+        self.send.clone()
+    }
+
+    fn connect(&mut self, send: MessageSender<Self::Message>) {
+        self.send = send;
+    }
+}
+
+impl AudioSink<RawAudioBuffer> for DynamicsProcessor {
+    fn input(&self) -> Option<MessageReceiver<RawAudioBuffer>> {
+        self.input.clone()
+    }
+
+    fn set_input(&mut self, input: MessageReceiver<RawAudioBuffer>) {
+        self.input = Some(input);
+    }
+
+    fn unset_input(&mut self) {
+        self.input = None;
+    }
+}
+
+impl AudioSource<RawAudioBuffer> for DynamicsProcessor {
+    fn output(&self) -> Option<MessageSender<RawAudioBuffer>> {
+        self.output.clone()
+    }
+
+    fn set_output(&mut self, output: MessageSender<RawAudioBuffer>) {
+        self.output = Some(output);
+    }
+
+    fn unset_output(&mut self) {
+        self.output = None;
+    }
+}
+
+impl AudioFilter<RawAudioBuffer, RawAudioBuffer> for DynamicsProcessor {}
+
+impl ChainedAudioFilter<RawAudioBuffer> for DynamicsProcessor {
+    fn info(&self) -> AudioFilterInfo {
+        AudioFilterInfo::new("dynamics_processor")
+    }
+}
@@ -0,0 +1,139 @@
+use super::*;
+
+use crate::audio_system::audio::RawAudioFormat;
+
+use std::f64::consts::PI;
+
+const SAMPLE_RATE: u32 = 48000;
+const FREQUENCY: f64 = 1000.0;
+
+fn tone(amplitude: f64, n: usize) -> f64 {
+    amplitude * (2.0 * PI * FREQUENCY * n as f64 / f64::from(SAMPLE_RATE)).sin()
+}
+
+fn process(dynamics: &mut DynamicsProcessor, samples: &[f64], channels: u8) -> Vec<f64> {
+    let in_send = dynamics.create_input();
+    let out_recv = dynamics.create_output();
+
+    let _ = in_send.send(RawAudioBuffer::from_normalized_samples(
+        samples,
+        RawAudioFormat::F32LE,
+        channels,
+        SAMPLE_RATE,
+    ));
+    let _ = dynamics.update();
+
+    out_recv.recv().unwrap().to_normalized_samples()
+}
+
+fn lookahead() -> usize {
+    LOOKAHEAD_DURATION.to_no_samples(SAMPLE_RATE)
+}
+
+#[test]
+fn test_delay_quiet_audio() {
+    let (send, _) = unidirectional_queue();
+    let mut dynamics = DynamicsProcessor::new(send);
+
+    let input: Vec<f64> = (0..4800).map(|n| tone(0.5, n)).collect();
+    let output = process(&mut dynamics, &input, 1);
+
+    assert_eq!(output.len(), input.len());
+    assert!(output[..lookahead()].iter().all(|sample| *sample == 0.0));
+    for (output, input) in output[lookahead()..].iter().zip(input.iter()) {
+        assert!((output - input).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn test_limit_burst() {
+    let (send, _) = unidirectional_queue();
+    let mut dynamics = DynamicsProcessor::new(send);
+    let threshold = db_to_gain(dynamics.limiter_params().threshold);
+
+    let input: Vec<f64> = (0..24000)
+        .map(|n| {
+            let amplitude = if (2400..4800).contains(&n) { 4.0 } else { 0.3 };
+            tone(amplitude, n)
+        })
+        .collect();
+    let output = process(&mut dynamics, &input, 1);
+
+    assert!(output.iter().all(|sample| sample.abs() <= threshold + 1e-6));
+    // The gain is reduced in advance rather than clipping the peaks, so the
+    // waveform of the burst is only scaled.
+    let gains: Vec<f64> = output[3000..4800]
+        .iter()
+        .zip(input[3000 - lookahead()..4800 - lookahead()].iter())
+        .filter(|(_, input)| input.abs() > 1.0)
+        .map(|(output, input)| output / input)
+        .collect();
+    let min_gain = gains.iter().copied().fold(f64::MAX, f64::min);
+    let max_gain = gains.iter().copied().fold(0.0, f64::max);
+    assert!(max_gain - min_gain < 1e-3);
+    assert!((max_gain * 4.0 - threshold).abs() < 1e-3);
+
+    // The gain is restored after the release.
+    let quiet_end = output[23000..]
+        .iter()
+        .fold(0.0f64, |peak, sample| peak.max(sample.abs()));
+    assert!((quiet_end - 0.3).abs() < 0.01);
+}
+
+#[test]
+fn test_independent_channels() {
+    let (send, _) = unidirectional_queue();
+    let mut dynamics = DynamicsProcessor::new(send);
+
+    let input: Vec<f64> = (0..4800)
+        .flat_map(|n| [tone(2.0, n), tone(0.3, n)])
+        .collect();
+    let output = process(&mut dynamics, &input, 2);
+
+    let threshold = db_to_gain(dynamics.limiter_params().threshold);
+    let left_peak = output
+        .iter()
+        .step_by(2)
+        .fold(0.0f64, |peak, sample| peak.max(sample.abs()));
+    assert!(left_peak <= threshold + 1e-6);
+
+    // The loud left channel doesn't reduce the gain of the right one.
+    for (n, sample) in output
+        .iter()
+        .skip(1)
+        .step_by(2)
+        .enumerate()
+        .skip(lookahead())
+    {
+        assert!((sample - tone(0.3, n - lookahead())).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn test_compress() {
+    let (send, _) = unidirectional_queue();
+    let mut dynamics = DynamicsProcessor::new(send);
+    dynamics.set_compressor_enabled(true);
+    dynamics.set_compressor_params(CompressorParams {
+        threshold: -20.0,
+        ratio: 4.0,
+        attack: ClockTime::from_millis(1),
+        release: ClockTime::from_millis(1000),
+    });
+
+    let quiet: Vec<f64> = (0..4800).map(|n| tone(0.05, n)).collect();
+    let output = process(&mut dynamics, &quiet, 1);
+    let quiet_peak = output[2400..]
+        .iter()
+        .fold(0.0f64, |peak, sample| peak.max(sample.abs()));
+    assert!((quiet_peak - 0.05).abs() < 1e-3);
+
+    // The peak of 0 dBFS is 20 dB above the threshold, which becomes 5 dB.
+    let loud: Vec<f64> = (0..48000).map(|n| tone(1.0, n)).collect();
+    let output = process(&mut dynamics, &loud, 1);
+    let loud_peak = output[24000..]
+        .iter()
+        .fold(0.0f64, |peak, sample| peak.max(sample.abs()));
+    let loud_peak_db = 20.0 * loud_peak.log10();
+    assert!((loud_peak_db - -15.0).abs() < 1.5, "{loud_peak_db}");
+}
@@ -0,0 +1,344 @@
+#[cfg(test)]
+mod tests;
+
+use crate::error;
+use crate::util::{Element, Runnable};
+
+use crate::audio_system::audio::{RawAudioBuffer, RawAudioInfo};
+use crate::audio_system::element::{
+    AudioFilter, AudioSink, AudioSource, AudioSystemElementMessage,
+};
+
+use super::filter_chain::{AudioFilterInfo, ChainedAudioFilter};
+
+use std::f64::consts::{FRAC_1_SQRT_2, PI};
+
+use mueue::*;
+
+/// The frequencies of the bands are kept below this fraction of the sample rate.
+const MAX_RELATIVE_FREQUENCY: f64 = 0.49;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EqBandKind {
+    HighPass,
+    LowShelf,
+    Peaking,
+    HighShelf,
+    LowPass,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EqBand {
+    pub kind: EqBandKind,
+    /// The cutoff, center or corner frequency in Hz.
+    pub frequency: f64,
+    /// The gain in dB, which is ignored by the pass filters.
+    pub gain: f64,
+    /// The quality factor, which is the slope for the shelves.
+    pub q: f64,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum EqPreset {
+    #[default]
+    Flat,
+    /// Removes the handling rumble and emphasises the intelligibility of the
+    /// speech.
+    Voice,
+}
+
+impl EqPreset {
+    pub fn bands(self) -> Vec<EqBand> {
+        match self {
+            Self::Flat => Vec::new(),
+            Self::Voice => vec![
+                EqBand {
+                    kind: EqBandKind::HighPass,
+                    frequency: 90.0,
+                    gain: 0.0,
+                    q: FRAC_1_SQRT_2,
+                },
+                EqBand {
+                    kind: EqBandKind::LowShelf,
+                    frequency: 250.0,
+                    gain: -2.0,
+                    q: FRAC_1_SQRT_2,
+                },
+                EqBand {
+                    kind: EqBandKind::Peaking,
+                    frequency: 3000.0,
+                    gain: 3.0,
+                    q: 1.0,
+                },
+                EqBand {
+                    kind: EqBandKind::HighShelf,
+                    frequency: 8000.0,
+                    gain: -1.0,
+                    q: FRAC_1_SQRT_2,
+                },
+            ],
+        }
+    }
+}
+
+/// Filters the audio with the cascade of biquads, one per band.
+pub struct Equalizer {
+    send: MessageSender<AudioSystemElementMessage>,
+    input: Option<MessageReceiver<RawAudioBuffer>>,
+    output: Option<MessageSender<RawAudioBuffer>>,
+
+    bands: Vec<EqBand>,
+
+    stream_info: Option<RawAudioInfo>,
+    biquads: Vec<Biquad>,
+}
+
+impl Equalizer {
+    pub fn new(send: MessageSender<AudioSystemElementMessage>) -> Self {
+        Self {
+            send,
+            input: None,
+            output: None,
+
+            bands: Vec::new(),
+
+            stream_info: None,
+            biquads: Vec::new(),
+        }
+    }
+
+    pub fn bands(&self) -> &[EqBand] {
+        &self.bands
+    }
+
+    /// The audio passes unchanged if there are no bands.
+    pub fn set_bands(&mut self, bands: Vec<EqBand>) {
+        self.bands = bands;
+        self.update_coefficients();
+    }
+
+    pub fn set_preset(&mut self, preset: EqPreset) {
+        self.set_bands(preset.bands());
+    }
+
+    fn process(&mut self, audio: RawAudioBuffer) -> RawAudioBuffer {
+        if self.bands.is_empty()
+            || audio.format().no_bytes() == 0
+            || audio.channels() == 0
+            || audio.sample_rate() == 0
+        {
+            return audio;
+        }
+
+        let stream_info = audio.info();
+        if self.stream_info != Some(stream_info) {
+            self.stream_info = Some(stream_info);
+            self.update_coefficients();
+        }
+
+        let channels = usize::from(stream_info.channels);
+        let mut samples = audio.to_normalized_samples();
+        for frame in samples.chunks_exact_mut(channels) {
+            for (channel, sample) in frame.iter_mut().enumerate() {
+                for biquad in self.biquads.iter_mut() {
+                    *sample = biquad.process(*sample, channel);
+                }
+            }
+        }
+
+        RawAudioBuffer::from_normalized_samples(
+            &samples,
+            stream_info.format,
+            stream_info.channels,
+            stream_info.sample_rate,
+        )
+        .with_channel_layout(stream_info.channel_layout)
+    }
+
+    // The state of the filters is kept while the number of the bands and
+    // channels stays the same, so that the changes don't click.
+    fn update_coefficients(&mut self) {
+        let Some(stream_info) = self.stream_info else {
+            return;
+        };
+
+        let channels = usize::from(stream_info.channels);
+        let sample_rate = f64::from(stream_info.sample_rate);
+        let is_compatible = self.biquads.len() == self.bands.len()
+            && self
+                .biquads
+                .iter()
+                .all(|biquad| biquad.states.len() == channels);
+        if !is_compatible {
+            self.biquads = vec![Biquad::new(channels); self.bands.len()];
+        }
+
+        for (biquad, band) in self.biquads.iter_mut().zip(self.bands.iter()) {
+            biquad.set_coefficients(band, sample_rate);
+        }
+    }
+
+    fn reset(&mut self) {
+        self.stream_info = None;
+        self.biquads.clear();
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Biquad {
+    // The coefficients normalized by a0.
+    b: [f64; 3],
+    a: [f64; 2],
+    // The transposed direct form II state of every channel.
+    states: Vec<[f64; 2]>,
+}
+
+impl Biquad {
+    fn new(channels: usize) -> Self {
+        Self {
+            b: [1.0, 0.0, 0.0],
+            a: [0.0, 0.0],
+            states: vec![[0.0; 2]; channels],
+        }
+    }
+
+    // The formulas are from the Audio EQ Cookbook by Robert Bristow-Johnson.
+    fn set_coefficients(&mut self, band: &EqBand, sample_rate: f64) {
+        let frequency = band
+            .frequency
+            .clamp(1.0, sample_rate * MAX_RELATIVE_FREQUENCY);
+        let q = band.q.max(0.01);
+
+        let w0 = 2.0 * PI * frequency / sample_rate;
+        let (sin_w0, cos_w0) = w0.sin_cos();
+        let alpha = sin_w0 / (2.0 * q);
+        let amp = 10f64.powf(band.gain / 40.0);
+        let shelf_alpha = 2.0 * amp.sqrt() * alpha;
+
+        let (b, a) = match band.kind {
+            EqBandKind::HighPass => (
+                [(1.0 + cos_w0) / 2.0, -(1.0 + cos_w0), (1.0 + cos_w0) / 2.0],
+                [1.0 + alpha, -2.0 * cos_w0, 1.0 - alpha],
+            ),
+            EqBandKind::LowPass => (
+                [(1.0 - cos_w0) / 2.0, 1.0 - cos_w0, (1.0 - cos_w0) / 2.0],
+                [1.0 + alpha, -2.0 * cos_w0, 1.0 - alpha],
+            ),
+            EqBandKind::Peaking => (
+                [1.0 + alpha * amp, -2.0 * cos_w0, 1.0 - alpha * amp],
+                [1.0 + alpha / amp, -2.0 * cos_w0, 1.0 - alpha / amp],
+            ),
+            EqBandKind::LowShelf => (
+                [
+                    amp * ((amp + 1.0) - (amp - 1.0) * cos_w0 + shelf_alpha),
+                    2.0 * amp * ((amp - 1.0) - (amp + 1.0) * cos_w0),
+                    amp * ((amp + 1.0) - (amp - 1.0) * cos_w0 - shelf_alpha),
+                ],
+                [
+                    (amp + 1.0) + (amp - 1.0) * cos_w0 + shelf_alpha,
+                    -2.0 * ((amp - 1.0) + (amp + 1.0) * cos_w0),
+                    (amp + 1.0) + (amp - 1.0) * cos_w0 - shelf_alpha,
+                ],
+            ),
+            EqBandKind::HighShelf => (
+                [
+                    amp * ((amp + 1.0) + (amp - 1.0) * cos_w0 + shelf_alpha),
+                    -2.0 * amp * ((amp - 1.0) + (amp + 1.0) * cos_w0),
+                    amp * ((amp + 1.0) + (amp - 1.0) * cos_w0 - shelf_alpha),
+                ],
+                [
+                    (amp + 1.0) - (amp - 1.0) * cos_w0 + shelf_alpha,
+                    2.0 * ((amp - 1.0) - (amp + 1.0) * cos_w0),
+                    (amp + 1.0) - (amp - 1.0) * cos_w0 - shelf_alpha,
+                ],
+            ),
+        };
+
+        self.b = [b[0] / a[0], b[1] / a[0], b[2] / a[0]];
+        self.a = [a[1] / a[0], a[2] / a[0]];
+    }
+
+    fn process(&mut self, sample: f64, channel: usize) -> f64 {
+        let state = &mut self.states[channel];
+
+        let output = self.b[0] * sample + state[0];
+        state[0] = self.b[1] * sample - self.a[0] * output + state[1];
+        state[1] = self.b[2] * sample - self.a[1] * output;
+
+        output
+    }
+}
+
+impl Runnable for Equalizer {
+    fn update(&mut self) -> error::Result<()> {
+        let Some(input) = self.input.clone() else {
+            return Ok(());
+        };
+
+        for audio in input.iter() {
+            let audio = self.process(audio);
+
+            if let Some(output) = self.output.as_ref() {
+                let _ = output.send(audio);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn on_stop(&mut self) {
+        self.reset();
+    }
+}
+
+impl Element for Equalizer {
+    type Message = AudioSystemElementMessage;
+
+    fn sender(&self) -> MessageSender<Self::Message> {
+        self.send.clone()
+    }
+
+    fn connect(&mut self, send: MessageSender<Self::Message>) {
+        self.send = send;
+    }
+}
+
+impl AudioSink<RawAudioBuffer> for Equalizer {
+    fn input(&self) -> Option<MessageReceiver<RawAudioBuffer>> {
+        self.input.clone()
+    }
+
+    fn set_input(&mut self, input: MessageReceiver<RawAudioBuffer>) {
+        self.input = Some(input);
+    }
+
+    fn unset_input(&mut self) {
+        self.input = None;
+    }
+}
+
+impl AudioSource<RawAudioBuffer> for Equalizer {
+    fn output(&self) -> Option<MessageSender<RawAudioBuffer>> {
+        self.output.clone()
+    }
+
+    fn set_output(&mut self, output: MessageSender<RawAudioBuffer>) {
+        self.output = Some(output);
+    }
+
+    fn unset_output(&mut self) {
+        self.output = None;
+    }
+}
+
+impl AudioFilter<RawAudioBuffer, RawAudioBuffer> for Equalizer {}
+
+impl ChainedAudioFilter<RawAudioBuffer> for Equalizer {
+    fn info(&self) -> AudioFilterInfo {
+        AudioFilterInfo::new("equalizer")
+    }
+
+    fn is_active(&self) -> bool {
+        !self.bands.is_empty()
+    }
+}
@@ -0,0 +1,99 @@
+use super::*;
+
+use crate::audio_system::audio::RawAudioFormat;
+
+/// Filters a second of the tone and returns the RMS gain in dB of its second
+/// half, where the filters have settled.
+fn response(eq: &mut Equalizer, frequency: f64, sample_rate: u32) -> f64 {
+    let in_send = eq.create_input();
+    let out_recv = eq.create_output();
+
+    let input: Vec<f64> = (0..sample_rate as usize)
+        .map(|n| 0.5 * (2.0 * PI * frequency * n as f64 / f64::from(sample_rate)).sin())
+        .collect();
+    let _ = in_send.send(RawAudioBuffer::from_normalized_samples(
+        &input,
+        RawAudioFormat::F32LE,
+        1,
+        sample_rate,
+    ));
+    let _ = eq.update();
+    let output = out_recv.recv().unwrap().to_normalized_samples();
+
+    let rms = |samples: &[f64]| {
+        (samples.iter().map(|sample| sample * sample).sum::<f64>() / samples.len() as f64).sqrt()
+    };
+    let half = input.len() / 2;
+    20.0 * (rms(&output[half..]) / rms(&input[half..])).log10()
+}
+
+fn band(kind: EqBandKind, frequency: f64, gain: f64) -> EqBand {
+    EqBand {
+        kind,
+        frequency,
+        gain,
+        q: FRAC_1_SQRT_2,
+    }
+}
+
+#[test]
+fn test_pass_flat() {
+    let (send, _) = unidirectional_queue();
+    let mut eq = Equalizer::new(send);
+    let in_send = eq.create_input();
+    let out_recv = eq.create_output();
+
+    let audio = RawAudioBuffer::new(vec![1, 2, 3, 4], RawAudioFormat::S16LE, 1, 48000);
+    let _ = in_send.send(audio.clone());
+    let _ = eq.update();
+
+    assert_eq!(out_recv.recv().unwrap(), audio);
+}
+
+#[test]
+fn test_high_pass() {
+    let (send, _) = unidirectional_queue();
+    let mut eq = Equalizer::new(send);
+    eq.set_bands(vec![band(EqBandKind::HighPass, 100.0, 0.0)]);
+
+    assert!(response(&mut eq, 25.0, 48000) < -20.0);
+    assert!(response(&mut eq, 1000.0, 48000).abs() < 0.1);
+}
+
+#[test]
+fn test_peaking_and_shelves() {
+    let (send, _) = unidirectional_queue();
+    let mut eq = Equalizer::new(send);
+
+    eq.set_bands(vec![EqBand {
+        kind: EqBandKind::Peaking,
+        frequency: 1000.0,
+        gain: 6.0,
+        q: 1.0,
+    }]);
+    assert!((response(&mut eq, 1000.0, 48000) - 6.0).abs() < 0.1);
+    assert!(response(&mut eq, 100.0, 48000).abs() < 0.5);
+
+    eq.set_bands(vec![band(EqBandKind::LowShelf, 300.0, -6.0)]);
+    assert!((response(&mut eq, 50.0, 48000) - -6.0).abs() < 0.5);
+    assert!(response(&mut eq, 5000.0, 48000).abs() < 0.5);
+
+    eq.set_bands(vec![band(EqBandKind::HighShelf, 3000.0, 6.0)]);
+    assert!((response(&mut eq, 15000.0, 48000) - 6.0).abs() < 0.5);
+    assert!(response(&mut eq, 200.0, 48000).abs() < 0.5);
+
+    eq.set_bands(vec![band(EqBandKind::LowPass, 2000.0, 0.0)]);
+    assert!(response(&mut eq, 10000.0, 48000) < -20.0);
+}
+
+#[test]
+fn test_recalculate_on_sample_rate_change() {
+    let (send, _) = unidirectional_queue();
+    let mut eq = Equalizer::new(send);
+    eq.set_preset(EqPreset::Voice);
+
+    let at_48k = response(&mut eq, 3000.0, 48000);
+    let at_16k = response(&mut eq, 3000.0, 16000);
+    assert!(at_48k > 2.0);
+    assert!((at_48k - at_16k).abs() < 0.5);
+}
@@ -0,0 +1,518 @@
+#[cfg(test)]
+mod tests;
+
+use crate::error;
+use crate::util::{Element, Runnable};
+
+use crate::audio_system::audio::{
+    AudioCodec, EncodedAudioBuffer, EncodedAudioHeader, RawAudioBuffer, RawAudioFormat,
+    RawAudioInfo,
+};
+use crate::audio_system::element::{AudioSink, AudioSystemElementMessage};
+
+use std::fs::File;
+use std::io::{self, BufWriter, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use mueue::*;
+
+const WAV_HEADER_LEN: u32 = 44;
+const WAV_FORMAT_PCM: u16 = 1;
+const WAV_FORMAT_IEEE_FLOAT: u16 = 3;
+
+/// Records the raw audio into a WAV file. The sizes in the header are written
+/// when the writer is stopped or dropped. A WAV file has only one format, so
+/// the audio in the other ones is skipped and reported with an error once per
+/// change of its format.
+pub struct WavFileWriter {
+    send: MessageSender<AudioSystemElementMessage>,
+    input: Option<MessageReceiver<RawAudioBuffer>>,
+
+    file: Option<BufWriter<File>>,
+    stream_info: Option<RawAudioInfo>,
+    skipped_info: Option<RawAudioInfo>,
+    data_len: u32,
+}
+
+impl WavFileWriter {
+    pub fn create(
+        send: MessageSender<AudioSystemElementMessage>,
+        path: impl AsRef<Path>,
+    ) -> error::Result<Self> {
+        let file = BufWriter::new(File::create(path)?);
+
+        Ok(Self {
+            send,
+            input: None,
+
+            file: Some(file),
+            stream_info: None,
+            skipped_info: None,
+            data_len: 0,
+        })
+    }
+
+    /// Completes the file. The audio which comes after it is dropped.
+    pub fn finish(&mut self) -> error::Result<()> {
+        let Some(mut file) = self.file.take() else {
+            return Ok(());
+        };
+
+        if self.stream_info.is_some() {
+            file.seek(SeekFrom::Start(4))?;
+            file.write_all(&(WAV_HEADER_LEN - 8 + self.data_len).to_le_bytes())?;
+            file.seek(SeekFrom::Start(u64::from(WAV_HEADER_LEN) - 4))?;
+            file.write_all(&self.data_len.to_le_bytes())?;
+        }
+
+        file.flush()?;
+        Ok(())
+    }
+
+    /// Writes the audio which doesn't come from the input.
+    pub fn write(&mut self, mut audio: RawAudioBuffer) -> error::Result<()> {
+        let Some(file) = self.file.as_mut() else {
+            return Ok(());
+        };
+
+        if audio.format().no_bytes() == 0 || audio.channels() == 0 || audio.sample_rate() == 0 {
+            return Ok(());
+        }
+
+        let stream_info = audio.info();
+        match self.stream_info {
+            Some(info) if info != stream_info => {
+                self.skip(
+                    stream_info,
+                    format!(
+                        "A WAV file has only one format, the {:?} audio with {} channels at \
+                         {} Hz is skipped",
+                        stream_info.format, stream_info.channels, stream_info.sample_rate
+                    ),
+                );
+                return Ok(());
+            }
+            Some(_) => {}
+            None => {
+                write_wav_header(file, &stream_info)?;
+                self.stream_info = Some(stream_info);
+            }
+        }
+
+        let len = u32::try_from(audio.len())
+            .ok()
+            .filter(|len| self.data_len.checked_add(WAV_HEADER_LEN + len).is_some())
+            .ok_or_else(|| error::Error::Other(String::from("The WAV file is too large")))?;
+
+        if is_big_endian(audio.format()) {
+            let no_bytes = audio.format().no_bytes();
+            audio
+                .as_slice_mut()
+                .chunks_exact_mut(no_bytes)
+                .for_each(<[u8]>::reverse);
+        }
+
+        file.write_all(audio.as_slice())?;
+        self.data_len += len;
+        self.skipped_info = None;
+
+        Ok(())
+    }
+
+    fn skip(&mut self, info: RawAudioInfo, reason: String) {
+        if self.skipped_info.replace(info) != Some(info) {
+            let err = error::Error::Other(reason);
+            let _ = self.send.send(AudioSystemElementMessage::Error(err));
+        }
+    }
+}
+
+fn is_big_endian(format: RawAudioFormat) -> bool {
+    use RawAudioFormat::*;
+
+    matches!(format, S16BE | S24BE | S32BE | F32BE)
+}
+
+fn write_wav_header(file: &mut impl Write, info: &RawAudioInfo) -> io::Result<()> {
+    let format_tag = if info.format.is_float() {
+        WAV_FORMAT_IEEE_FLOAT
+    } else {
+        WAV_FORMAT_PCM
+    };
+    let block_align = info.format.no_bytes() as u16 * u16::from(info.channels);
+
+    // The sizes are filled in when the file is finished.
+    file.write_all(b"RIFF")?;
+    file.write_all(&0u32.to_le_bytes())?;
+    file.write_all(b"WAVE")?;
+
+    file.write_all(b"fmt ")?;
+    file.write_all(&16u32.to_le_bytes())?;
+    file.write_all(&format_tag.to_le_bytes())?;
+    file.write_all(&u16::from(info.channels).to_le_bytes())?;
+    file.write_all(&info.sample_rate.to_le_bytes())?;
+    file.write_all(&(info.sample_rate * u32::from(block_align)).to_le_bytes())?;
+    file.write_all(&block_align.to_le_bytes())?;
+    file.write_all(&(info.format.bit_depth() as u16).to_le_bytes())?;
+
+    file.write_all(b"data")?;
+    file.write_all(&0u32.to_le_bytes())
+}
+
+impl Drop for WavFileWriter {
+    fn drop(&mut self) {
+        let _ = self.finish();
+    }
+}
+
+impl Runnable for WavFileWriter {
+    fn update(&mut self) -> error::Result<()> {
+        let Some(input) = self.input.clone() else {
+            return Ok(());
+        };
+
+        for audio in input.iter() {
+            if let Err(err) = self.write(audio) {
+                let _ = self.finish();
+                let _ = self.send.send(AudioSystemElementMessage::Error(err));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn on_stop(&mut self) {
+        if let Err(err) = self.finish() {
+            let _ = self.send.send(AudioSystemElementMessage::Error(err));
+        }
+    }
+}
+
+impl Element for WavFileWriter {
+    type Message = AudioSystemElementMessage;
+
+    fn sender(&self) -> MessageSender<Self::Message> {
+        self.send.clone()
+    }
+
+    fn connect(&mut self, send: MessageSender<Self::Message>) {
+        self.send = send;
+    }
+}
+
+impl AudioSink<RawAudioBuffer> for WavFileWriter {
+    fn input(&self) -> Option<MessageReceiver<RawAudioBuffer>> {
+        self.input.clone()
+    }
+
+    fn set_input(&mut self, input: MessageReceiver<RawAudioBuffer>) {
+        self.input = Some(input);
+    }
+
+    fn unset_input(&mut self) {
+        self.input = None;
+    }
+}
+
+/// Ogg Opus always counts the samples at this rate.
+const OPUS_GRANULE_RATE: u32 = 48000;
+const OGG_SERIAL_NUMBER: u32 = 0x6666_6f6e;
+
+const OGG_CONTINUED_PACKET: u8 = 0x01;
+const OGG_BEGINNING_OF_STREAM: u8 = 0x02;
+const OGG_END_OF_STREAM: u8 = 0x04;
+
+const OGG_CRC_TABLE: [u32; 256] = ogg_crc_table();
+
+/// Stores the Opus packets into an Ogg file as they were received, so that they
+/// aren't encoded again. The packets which the file can't store are skipped and
+/// reported with an error once per change of their header.
+pub struct OggOpusFileWriter {
+    send: MessageSender<AudioSystemElementMessage>,
+    input: Option<MessageReceiver<EncodedAudioBuffer>>,
+
+    file: Option<BufWriter<File>>,
+    stream_header: Option<EncodedAudioHeader>,
+    skipped_header: Option<EncodedAudioHeader>,
+    page_sequence_number: u32,
+    granule_position: u64,
+    // The last packet waits for the next one, so that it can end the stream.
+    pending_packet: Option<(Vec<u8>, u64)>,
+}
+
+impl OggOpusFileWriter {
+    pub fn create(
+        send: MessageSender<AudioSystemElementMessage>,
+        path: impl AsRef<Path>,
+    ) -> error::Result<Self> {
+        let file = BufWriter::new(File::create(path)?);
+
+        Ok(Self {
+            send,
+            input: None,
+
+            file: Some(file),
+            stream_header: None,
+            skipped_header: None,
+            page_sequence_number: 0,
+            granule_position: 0,
+            pending_packet: None,
+        })
+    }
+
+    /// Completes the file. The audio which comes after it is dropped.
+    pub fn finish(&mut self) -> error::Result<()> {
+        if let Some((packet, granule_position)) = self.pending_packet.take() {
+            self.write_page(&packet, granule_position, OGG_END_OF_STREAM)?;
+        }
+
+        if let Some(mut file) = self.file.take() {
+            file.flush()?;
+        }
+
+        Ok(())
+    }
+
+    fn write(&mut self, audio: EncodedAudioBuffer) -> error::Result<()> {
+        if self.file.is_none() || audio.data.is_empty() {
+            return Ok(());
+        }
+
+        if audio.header.codec != AudioCodec::Opus {
+            self.skip(
+                audio.header,
+                format!("Ogg Opus can't store the {:?} audio", audio.header.codec),
+            );
+            return Ok(());
+        }
+
+        match self.stream_header {
+            // The Ogg stream can't change its channels in the middle.
+            Some(header) if header.channels != audio.header.channels => {
+                self.skip(
+                    audio.header,
+                    format!(
+                        "The recording has {} channels, the audio with {} is skipped",
+                        header.channels, audio.header.channels
+                    ),
+                );
+                return Ok(());
+            }
+            Some(_) => {}
+            None => {
+                self.write_stream_headers(&audio.header)?;
+                self.stream_header = Some(audio.header);
+            }
+        }
+
+        if let Some((packet, granule_position)) = self.pending_packet.take() {
+            self.write_page(&packet, granule_position, 0)?;
+        }
+
+        self.granule_position += opus_packet_duration(&audio.data);
+        self.pending_packet = Some((audio.data, self.granule_position));
+        self.skipped_header = None;
+
+        Ok(())
+    }
+
+    fn skip(&mut self, header: EncodedAudioHeader, reason: String) {
+        if self.skipped_header.replace(header) != Some(header) {
+            let err = error::Error::Other(reason);
+            let _ = self.send.send(AudioSystemElementMessage::Error(err));
+        }
+    }
+
+    fn write_stream_headers(&mut self, header: &EncodedAudioHeader) -> error::Result<()> {
+        // Only the mapping family 0 can be written without knowing how the
+        // encoder split the channels into the streams.
+        if !(1..=2).contains(&header.channels) {
+            return Err(error::Error::Other(format!(
+                "Ogg Opus can't store {} channels",
+                header.channels
+            )));
+        }
+
+        let mut opus_head = Vec::from(*b"OpusHead");
+        opus_head.push(1);
+        opus_head.push(header.channels);
+        // The pre-skip of the remote encoder isn't known.
+        opus_head.extend_from_slice(&0u16.to_le_bytes());
+        opus_head.extend_from_slice(&header.sample_rate.to_le_bytes());
+        opus_head.extend_from_slice(&0i16.to_le_bytes());
+        opus_head.push(0);
+        self.write_page(&opus_head, 0, OGG_BEGINNING_OF_STREAM)?;
+
+        let vendor = b"ffone";
+        let mut opus_tags = Vec::from(*b"OpusTags");
+        opus_tags.extend_from_slice(&(vendor.len() as u32).to_le_bytes());
+        opus_tags.extend_from_slice(vendor);
+        opus_tags.extend_from_slice(&0u32.to_le_bytes());
+        self.write_page(&opus_tags, 0, 0)
+    }
+
+    fn write_page(&mut self, packet: &[u8], granule_position: u64, flags: u8) -> error::Result<()> {
+        let Some(file) = self.file.as_mut() else {
+            return Ok(());
+        };
+
+        let mut lacing_values = vec![255; packet.len() / 255];
+        lacing_values.push((packet.len() % 255) as u8);
+
+        // The packets which don't fit into one page are continued on the next.
+        let no_pages = lacing_values.chunks(255).len();
+        let mut data = packet;
+        for (no_page, page_lacing_values) in lacing_values.chunks(255).enumerate() {
+            let is_first = no_page == 0;
+            let is_last = no_page + 1 == no_pages;
+
+            let page_len = page_lacing_values.iter().copied().map(usize::from).sum();
+            let (chunk, rest) = data.split_at(page_len);
+            data = rest;
+
+            let mut header_type = 0;
+            if is_first {
+                header_type |= flags & OGG_BEGINNING_OF_STREAM;
+            } else {
+                header_type |= OGG_CONTINUED_PACKET;
+            }
+            if is_last {
+                header_type |= flags & OGG_END_OF_STREAM;
+            }
+
+            // The granule position of the page where no packet ends is -1.
+            let page_granule_position = if is_last { granule_position } else { u64::MAX };
+
+            let mut page = Vec::with_capacity(27 + page_lacing_values.len() + chunk.len());
+            page.extend_from_slice(b"OggS");
+            page.push(0);
+            page.push(header_type);
+            page.extend_from_slice(&page_granule_position.to_le_bytes());
+            page.extend_from_slice(&OGG_SERIAL_NUMBER.to_le_bytes());
+            page.extend_from_slice(&self.page_sequence_number.to_le_bytes());
+            page.extend_from_slice(&0u32.to_le_bytes());
+            page.push(page_lacing_values.len() as u8);
+            page.extend_from_slice(page_lacing_values);
+            page.extend_from_slice(chunk);
+
+            let crc = ogg_crc(&page);
+            page[22..26].copy_from_slice(&crc.to_le_bytes());
+
+            file.write_all(&page)?;
+            self.page_sequence_number += 1;
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the number of the samples at 48 kHz which are encoded by the packet.
+fn opus_packet_duration(packet: &[u8]) -> u64 {
+    let Some(&toc) = packet.first() else {
+        return 0;
+    };
+
+    // The durations of the frames are in halves of a millisecond.
+    let config = usize::from(toc >> 3);
+    let frame_duration: u64 = match config {
+        // SILK
+        0..=11 => [20, 40, 80, 120][config % 4],
+        // Hybrid
+        12..=15 => [20, 40][config % 2],
+        // CELT
+        _ => [5, 10, 20, 40][config % 4],
+    };
+
+    let no_frames = match toc & 0x3 {
+        0 => 1,
+        1 | 2 => 2,
+        _ => packet.get(1).map_or(0, |count| u64::from(count & 0x3f)),
+    };
+
+    frame_duration * u64::from(OPUS_GRANULE_RATE) / 2000 * no_frames
+}
+
+const fn ogg_crc_table() -> [u32; 256] {
+    let mut table = [0; 256];
+
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = (i as u32) << 24;
+
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 0x8000_0000 != 0 {
+                (crc << 1) ^ 0x04c1_1db7
+            } else {
+                crc << 1
+            };
+            bit += 1;
+        }
+
+        table[i] = crc;
+        i += 1;
+    }
+
+    table
+}
+
+fn ogg_crc(data: &[u8]) -> u32 {
+    data.iter().fold(0, |crc, byte| {
+        (crc << 8) ^ OGG_CRC_TABLE[usize::from((crc >> 24) as u8 ^ byte)]
+    })
+}
+
+impl Drop for OggOpusFileWriter {
+    fn drop(&mut self) {
+        let _ = self.finish();
+    }
+}
+
+impl Runnable for OggOpusFileWriter {
+    fn update(&mut self) -> error::Result<()> {
+        let Some(input) = self.input.clone() else {
+            return Ok(());
+        };
+
+        for audio in input.iter() {
+            if let Err(err) = self.write(audio) {
+                let _ = self.finish();
+                let _ = self.send.send(AudioSystemElementMessage::Error(err));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn on_stop(&mut self) {
+        if let Err(err) = self.finish() {
+            let _ = self.send.send(AudioSystemElementMessage::Error(err));
+        }
+    }
+}
+
+impl Element for OggOpusFileWriter {
+    type Message = AudioSystemElementMessage;
+
+    fn sender(&self) -> MessageSender<Self::Message> {
+        self.send.clone()
+    }
+
+    fn connect(&mut self, send: MessageSender<Self::Message>) {
+        self.send = send;
+    }
+}
+
+impl AudioSink<EncodedAudioBuffer> for OggOpusFileWriter {
+    fn input(&self) -> Option<MessageReceiver<EncodedAudioBuffer>> {
+        self.input.clone()
+    }
+
+    fn set_input(&mut self, input: MessageReceiver<EncodedAudioBuffer>) {
+        self.input = Some(input);
+    }
+
+    fn unset_input(&mut self) {
+        self.input = None;
+    }
+}
@@ -0,0 +1,188 @@
+use super::*;
+
+use crate::test_util::{encoded_audio, temp_path};
+
+struct OggPage {
+    header_type: u8,
+    granule_position: u64,
+    sequence_number: u32,
+    data: Vec<u8>,
+}
+
+fn parse_ogg_pages(mut file: &[u8]) -> Vec<OggPage> {
+    let mut pages = Vec::new();
+
+    while !file.is_empty() {
+        assert_eq!(&file[..4], b"OggS");
+        let no_segments = usize::from(file[26]);
+        let data_len: usize = file[27..27 + no_segments]
+            .iter()
+            .copied()
+            .map(usize::from)
+            .sum();
+        let page_len = 27 + no_segments + data_len;
+
+        let mut page = file[..page_len].to_vec();
+        let crc = u32::from_le_bytes(page[22..26].try_into().unwrap());
+        page[22..26].fill(0);
+        assert_eq!(ogg_crc(&page), crc);
+
+        pages.push(OggPage {
+            header_type: file[5],
+            granule_position: u64::from_le_bytes(file[6..14].try_into().unwrap()),
+            sequence_number: u32::from_le_bytes(file[18..22].try_into().unwrap()),
+            data: file[27 + no_segments..page_len].to_vec(),
+        });
+        file = &file[page_len..];
+    }
+
+    pages
+}
+
+#[test]
+fn test_write_wav() {
+    let path = temp_path("test_write_wav.wav");
+    let (send, recv) = unidirectional_queue();
+    let mut writer = WavFileWriter::create(send, &path).unwrap();
+    let in_send = writer.create_input();
+
+    let audio = RawAudioBuffer::new(vec![0x01, 0x02, 0x03, 0x04], RawAudioFormat::S16BE, 2, 8000);
+    let mono_audio = RawAudioBuffer::new(vec![0x05, 0x06], RawAudioFormat::S16BE, 1, 8000);
+    let _ = in_send.send(audio.clone());
+    // The audio in the other format doesn't fit into the file.
+    let _ = in_send.send(mono_audio.clone());
+    let _ = in_send.send(mono_audio);
+    let _ = in_send.send(audio);
+    let _ = writer.update();
+    writer.on_stop();
+
+    // The skipped audio is reported once.
+    assert_eq!(
+        recv.iter()
+            .filter(|msg| matches!(msg, AudioSystemElementMessage::Error(_)))
+            .count(),
+        1
+    );
+
+    let file = std::fs::read(&path).unwrap();
+    let _ = std::fs::remove_file(&path);
+
+    assert_eq!(file.len(), 44 + 8);
+    assert_eq!(&file[..4], b"RIFF");
+    assert_eq!(u32::from_le_bytes(file[4..8].try_into().unwrap()), 36 + 8);
+    assert_eq!(&file[8..16], b"WAVEfmt ");
+    assert_eq!(u16::from_le_bytes([file[20], file[21]]), WAV_FORMAT_PCM);
+    assert_eq!(u16::from_le_bytes([file[22], file[23]]), 2);
+    assert_eq!(u32::from_le_bytes(file[24..28].try_into().unwrap()), 8000);
+    assert_eq!(u32::from_le_bytes(file[28..32].try_into().unwrap()), 32000);
+    assert_eq!(u16::from_le_bytes([file[32], file[33]]), 4);
+    assert_eq!(u16::from_le_bytes([file[34], file[35]]), 16);
+    assert_eq!(&file[36..40], b"data");
+    assert_eq!(u32::from_le_bytes(file[40..44].try_into().unwrap()), 8);
+    assert_eq!(
+        &file[44..],
+        &[0x02, 0x01, 0x04, 0x03, 0x02, 0x01, 0x04, 0x03]
+    );
+}
+
+#[test]
+fn test_write_ogg_opus() {
+    let path = temp_path("test_write_ogg_opus.opus");
+    let (send, recv) = unidirectional_queue();
+    let mut writer = OggOpusFileWriter::create(send, &path).unwrap();
+    let in_send = writer.create_input();
+
+    // One, two and three frames of 20 ms.
+    let _ = in_send.send(encoded_audio(AudioCodec::Opus, 2, vec![0x08, 0xaa]));
+    let _ = in_send.send(encoded_audio(AudioCodec::PcmS16LE, 2, vec![0x00, 0x00]));
+    let _ = in_send.send(encoded_audio(AudioCodec::Opus, 2, vec![0x09, 0xbb]));
+    let _ = in_send.send(encoded_audio(AudioCodec::Opus, 2, vec![0x0b, 0x03, 0xcc]));
+    // The packet is too long for one page.
+    let mut long_packet = vec![0xdd; 70000];
+    long_packet[0] = 0x08;
+    let _ = in_send.send(encoded_audio(AudioCodec::Opus, 2, long_packet.clone()));
+    let _ = writer.update();
+    writer.on_stop();
+
+    let file = std::fs::read(&path).unwrap();
+    let _ = std::fs::remove_file(&path);
+    let pages = parse_ogg_pages(&file);
+
+    // The PCM audio is reported.
+    assert!(matches!(
+        recv.iter().collect::<Vec<_>>()[..],
+        [AudioSystemElementMessage::Error(_)]
+    ));
+
+    assert_eq!(pages.len(), 7);
+    assert!(pages
+        .iter()
+        .enumerate()
+        .all(|(i, page)| page.sequence_number == i as u32));
+    assert_eq!(
+        pages
+            .iter()
+            .map(|page| page.header_type)
+            .collect::<Vec<_>>(),
+        vec![
+            OGG_BEGINNING_OF_STREAM,
+            0,
+            0,
+            0,
+            0,
+            0,
+            OGG_CONTINUED_PACKET | OGG_END_OF_STREAM
+        ]
+    );
+    assert_eq!(
+        pages
+            .iter()
+            .map(|page| page.granule_position)
+            .collect::<Vec<_>>(),
+        vec![0, 0, 960, 2880, 5760, u64::MAX, 6720]
+    );
+
+    assert_eq!(&pages[0].data[..8], b"OpusHead");
+    assert_eq!(pages[0].data[9], 2);
+    assert_eq!(&pages[1].data[..8], b"OpusTags");
+    assert_eq!(pages[2].data, vec![0x08, 0xaa]);
+    assert_eq!(
+        [&pages[5].data[..], &pages[6].data[..]].concat(),
+        long_packet
+    );
+}
+
+#[test]
+fn test_write_ogg_opus_skipped() {
+    let path = temp_path("test_write_ogg_opus_skipped.opus");
+    let (send, recv) = unidirectional_queue();
+    let mut writer = OggOpusFileWriter::create(send, &path).unwrap();
+    let in_send = writer.create_input();
+
+    let mono = encoded_audio(AudioCodec::Opus, 1, vec![0x08, 0xbb]);
+
+    let _ = in_send.send(encoded_audio(AudioCodec::Opus, 2, vec![0x08, 0xaa]));
+    let _ = in_send.send(encoded_audio(AudioCodec::Flac, 2, vec![0x00]));
+    let _ = in_send.send(encoded_audio(AudioCodec::Flac, 2, vec![0x00]));
+    let _ = in_send.send(mono.clone());
+    let _ = in_send.send(mono);
+    let _ = in_send.send(encoded_audio(AudioCodec::Opus, 2, vec![0x08, 0xcc]));
+    let _ = in_send.send(encoded_audio(AudioCodec::Flac, 2, vec![0x00]));
+    let _ = writer.update();
+    writer.on_stop();
+
+    let file = std::fs::read(&path).unwrap();
+    let _ = std::fs::remove_file(&path);
+    let pages = parse_ogg_pages(&file);
+
+    // Each run of the audio which can't be stored is reported once.
+    let errors = recv
+        .iter()
+        .filter(|msg| matches!(msg, AudioSystemElementMessage::Error(_)))
+        .count();
+    assert_eq!(errors, 3);
+
+    assert_eq!(pages.len(), 4);
+    assert_eq!(pages[2].data, vec![0x08, 0xaa]);
+    assert_eq!(pages[3].data, vec![0x08, 0xcc]);
+}
@@ -0,0 +1,350 @@
+#[cfg(test)]
+mod tests;
+
+use crate::error;
+use crate::util::{Element, Runnable};
+
+use crate::audio_system::audio::{EncodedAudioBuffer, RawAudioBuffer, TimestampedRawAudioBuffer};
+use crate::audio_system::element::*;
+
+use std::any::Any;
+use std::collections::HashMap;
+
+use mueue::*;
+
+crate::impl_as_trait!(any -> Any);
+
+#[derive(
+    Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize, serde::Deserialize,
+)]
+pub struct AudioFilterInfo {
+    pub name: String,
+}
+
+impl AudioFilterInfo {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: String::from(name),
+        }
+    }
+}
+
+/// The stage which the config chains into the section of the pipeline which
+/// carries the audio of its type. The stages may be provided from outside of
+/// the core.
+pub trait ChainedAudioFilter<T: Message>: AudioFilter<T, T> + AsAny {
+    fn info(&self) -> AudioFilterInfo;
+
+    /// Whether the filter would change the audio. The raw audio isn't converted
+    /// to float for the chain if none of its filters is active.
+    fn is_active(&self) -> bool {
+        true
+    }
+}
+
+pub trait ChainedAudioFilterBuilder<T: Message>:
+    AudioSystemElementBuilder<Element = dyn ChainedAudioFilter<T>>
+{
+}
+
+impl<T: Message, B> ChainedAudioFilterBuilder<T> for B where
+    B: AudioSystemElementBuilder<Element = dyn ChainedAudioFilter<T>>
+{
+}
+
+/// The filters provided from outside of the core, by the section of the
+/// pipeline which they fit into.
+#[derive(Default)]
+pub struct AudioFilterBuilders {
+    pub encoded: Vec<Box<dyn ChainedAudioFilterBuilder<EncodedAudioBuffer>>>,
+    pub decoded: Vec<Box<dyn ChainedAudioFilterBuilder<TimestampedRawAudioBuffer>>>,
+    pub raw: Vec<Box<dyn ChainedAudioFilterBuilder<RawAudioBuffer>>>,
+}
+
+/// Describes which filters are chained into each section of the pipeline and
+/// in which order. The sections are separated by the elements which change the
+/// type of the audio, so a filter fits only into the section of its type.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct AudioPipelineConfig {
+    /// Process the received audio before the jitter buffer.
+    pub encoded_filters: Vec<AudioFilterInfo>,
+    /// Process the decoded audio before the synchronizer, while it still has
+    /// its timestamps.
+    pub decoded_filters: Vec<AudioFilterInfo>,
+    /// Process the audio which is played by the virtual microphone.
+    pub raw_filters: Vec<AudioFilterInfo>,
+}
+
+impl Default for AudioPipelineConfig {
+    fn default() -> Self {
+        let infos = |names: &[&str]| names.iter().copied().map(AudioFilterInfo::new).collect();
+
+        Self {
+            encoded_filters: Vec::new(),
+            decoded_filters: infos(&["loss_concealer", "noise_gate"]),
+            raw_filters: infos(&[
+                "equalizer",
+                "noise_suppressor",
+                "automatic_gain_control",
+                "gain",
+                "dynamics_processor",
+            ]),
+        }
+    }
+}
+
+/// Keeps the filters of one section which aren't chained, so that they may be
+/// inserted later.
+pub struct AudioFilterPool<T: Message> {
+    filters: HashMap<AudioFilterInfo, Box<dyn ChainedAudioFilter<T>>>,
+}
+
+impl<T: Message> AudioFilterPool<T> {
+    /// The later filters replace the earlier ones of the same name.
+    pub fn new(filters: impl IntoIterator<Item = Box<dyn ChainedAudioFilter<T>>>) -> Self {
+        Self {
+            filters: filters
+                .into_iter()
+                .map(|filter| (filter.info(), filter))
+                .collect(),
+        }
+    }
+
+    pub fn contains(&self, info: &AudioFilterInfo) -> bool {
+        self.filters.contains_key(info)
+    }
+
+    pub fn take(&mut self, info: &AudioFilterInfo) -> Option<Box<dyn ChainedAudioFilter<T>>> {
+        self.filters.remove(info)
+    }
+
+    pub fn put(&mut self, filter: Box<dyn ChainedAudioFilter<T>>) {
+        self.filters.insert(filter.info(), filter);
+    }
+}
+
+/// Runs the filters one after another. The filters may be inserted and removed
+/// while the chain is running without losing the queued buffers.
+pub struct AudioFilterChain<T: Message> {
+    send: MessageSender<AudioSystemElementMessage>,
+    input: Option<MessageReceiver<T>>,
+    output: Option<MessageSender<T>>,
+
+    filters: Vec<Box<dyn ChainedAudioFilter<T>>>,
+
+    is_running: bool,
+}
+
+pub type EncodedAudioFilterChain = AudioFilterChain<EncodedAudioBuffer>;
+pub type DecodedAudioFilterChain = AudioFilterChain<TimestampedRawAudioBuffer>;
+pub type RawAudioFilterChain = AudioFilterChain<RawAudioBuffer>;
+
+impl<T: Message> AudioFilterChain<T> {
+    pub fn new(send: MessageSender<AudioSystemElementMessage>) -> Self {
+        Self {
+            send,
+            input: None,
+            output: None,
+
+            filters: Vec::new(),
+
+            is_running: false,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.filters.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.filters.is_empty()
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.filters.iter().any(|filter| filter.is_active())
+    }
+
+    pub fn infos(&self) -> Vec<AudioFilterInfo> {
+        self.filters.iter().map(|filter| filter.info()).collect()
+    }
+
+    pub fn position(&self, info: &AudioFilterInfo) -> Option<usize> {
+        self.filters
+            .iter()
+            .position(|filter| filter.info() == *info)
+    }
+
+    /// Finds the first filter of the given type.
+    pub fn filter<F: ChainedAudioFilter<T> + 'static>(&self) -> Option<&F> {
+        self.filters
+            .iter()
+            .find_map(|filter| filter.as_ref().as_any().downcast_ref())
+    }
+
+    /// Finds the first filter of the given type.
+    pub fn filter_mut<F: ChainedAudioFilter<T> + 'static>(&mut self) -> Option<&mut F> {
+        self.filters
+            .iter_mut()
+            .find_map(|filter| filter.as_mut().as_any_mut().downcast_mut())
+    }
+
+    pub fn push(&mut self, filter: Box<dyn ChainedAudioFilter<T>>) {
+        self.insert(self.filters.len(), filter);
+    }
+
+    /// The index is clamped to the length of the chain.
+    pub fn insert(&mut self, index: usize, mut filter: Box<dyn ChainedAudioFilter<T>>) {
+        let index = index.min(self.filters.len());
+        if self.is_running {
+            filter.on_start();
+        }
+
+        // The queue which fed the filter at the index is preserved, so the
+        // buffers in it reach the filter after the new one.
+        if index == 0 {
+            if let Some(input) = self.input.clone() {
+                filter.set_input(input);
+            }
+
+            match self.filters.first_mut() {
+                Some(next) => filter.chain(next.as_audio_sink_mut()),
+                None => {
+                    if let Some(output) = self.output.clone() {
+                        filter.set_output(output);
+                    }
+                }
+            }
+        } else {
+            let prev = &mut self.filters[index - 1];
+            if let Some(output) = prev.output() {
+                filter.set_output(output);
+            }
+
+            prev.chain(filter.as_audio_sink_mut());
+        }
+
+        self.filters.insert(index, filter);
+    }
+
+    pub fn remove(&mut self, info: &AudioFilterInfo) -> Option<Box<dyn ChainedAudioFilter<T>>> {
+        let index = self.position(info)?;
+
+        // The filter and the ones after it process everything which is queued
+        // for them before the queues are relinked.
+        for filter in self.filters[index..].iter_mut() {
+            let _ = filter.update();
+        }
+
+        let mut filter = self.filters.remove(index);
+        if index == 0 {
+            if let Some(next) = self.filters.first_mut() {
+                match self.input.clone() {
+                    Some(input) => next.set_input(input),
+                    None => next.unset_input(),
+                }
+            }
+        } else {
+            let prev = &mut self.filters[index - 1];
+            match filter.output() {
+                Some(output) => prev.set_output(output),
+                None => prev.unset_output(),
+            }
+        }
+
+        filter.unset_input();
+        filter.unset_output();
+        if self.is_running {
+            filter.on_stop();
+        }
+
+        Some(filter)
+    }
+}
+
+impl<T: Message> Runnable for AudioFilterChain<T> {
+    fn update(&mut self) -> error::Result<()> {
+        if self.filters.is_empty() {
+            if let (Some(input), Some(output)) = (self.input.as_ref(), self.output.as_ref()) {
+                for audio in input.iter() {
+                    let _ = output.send(audio);
+                }
+            }
+        }
+
+        for filter in self.filters.iter_mut() {
+            filter.update()?;
+        }
+
+        Ok(())
+    }
+
+    fn on_start(&mut self) {
+        self.filters.iter_mut().for_each(|filter| filter.on_start());
+
+        self.is_running = true;
+    }
+
+    fn on_stop(&mut self) {
+        self.is_running = false;
+
+        self.filters.iter_mut().for_each(|filter| filter.on_stop());
+    }
+}
+
+impl<T: Message> Element for AudioFilterChain<T> {
+    type Message = AudioSystemElementMessage;
+
+    fn sender(&self) -> MessageSender<Self::Message> {
+        self.send.clone()
+    }
+
+    fn connect(&mut self, send: MessageSender<Self::Message>) {
+        self.send = send;
+    }
+}
+
+impl<T: Message> AudioSink<T> for AudioFilterChain<T> {
+    fn input(&self) -> Option<MessageReceiver<T>> {
+        self.input.clone()
+    }
+
+    fn set_input(&mut self, input: MessageReceiver<T>) {
+        if let Some(first) = self.filters.first_mut() {
+            first.set_input(input.clone());
+        }
+
+        self.input = Some(input);
+    }
+
+    fn unset_input(&mut self) {
+        if let Some(first) = self.filters.first_mut() {
+            first.unset_input();
+        }
+
+        self.input = None;
+    }
+}
+
+impl<T: Message> AudioSource<T> for AudioFilterChain<T> {
+    fn output(&self) -> Option<MessageSender<T>> {
+        self.output.clone()
+    }
+
+    fn set_output(&mut self, output: MessageSender<T>) {
+        if let Some(last) = self.filters.last_mut() {
+            last.set_output(output.clone());
+        }
+
+        self.output = Some(output);
+    }
+
+    fn unset_output(&mut self) {
+        if let Some(last) = self.filters.last_mut() {
+            last.unset_output();
+        }
+
+        self.output = None;
+    }
+}
+
+impl<T: Message> AudioFilter<T, T> for AudioFilterChain<T> {}
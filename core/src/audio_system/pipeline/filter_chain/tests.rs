@@ -0,0 +1,180 @@
+use super::*;
+
+use crate::audio_system::audio::RawAudioFormat;
+
+/// Adds the offset to every sample, so the order of the filters is visible.
+struct Offset {
+    name: &'static str,
+    offset: u8,
+
+    send: MessageSender<AudioSystemElementMessage>,
+    input: Option<MessageReceiver<RawAudioBuffer>>,
+    output: Option<MessageSender<RawAudioBuffer>>,
+}
+
+impl Offset {
+    fn boxed(name: &'static str, offset: u8) -> Box<dyn ChainedAudioFilter<RawAudioBuffer>> {
+        let (send, _) = unidirectional_queue();
+
+        Box::new(Self {
+            name,
+            offset,
+
+            send,
+            input: None,
+            output: None,
+        })
+    }
+}
+
+impl Runnable for Offset {
+    fn update(&mut self) -> error::Result<()> {
+        let (Some(input), Some(output)) = (self.input.as_ref(), self.output.as_ref()) else {
+            return Ok(());
+        };
+
+        for mut audio in input.iter() {
+            audio
+                .as_slice_mut()
+                .iter_mut()
+                .for_each(|sample| *sample += self.offset);
+            let _ = output.send(audio);
+        }
+
+        Ok(())
+    }
+}
+
+impl Element for Offset {
+    type Message = AudioSystemElementMessage;
+
+    fn sender(&self) -> MessageSender<Self::Message> {
+        self.send.clone()
+    }
+
+    fn connect(&mut self, send: MessageSender<Self::Message>) {
+        self.send = send;
+    }
+}
+
+impl AudioSink<RawAudioBuffer> for Offset {
+    fn input(&self) -> Option<MessageReceiver<RawAudioBuffer>> {
+        self.input.clone()
+    }
+
+    fn set_input(&mut self, input: MessageReceiver<RawAudioBuffer>) {
+        self.input = Some(input);
+    }
+
+    fn unset_input(&mut self) {
+        self.input = None;
+    }
+}
+
+impl AudioSource<RawAudioBuffer> for Offset {
+    fn output(&self) -> Option<MessageSender<RawAudioBuffer>> {
+        self.output.clone()
+    }
+
+    fn set_output(&mut self, output: MessageSender<RawAudioBuffer>) {
+        self.output = Some(output);
+    }
+
+    fn unset_output(&mut self) {
+        self.output = None;
+    }
+}
+
+impl AudioFilter<RawAudioBuffer, RawAudioBuffer> for Offset {}
+
+impl ChainedAudioFilter<RawAudioBuffer> for Offset {
+    fn info(&self) -> AudioFilterInfo {
+        AudioFilterInfo::new(self.name)
+    }
+}
+
+fn buffer(sample: u8) -> RawAudioBuffer {
+    RawAudioBuffer::new(vec![sample], RawAudioFormat::U8, 1, 8000)
+}
+
+fn samples(recv: &MessageReceiver<RawAudioBuffer>) -> Vec<u8> {
+    recv.iter().map(|audio| audio.as_slice()[0]).collect()
+}
+
+#[test]
+fn test_pass_through_empty_chain() {
+    let (send, _) = unidirectional_queue();
+    let mut chain = AudioFilterChain::new(send);
+    let in_send = chain.create_input();
+    let out_recv = chain.create_output();
+
+    let _ = in_send.send(buffer(1));
+    let _ = chain.update();
+
+    assert_eq!(samples(&out_recv), vec![1]);
+    assert!(!chain.is_active());
+}
+
+#[test]
+fn test_chain_filters_in_order() {
+    let (send, _) = unidirectional_queue();
+    let mut chain = AudioFilterChain::new(send);
+    let in_send = chain.create_input();
+    let out_recv = chain.create_output();
+
+    chain.push(Offset::boxed("b", 10));
+    chain.insert(0, Offset::boxed("a", 1));
+    chain.push(Offset::boxed("c", 100));
+    assert_eq!(
+        chain.infos(),
+        vec![
+            AudioFilterInfo::new("a"),
+            AudioFilterInfo::new("b"),
+            AudioFilterInfo::new("c")
+        ]
+    );
+
+    let _ = in_send.send(buffer(0));
+    let _ = chain.update();
+    assert_eq!(samples(&out_recv), vec![111]);
+    assert!(chain.is_active());
+
+    assert!(chain.filter::<Offset>().is_some_and(|f| f.name == "a"));
+    assert!(chain.filter_mut::<Offset>().is_some_and(|f| f.name == "a"));
+}
+
+#[test]
+fn test_insert_and_remove_while_running() {
+    let (send, _) = unidirectional_queue();
+    let mut chain = AudioFilterChain::new(send);
+    let in_send = chain.create_input();
+    let out_recv = chain.create_output();
+    chain.on_start();
+
+    chain.push(Offset::boxed("a", 1));
+    chain.push(Offset::boxed("b", 10));
+
+    // The queued buffers are processed by the filter inserted before them.
+    let _ = in_send.send(buffer(0));
+    chain.insert(0, Offset::boxed("first", 100));
+    let _ = in_send.send(buffer(0));
+    chain.insert(2, Offset::boxed("middle", 50));
+    let _ = chain.update();
+    assert_eq!(samples(&out_recv), vec![161, 161]);
+
+    // The removed filters flush the queued buffers.
+    let _ = in_send.send(buffer(0));
+    assert!(chain.remove(&AudioFilterInfo::new("first")).is_some());
+    let _ = in_send.send(buffer(0));
+    assert!(chain.remove(&AudioFilterInfo::new("b")).is_some());
+    let _ = in_send.send(buffer(0));
+    assert!(chain.remove(&AudioFilterInfo::new("unknown")).is_none());
+    let _ = chain.update();
+    assert_eq!(samples(&out_recv), vec![161, 51, 51]);
+
+    assert!(chain.remove(&AudioFilterInfo::new("a")).is_some());
+    assert!(chain.remove(&AudioFilterInfo::new("middle")).is_some());
+    let _ = in_send.send(buffer(0));
+    let _ = chain.update();
+    assert_eq!(samples(&out_recv), vec![0]);
+}
@@ -0,0 +1,193 @@
+#[cfg(test)]
+mod tests;
+
+use crate::error;
+use crate::util::{ClockTime, Element, Runnable};
+
+use crate::audio_system::audio::RawAudioBuffer;
+use crate::audio_system::element::{
+    AudioFilter, AudioSink, AudioSource, AudioSystemElementMessage,
+};
+
+use super::filter_chain::{AudioFilterInfo, ChainedAudioFilter};
+
+use mueue::*;
+
+/// Changes of the gain are spread over this duration to avoid clicks.
+const RAMP_DURATION: ClockTime = ClockTime::from_millis(10);
+
+/// Scales the audio by the linear gain and mutes it.
+pub struct AudioGain {
+    send: MessageSender<AudioSystemElementMessage>,
+    input: Option<MessageReceiver<RawAudioBuffer>>,
+    output: Option<MessageSender<RawAudioBuffer>>,
+
+    gain: f64,
+    muted: bool,
+
+    // The gain which was applied to the last frame.
+    current_gain: f64,
+    ramp_start_gain: f64,
+    ramp_pos: usize,
+}
+
+impl AudioGain {
+    pub fn new(send: MessageSender<AudioSystemElementMessage>) -> Self {
+        Self {
+            send,
+            input: None,
+            output: None,
+
+            gain: 1.0,
+            muted: false,
+
+            current_gain: 1.0,
+            ramp_start_gain: 1.0,
+            ramp_pos: 0,
+        }
+    }
+
+    pub fn gain(&self) -> f64 {
+        self.gain
+    }
+
+    pub fn set_gain(&mut self, gain: f64) {
+        assert!(
+            gain.is_finite() && gain >= 0.0,
+            "The gain should be non-negative"
+        );
+
+        self.gain = gain;
+        self.start_ramp();
+    }
+
+    pub fn is_muted(&self) -> bool {
+        self.muted
+    }
+
+    pub fn set_muted(&mut self, muted: bool) {
+        self.muted = muted;
+        self.start_ramp();
+    }
+
+    fn target_gain(&self) -> f64 {
+        if self.muted {
+            0.0
+        } else {
+            self.gain
+        }
+    }
+
+    fn start_ramp(&mut self) {
+        self.ramp_start_gain = self.current_gain;
+        self.ramp_pos = 0;
+    }
+
+    fn apply(&mut self, mut audio: RawAudioBuffer) -> RawAudioBuffer {
+        let target_gain = self.target_gain();
+        if self.current_gain == target_gain && target_gain == 1.0 {
+            return audio;
+        }
+
+        let format = audio.format();
+        let channels = usize::from(audio.channels());
+        if format.no_bytes() == 0 || channels == 0 {
+            return audio;
+        }
+
+        let ramp_frames = RAMP_DURATION.to_no_samples(audio.sample_rate()).max(1);
+        for frame in audio
+            .as_slice_mut()
+            .chunks_exact_mut(format.no_bytes() * channels)
+        {
+            if self.current_gain != target_gain {
+                self.ramp_pos += 1;
+
+                self.current_gain = if self.ramp_pos >= ramp_frames {
+                    target_gain
+                } else {
+                    let progress = self.ramp_pos as f64 / ramp_frames as f64;
+                    self.ramp_start_gain + (target_gain - self.ramp_start_gain) * progress
+                };
+            }
+
+            // Writing the sample clips it, so integer formats never overflow.
+            for bytes in frame.chunks_exact_mut(format.no_bytes()) {
+                let sample = format.read_normalized_sample(bytes);
+                format.write_normalized_sample(sample * self.current_gain, bytes);
+            }
+        }
+
+        audio
+    }
+}
+
+impl Runnable for AudioGain {
+    fn update(&mut self) -> error::Result<()> {
+        let Some(input) = self.input.clone() else {
+            return Ok(());
+        };
+
+        for audio in input.iter() {
+            let audio = self.apply(audio);
+
+            if let Some(output) = self.output.as_ref() {
+                let _ = output.send(audio);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Element for AudioGain {
+    type Message = AudioSystemElementMessage;
+
+    fn sender(&self) -> MessageSender<Self::Message> {
+        self.send.clone()
+    }
+
+    fn connect(&mut self, send: MessageSender<Self::Message>) {
+        self.send = send;
+    }
+}
+
+impl AudioSink<RawAudioBuffer> for AudioGain {
+    fn input(&self) -> Option<MessageReceiver<RawAudioBuffer>> {
+        self.input.clone()
+    }
+
+    fn set_input(&mut self, input: MessageReceiver<RawAudioBuffer>) {
+        self.input = Some(input);
+    }
+
+    fn unset_input(&mut self) {
+        self.input = None;
+    }
+}
+
+impl AudioSource<RawAudioBuffer> for AudioGain {
+    fn output(&self) -> Option<MessageSender<RawAudioBuffer>> {
+        self.output.clone()
+    }
+
+    fn set_output(&mut self, output: MessageSender<RawAudioBuffer>) {
+        self.output = Some(output);
+    }
+
+    fn unset_output(&mut self) {
+        self.output = None;
+    }
+}
+
+impl AudioFilter<RawAudioBuffer, RawAudioBuffer> for AudioGain {}
+
+impl ChainedAudioFilter<RawAudioBuffer> for AudioGain {
+    fn info(&self) -> AudioFilterInfo {
+        AudioFilterInfo::new("gain")
+    }
+
+    fn is_active(&self) -> bool {
+        self.current_gain != 1.0 || self.target_gain() != 1.0
+    }
+}
@@ -0,0 +1,98 @@
+use super::*;
+
+use crate::audio_system::audio::RawAudioFormat;
+
+const SAMPLE_RATE: u32 = 48000;
+
+fn constant(level: f64, format: RawAudioFormat, no_frames: usize) -> RawAudioBuffer {
+    RawAudioBuffer::from_normalized_samples(&vec![level; no_frames], format, 1, SAMPLE_RATE)
+}
+
+fn process(gain: &mut AudioGain, audio: RawAudioBuffer) -> Vec<f64> {
+    let in_send = gain.create_input();
+    let out_recv = gain.create_output();
+
+    let _ = in_send.send(audio);
+    let _ = gain.update();
+
+    out_recv.recv().unwrap().to_normalized_samples()
+}
+
+#[test]
+fn test_pass_unity_gain() {
+    let (send, _) = unidirectional_queue();
+    let mut gain = AudioGain::new(send);
+
+    let audio = constant(0.5, RawAudioFormat::S16LE, 480);
+    assert_eq!(
+        process(&mut gain, audio.clone()),
+        audio.to_normalized_samples()
+    );
+    assert!(!gain.is_active());
+
+    // The gain is active until it ramps back to unity.
+    gain.set_gain(0.5);
+    assert!(gain.is_active());
+    let _ = process(&mut gain, constant(0.5, RawAudioFormat::S16LE, 960));
+    gain.set_gain(1.0);
+    assert!(gain.is_active());
+    let _ = process(&mut gain, constant(0.5, RawAudioFormat::S16LE, 960));
+    assert!(!gain.is_active());
+}
+
+#[test]
+fn test_ramp_gain() {
+    let (send, _) = unidirectional_queue();
+    let mut gain = AudioGain::new(send);
+    gain.set_gain(0.5);
+
+    let samples = process(&mut gain, constant(0.5, RawAudioFormat::F32LE, 960));
+
+    let ramp_frames = RAMP_DURATION.to_no_samples(SAMPLE_RATE);
+    assert!(samples[..ramp_frames]
+        .windows(2)
+        .all(|pair| pair[1] < pair[0] && pair[0] - pair[1] < 0.001));
+    assert!(samples[ramp_frames..].iter().all(|sample| *sample == 0.25));
+}
+
+#[test]
+fn test_mute() {
+    let (send, _) = unidirectional_queue();
+    let mut gain = AudioGain::new(send);
+    gain.set_muted(true);
+
+    let _ = process(&mut gain, constant(0.5, RawAudioFormat::U8, 480));
+    let samples = process(&mut gain, constant(0.5, RawAudioFormat::U8, 480));
+    assert!(samples.iter().all(|sample| *sample == 0.0));
+
+    // Unmuting restores the gain, which was set while muted.
+    gain.set_gain(2.0);
+    gain.set_muted(false);
+    let _ = process(&mut gain, constant(0.25, RawAudioFormat::S16BE, 480));
+    let samples = process(&mut gain, constant(0.25, RawAudioFormat::S16BE, 480));
+    assert!(samples.iter().all(|sample| *sample == 0.5));
+}
+
+#[test]
+fn test_clip_integer_formats() {
+    for format in [
+        RawAudioFormat::U8,
+        RawAudioFormat::S16LE,
+        RawAudioFormat::S24BE,
+        RawAudioFormat::S32LE,
+    ] {
+        let (send, _) = unidirectional_queue();
+        let mut gain = AudioGain::new(send);
+        gain.set_gain(4.0);
+
+        let loud = constant(0.9, format, 960);
+        let max = constant(1.0, format, 1).to_normalized_samples()[0];
+
+        let samples = process(&mut gain, loud);
+        assert!(samples.iter().all(|sample| *sample <= max));
+        assert_eq!(*samples.last().unwrap(), max);
+
+        let samples = process(&mut gain, constant(-0.9, format, 480));
+        assert!(samples.iter().all(|sample| *sample == -1.0));
+    }
+}
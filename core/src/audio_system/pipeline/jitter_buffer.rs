@@ -0,0 +1,308 @@
+#[cfg(test)]
+mod tests;
+
+use crate::error;
+use crate::util::{Clock, ClockTime, Element, Runnable};
+
+use crate::audio_system::audio::EncodedAudioBuffer;
+use crate::audio_system::element::{
+    AudioFilter, AudioSink, AudioSource, AudioSystemElementMessage,
+};
+
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use mueue::*;
+
+const MIN_TARGET_DEPTH: ClockTime = ClockTime::from_millis(5);
+const MAX_TARGET_DEPTH: ClockTime = ClockTime::from_millis(200);
+
+/// How many times the target depth exceeds the measured jitter.
+const JITTER_MULTIPLIER: f64 = 3.0;
+/// The gain of the jitter estimator from RFC 3550.
+const JITTER_GAIN: f64 = 1.0 / 16.0;
+
+const HALF_SEQUENCE_RANGE: u32 = 1 << 31;
+
+struct QueuedAudioBuffer {
+    arrival_ts: ClockTime,
+    audio: EncodedAudioBuffer,
+}
+
+/// Reorders the received audio and releases it after the target depth, which
+/// follows the measured jitter. The PCM is released into its own output, since
+/// it doesn't need a real decoder.
+pub struct JitterBuffer {
+    send: MessageSender<AudioSystemElementMessage>,
+    input: Option<MessageReceiver<EncodedAudioBuffer>>,
+    output: Option<MessageSender<EncodedAudioBuffer>>,
+    pcm_output: Option<MessageSender<EncodedAudioBuffer>>,
+
+    sys_clock: Arc<dyn Clock>,
+
+    queue: BTreeMap<u64, QueuedAudioBuffer>,
+    last_released_key: Option<u64>,
+
+    highest_sequence_number: Option<u64>,
+
+    prev_transit: Option<i128>,
+    jitter: f64,
+    target_depth: ClockTime,
+
+    no_late_buffers: usize,
+
+    transmission_paused: Rc<Cell<bool>>,
+}
+
+impl JitterBuffer {
+    pub fn new(send: MessageSender<AudioSystemElementMessage>, sys_clock: Arc<dyn Clock>) -> Self {
+        Self {
+            send,
+            input: None,
+            output: None,
+            pcm_output: None,
+
+            sys_clock,
+
+            queue: BTreeMap::new(),
+            last_released_key: None,
+
+            highest_sequence_number: None,
+
+            prev_transit: None,
+            jitter: 0.0,
+            target_depth: MIN_TARGET_DEPTH,
+
+            no_late_buffers: 0,
+
+            transmission_paused: Rc::new(Cell::new(false)),
+        }
+    }
+
+    pub fn target_depth(&self) -> ClockTime {
+        self.target_depth
+    }
+
+    pub fn no_late_buffers(&self) -> usize {
+        self.no_late_buffers
+    }
+
+    /// Whether the sender marked the last released audio as silence or DTX, so
+    /// that the absence of the audio after it is intentional.
+    pub fn transmission_paused_state(&self) -> Rc<Cell<bool>> {
+        self.transmission_paused.clone()
+    }
+
+    pub fn pcm_output(&self) -> Option<MessageSender<EncodedAudioBuffer>> {
+        self.pcm_output.clone()
+    }
+
+    pub fn set_pcm_output(&mut self, output: MessageSender<EncodedAudioBuffer>) {
+        self.pcm_output = Some(output);
+    }
+
+    pub fn unset_pcm_output(&mut self) {
+        self.pcm_output = None;
+    }
+
+    pub fn chain_pcm(&mut self, sink: &mut dyn AudioSink<EncodedAudioBuffer>) {
+        let (output, input) = unidirectional_queue();
+
+        self.set_pcm_output(output);
+        sink.set_input(input);
+    }
+
+    fn collect_audio_buffers(&mut self) {
+        let Some(input) = self.input.clone() else {
+            return;
+        };
+
+        for audio in input.iter() {
+            self.push(audio);
+        }
+    }
+
+    fn push(&mut self, audio: EncodedAudioBuffer) {
+        let arrival_ts = self.sys_clock.get_time();
+
+        if audio.flags.discontinuity {
+            self.drain();
+            self.reset();
+        }
+
+        let Some(key) = self.ordering_key(&audio) else {
+            // There is nothing to order such buffers by.
+            self.release(audio);
+
+            return;
+        };
+
+        let is_late = self
+            .last_released_key
+            .is_some_and(|last_key| key <= last_key);
+        if is_late || self.queue.contains_key(&key) {
+            self.no_late_buffers += usize::from(is_late);
+
+            return;
+        }
+
+        if let Some(start_ts) = audio.start_ts {
+            self.update_jitter(arrival_ts, start_ts);
+        }
+
+        self.queue
+            .insert(key, QueuedAudioBuffer { arrival_ts, audio });
+    }
+
+    fn ordering_key(&mut self, audio: &EncodedAudioBuffer) -> Option<u64> {
+        match audio.sequence_number {
+            Some(sequence_number) => Some(self.extend_sequence_number(sequence_number)),
+            None => audio.start_ts.map(ClockTime::as_nanos),
+        }
+    }
+
+    // Sequence numbers wrap around, so they are extended with the number of cycles.
+    fn extend_sequence_number(&mut self, sequence_number: u32) -> u64 {
+        let Some(highest) = self.highest_sequence_number else {
+            // Start from the first cycle so that buffers from the previous one still fit.
+            let extended = (1 << u32::BITS) | u64::from(sequence_number);
+            self.highest_sequence_number = Some(extended);
+
+            return extended;
+        };
+
+        let highest_cycle = highest >> u32::BITS;
+        let highest_sequence_number = highest as u32;
+
+        let cycle = if sequence_number < highest_sequence_number
+            && highest_sequence_number - sequence_number > HALF_SEQUENCE_RANGE
+        {
+            highest_cycle + 1
+        } else if sequence_number > highest_sequence_number
+            && sequence_number - highest_sequence_number > HALF_SEQUENCE_RANGE
+        {
+            highest_cycle - 1
+        } else {
+            highest_cycle
+        };
+
+        let extended = (cycle << u32::BITS) | u64::from(sequence_number);
+        self.highest_sequence_number = Some(highest.max(extended));
+
+        extended
+    }
+
+    fn update_jitter(&mut self, arrival_ts: ClockTime, start_ts: ClockTime) {
+        let transit = i128::from(arrival_ts.as_nanos()) - i128::from(start_ts.as_nanos());
+
+        if let Some(prev_transit) = self.prev_transit {
+            let diff = (transit - prev_transit).abs() as f64;
+            self.jitter += (diff - self.jitter) * JITTER_GAIN;
+        }
+        self.prev_transit = Some(transit);
+
+        let target_depth = ClockTime::from_nanos((self.jitter * JITTER_MULTIPLIER) as u64);
+        self.target_depth = target_depth.clamp(MIN_TARGET_DEPTH, MAX_TARGET_DEPTH);
+    }
+
+    fn process_audio_buffers(&mut self) {
+        let now = self.sys_clock.get_time();
+
+        while let Some(entry) = self.queue.first_entry() {
+            if now < entry.get().arrival_ts + self.target_depth {
+                break;
+            }
+
+            let (key, queued) = entry.remove_entry();
+            self.last_released_key = Some(key);
+            self.release(queued.audio);
+        }
+    }
+
+    fn release(&self, audio: EncodedAudioBuffer) {
+        self.transmission_paused
+            .set(audio.flags.dtx || audio.flags.silence);
+
+        let output = if audio.header.codec.is_pcm() {
+            self.pcm_output.as_ref()
+        } else {
+            self.output.as_ref()
+        };
+
+        if let Some(output) = output {
+            let _ = output.send(audio);
+        }
+    }
+
+    fn drain(&mut self) {
+        while let Some((key, queued)) = self.queue.pop_first() {
+            self.last_released_key = Some(key);
+            self.release(queued.audio);
+        }
+    }
+
+    fn reset(&mut self) {
+        self.last_released_key = None;
+        self.highest_sequence_number = None;
+        self.prev_transit = None;
+    }
+}
+
+impl Runnable for JitterBuffer {
+    fn update(&mut self) -> error::Result<()> {
+        self.collect_audio_buffers();
+        self.process_audio_buffers();
+
+        Ok(())
+    }
+
+    fn on_stop(&mut self) {
+        self.collect_audio_buffers();
+        self.drain();
+        self.reset();
+    }
+}
+
+impl Element for JitterBuffer {
+    type Message = AudioSystemElementMessage;
+
+    fn sender(&self) -> MessageSender<Self::Message> {
+        self.send.clone()
+    }
+
+    fn connect(&mut self, send: MessageSender<Self::Message>) {
+        self.send = send;
+    }
+}
+
+impl AudioSink<EncodedAudioBuffer> for JitterBuffer {
+    fn input(&self) -> Option<MessageReceiver<EncodedAudioBuffer>> {
+        self.input.clone()
+    }
+
+    fn set_input(&mut self, input: MessageReceiver<EncodedAudioBuffer>) {
+        self.input = Some(input);
+    }
+
+    fn unset_input(&mut self) {
+        self.input = None;
+    }
+}
+
+impl AudioSource<EncodedAudioBuffer> for JitterBuffer {
+    fn output(&self) -> Option<MessageSender<EncodedAudioBuffer>> {
+        self.output.clone()
+    }
+
+    fn set_output(&mut self, output: MessageSender<EncodedAudioBuffer>) {
+        self.output = Some(output);
+    }
+
+    fn unset_output(&mut self) {
+        self.output = None;
+    }
+}
+
+impl AudioFilter<EncodedAudioBuffer, EncodedAudioBuffer> for JitterBuffer {}
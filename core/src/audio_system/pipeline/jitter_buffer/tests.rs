@@ -0,0 +1,259 @@
+use super::*;
+
+use crate::audio_system::audio::AudioCodec;
+use crate::test_util::encoded_audio;
+use crate::util::ClockInfo;
+
+use std::cell::Cell;
+
+const PACKET_DURATION: ClockTime = ClockTime::from_millis(20);
+
+struct FakeSystemClock(Cell<ClockTime>);
+
+impl FakeSystemClock {
+    fn new() -> Self {
+        Self(Cell::new(ClockTime::ZERO))
+    }
+
+    fn move_forward(&self, time: ClockTime) {
+        self.0.set(self.0.get() + time);
+    }
+}
+
+impl Clock for FakeSystemClock {
+    fn info(&self) -> ClockInfo {
+        ClockInfo {
+            name: String::from("Fake System Clock"),
+        }
+    }
+
+    fn get_time(&self) -> ClockTime {
+        self.0.get()
+    }
+}
+
+fn packet(sequence_number: Option<u32>, no_packet: u64) -> EncodedAudioBuffer {
+    packet_with_codec(AudioCodec::Opus, sequence_number, no_packet)
+}
+
+fn packet_with_codec(
+    codec: AudioCodec,
+    sequence_number: Option<u32>,
+    no_packet: u64,
+) -> EncodedAudioBuffer {
+    EncodedAudioBuffer {
+        sequence_number,
+        start_ts: Some(PACKET_DURATION * no_packet),
+        ..encoded_audio(codec, 1, vec![42; 16])
+    }
+}
+
+fn received_sequence_numbers(recv: &MessageReceiver<EncodedAudioBuffer>) -> Vec<Option<u32>> {
+    recv.iter().map(|audio| audio.sequence_number).collect()
+}
+
+#[test]
+fn test_reorder_by_sequence_number() {
+    let (send, _) = unidirectional_queue();
+    let sys_clock = Arc::new(FakeSystemClock::new());
+    let mut jitter = JitterBuffer::new(send, sys_clock.clone());
+
+    let in_send = jitter.create_input();
+    let out_recv = jitter.create_output();
+
+    for no_packet in [1, 0, 3, 2] {
+        let _ = in_send.send(packet(Some(no_packet as u32), no_packet));
+    }
+    let _ = jitter.update();
+    assert!(out_recv.recv().is_none());
+
+    sys_clock.move_forward(jitter.target_depth());
+    let _ = jitter.update();
+    assert_eq!(
+        received_sequence_numbers(&out_recv),
+        vec![Some(0), Some(1), Some(2), Some(3)]
+    );
+}
+
+#[test]
+fn test_reorder_by_timestamp() {
+    let (send, _) = unidirectional_queue();
+    let sys_clock = Arc::new(FakeSystemClock::new());
+    let mut jitter = JitterBuffer::new(send, sys_clock.clone());
+
+    let in_send = jitter.create_input();
+    let out_recv = jitter.create_output();
+
+    for no_packet in [2, 0, 1] {
+        let _ = in_send.send(packet(None, no_packet));
+    }
+    let _ = jitter.update();
+
+    sys_clock.move_forward(jitter.target_depth());
+    let _ = jitter.update();
+
+    let start_tss: Vec<_> = out_recv.iter().map(|audio| audio.start_ts).collect();
+    assert_eq!(
+        start_tss,
+        vec![
+            Some(ClockTime::ZERO),
+            Some(PACKET_DURATION),
+            Some(PACKET_DURATION * 2)
+        ]
+    );
+}
+
+#[test]
+fn test_drop_late_buffers() {
+    let (send, _) = unidirectional_queue();
+    let sys_clock = Arc::new(FakeSystemClock::new());
+    let mut jitter = JitterBuffer::new(send, sys_clock.clone());
+
+    let in_send = jitter.create_input();
+    let out_recv = jitter.create_output();
+
+    let _ = in_send.send(packet(Some(1), 1));
+    let _ = jitter.update();
+    sys_clock.move_forward(jitter.target_depth());
+    let _ = jitter.update();
+    assert_eq!(received_sequence_numbers(&out_recv), vec![Some(1)]);
+
+    let _ = in_send.send(packet(Some(0), 0));
+    let _ = in_send.send(packet(Some(1), 1));
+    let _ = in_send.send(packet(Some(2), 2));
+    let _ = jitter.update();
+    sys_clock.move_forward(jitter.target_depth());
+    let _ = jitter.update();
+
+    assert_eq!(received_sequence_numbers(&out_recv), vec![Some(2)]);
+    assert_eq!(jitter.no_late_buffers(), 2);
+}
+
+#[test]
+fn test_sequence_number_wrap_around() {
+    let (send, _) = unidirectional_queue();
+    let sys_clock = Arc::new(FakeSystemClock::new());
+    let mut jitter = JitterBuffer::new(send, sys_clock.clone());
+
+    let in_send = jitter.create_input();
+    let out_recv = jitter.create_output();
+
+    let _ = in_send.send(packet(Some(1), 2));
+    let _ = in_send.send(packet(Some(u32::MAX), 0));
+    let _ = in_send.send(packet(Some(0), 1));
+    let _ = jitter.update();
+    sys_clock.move_forward(jitter.target_depth());
+    let _ = jitter.update();
+
+    assert_eq!(
+        received_sequence_numbers(&out_recv),
+        vec![Some(u32::MAX), Some(0), Some(1)]
+    );
+}
+
+#[test]
+fn test_adapt_target_depth() {
+    let (send, _) = unidirectional_queue();
+    let sys_clock = Arc::new(FakeSystemClock::new());
+    let mut jitter = JitterBuffer::new(send, sys_clock.clone());
+
+    let in_send = jitter.create_input();
+    let _out_recv = jitter.create_output();
+
+    for no_packet in 0..50 {
+        let _ = in_send.send(packet(Some(no_packet as u32), no_packet));
+        let _ = jitter.update();
+        sys_clock.move_forward(PACKET_DURATION);
+    }
+    assert_eq!(jitter.target_depth(), MIN_TARGET_DEPTH);
+
+    // Packets arrive in bursts of two.
+    for no_packet in 50..150 {
+        let _ = in_send.send(packet(Some(no_packet as u32), no_packet));
+        let _ = jitter.update();
+        if no_packet % 2 == 1 {
+            sys_clock.move_forward(PACKET_DURATION * 2);
+        }
+    }
+    assert!(jitter.target_depth() > ClockTime::from_millis(40));
+    assert!(jitter.target_depth() <= MAX_TARGET_DEPTH);
+}
+
+#[test]
+fn test_discontinuity() {
+    let (send, _) = unidirectional_queue();
+    let sys_clock = Arc::new(FakeSystemClock::new());
+    let mut jitter = JitterBuffer::new(send, sys_clock.clone());
+
+    let in_send = jitter.create_input();
+    let out_recv = jitter.create_output();
+
+    let _ = in_send.send(packet(Some(100), 100));
+    let _ = jitter.update();
+
+    let mut restarted_audio = packet(Some(0), 0);
+    restarted_audio.flags.discontinuity = true;
+    let _ = in_send.send(restarted_audio);
+    let _ = jitter.update();
+    assert_eq!(received_sequence_numbers(&out_recv), vec![Some(100)]);
+
+    sys_clock.move_forward(jitter.target_depth());
+    let _ = jitter.update();
+    assert_eq!(received_sequence_numbers(&out_recv), vec![Some(0)]);
+    assert_eq!(jitter.no_late_buffers(), 0);
+}
+
+#[test]
+fn test_route_pcm() {
+    let (send, _) = unidirectional_queue();
+    let sys_clock = Arc::new(FakeSystemClock::new());
+    let mut jitter = JitterBuffer::new(send, sys_clock.clone());
+
+    let in_send = jitter.create_input();
+    let out_recv = jitter.create_output();
+    let (pcm_send, pcm_recv) = unidirectional_queue();
+    jitter.set_pcm_output(pcm_send);
+
+    // The PCM is reordered along with the other codecs.
+    for (no_packet, codec) in [
+        (1, AudioCodec::PcmS16LE),
+        (0, AudioCodec::PcmS16LE),
+        (2, AudioCodec::Opus),
+    ] {
+        let _ = in_send.send(packet_with_codec(codec, Some(no_packet as u32), no_packet));
+    }
+    let _ = jitter.update();
+    sys_clock.move_forward(MAX_TARGET_DEPTH);
+    let _ = jitter.update();
+
+    assert_eq!(received_sequence_numbers(&out_recv), vec![Some(2)]);
+    assert_eq!(received_sequence_numbers(&pcm_recv), vec![Some(0), Some(1)]);
+}
+
+#[test]
+fn test_transmission_paused() {
+    let (send, _) = unidirectional_queue();
+    let sys_clock = Arc::new(FakeSystemClock::new());
+    let mut jitter = JitterBuffer::new(send, sys_clock.clone());
+    let transmission_paused = jitter.transmission_paused_state();
+
+    let in_send = jitter.create_input();
+    let _out_recv = jitter.create_output();
+
+    let mut dtx_audio = packet(Some(0), 0);
+    dtx_audio.flags.dtx = true;
+    let _ = in_send.send(dtx_audio);
+    let _ = jitter.update();
+    // The state follows the released audio, not the received one.
+    assert!(!transmission_paused.get());
+
+    sys_clock.move_forward(jitter.target_depth());
+    let _ = jitter.update();
+    assert!(transmission_paused.get());
+
+    let _ = in_send.send(packet(Some(10), 10));
+    let _ = jitter.update();
+    sys_clock.move_forward(MAX_TARGET_DEPTH);
+    let _ = jitter.update();
+    assert!(!transmission_paused.get());
+}
@@ -0,0 +1,308 @@
+#[cfg(test)]
+mod tests;
+
+use crate::error;
+use crate::util::{ClockTime, Element, Runnable};
+
+use crate::audio_system::audio::{RawAudioBuffer, RawAudioInfo, TimestampedRawAudioBuffer};
+use crate::audio_system::element::{
+    AudioFilter, AudioSink, AudioSource, AudioSystemElementMessage,
+};
+
+use super::filter_chain::{AudioFilterInfo, ChainedAudioFilter};
+
+use mueue::*;
+
+/// Smaller gaps are caused by rounding of the timestamps.
+const MIN_GAP_DURATION: ClockTime = ClockTime::from_millis(1);
+/// Longer gaps are considered to be pauses in the stream.
+const MAX_CONCEALMENT_DURATION: ClockTime = ClockTime::from_millis(120);
+
+const HISTORY_DURATION: ClockTime = ClockTime::from_millis(40);
+const MIN_PITCH_PERIOD: ClockTime = ClockTime::from_micros(2500);
+const MAX_PITCH_PERIOD: ClockTime = ClockTime::from_millis(20);
+/// The minimal normalized autocorrelation of a pitch period.
+const MIN_PITCH_CORRELATION: f64 = 0.3;
+
+const FADE_OUT_DURATION: ClockTime = ClockTime::from_millis(60);
+const CROSSFADE_DURATION: ClockTime = ClockTime::from_millis(5);
+
+/// Fills the gaps between decoded buffers by repeating the last pitch period of
+/// the audio, which gradually fades out. The synthesized audio goes on past the
+/// gap and is crossfaded into the audio which follows it.
+pub struct LossConcealer {
+    send: MessageSender<AudioSystemElementMessage>,
+    input: Option<MessageReceiver<TimestampedRawAudioBuffer>>,
+    output: Option<MessageSender<TimestampedRawAudioBuffer>>,
+
+    stream_info: Option<RawAudioInfo>,
+    history: Vec<f64>,
+    expected_ts: Option<ClockTime>,
+
+    no_concealments: usize,
+}
+
+impl LossConcealer {
+    pub fn new(send: MessageSender<AudioSystemElementMessage>) -> Self {
+        Self {
+            send,
+            input: None,
+            output: None,
+
+            stream_info: None,
+            history: Vec::new(),
+            expected_ts: None,
+
+            no_concealments: 0,
+        }
+    }
+
+    pub fn no_concealments(&self) -> usize {
+        self.no_concealments
+    }
+
+    fn process(&mut self, mut audio: TimestampedRawAudioBuffer) {
+        if audio == TimestampedRawAudioBuffer::NULL {
+            self.reset();
+            self.release(audio);
+
+            return;
+        }
+        if audio.no_samples() == 0 {
+            self.release(audio);
+
+            return;
+        }
+
+        let stream_info = audio.info();
+        if self.stream_info != Some(stream_info) {
+            self.reset();
+            self.stream_info = Some(stream_info);
+        }
+
+        if let Some((concealment, tail)) = self.conceal(&audio) {
+            self.no_concealments += 1;
+            let _ = self.send.send(AudioSystemElementMessage::LossConcealed {
+                duration: concealment.duration(),
+            });
+            self.release(concealment);
+
+            audio = crossfade(audio, &tail);
+        }
+
+        self.remember(&audio);
+        self.release(audio);
+    }
+
+    /// Returns the concealment and the synthesized audio which overlaps the
+    /// beginning of the buffer.
+    fn conceal(
+        &self,
+        audio: &TimestampedRawAudioBuffer,
+    ) -> Option<(TimestampedRawAudioBuffer, Vec<f64>)> {
+        let stream_info = self.stream_info?;
+        let expected_ts = self.expected_ts?;
+        let start = audio.start()?;
+
+        let gap = start.saturating_sub(expected_ts);
+        if gap < MIN_GAP_DURATION || gap > MAX_CONCEALMENT_DURATION || self.history.is_empty() {
+            return None;
+        }
+
+        let channels = usize::from(stream_info.channels);
+        let no_frames = gap.to_no_samples(stream_info.sample_rate);
+        let mut samples = synthesize(
+            &self.history,
+            channels,
+            stream_info.sample_rate,
+            no_frames + CROSSFADE_DURATION.to_no_samples(stream_info.sample_rate),
+        );
+        let tail = samples.split_off(no_frames * channels);
+        let raw = RawAudioBuffer::from_normalized_samples(
+            &samples,
+            stream_info.format,
+            stream_info.channels,
+            stream_info.sample_rate,
+        )
+        .with_channel_layout(stream_info.channel_layout);
+
+        Some((TimestampedRawAudioBuffer::new(raw, Some(expected_ts)), tail))
+    }
+
+    fn remember(&mut self, audio: &TimestampedRawAudioBuffer) {
+        let history_len =
+            HISTORY_DURATION.to_no_samples(audio.sample_rate()) * usize::from(audio.channels());
+
+        self.history.extend(audio.as_raw().to_normalized_samples());
+        if self.history.len() > history_len {
+            self.history.drain(..self.history.len() - history_len);
+        }
+
+        self.expected_ts = match audio.stop() {
+            Some(stop) => Some(stop),
+            None => self.expected_ts.map(|ts| ts + audio.duration()),
+        };
+    }
+
+    fn release(&self, audio: TimestampedRawAudioBuffer) {
+        if let Some(output) = self.output.as_ref() {
+            let _ = output.send(audio);
+        }
+    }
+
+    fn reset(&mut self) {
+        self.stream_info = None;
+        self.history.clear();
+        self.expected_ts = None;
+    }
+}
+
+fn synthesize(history: &[f64], channels: usize, sample_rate: u32, no_frames: usize) -> Vec<f64> {
+    let history_frames = history.len() / channels;
+    let period = estimate_pitch_period(history, channels, sample_rate).unwrap_or(history_frames);
+    let last_period = &history[(history_frames - period) * channels..];
+
+    let fade_out_frames = FADE_OUT_DURATION.to_no_samples(sample_rate).max(1);
+
+    let mut samples = Vec::with_capacity(no_frames * channels);
+    for frame in 0..no_frames {
+        let gain = 1.0 - (frame as f64 / fade_out_frames as f64).min(1.0);
+        let offset = (frame % period) * channels;
+
+        samples.extend(
+            last_period[offset..offset + channels]
+                .iter()
+                .map(|sample| sample * gain),
+        );
+    }
+
+    samples
+}
+
+/// Finds the lag in frames at which the end of the history resembles itself the most.
+fn estimate_pitch_period(history: &[f64], channels: usize, sample_rate: u32) -> Option<usize> {
+    let mono: Vec<f64> = history
+        .chunks_exact(channels)
+        .map(|frame| frame.iter().sum::<f64>() / channels as f64)
+        .collect();
+
+    let min_lag = MIN_PITCH_PERIOD.to_no_samples(sample_rate).max(1);
+    let max_lag = MAX_PITCH_PERIOD
+        .to_no_samples(sample_rate)
+        .min(mono.len() / 2);
+    if min_lag > max_lag {
+        return None;
+    }
+
+    let window = &mono[mono.len() - max_lag..];
+    let energy: f64 = window.iter().map(|sample| sample * sample).sum();
+
+    let mut best = None;
+    let mut best_correlation = MIN_PITCH_CORRELATION;
+    for lag in min_lag..=max_lag {
+        let lagged = &mono[mono.len() - max_lag - lag..mono.len() - lag];
+
+        let cross: f64 = window.iter().zip(lagged).map(|(a, b)| a * b).sum();
+        let lagged_energy: f64 = lagged.iter().map(|sample| sample * sample).sum();
+        if energy == 0.0 || lagged_energy == 0.0 {
+            continue;
+        }
+
+        let correlation = cross / (energy * lagged_energy).sqrt();
+        if correlation > best_correlation {
+            best_correlation = correlation;
+            best = Some(lag);
+        }
+    }
+
+    best
+}
+
+fn crossfade(audio: TimestampedRawAudioBuffer, tail: &[f64]) -> TimestampedRawAudioBuffer {
+    let start = audio.start();
+    let mut raw = audio.into_raw();
+
+    let format = raw.format();
+    let channels = usize::from(raw.channels());
+    let crossfade_frames = (tail.len() / channels).min(raw.no_samples());
+
+    for (no_sample, (bytes, tail_sample)) in raw
+        .as_slice_mut()
+        .chunks_exact_mut(format.no_bytes())
+        .zip(tail)
+        .take(crossfade_frames * channels)
+        .enumerate()
+    {
+        let gain = (no_sample / channels) as f64 / crossfade_frames as f64;
+        let sample = format.read_normalized_sample(bytes);
+        format.write_normalized_sample(sample * gain + tail_sample * (1.0 - gain), bytes);
+    }
+
+    TimestampedRawAudioBuffer::new(raw, start)
+}
+
+impl Runnable for LossConcealer {
+    fn update(&mut self) -> error::Result<()> {
+        let Some(input) = self.input.clone() else {
+            return Ok(());
+        };
+
+        for audio in input.iter() {
+            self.process(audio);
+        }
+
+        Ok(())
+    }
+
+    fn on_stop(&mut self) {
+        self.reset();
+    }
+}
+
+impl Element for LossConcealer {
+    type Message = AudioSystemElementMessage;
+
+    fn sender(&self) -> MessageSender<Self::Message> {
+        self.send.clone()
+    }
+
+    fn connect(&mut self, send: MessageSender<Self::Message>) {
+        self.send = send;
+    }
+}
+
+impl AudioSink<TimestampedRawAudioBuffer> for LossConcealer {
+    fn input(&self) -> Option<MessageReceiver<TimestampedRawAudioBuffer>> {
+        self.input.clone()
+    }
+
+    fn set_input(&mut self, input: MessageReceiver<TimestampedRawAudioBuffer>) {
+        self.input = Some(input);
+    }
+
+    fn unset_input(&mut self) {
+        self.input = None;
+    }
+}
+
+impl AudioSource<TimestampedRawAudioBuffer> for LossConcealer {
+    fn output(&self) -> Option<MessageSender<TimestampedRawAudioBuffer>> {
+        self.output.clone()
+    }
+
+    fn set_output(&mut self, output: MessageSender<TimestampedRawAudioBuffer>) {
+        self.output = Some(output);
+    }
+
+    fn unset_output(&mut self) {
+        self.output = None;
+    }
+}
+
+impl AudioFilter<TimestampedRawAudioBuffer, TimestampedRawAudioBuffer> for LossConcealer {}
+
+impl ChainedAudioFilter<TimestampedRawAudioBuffer> for LossConcealer {
+    fn info(&self) -> AudioFilterInfo {
+        AudioFilterInfo::new("loss_concealer")
+    }
+}
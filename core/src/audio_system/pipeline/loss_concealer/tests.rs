@@ -0,0 +1,164 @@
+use super::*;
+
+use crate::audio_system::audio::RawAudioFormat;
+
+use std::f64::consts::PI;
+
+const SAMPLE_RATE: u32 = 48000;
+const FREQUENCY: f64 = 440.0;
+const AMPLITUDE: f64 = 0.5;
+const BUFFER_DURATION: ClockTime = ClockTime::from_millis(20);
+
+fn tone(start: ClockTime, duration: ClockTime) -> TimestampedRawAudioBuffer {
+    let first_sample = start.to_no_samples(SAMPLE_RATE);
+    let samples: Vec<f64> = (first_sample..first_sample + duration.to_no_samples(SAMPLE_RATE))
+        .map(|n| AMPLITUDE * (2.0 * PI * FREQUENCY * n as f64 / SAMPLE_RATE as f64).sin())
+        .collect();
+    let raw =
+        RawAudioBuffer::from_normalized_samples(&samples, RawAudioFormat::S16LE, 1, SAMPLE_RATE);
+
+    TimestampedRawAudioBuffer::new(raw, Some(start))
+}
+
+fn no_concealments(notification_recv: &MessageReceiver<AudioSystemElementMessage>) -> usize {
+    notification_recv
+        .iter()
+        .filter(|msg| matches!(msg, AudioSystemElementMessage::LossConcealed { .. }))
+        .count()
+}
+
+#[test]
+fn test_pass_continuous_audio() {
+    let (send, notification_recv) = unidirectional_queue();
+    let mut plc = LossConcealer::new(send);
+
+    let in_send = plc.create_input();
+    let out_recv = plc.create_output();
+
+    for no_buf in 0..3 {
+        let _ = in_send.send(tone(BUFFER_DURATION * no_buf, BUFFER_DURATION));
+    }
+    let _ = plc.update();
+
+    for no_buf in 0..3 {
+        assert_eq!(
+            out_recv.recv().unwrap(),
+            tone(BUFFER_DURATION * no_buf, BUFFER_DURATION)
+        );
+    }
+    assert!(out_recv.recv().is_none());
+    assert_eq!(no_concealments(&notification_recv), 0);
+}
+
+#[test]
+fn test_conceal_lost_buffer() {
+    let (send, notification_recv) = unidirectional_queue();
+    let mut plc = LossConcealer::new(send);
+
+    let in_send = plc.create_input();
+    let out_recv = plc.create_output();
+
+    let _ = in_send.send(tone(ClockTime::ZERO, BUFFER_DURATION * 2));
+    let _ = in_send.send(tone(BUFFER_DURATION * 3, BUFFER_DURATION));
+    let _ = plc.update();
+
+    assert!(out_recv.recv().is_some());
+
+    let concealment = out_recv.recv().unwrap();
+    assert_eq!(concealment.start(), Some(BUFFER_DURATION * 2));
+    assert_eq!(concealment.duration(), BUFFER_DURATION);
+
+    // The beginning of the concealment continues the tone.
+    let expected = tone(BUFFER_DURATION * 2, ClockTime::from_millis(2)).into_raw();
+    let concealed = concealment.as_raw().to_normalized_samples();
+    for (expected, concealed) in expected.to_normalized_samples().iter().zip(concealed) {
+        assert!((expected - concealed).abs() < 0.05);
+    }
+
+    assert_eq!(out_recv.recv().unwrap().start(), Some(BUFFER_DURATION * 3));
+    assert!(out_recv.recv().is_none());
+
+    assert_eq!(no_concealments(&notification_recv), 1);
+    assert_eq!(plc.no_concealments(), 1);
+}
+
+#[test]
+fn test_fade_out_concealment() {
+    let (send, _) = unidirectional_queue();
+    let mut plc = LossConcealer::new(send);
+
+    let in_send = plc.create_input();
+    let out_recv = plc.create_output();
+
+    let _ = in_send.send(tone(ClockTime::ZERO, BUFFER_DURATION * 2));
+    let _ = in_send.send(tone(BUFFER_DURATION * 7, BUFFER_DURATION));
+    let _ = plc.update();
+
+    let _ = out_recv.recv();
+    let concealment = out_recv.recv().unwrap();
+    assert_eq!(concealment.duration(), BUFFER_DURATION * 5);
+
+    let fade_out_samples = FADE_OUT_DURATION.to_no_samples(SAMPLE_RATE);
+    let concealed = concealment.as_raw().to_normalized_samples();
+    assert!(concealed[fade_out_samples..]
+        .iter()
+        .all(|sample| *sample == 0.0));
+}
+
+#[test]
+fn test_crossfade_after_short_gap() {
+    let (send, _) = unidirectional_queue();
+    let mut plc = LossConcealer::new(send);
+
+    let in_send = plc.create_input();
+    let out_recv = plc.create_output();
+
+    // The concealment is still loud when the audio comes back.
+    let _ = in_send.send(tone(ClockTime::ZERO, BUFFER_DURATION * 2));
+    let _ = in_send.send(tone(BUFFER_DURATION * 3, BUFFER_DURATION));
+    let _ = plc.update();
+
+    let _ = out_recv.recv();
+    let concealed = out_recv.recv().unwrap().as_raw().to_normalized_samples();
+    let received = out_recv.recv().unwrap().as_raw().to_normalized_samples();
+
+    let boundary = concealed.len();
+    let samples = [concealed, received].concat();
+    let max_tone_step = 2.0 * PI * FREQUENCY * AMPLITUDE / SAMPLE_RATE as f64;
+    assert!(samples[boundary - 48..boundary + 480]
+        .windows(2)
+        .all(|pair| (pair[1] - pair[0]).abs() < max_tone_step * 1.5));
+}
+
+#[test]
+fn test_ignore_long_gap() {
+    let (send, notification_recv) = unidirectional_queue();
+    let mut plc = LossConcealer::new(send);
+
+    let in_send = plc.create_input();
+    let out_recv = plc.create_output();
+
+    let _ = in_send.send(tone(ClockTime::ZERO, BUFFER_DURATION));
+    let _ = in_send.send(tone(ClockTime::from_secs(1), BUFFER_DURATION));
+    let _ = plc.update();
+
+    assert_eq!(out_recv.iter().count(), 2);
+    assert_eq!(no_concealments(&notification_recv), 0);
+}
+
+#[test]
+fn test_reset_on_eos() {
+    let (send, notification_recv) = unidirectional_queue();
+    let mut plc = LossConcealer::new(send);
+
+    let in_send = plc.create_input();
+    let out_recv = plc.create_output();
+
+    let _ = in_send.send(tone(ClockTime::ZERO, BUFFER_DURATION));
+    let _ = in_send.send(TimestampedRawAudioBuffer::NULL);
+    let _ = in_send.send(tone(BUFFER_DURATION * 2, BUFFER_DURATION));
+    let _ = plc.update();
+
+    assert_eq!(out_recv.iter().count(), 3);
+    assert_eq!(no_concealments(&notification_recv), 0);
+}
@@ -1,16 +1,43 @@
+#[cfg(test)]
+mod tests;
+
+pub mod agc;
 pub mod audio_decoder;
+pub mod converter;
 pub mod demuxer;
+pub mod dynamics;
+pub mod equalizer;
+pub mod file_writer;
+pub mod filter_chain;
+pub mod gain;
+pub mod jitter_buffer;
+pub mod loss_concealer;
+pub mod noise_gate;
+pub mod noise_suppressor;
+pub mod pcm_decoder;
+pub mod resampler;
 pub mod resizer;
 pub mod sync;
+pub mod tee;
+pub mod time_stretcher;
 pub mod virtual_microphone;
 
 use audio_decoder::*;
+use converter::AudioConverter;
 use demuxer::*;
+use file_writer::{OggOpusFileWriter, WavFileWriter};
+use filter_chain::*;
+use jitter_buffer::*;
+use noise_gate::NoiseGate;
+use pcm_decoder::*;
+use resampler::AudioResampler;
 use resizer::AudioResizer;
 use sync::*;
+use tee::AudioTee;
 use virtual_microphone::*;
 
-use super::element::{AsAudioSink, AsAudioSource};
+use super::audio::{EncodedAudioBuffer, RawAudioBuffer, RawAudioFormat, TimestampedRawAudioBuffer};
+use super::element::{AsAudioSink, AsAudioSource, AudioSink, AudioSource};
 
 use crate::error;
 use crate::util::{Runnable, RunnableStateMachine};
@@ -32,6 +59,8 @@ macro_rules! add_pipeline_element {
         $(
             @modify_on_take ($elem_on_take:ident: $elem_on_take_ty:ty) => $on_take_mod:block;
         )*
+
+        $( @relink $relink:ident; )?
     ) => {
         paste::paste! {
             pub(super) fn [< set_ $func >](&mut self, mut elem: $elem) {
@@ -61,6 +90,8 @@ macro_rules! add_pipeline_element {
                 )*
 
                 self.$name = Some(elem);
+
+                $( self.$relink(); )?
             }
 
             pub(super) fn [< take_ $func >](&mut self) -> Option<$elem> {
@@ -76,7 +107,7 @@ macro_rules! add_pipeline_element {
                     }
                 )?
 
-                self.$name.take().map(|mut elem| {
+                let elem = self.$name.take().map(|mut elem| {
                     if self.is_running {
                         elem.on_stop();
                     }
@@ -91,7 +122,11 @@ macro_rules! add_pipeline_element {
                     )*
 
                     elem
-                })
+                });
+
+                $( self.$relink(); )?
+
+                elem
             }
 
             pub(super) fn [< has_ $func >](&self) -> bool {
@@ -111,12 +146,26 @@ macro_rules! add_pipeline_element {
 
 pub(super) type AudioPipelineStateMachine = RunnableStateMachine<AudioPipeline>;
 
-// TODO: Make extendable and polymorphic.
+/// The elements which change the type of the audio form the fixed spine of the
+/// pipeline. Each section between them runs a filter chain of its type, which
+/// is arranged by the config and may be changed while the pipeline is running.
 pub(super) struct AudioPipeline {
     demux: Option<AudioDemuxer>,
+    received_tee: Option<AudioTee<EncodedAudioBuffer>>,
+    received_rec: Option<OggOpusFileWriter>,
+    encoded_filters: Option<EncodedAudioFilterChain>,
+    jitter: Option<JitterBuffer>,
     dec: Option<Box<dyn AudioDecoder>>,
+    pcm_dec: Option<PcmDecoder>,
+    decoded_filters: Option<DecodedAudioFilterChain>,
     sync: Option<Synchronizer>,
     resizer: Option<AudioResizer>,
+    float_conv: Option<AudioConverter>,
+    resampler: Option<AudioResampler>,
+    raw_filters: Option<RawAudioFilterChain>,
+    conv: Option<AudioConverter>,
+    played_tee: Option<AudioTee<RawAudioBuffer>>,
+    played_rec: Option<WavFileWriter>,
     mic: Option<Box<dyn VirtualMicrophone>>,
 
     is_running: bool,
@@ -127,9 +176,21 @@ impl AudioPipeline {
     pub(super) fn new() -> Self {
         Self {
             demux: None,
+            received_tee: None,
+            received_rec: None,
+            encoded_filters: None,
+            jitter: None,
             dec: None,
+            pcm_dec: None,
+            decoded_filters: None,
             sync: None,
             resizer: None,
+            float_conv: None,
+            resampler: None,
+            raw_filters: None,
+            conv: None,
+            played_tee: None,
+            played_rec: None,
             mic: None,
 
             is_running: false,
@@ -142,7 +203,68 @@ impl AudioPipeline {
         @long_name audio_demuxer;
         @name demux;
 
+        @next received_tee;
+    }
+
+    add_pipeline_element! {
+        @element AudioTee<EncodedAudioBuffer>;
+
+        @long_name received_audio_tee;
+        @name received_tee;
+
+        @prev demux;
+        @next encoded_filters;
+
+        @modify_on_set (received_rec: &mut OggOpusFileWriter) => {
+            received_tee.add_branch(received_rec);
+        };
+    }
+
+    add_pipeline_element! {
+        @element OggOpusFileWriter;
+
+        @long_name received_audio_recorder;
+        @name received_rec;
+
+        @modify_on_set (received_tee: &mut AudioTee<EncodedAudioBuffer>) => {
+            received_tee.add_branch(received_rec);
+        };
+        @modify_on_take (received_tee: &mut AudioTee<EncodedAudioBuffer>) => {
+            received_rec.unset_input();
+        };
+    }
+
+    add_pipeline_element! {
+        @element EncodedAudioFilterChain;
+
+        @long_name encoded_filter_chain;
+        @name encoded_filters;
+
+        @prev received_tee;
+        @next jitter;
+    }
+
+    add_pipeline_element! {
+        @element JitterBuffer;
+
+        @long_name jitter_buffer;
+        @name jitter;
+
+        @prev encoded_filters;
         @next dec;
+
+        @modify_on_set (pcm_dec: &mut PcmDecoder) => {
+            jitter.chain_pcm(pcm_dec);
+        };
+        @modify_on_set (sync: &mut Synchronizer) => {
+            sync.set_transmission_state(Some(jitter.transmission_paused_state()));
+        };
+        @modify_on_take (pcm_dec: &mut PcmDecoder) => {
+            pcm_dec.unset_input();
+        };
+        @modify_on_take (sync: &mut Synchronizer) => {
+            sync.unset_transmission_state();
+        };
     }
 
     add_pipeline_element! {
@@ -151,8 +273,49 @@ impl AudioPipeline {
         @long_name audio_decoder;
         @name dec;
 
-        @prev demux;
+        @prev jitter;
+        @next decoded_filters;
+
+        @relink relink_pcm_decoder_output;
+    }
+
+    add_pipeline_element! {
+        @element PcmDecoder;
+
+        @long_name pcm_decoder;
+        @name pcm_dec;
+
+        @modify_on_set (jitter: &mut JitterBuffer) => {
+            jitter.chain_pcm(pcm_dec);
+        };
+        @modify_on_take (jitter: &mut JitterBuffer) => {
+            jitter.unset_pcm_output();
+        };
+
+        @relink relink_pcm_decoder_output;
+    }
+
+    // The synchronizer fills the gaps in the audio with silence while the noise
+    // gate of the chain is closed.
+    add_pipeline_element! {
+        @element DecodedAudioFilterChain;
+
+        @long_name decoded_filter_chain;
+        @name decoded_filters;
+
+        @prev dec;
         @next sync;
+
+        @modify_on_set (sync: &mut Synchronizer) => {
+            sync.set_noise_gate_state(
+                decoded_filters.filter::<NoiseGate>().map(NoiseGate::closed_state),
+            );
+        };
+        @modify_on_take (sync: &mut Synchronizer) => {
+            sync.unset_noise_gate_state();
+        };
+
+        @relink relink_pcm_decoder_output;
     }
 
     add_pipeline_element! {
@@ -161,8 +324,17 @@ impl AudioPipeline {
         @long_name synchronizer;
         @name sync;
 
-        @prev dec;
+        @prev decoded_filters;
         @next resizer;
+
+        @modify_on_set (decoded_filters: &mut DecodedAudioFilterChain) => {
+            sync.set_noise_gate_state(
+                decoded_filters.filter::<NoiseGate>().map(NoiseGate::closed_state),
+            );
+        };
+        @modify_on_set (jitter: &mut JitterBuffer) => {
+            sync.set_transmission_state(Some(jitter.transmission_paused_state()));
+        };
     }
 
     add_pipeline_element! {
@@ -172,7 +344,81 @@ impl AudioPipeline {
         @name resizer;
 
         @prev sync;
+        @next float_conv;
+    }
+
+    // The filters process the audio in float, so that it isn't clipped until
+    // it's limited. The audio stays in its format while none of them is active.
+    add_pipeline_element! {
+        @element AudioConverter;
+
+        @long_name float_converter;
+        @name float_conv;
+
+        @prev resizer;
+        @next resampler;
+    }
+
+    add_pipeline_element! {
+        @element AudioResampler;
+
+        @long_name resampler;
+        @name resampler;
+
+        @prev float_conv;
+        @next raw_filters;
+
+        @modify_on_set (mic: &mut Box<dyn VirtualMicrophone>) => {
+            resampler.set_output_sample_rate(mic.sample_rate());
+        };
+    }
+
+    add_pipeline_element! {
+        @element RawAudioFilterChain;
+
+        @long_name raw_filter_chain;
+        @name raw_filters;
+
+        @prev resampler;
+        @next conv;
+    }
+
+    add_pipeline_element! {
+        @element AudioConverter;
+
+        @long_name converter;
+        @name conv;
+
+        @prev raw_filters;
+        @next played_tee;
+    }
+
+    add_pipeline_element! {
+        @element AudioTee<RawAudioBuffer>;
+
+        @long_name played_audio_tee;
+        @name played_tee;
+
+        @prev conv;
         @next mic;
+
+        @modify_on_set (played_rec: &mut WavFileWriter) => {
+            played_tee.add_branch(played_rec);
+        };
+    }
+
+    add_pipeline_element! {
+        @element WavFileWriter;
+
+        @long_name played_audio_recorder;
+        @name played_rec;
+
+        @modify_on_set (played_tee: &mut AudioTee<RawAudioBuffer>) => {
+            played_tee.add_branch(played_rec);
+        };
+        @modify_on_take (played_tee: &mut AudioTee<RawAudioBuffer>) => {
+            played_rec.unset_input();
+        };
     }
 
     add_pipeline_element! {
@@ -181,29 +427,134 @@ impl AudioPipeline {
         @long_name virtual_microphone;
         @name mic;
 
-        @prev resizer;
+        @prev played_tee;
 
         @modify_on_set (sync: &mut Synchronizer) => {
             sync.set_virtual_microphone_clock(mic.provide_clock());
         };
+        @modify_on_set (resampler: &mut AudioResampler) => {
+            resampler.set_output_sample_rate(mic.sample_rate());
+        };
         @modify_on_take (sync: &mut Synchronizer) => {
             sync.unset_virtual_microphone_clock();
         };
+        @modify_on_take (resampler: &mut AudioResampler) => {
+            resampler.set_output_sample_rate(None);
+        };
+    }
+
+    pub(super) fn decoded_audio_filter_mut<F>(&mut self) -> Option<&mut F>
+    where
+        F: ChainedAudioFilter<TimestampedRawAudioBuffer> + 'static,
+    {
+        self.decoded_filters.as_mut()?.filter_mut()
+    }
+
+    pub(super) fn raw_audio_filter_mut<F>(&mut self) -> Option<&mut F>
+    where
+        F: ChainedAudioFilter<RawAudioBuffer> + 'static,
+    {
+        self.raw_filters.as_mut()?.filter_mut()
+    }
+
+    /// Has to be called after the noise gate is inserted into or removed from
+    /// the decoded filter chain.
+    pub(super) fn relink_noise_gate_state(&mut self) {
+        let Some(sync) = self.sync.as_mut() else {
+            return;
+        };
+
+        let closed_state = self
+            .decoded_filters
+            .as_ref()
+            .and_then(|chain| chain.filter::<NoiseGate>())
+            .map(NoiseGate::closed_state);
+        sync.set_noise_gate_state(closed_state);
+    }
+
+    fn update_float_converter_format(&mut self) {
+        if let Some(float_conv) = self.float_conv.as_mut() {
+            let is_active = self
+                .raw_filters
+                .as_ref()
+                .is_some_and(RawAudioFilterChain::is_active);
+            float_conv.set_target_format(is_active.then_some(RawAudioFormat::F32LE));
+        }
+    }
+
+    // The audio is converted back to the format it had before the filters,
+    // unless the microphone negotiated another one.
+    fn update_converter_format(&mut self) {
+        if let Some(conv) = self.conv.as_mut() {
+            let target_format = self
+                .mic
+                .as_ref()
+                .and_then(|mic| mic.format())
+                .or_else(|| self.float_conv.as_ref()?.source_format());
+            conv.set_target_format(target_format);
+        }
+    }
+
+    // PCM bypasses the audio decoder, so its decoded audio has to be merged into
+    // the same queue which feeds the decoded filter chain.
+    fn relink_pcm_decoder_output(&mut self) {
+        let Some(pcm_dec) = self.pcm_dec.as_mut() else {
+            return;
+        };
+
+        match (
+            self.dec.as_ref().and_then(|dec| dec.output()),
+            self.decoded_filters.as_mut(),
+        ) {
+            (Some(output), Some(_)) => pcm_dec.set_output(output),
+            (None, Some(decoded_filters)) => pcm_dec.chain(decoded_filters),
+            (_, None) => pcm_dec.unset_output(),
+        }
     }
 }
 
 impl Runnable for AudioPipeline {
     fn update(&mut self) -> error::Result<()> {
+        self.demux.as_mut().map(Runnable::update);
+        self.received_tee.as_mut().map(Runnable::update);
+        self.received_rec.as_mut().map(Runnable::update);
+        self.encoded_filters.as_mut().map(Runnable::update);
+        self.jitter.as_mut().map(Runnable::update);
         self.dec.as_mut().map(Runnable::update);
+        self.pcm_dec.as_mut().map(Runnable::update);
+        self.decoded_filters.as_mut().map(Runnable::update);
         self.sync.as_mut().map(Runnable::update);
+        self.resizer.as_mut().map(Runnable::update);
+        self.update_float_converter_format();
+        self.float_conv.as_mut().map(Runnable::update);
+        self.resampler.as_mut().map(Runnable::update);
+        self.raw_filters.as_mut().map(Runnable::update);
+        self.update_converter_format();
+        self.conv.as_mut().map(Runnable::update);
+        self.played_tee.as_mut().map(Runnable::update);
+        self.played_rec.as_mut().map(Runnable::update);
         self.mic.as_mut().map(Runnable::update);
 
         Ok(())
     }
 
     fn on_start(&mut self) {
+        self.demux.as_mut().map(Runnable::on_start);
+        self.received_tee.as_mut().map(Runnable::on_start);
+        self.received_rec.as_mut().map(Runnable::on_start);
+        self.encoded_filters.as_mut().map(Runnable::on_start);
+        self.jitter.as_mut().map(Runnable::on_start);
         self.dec.as_mut().map(Runnable::on_start);
+        self.pcm_dec.as_mut().map(Runnable::on_start);
+        self.decoded_filters.as_mut().map(Runnable::on_start);
         self.sync.as_mut().map(Runnable::on_start);
+        self.resizer.as_mut().map(Runnable::on_start);
+        self.float_conv.as_mut().map(Runnable::on_start);
+        self.resampler.as_mut().map(Runnable::on_start);
+        self.raw_filters.as_mut().map(Runnable::on_start);
+        self.conv.as_mut().map(Runnable::on_start);
+        self.played_tee.as_mut().map(Runnable::on_start);
+        self.played_rec.as_mut().map(Runnable::on_start);
         self.mic.as_mut().map(Runnable::on_start);
 
         self.is_running = true;
@@ -212,8 +563,22 @@ impl Runnable for AudioPipeline {
     fn on_stop(&mut self) {
         self.is_running = false;
 
+        self.demux.as_mut().map(Runnable::on_stop);
+        self.received_tee.as_mut().map(Runnable::on_stop);
+        self.received_rec.as_mut().map(Runnable::on_stop);
+        self.encoded_filters.as_mut().map(Runnable::on_stop);
+        self.jitter.as_mut().map(Runnable::on_stop);
         self.dec.as_mut().map(Runnable::on_stop);
+        self.pcm_dec.as_mut().map(Runnable::on_stop);
+        self.decoded_filters.as_mut().map(Runnable::on_stop);
         self.sync.as_mut().map(Runnable::on_stop);
+        self.resizer.as_mut().map(Runnable::on_stop);
+        self.float_conv.as_mut().map(Runnable::on_stop);
+        self.resampler.as_mut().map(Runnable::on_stop);
+        self.raw_filters.as_mut().map(Runnable::on_stop);
+        self.conv.as_mut().map(Runnable::on_stop);
+        self.played_tee.as_mut().map(Runnable::on_stop);
+        self.played_rec.as_mut().map(Runnable::on_stop);
         self.mic.as_mut().map(Runnable::on_stop);
     }
 }
@@ -0,0 +1,302 @@
+#[cfg(test)]
+mod tests;
+
+use crate::error;
+use crate::util::{ClockTime, Element, Runnable};
+
+use crate::audio_system::audio::TimestampedRawAudioBuffer;
+use crate::audio_system::element::{
+    AudioFilter, AudioSink, AudioSource, AudioSystemElementMessage,
+};
+
+use super::filter_chain::{AudioFilterInfo, ChainedAudioFilter};
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+use mueue::*;
+
+/// Every frame of this duration is classified as either speech or non-speech.
+const DETECTION_FRAME_DURATION: ClockTime = ClockTime::from_millis(10);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NoiseGateParams {
+    /// The RMS level in dBFS from which the audio is considered to be speech.
+    pub threshold: f64,
+    /// How long it takes to open the gate.
+    pub attack: ClockTime,
+    /// How long the gate stays open after the speech.
+    pub hold: ClockTime,
+    /// How long it takes to close the gate.
+    pub release: ClockTime,
+}
+
+impl Default for NoiseGateParams {
+    fn default() -> Self {
+        Self {
+            threshold: -45.0,
+            attack: ClockTime::from_millis(5),
+            hold: ClockTime::from_millis(200),
+            release: ClockTime::from_millis(100),
+        }
+    }
+}
+
+/// Detects the voice activity by the level of the audio and silences the audio
+/// between the speech if the gate is enabled.
+pub struct NoiseGate {
+    send: MessageSender<AudioSystemElementMessage>,
+    input: Option<MessageReceiver<TimestampedRawAudioBuffer>>,
+    output: Option<MessageSender<TimestampedRawAudioBuffer>>,
+
+    params: NoiseGateParams,
+    enabled: bool,
+    suppress_silence: bool,
+
+    speaking: bool,
+    hold_remaining: ClockTime,
+    gain: f64,
+    closed: Rc<Cell<bool>>,
+}
+
+impl NoiseGate {
+    pub fn new(send: MessageSender<AudioSystemElementMessage>) -> Self {
+        Self {
+            send,
+            input: None,
+            output: None,
+
+            params: NoiseGateParams::default(),
+            enabled: false,
+            suppress_silence: false,
+
+            speaking: false,
+            hold_remaining: ClockTime::ZERO,
+            gain: 1.0,
+            closed: Rc::new(Cell::new(false)),
+        }
+    }
+
+    pub fn params(&self) -> NoiseGateParams {
+        self.params
+    }
+
+    pub fn set_params(&mut self, params: NoiseGateParams) {
+        self.params = params;
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// The voice activity is detected even if the gate is disabled.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.gain = 1.0;
+            self.closed.set(false);
+        }
+    }
+
+    pub fn suppresses_silence(&self) -> bool {
+        self.suppress_silence
+    }
+
+    /// The audio isn't passed at all while the gate is closed, like with DTX.
+    pub fn set_suppress_silence(&mut self, suppress_silence: bool) {
+        self.suppress_silence = suppress_silence;
+    }
+
+    pub fn is_speaking(&self) -> bool {
+        self.speaking
+    }
+
+    /// Whether the gate is closed completely, so that the absence of the audio
+    /// after it is intentional.
+    pub fn closed_state(&self) -> Rc<Cell<bool>> {
+        self.closed.clone()
+    }
+
+    fn process(&mut self, audio: TimestampedRawAudioBuffer) -> Option<TimestampedRawAudioBuffer> {
+        if audio == TimestampedRawAudioBuffer::NULL {
+            self.reset();
+
+            return Some(audio);
+        }
+
+        let format = audio.format();
+        let channels = usize::from(audio.channels());
+        let sample_rate = audio.sample_rate();
+        if format.no_bytes() == 0 || channels == 0 || sample_rate == 0 {
+            return Some(audio);
+        }
+
+        let start = audio.start();
+        let mut raw = audio.into_raw();
+
+        let detection_frames = DETECTION_FRAME_DURATION.to_no_samples(sample_rate).max(1);
+        let attack_step = gain_step(self.params.attack, sample_rate);
+        let release_step = gain_step(self.params.release, sample_rate);
+
+        let mut passed = false;
+        for block in raw
+            .as_slice_mut()
+            .chunks_mut(detection_frames * channels * format.no_bytes())
+        {
+            let samples: Vec<f64> = block
+                .chunks_exact(format.no_bytes())
+                .map(|bytes| format.read_normalized_sample(bytes))
+                .collect();
+            if samples.is_empty() {
+                continue;
+            }
+
+            let no_frames = samples.len() / channels;
+            self.classify(&samples, ClockTime::from_no_samples(no_frames, sample_rate));
+
+            if !self.enabled {
+                passed = true;
+                continue;
+            }
+
+            let open = self.speaking;
+            for (frame, bytes) in samples
+                .chunks_exact(channels)
+                .zip(block.chunks_exact_mut(channels * format.no_bytes()))
+            {
+                self.gain = if open {
+                    (self.gain + attack_step).min(1.0)
+                } else {
+                    (self.gain - release_step).max(0.0)
+                };
+                passed |= self.gain > 0.0;
+
+                for (sample, bytes) in frame.iter().zip(bytes.chunks_exact_mut(format.no_bytes())) {
+                    format.write_normalized_sample(sample * self.gain, bytes);
+                }
+            }
+        }
+
+        self.closed.set(self.enabled && self.gain == 0.0);
+
+        if !passed && self.suppress_silence {
+            return None;
+        }
+
+        Some(TimestampedRawAudioBuffer::new(raw, start))
+    }
+
+    fn classify(&mut self, samples: &[f64], duration: ClockTime) {
+        let mean_square =
+            samples.iter().map(|sample| sample * sample).sum::<f64>() / samples.len() as f64;
+        let level = 10.0 * mean_square.max(f64::MIN_POSITIVE).log10();
+
+        let speaking = if level >= self.params.threshold {
+            self.hold_remaining = self.params.hold;
+            true
+        } else if self.hold_remaining > ClockTime::ZERO {
+            self.hold_remaining = self.hold_remaining.saturating_sub(duration);
+            true
+        } else {
+            false
+        };
+
+        if self.speaking != speaking {
+            self.speaking = speaking;
+            let _ = self
+                .send
+                .send(AudioSystemElementMessage::SpeakingChanged { speaking });
+        }
+    }
+
+    fn reset(&mut self) {
+        self.hold_remaining = ClockTime::ZERO;
+        self.gain = if self.enabled { 0.0 } else { 1.0 };
+        self.closed.set(self.enabled);
+
+        if self.speaking {
+            self.speaking = false;
+            let _ = self
+                .send
+                .send(AudioSystemElementMessage::SpeakingChanged { speaking: false });
+        }
+    }
+
+    fn release(&self, audio: TimestampedRawAudioBuffer) {
+        if let Some(output) = self.output.as_ref() {
+            let _ = output.send(audio);
+        }
+    }
+}
+
+fn gain_step(duration: ClockTime, sample_rate: u32) -> f64 {
+    1.0 / duration.to_no_samples(sample_rate).max(1) as f64
+}
+
+impl Runnable for NoiseGate {
+    fn update(&mut self) -> error::Result<()> {
+        let Some(input) = self.input.clone() else {
+            return Ok(());
+        };
+
+        for audio in input.iter() {
+            if let Some(audio) = self.process(audio) {
+                self.release(audio);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn on_stop(&mut self) {
+        self.reset();
+    }
+}
+
+impl Element for NoiseGate {
+    type Message = AudioSystemElementMessage;
+
+    fn sender(&self) -> MessageSender<Self::Message> {
+        self.send.clone()
+    }
+
+    fn connect(&mut self, send: MessageSender<Self::Message>) {
+        self.send = send;
+    }
+}
+
+impl AudioSink<TimestampedRawAudioBuffer> for NoiseGate {
+    fn input(&self) -> Option<MessageReceiver<TimestampedRawAudioBuffer>> {
+        self.input.clone()
+    }
+
+    fn set_input(&mut self, input: MessageReceiver<TimestampedRawAudioBuffer>) {
+        self.input = Some(input);
+    }
+
+    fn unset_input(&mut self) {
+        self.input = None;
+    }
+}
+
+impl AudioSource<TimestampedRawAudioBuffer> for NoiseGate {
+    fn output(&self) -> Option<MessageSender<TimestampedRawAudioBuffer>> {
+        self.output.clone()
+    }
+
+    fn set_output(&mut self, output: MessageSender<TimestampedRawAudioBuffer>) {
+        self.output = Some(output);
+    }
+
+    fn unset_output(&mut self) {
+        self.output = None;
+    }
+}
+
+impl AudioFilter<TimestampedRawAudioBuffer, TimestampedRawAudioBuffer> for NoiseGate {}
+
+impl ChainedAudioFilter<TimestampedRawAudioBuffer> for NoiseGate {
+    fn info(&self) -> AudioFilterInfo {
+        AudioFilterInfo::new("noise_gate")
+    }
+}
@@ -0,0 +1,134 @@
+use super::*;
+
+use crate::audio_system::audio::{RawAudioBuffer, RawAudioFormat};
+
+use std::f64::consts::PI;
+
+const SAMPLE_RATE: u32 = 48000;
+
+fn tone(level: f64, duration: ClockTime) -> TimestampedRawAudioBuffer {
+    let no_frames = duration.to_no_samples(SAMPLE_RATE);
+    let samples: Vec<f64> = (0..no_frames)
+        .map(|n| level * (2.0 * PI * 440.0 * n as f64 / f64::from(SAMPLE_RATE)).sin())
+        .collect();
+
+    TimestampedRawAudioBuffer::new(
+        RawAudioBuffer::from_normalized_samples(&samples, RawAudioFormat::F32LE, 1, SAMPLE_RATE),
+        Some(ClockTime::ZERO),
+    )
+}
+
+fn speaking_changes(recv: &MessageReceiver<AudioSystemElementMessage>) -> Vec<bool> {
+    recv.iter()
+        .filter_map(|msg| match msg {
+            AudioSystemElementMessage::SpeakingChanged { speaking } => Some(speaking),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn test_detect_speech_when_disabled() {
+    let (send, recv) = unidirectional_queue();
+    let mut gate = NoiseGate::new(send);
+    let in_send = gate.create_input();
+    let out_recv = gate.create_output();
+
+    let _ = in_send.send(tone(0.5, ClockTime::from_millis(100)));
+    let _ = in_send.send(tone(0.0, ClockTime::from_millis(500)));
+    let _ = gate.update();
+
+    assert_eq!(speaking_changes(&recv), vec![true, false]);
+    assert!(!gate.is_speaking());
+
+    // The disabled gate doesn't change the audio.
+    let audio = out_recv.recv().unwrap();
+    assert_eq!(
+        audio.as_raw().to_normalized_samples(),
+        tone(0.5, ClockTime::from_millis(100))
+            .as_raw()
+            .to_normalized_samples()
+    );
+    assert!(!gate.closed_state().get());
+}
+
+#[test]
+fn test_hold_and_release() {
+    let (send, _) = unidirectional_queue();
+    let mut gate = NoiseGate::new(send);
+    gate.set_enabled(true);
+    let in_send = gate.create_input();
+    let out_recv = gate.create_output();
+
+    let _ = in_send.send(tone(0.5, ClockTime::from_millis(100)));
+    let _ = in_send.send(tone(0.003, ClockTime::from_millis(100)));
+    let _ = gate.update();
+
+    let _ = out_recv.recv().unwrap();
+    // The quiet audio after the speech passes during the hold.
+    let held = out_recv.recv().unwrap().as_raw().to_normalized_samples();
+    let expected = tone(0.003, ClockTime::from_millis(100))
+        .as_raw()
+        .to_normalized_samples();
+    assert_eq!(held, expected);
+
+    let _ = in_send.send(tone(0.003, ClockTime::from_millis(400)));
+    let _ = gate.update();
+
+    let released = out_recv.recv().unwrap().as_raw().to_normalized_samples();
+    assert!(released[..10].iter().any(|sample| *sample != 0.0));
+    assert!(released[released.len() - 10..]
+        .iter()
+        .all(|sample| *sample == 0.0));
+    assert!(gate.closed_state().get());
+}
+
+#[test]
+fn test_attack() {
+    let (send, _) = unidirectional_queue();
+    let mut gate = NoiseGate::new(send);
+    gate.set_enabled(true);
+    let in_send = gate.create_input();
+    let out_recv = gate.create_output();
+
+    // The closed gate opens gradually.
+    let _ = in_send.send(TimestampedRawAudioBuffer::NULL);
+    let _ = in_send.send(tone(0.5, ClockTime::from_millis(20)));
+    let _ = gate.update();
+    let _ = out_recv.recv().unwrap();
+
+    let samples = out_recv.recv().unwrap().as_raw().to_normalized_samples();
+    let expected = tone(0.5, ClockTime::from_millis(20))
+        .as_raw()
+        .to_normalized_samples();
+    let attack_frames = gate.params().attack.to_no_samples(SAMPLE_RATE);
+
+    assert!(samples[1].abs() < expected[1].abs());
+    assert_eq!(samples[attack_frames..], expected[attack_frames..]);
+}
+
+#[test]
+fn test_suppress_silence() {
+    let (send, _) = unidirectional_queue();
+    let mut gate = NoiseGate::new(send);
+    gate.set_enabled(true);
+    gate.set_suppress_silence(true);
+    let in_send = gate.create_input();
+    let out_recv = gate.create_output();
+
+    let _ = in_send.send(tone(0.5, ClockTime::from_millis(100)));
+    let _ = in_send.send(tone(0.0, ClockTime::from_millis(500)));
+    let _ = in_send.send(tone(0.0, ClockTime::from_millis(100)));
+    let _ = gate.update();
+
+    assert!(out_recv.recv().is_some());
+    assert!(out_recv.recv().is_some());
+    // The audio isn't passed after the gate is closed.
+    assert!(out_recv.recv().is_none());
+    assert!(gate.closed_state().get());
+
+    let _ = in_send.send(tone(0.5, ClockTime::from_millis(100)));
+    let _ = gate.update();
+    assert!(out_recv.recv().is_some());
+    assert!(!gate.closed_state().get());
+}
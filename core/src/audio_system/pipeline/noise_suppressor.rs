@@ -0,0 +1,382 @@
+#[cfg(test)]
+mod tests;
+
+use crate::error;
+use crate::util::{ClockTime, Element, Runnable};
+
+use crate::audio_system::audio::{RawAudioBuffer, RawAudioInfo};
+use crate::audio_system::element::{
+    AudioFilter, AudioSink, AudioSource, AudioSystemElementMessage,
+};
+
+use super::filter_chain::{AudioFilterInfo, ChainedAudioFilter};
+
+use std::f64::consts::PI;
+
+use mueue::*;
+
+/// The duration of the analysed frames, which is rounded up to the power of two
+/// samples. The frames overlap by half.
+const FRAME_DURATION: ClockTime = ClockTime::from_millis(20);
+
+/// How fast the noise estimate may rise when the audio gets louder.
+const NOISE_RISE_DB_PER_SEC: f64 = 3.0;
+const POWER_SMOOTHING: f64 = 0.1;
+/// The minimum of the smoothed power underestimates the mean power of the noise.
+const NOISE_BIAS: f64 = 3.0;
+const GAIN_SMOOTHING: f64 = 0.6;
+/// The attenuation of the noise at the full strength.
+const MAX_ATTENUATION_DB: f64 = 30.0;
+
+const DEFAULT_STRENGTH: f64 = 0.7;
+
+/// Attenuates the stationary noise by subtracting its spectrum, which is learnt
+/// by tracking the minimal power of every frequency.
+pub struct NoiseSuppressor {
+    send: MessageSender<AudioSystemElementMessage>,
+    input: Option<MessageReceiver<RawAudioBuffer>>,
+    output: Option<MessageSender<RawAudioBuffer>>,
+
+    strength: f64,
+    bypassed: bool,
+
+    stream_info: Option<RawAudioInfo>,
+    state: Option<SpectralState>,
+}
+
+impl NoiseSuppressor {
+    pub fn new(send: MessageSender<AudioSystemElementMessage>) -> Self {
+        Self {
+            send,
+            input: None,
+            output: None,
+
+            strength: DEFAULT_STRENGTH,
+            bypassed: true,
+
+            stream_info: None,
+            state: None,
+        }
+    }
+
+    pub fn strength(&self) -> f64 {
+        self.strength
+    }
+
+    /// The strength is clamped to the range from 0.0, which keeps the audio
+    /// intact, to 1.0.
+    pub fn set_strength(&mut self, strength: f64) {
+        if !strength.is_nan() {
+            self.strength = strength.clamp(0.0, 1.0);
+        }
+    }
+
+    pub fn is_bypassed(&self) -> bool {
+        self.bypassed
+    }
+
+    pub fn set_bypassed(&mut self, bypassed: bool) {
+        if self.bypassed != bypassed {
+            self.bypassed = bypassed;
+            self.reset();
+        }
+    }
+
+    fn process(&mut self, audio: RawAudioBuffer) -> RawAudioBuffer {
+        if self.bypassed || audio.format().no_bytes() == 0 || audio.sample_rate() == 0 {
+            return audio;
+        }
+
+        let stream_info = audio.info();
+        if self.stream_info != Some(stream_info) {
+            self.stream_info = Some(stream_info);
+            self.state = Some(SpectralState::new(
+                usize::from(stream_info.channels).max(1),
+                stream_info.sample_rate,
+            ));
+        }
+
+        let Some(state) = self.state.as_mut() else {
+            return audio;
+        };
+
+        let samples = state.process(&audio.to_normalized_samples(), self.strength);
+        RawAudioBuffer::from_normalized_samples(
+            &samples,
+            stream_info.format,
+            stream_info.channels,
+            stream_info.sample_rate,
+        )
+        .with_channel_layout(stream_info.channel_layout)
+    }
+
+    fn reset(&mut self) {
+        self.stream_info = None;
+        self.state = None;
+    }
+}
+
+#[derive(Debug, Clone)]
+struct SpectralState {
+    channels: usize,
+    frame_len: usize,
+    hop: usize,
+    // The square root of the periodic Hann window, which is applied both before
+    // and after the processing.
+    window: Vec<f64>,
+    noise_rise: f64,
+
+    pending: Vec<f64>,
+    overlap: Vec<f64>,
+    channel_states: Vec<ChannelState>,
+}
+
+#[derive(Debug, Clone)]
+struct ChannelState {
+    power: Vec<f64>,
+    noise: Vec<f64>,
+    gains: Vec<f64>,
+}
+
+impl SpectralState {
+    fn new(channels: usize, sample_rate: u32) -> Self {
+        let frame_len = FRAME_DURATION
+            .to_no_samples(sample_rate)
+            .next_power_of_two()
+            .max(4);
+        let hop = frame_len / 2;
+        let no_bins = frame_len / 2 + 1;
+
+        let window = (0..frame_len)
+            .map(|n| (0.5 - 0.5 * (2.0 * PI * n as f64 / frame_len as f64).cos()).sqrt())
+            .collect();
+        let hop_secs = hop as f64 / f64::from(sample_rate);
+
+        Self {
+            channels,
+            frame_len,
+            hop,
+            window,
+            noise_rise: 10f64.powf(NOISE_RISE_DB_PER_SEC * hop_secs / 10.0),
+
+            // The first frame is padded with silence, so that the output keeps
+            // pace with the input.
+            pending: vec![0.0; hop * channels],
+            overlap: vec![0.0; frame_len * channels],
+            channel_states: vec![
+                ChannelState {
+                    power: vec![0.0; no_bins],
+                    noise: vec![0.0; no_bins],
+                    gains: vec![1.0; no_bins],
+                };
+                channels
+            ],
+        }
+    }
+
+    fn process(&mut self, samples: &[f64], strength: f64) -> Vec<f64> {
+        let channels = self.channels;
+        let frame_len = self.frame_len;
+        let hop = self.hop;
+
+        self.pending.extend_from_slice(samples);
+
+        let mut output = Vec::with_capacity(samples.len() + hop * channels);
+        let mut re = vec![0.0; frame_len];
+        let mut im = vec![0.0; frame_len];
+        while self.pending.len() >= frame_len * channels {
+            for channel in 0..channels {
+                for (n, (re, im)) in re.iter_mut().zip(im.iter_mut()).enumerate() {
+                    *re = self.pending[n * channels + channel] * self.window[n];
+                    *im = 0.0;
+                }
+
+                fft(&mut re, &mut im, false);
+                self.channel_states[channel].suppress(&mut re, &mut im, strength, self.noise_rise);
+                fft(&mut re, &mut im, true);
+
+                for (n, sample) in re.iter().enumerate() {
+                    self.overlap[n * channels + channel] += sample * self.window[n];
+                }
+            }
+
+            output.extend(self.overlap.drain(..hop * channels));
+            self.overlap.resize(frame_len * channels, 0.0);
+            self.pending.drain(..hop * channels);
+        }
+
+        output
+    }
+}
+
+impl ChannelState {
+    fn suppress(&mut self, re: &mut [f64], im: &mut [f64], strength: f64, noise_rise: f64) {
+        let frame_len = re.len();
+        let over_subtraction = 1.0 + strength;
+        let min_gain = 10f64.powf(-MAX_ATTENUATION_DB * strength / 20.0);
+
+        for bin in 0..=frame_len / 2 {
+            let power = re[bin] * re[bin] + im[bin] * im[bin];
+
+            let noise = &mut self.noise[bin];
+            let smoothed_power = &mut self.power[bin];
+            if *noise == 0.0 {
+                *smoothed_power = power;
+            } else {
+                *smoothed_power += (power - *smoothed_power) * POWER_SMOOTHING;
+            }
+
+            if *noise == 0.0 || *smoothed_power < *noise {
+                *noise = *smoothed_power;
+            } else {
+                *noise *= noise_rise;
+            }
+
+            let target_gain = if power > 0.0 {
+                (1.0 - over_subtraction * NOISE_BIAS * *noise / power).max(min_gain)
+            } else {
+                min_gain
+            };
+
+            // The smoothing in time reduces the musical noise.
+            let gain = &mut self.gains[bin];
+            *gain += (target_gain - *gain) * GAIN_SMOOTHING;
+
+            re[bin] *= *gain;
+            im[bin] *= *gain;
+            if bin != 0 && bin != frame_len / 2 {
+                re[frame_len - bin] *= *gain;
+                im[frame_len - bin] *= *gain;
+            }
+        }
+    }
+}
+
+/// The in-place iterative radix-2 FFT, the length must be a power of two. The
+/// inverse transform is normalized.
+fn fft(re: &mut [f64], im: &mut [f64], inverse: bool) {
+    let len = re.len();
+    debug_assert!(len.is_power_of_two() && im.len() == len);
+
+    let mut j = 0;
+    for i in 1..len {
+        let mut bit = len >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+
+    let sign = if inverse { 1.0 } else { -1.0 };
+    let mut size = 2;
+    while size <= len {
+        let angle = sign * 2.0 * PI / size as f64;
+        for start in (0..len).step_by(size) {
+            for k in 0..size / 2 {
+                let (w_im, w_re) = (angle * k as f64).sin_cos();
+
+                let a = start + k;
+                let b = a + size / 2;
+                let t_re = re[b] * w_re - im[b] * w_im;
+                let t_im = re[b] * w_im + im[b] * w_re;
+
+                re[b] = re[a] - t_re;
+                im[b] = im[a] - t_im;
+                re[a] += t_re;
+                im[a] += t_im;
+            }
+        }
+
+        size <<= 1;
+    }
+
+    if inverse {
+        for (re, im) in re.iter_mut().zip(im.iter_mut()) {
+            *re /= len as f64;
+            *im /= len as f64;
+        }
+    }
+}
+
+impl Runnable for NoiseSuppressor {
+    fn update(&mut self) -> error::Result<()> {
+        let Some(input) = self.input.clone() else {
+            return Ok(());
+        };
+
+        for audio in input.iter() {
+            let audio = self.process(audio);
+            if audio.no_samples() == 0 {
+                continue;
+            }
+
+            if let Some(output) = self.output.as_ref() {
+                let _ = output.send(audio);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn on_stop(&mut self) {
+        self.reset();
+    }
+}
+
+impl Element for NoiseSuppressor {
+    type Message = AudioSystemElementMessage;
+
+    fn sender(&self) -> MessageSender<Self::Message> {
+        self.send.clone()
+    }
+
+    fn connect(&mut self, send: MessageSender<Self::Message>) {
+        self.send = send;
+    }
+}
+
+impl AudioSink<RawAudioBuffer> for NoiseSuppressor {
+    fn input(&self) -> Option<MessageReceiver<RawAudioBuffer>> {
+        self.input.clone()
+    }
+
+    fn set_input(&mut self, input: MessageReceiver<RawAudioBuffer>) {
+        self.input = Some(input);
+    }
+
+    fn unset_input(&mut self) {
+        self.input = None;
+    }
+}
+
+impl AudioSource<RawAudioBuffer> for NoiseSuppressor {
+    fn output(&self) -> Option<MessageSender<RawAudioBuffer>> {
+        self.output.clone()
+    }
+
+    fn set_output(&mut self, output: MessageSender<RawAudioBuffer>) {
+        self.output = Some(output);
+    }
+
+    fn unset_output(&mut self) {
+        self.output = None;
+    }
+}
+
+impl AudioFilter<RawAudioBuffer, RawAudioBuffer> for NoiseSuppressor {}
+
+impl ChainedAudioFilter<RawAudioBuffer> for NoiseSuppressor {
+    fn info(&self) -> AudioFilterInfo {
+        AudioFilterInfo::new("noise_suppressor")
+    }
+
+    fn is_active(&self) -> bool {
+        !self.bypassed
+    }
+}
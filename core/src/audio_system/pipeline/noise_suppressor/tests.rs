@@ -0,0 +1,167 @@
+use super::*;
+
+use crate::audio_system::audio::RawAudioFormat;
+
+const SAMPLE_RATE: u32 = 48000;
+const FREQUENCY: f64 = 1000.0;
+const TONE_AMPLITUDE: f64 = 0.3;
+const NOISE_AMPLITUDE: f64 = 0.05;
+const BUFFER_FRAMES: usize = 1024;
+
+/// Uniform white noise from the xorshift generator.
+struct Noise(u32);
+
+impl Noise {
+    fn next_sample(&mut self) -> f64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+
+        (f64::from(self.0) / f64::from(u32::MAX) * 2.0 - 1.0) * NOISE_AMPLITUDE
+    }
+}
+
+fn tone(n: usize) -> f64 {
+    TONE_AMPLITUDE * (2.0 * PI * FREQUENCY * n as f64 / SAMPLE_RATE as f64).sin()
+}
+
+fn buffer(samples: &[f64]) -> RawAudioBuffer {
+    RawAudioBuffer::from_normalized_samples(samples, RawAudioFormat::F32LE, 1, SAMPLE_RATE)
+}
+
+/// Feeds a second of the noise and then two seconds of the tone with the noise.
+/// Returns the input and output of the last second.
+fn suppress(ns: &mut NoiseSuppressor) -> (Vec<f64>, Vec<f64>) {
+    let in_send = ns.create_input();
+    let out_recv = ns.create_output();
+
+    let mut noise = Noise(0x1234_5678);
+    let mut input = Vec::new();
+    for no_buf in 0..141 {
+        let samples: Vec<f64> = (no_buf * BUFFER_FRAMES..(no_buf + 1) * BUFFER_FRAMES)
+            .map(|n| {
+                let tone = if n < SAMPLE_RATE as usize {
+                    0.0
+                } else {
+                    tone(n)
+                };
+                tone + noise.next_sample()
+            })
+            .collect();
+
+        let audio = buffer(&samples);
+        input.extend(audio.to_normalized_samples());
+        let _ = in_send.send(audio);
+    }
+    let _ = ns.update();
+
+    let output: Vec<f64> = out_recv
+        .iter()
+        .flat_map(|audio| audio.to_normalized_samples())
+        .collect();
+    assert_eq!(output.len(), input.len());
+
+    let last_second = input.len() - SAMPLE_RATE as usize..;
+    (
+        input[last_second.clone()].to_vec(),
+        output[last_second].to_vec(),
+    )
+}
+
+/// Splits the signal into the tone, which is found by the least squares, and
+/// the rest. Returns their powers.
+fn tone_and_residual_power(samples: &[f64]) -> (f64, f64) {
+    let omega = 2.0 * PI * FREQUENCY / SAMPLE_RATE as f64;
+    let (sin, cos) = samples
+        .iter()
+        .enumerate()
+        .fold((0.0, 0.0), |(sin, cos), (n, s)| {
+            let (n_sin, n_cos) = (omega * n as f64).sin_cos();
+            (sin + s * n_sin, cos + s * n_cos)
+        });
+    let len = samples.len() as f64;
+    let (a, b) = (2.0 * sin / len, 2.0 * cos / len);
+
+    let residual_power = samples
+        .iter()
+        .enumerate()
+        .map(|(n, s)| {
+            let (n_sin, n_cos) = (omega * n as f64).sin_cos();
+            (s - a * n_sin - b * n_cos).powi(2)
+        })
+        .sum::<f64>()
+        / len;
+
+    ((a * a + b * b) / 2.0, residual_power)
+}
+
+fn snr_db(samples: &[f64]) -> f64 {
+    let (tone_power, residual_power) = tone_and_residual_power(samples);
+    10.0 * (tone_power / residual_power).log10()
+}
+
+#[test]
+fn test_pass_when_bypassed() {
+    let (send, _) = unidirectional_queue();
+    let mut ns = NoiseSuppressor::new(send);
+    assert!(ns.is_bypassed());
+
+    let (input, output) = suppress(&mut ns);
+    assert_eq!(input, output);
+}
+
+#[test]
+fn test_improve_snr() {
+    let (send, _) = unidirectional_queue();
+    let mut ns = NoiseSuppressor::new(send);
+    ns.set_bypassed(false);
+
+    let (input, output) = suppress(&mut ns);
+    assert!(snr_db(&output) - snr_db(&input) > 10.0);
+
+    // The tone itself keeps its level.
+    let (input_tone_power, _) = tone_and_residual_power(&input);
+    let (output_tone_power, _) = tone_and_residual_power(&output);
+    assert!(10.0 * (output_tone_power / input_tone_power).log10().abs() < 1.0);
+}
+
+#[test]
+fn test_scale_with_strength() {
+    let snr_improvement = |strength| {
+        let (send, _) = unidirectional_queue();
+        let mut ns = NoiseSuppressor::new(send);
+        ns.set_bypassed(false);
+        ns.set_strength(strength);
+
+        let (input, output) = suppress(&mut ns);
+        snr_db(&output) - snr_db(&input)
+    };
+
+    // The zero strength only delays the audio.
+    assert!(snr_improvement(0.0).abs() < 0.1);
+    assert!(snr_improvement(1.0) > snr_improvement(0.5));
+}
+
+#[test]
+fn test_reconstruct_with_zero_strength() {
+    let (send, _) = unidirectional_queue();
+    let mut ns = NoiseSuppressor::new(send);
+    ns.set_bypassed(false);
+    ns.set_strength(0.0);
+
+    let in_send = ns.create_input();
+    let out_recv = ns.create_output();
+
+    let samples: Vec<f64> = (0..BUFFER_FRAMES * 4).map(tone).collect();
+    let _ = in_send.send(buffer(&samples));
+    let _ = ns.update();
+
+    let latency = FRAME_DURATION
+        .to_no_samples(SAMPLE_RATE)
+        .next_power_of_two()
+        / 2;
+    let output = out_recv.recv().unwrap().to_normalized_samples();
+    for (expected, reconstructed) in samples.iter().zip(&output[latency..]) {
+        assert!((expected - reconstructed).abs() < 1e-6);
+    }
+}
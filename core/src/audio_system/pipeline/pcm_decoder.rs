@@ -0,0 +1,113 @@
+#[cfg(test)]
+mod tests;
+
+use crate::error;
+use crate::util::{Element, Runnable};
+
+use crate::audio_system::audio::{EncodedAudioBuffer, RawAudioBuffer, TimestampedRawAudioBuffer};
+use crate::audio_system::element::{
+    AudioFilter, AudioSink, AudioSource, AudioSystemElementMessage,
+};
+
+use mueue::*;
+
+pub struct PcmDecoder {
+    send: MessageSender<AudioSystemElementMessage>,
+    input: Option<MessageReceiver<EncodedAudioBuffer>>,
+    output: Option<MessageSender<TimestampedRawAudioBuffer>>,
+}
+
+impl PcmDecoder {
+    pub fn new(send: MessageSender<AudioSystemElementMessage>) -> Self {
+        Self {
+            send,
+            input: None,
+            output: None,
+        }
+    }
+
+    fn decode(audio: EncodedAudioBuffer) -> error::Result<TimestampedRawAudioBuffer> {
+        let header = audio.header;
+        let format = header.codec.raw_audio_format().ok_or_else(|| {
+            error::Error::AudioDecodingFailed(format!(
+                "Unsupported audio codec: {:?}",
+                header.codec
+            ))
+        })?;
+
+        let raw = RawAudioBuffer::new(audio.data, format, header.channels, header.sample_rate)
+            .with_channel_layout(header.channel_layout);
+        if !raw.len().is_multiple_of(raw.frame_size()) {
+            return Err(error::Error::AudioDecodingFailed(
+                "PCM data doesn't consist of whole frames".to_string(),
+            ));
+        }
+
+        Ok(TimestampedRawAudioBuffer::new(raw, audio.start_ts))
+    }
+}
+
+impl Runnable for PcmDecoder {
+    fn update(&mut self) -> error::Result<()> {
+        let Some(input) = self.input.as_ref() else {
+            return Ok(());
+        };
+
+        for audio in input.iter() {
+            match Self::decode(audio) {
+                Ok(audio) => {
+                    if let Some(output) = self.output.as_ref() {
+                        let _ = output.send(audio);
+                    }
+                }
+                Err(err) => {
+                    let _ = self.send.send(AudioSystemElementMessage::Error(err));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Element for PcmDecoder {
+    type Message = AudioSystemElementMessage;
+
+    fn sender(&self) -> MessageSender<Self::Message> {
+        self.send.clone()
+    }
+
+    fn connect(&mut self, send: MessageSender<Self::Message>) {
+        self.send = send;
+    }
+}
+
+impl AudioSink<EncodedAudioBuffer> for PcmDecoder {
+    fn input(&self) -> Option<MessageReceiver<EncodedAudioBuffer>> {
+        self.input.clone()
+    }
+
+    fn set_input(&mut self, input: MessageReceiver<EncodedAudioBuffer>) {
+        self.input = Some(input);
+    }
+
+    fn unset_input(&mut self) {
+        self.input = None;
+    }
+}
+
+impl AudioSource<TimestampedRawAudioBuffer> for PcmDecoder {
+    fn output(&self) -> Option<MessageSender<TimestampedRawAudioBuffer>> {
+        self.output.clone()
+    }
+
+    fn set_output(&mut self, output: MessageSender<TimestampedRawAudioBuffer>) {
+        self.output = Some(output);
+    }
+
+    fn unset_output(&mut self) {
+        self.output = None;
+    }
+}
+
+impl AudioFilter<EncodedAudioBuffer, TimestampedRawAudioBuffer> for PcmDecoder {}
@@ -0,0 +1,66 @@
+use super::*;
+
+use crate::audio_system::audio::{AudioCodec, ChannelLayout, RawAudioFormat};
+use crate::test_util::encoded_audio;
+use crate::util::ClockTime;
+
+const SAMPLE_RATE: u32 = 48000;
+
+#[test]
+fn test_decode_pcm() {
+    let (send, _) = unidirectional_queue();
+    let mut dec = PcmDecoder::new(send);
+
+    let in_send = dec.create_input();
+    let out_recv = dec.create_output();
+
+    let data: Vec<u8> = (0..16).collect();
+    let mut audio = encoded_audio(AudioCodec::PcmS16BE, 2, data.clone());
+    audio.start_ts = Some(ClockTime::from_millis(10));
+    let _ = in_send.send(audio);
+    let _ = dec.update();
+
+    let audio = out_recv.recv().unwrap();
+    assert_eq!(audio.as_slice(), data.as_slice());
+    assert_eq!(audio.format(), RawAudioFormat::S16BE);
+    assert_eq!(audio.channels(), 2);
+    assert_eq!(audio.channel_layout(), ChannelLayout::Stereo);
+    assert_eq!(audio.sample_rate(), SAMPLE_RATE);
+    assert_eq!(audio.no_samples(), 4);
+    assert_eq!(audio.start(), Some(ClockTime::from_millis(10)));
+}
+
+#[test]
+fn test_decode_partial_frame() {
+    let (send, notification_recv) = unidirectional_queue();
+    let mut dec = PcmDecoder::new(send);
+
+    let in_send = dec.create_input();
+    let out_recv = dec.create_output();
+
+    let _ = in_send.send(encoded_audio(AudioCodec::PcmS24LE, 1, vec![0; 7]));
+    let _ = dec.update();
+
+    assert!(out_recv.recv().is_none());
+    assert!(matches!(
+        notification_recv.recv(),
+        Some(AudioSystemElementMessage::Error(
+            error::Error::AudioDecodingFailed(_)
+        ))
+    ));
+}
+
+#[test]
+fn test_decode_compressed() {
+    let (send, notification_recv) = unidirectional_queue();
+    let mut dec = PcmDecoder::new(send);
+
+    let in_send = dec.create_input();
+    let out_recv = dec.create_output();
+
+    let _ = in_send.send(encoded_audio(AudioCodec::Opus, 1, vec![0; 8]));
+    let _ = dec.update();
+
+    assert!(out_recv.recv().is_none());
+    assert!(notification_recv.recv().is_some());
+}
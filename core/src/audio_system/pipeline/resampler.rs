@@ -0,0 +1,333 @@
+#[cfg(test)]
+mod tests;
+
+use crate::error;
+use crate::util::{Element, Runnable};
+
+use crate::audio_system::audio::{RawAudioBuffer, RawAudioInfo};
+use crate::audio_system::element::{
+    AudioFilter, AudioSink, AudioSource, AudioSystemElementMessage,
+};
+
+use std::f64::consts::PI;
+
+use mueue::*;
+
+/// The number of taps on each side of the filter when the audio isn't downsampled.
+const HALF_TAPS: usize = 32;
+const NO_PHASES: usize = 128;
+
+/// The cutoff frequency relative to the lowest of the Nyquist frequencies.
+const CUTOFF: f64 = 0.9;
+/// The filter isn't rebuilt if its cutoff changes less, e.g. during drift correction.
+const CUTOFF_TOLERANCE: f64 = 0.01;
+
+/// A streaming band-limited interpolator of interleaved audio, which is based on
+/// the windowed-sinc filter with precomputed phases.
+#[derive(Debug, Clone)]
+pub struct SincResampler {
+    channels: usize,
+    ratio: f64,
+
+    cutoff: f64,
+    half_taps: usize,
+    filter: Vec<f64>,
+
+    pending: Vec<f64>,
+    // The position of the next output frame in the pending input frames.
+    position: f64,
+}
+
+impl SincResampler {
+    /// The ratio is the output sample rate divided by the input one.
+    pub fn new(channels: u8, ratio: f64) -> Self {
+        let mut resampler = Self {
+            channels: usize::from(channels).max(1),
+            ratio: 1.0,
+
+            cutoff: 0.0,
+            half_taps: 0,
+            filter: Vec::new(),
+
+            pending: Vec::new(),
+            position: 0.0,
+        };
+        resampler.set_ratio(ratio);
+
+        resampler
+    }
+
+    pub fn ratio(&self) -> f64 {
+        self.ratio
+    }
+
+    pub fn set_ratio(&mut self, ratio: f64) {
+        assert!(
+            ratio.is_finite() && ratio > 0.0,
+            "The resampling ratio should be positive"
+        );
+        self.ratio = ratio;
+
+        let cutoff = CUTOFF * ratio.min(1.0);
+        if (cutoff - self.cutoff).abs() > self.cutoff * CUTOFF_TOLERANCE {
+            self.cutoff = cutoff;
+            self.half_taps = (HALF_TAPS as f64 / ratio.min(1.0)).ceil() as usize;
+            self.filter = build_filter(self.cutoff, self.half_taps);
+
+            self.ensure_left_context();
+        }
+    }
+
+    /// The number of input frames which are held back to compute the next output.
+    pub fn latency(&self) -> usize {
+        self.half_taps
+    }
+
+    pub fn reset(&mut self) {
+        self.pending.clear();
+        self.position = 0.0;
+
+        self.ensure_left_context();
+    }
+
+    /// Resamples interleaved samples, continuing the previously processed ones.
+    pub fn process(&mut self, samples: &[f64]) -> Vec<f64> {
+        let channels = self.channels;
+        let taps = self.half_taps * 2;
+        let step = 1.0 / self.ratio;
+
+        self.pending.extend_from_slice(samples);
+        let no_frames = self.pending.len() / channels;
+
+        let expected_no_frames = (samples.len() / channels) as f64 * self.ratio;
+        let mut output = Vec::with_capacity((expected_no_frames as usize + 1) * channels);
+
+        while (self.position as usize) + self.half_taps < no_frames {
+            let base = self.position.floor();
+            let phase = (self.position - base) * NO_PHASES as f64;
+            let phase_index = (phase.floor() as usize).min(NO_PHASES - 1);
+            let phase_frac = phase - phase_index as f64;
+
+            let first_taps = &self.filter[phase_index * taps..(phase_index + 1) * taps];
+            let second_taps = &self.filter[(phase_index + 1) * taps..(phase_index + 2) * taps];
+
+            let first_frame = base as usize + 1 - self.half_taps;
+            for channel in 0..channels {
+                let mut sample = 0.0;
+                for (tap, (first, second)) in first_taps.iter().zip(second_taps).enumerate() {
+                    let coeff = first + (second - first) * phase_frac;
+                    sample += self.pending[(first_frame + tap) * channels + channel] * coeff;
+                }
+
+                output.push(sample);
+            }
+
+            self.position += step;
+        }
+
+        let consumed_frames = (self.position.floor() as usize + 1)
+            .saturating_sub(self.half_taps)
+            .min(no_frames);
+        self.pending.drain(..consumed_frames * channels);
+        self.position -= consumed_frames as f64;
+
+        output
+    }
+
+    // The filter needs the frames preceding the current position, silence is
+    // assumed before the first one.
+    fn ensure_left_context(&mut self) {
+        let needed_frames = self.half_taps - 1;
+        let available_frames = self.position.floor() as usize;
+        if available_frames >= needed_frames {
+            return;
+        }
+
+        let missing_frames = needed_frames - available_frames;
+        self.pending.splice(
+            0..0,
+            std::iter::repeat_n(0.0, missing_frames * self.channels),
+        );
+        self.position += missing_frames as f64;
+    }
+}
+
+fn build_filter(cutoff: f64, half_taps: usize) -> Vec<f64> {
+    let taps = half_taps * 2;
+    let mut filter = Vec::with_capacity((NO_PHASES + 1) * taps);
+
+    for phase in 0..=NO_PHASES {
+        let frac = phase as f64 / NO_PHASES as f64;
+
+        let first_tap = filter.len();
+        for tap in 0..taps {
+            let x = (tap as f64 + 1.0 - half_taps as f64) - frac;
+            filter.push(cutoff * sinc(cutoff * x) * blackman_window(x, half_taps as f64));
+        }
+
+        // Every phase must pass the constant signal unchanged.
+        let sum: f64 = filter[first_tap..].iter().sum();
+        for coeff in filter[first_tap..].iter_mut() {
+            *coeff /= sum;
+        }
+    }
+
+    filter
+}
+
+fn sinc(x: f64) -> f64 {
+    if x == 0.0 {
+        1.0
+    } else {
+        (PI * x).sin() / (PI * x)
+    }
+}
+
+fn blackman_window(x: f64, half_width: f64) -> f64 {
+    if x.abs() >= half_width {
+        return 0.0;
+    }
+
+    let angle = PI * x / half_width;
+    0.42 + 0.5 * angle.cos() + 0.08 * (2.0 * angle).cos()
+}
+
+/// Converts the audio to the chosen sample rate.
+pub struct AudioResampler {
+    send: MessageSender<AudioSystemElementMessage>,
+    input: Option<MessageReceiver<RawAudioBuffer>>,
+    output: Option<MessageSender<RawAudioBuffer>>,
+
+    output_sample_rate: Option<u32>,
+
+    stream_info: Option<RawAudioInfo>,
+    resampler: Option<SincResampler>,
+}
+
+impl AudioResampler {
+    pub fn new(send: MessageSender<AudioSystemElementMessage>) -> Self {
+        Self {
+            send,
+            input: None,
+            output: None,
+
+            output_sample_rate: None,
+
+            stream_info: None,
+            resampler: None,
+        }
+    }
+
+    pub fn output_sample_rate(&self) -> Option<u32> {
+        self.output_sample_rate
+    }
+
+    /// The audio passes unchanged if the output sample rate isn't set.
+    pub fn set_output_sample_rate(&mut self, output_sample_rate: Option<u32>) {
+        if self.output_sample_rate != output_sample_rate {
+            self.output_sample_rate = output_sample_rate;
+            self.stream_info = None;
+            self.resampler = None;
+        }
+    }
+
+    fn resample(&mut self, audio: RawAudioBuffer) -> RawAudioBuffer {
+        let Some(output_sample_rate) = self.output_sample_rate else {
+            return audio;
+        };
+        if audio.sample_rate() == 0 || audio.format().no_bytes() == 0 {
+            return audio;
+        }
+
+        let stream_info = audio.info();
+        if self.stream_info != Some(stream_info) {
+            self.stream_info = Some(stream_info);
+            self.resampler = (stream_info.sample_rate != output_sample_rate).then(|| {
+                let ratio = f64::from(output_sample_rate) / f64::from(stream_info.sample_rate);
+                SincResampler::new(stream_info.channels, ratio)
+            });
+        }
+
+        let Some(resampler) = self.resampler.as_mut() else {
+            return audio;
+        };
+
+        let samples = resampler.process(&audio.to_normalized_samples());
+        RawAudioBuffer::from_normalized_samples(
+            &samples,
+            stream_info.format,
+            stream_info.channels,
+            output_sample_rate,
+        )
+        .with_channel_layout(stream_info.channel_layout)
+    }
+}
+
+impl Runnable for AudioResampler {
+    fn update(&mut self) -> error::Result<()> {
+        let Some(input) = self.input.clone() else {
+            return Ok(());
+        };
+
+        for audio in input.iter() {
+            let audio = self.resample(audio);
+            if audio.no_samples() == 0 {
+                continue;
+            }
+
+            if let Some(output) = self.output.as_ref() {
+                let _ = output.send(audio);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn on_stop(&mut self) {
+        if let Some(resampler) = self.resampler.as_mut() {
+            resampler.reset();
+        }
+    }
+}
+
+impl Element for AudioResampler {
+    type Message = AudioSystemElementMessage;
+
+    fn sender(&self) -> MessageSender<Self::Message> {
+        self.send.clone()
+    }
+
+    fn connect(&mut self, send: MessageSender<Self::Message>) {
+        self.send = send;
+    }
+}
+
+impl AudioSink<RawAudioBuffer> for AudioResampler {
+    fn input(&self) -> Option<MessageReceiver<RawAudioBuffer>> {
+        self.input.clone()
+    }
+
+    fn set_input(&mut self, input: MessageReceiver<RawAudioBuffer>) {
+        self.input = Some(input);
+    }
+
+    fn unset_input(&mut self) {
+        self.input = None;
+    }
+}
+
+impl AudioSource<RawAudioBuffer> for AudioResampler {
+    fn output(&self) -> Option<MessageSender<RawAudioBuffer>> {
+        self.output.clone()
+    }
+
+    fn set_output(&mut self, output: MessageSender<RawAudioBuffer>) {
+        self.output = Some(output);
+    }
+
+    fn unset_output(&mut self) {
+        self.output = None;
+    }
+}
+
+impl AudioFilter<RawAudioBuffer, RawAudioBuffer> for AudioResampler {}
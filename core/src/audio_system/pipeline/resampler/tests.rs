@@ -0,0 +1,152 @@
+use super::*;
+
+use crate::audio_system::audio::{ChannelLayout, RawAudioFormat};
+
+const AMPLITUDE: f64 = 0.5;
+
+fn sine(frequency: f64, sample_rate: u32, no_frames: usize, channels: usize) -> Vec<f64> {
+    (0..no_frames)
+        .flat_map(|n| {
+            let sample =
+                AMPLITUDE * (2.0 * PI * frequency * n as f64 / f64::from(sample_rate)).sin();
+            std::iter::repeat_n(sample, channels)
+        })
+        .collect()
+}
+
+fn rms(samples: &[f64]) -> f64 {
+    (samples.iter().map(|sample| sample * sample).sum::<f64>() / samples.len() as f64).sqrt()
+}
+
+#[test]
+fn test_convert_sample_rate() {
+    let ratio = 44100.0 / 48000.0;
+    let mut resampler = SincResampler::new(1, ratio);
+
+    let input = sine(1000.0, 48000, 48000, 1);
+    let output = resampler.process(&input);
+
+    let expected_no_frames = ((48000 - resampler.latency()) as f64 * ratio).round() as usize;
+    assert!(output.len().abs_diff(expected_no_frames) <= 1);
+
+    let expected = sine(1000.0, 44100, output.len(), 1);
+    for (expected, resampled) in expected.iter().zip(output.iter()).skip(resampler.latency()) {
+        assert!((expected - resampled).abs() < 1e-3);
+    }
+}
+
+#[test]
+fn test_keep_state_across_buffers() {
+    let input = sine(440.0, 48000, 4800, 2);
+
+    let mut whole_resampler = SincResampler::new(2, 16000.0 / 48000.0);
+    let whole_output = whole_resampler.process(&input);
+
+    let mut chunked_resampler = SincResampler::new(2, 16000.0 / 48000.0);
+    let chunked_output: Vec<f64> = input
+        .chunks(2 * 480)
+        .flat_map(|chunk| chunked_resampler.process(chunk))
+        .collect();
+
+    assert_eq!(chunked_output.len(), whole_output.len());
+    for (chunked, whole) in chunked_output.iter().zip(whole_output.iter()) {
+        assert!((chunked - whole).abs() < 1e-12);
+    }
+}
+
+#[test]
+fn test_suppress_aliasing() {
+    let mut resampler = SincResampler::new(1, 16000.0 / 48000.0);
+
+    // The tone is above the Nyquist frequency of the output.
+    let input = sine(12000.0, 48000, 48000, 1);
+    let output = resampler.process(&input);
+
+    assert!(rms(&output[resampler.latency()..]) < AMPLITUDE * 0.01);
+}
+
+#[test]
+fn test_fractional_ratio() {
+    let mut resampler = SincResampler::new(1, 1.001);
+
+    let mut no_frames = 0;
+    for _ in 0..10 {
+        no_frames += resampler.process(&sine(440.0, 48000, 4800, 1)).len();
+    }
+    resampler.set_ratio(0.999);
+    for _ in 0..10 {
+        no_frames += resampler.process(&sine(440.0, 48000, 4800, 1)).len();
+    }
+
+    let expected_no_frames = 48048 + 47952 - resampler.latency();
+    assert!(no_frames.abs_diff(expected_no_frames) <= 1);
+}
+
+#[test]
+fn test_resample_element() {
+    let (send, _) = unidirectional_queue();
+    let mut resampler = AudioResampler::new(send);
+
+    let in_send = resampler.create_input();
+    let out_recv = resampler.create_output();
+
+    let audio = RawAudioBuffer::from_normalized_samples(
+        &sine(440.0, 48000, 4800, 2),
+        RawAudioFormat::S16LE,
+        2,
+        48000,
+    );
+
+    let _ = in_send.send(audio.clone());
+    let _ = resampler.update();
+    assert_eq!(out_recv.recv(), Some(audio.clone()));
+
+    resampler.set_output_sample_rate(Some(24000));
+    let _ = in_send.send(audio);
+    let _ = resampler.update();
+
+    let resampled_audio = out_recv.recv().unwrap();
+    assert_eq!(resampled_audio.sample_rate(), 24000);
+    assert_eq!(resampled_audio.format(), RawAudioFormat::S16LE);
+    assert_eq!(resampled_audio.channel_layout(), ChannelLayout::Stereo);
+    assert!(resampled_audio.no_samples().abs_diff(2400) <= 64);
+}
+
+#[test]
+fn test_resample_element_across_buffers() {
+    let (send, _) = unidirectional_queue();
+    let mut resampler = AudioResampler::new(send);
+    resampler.set_output_sample_rate(Some(48000));
+
+    let in_send = resampler.create_input();
+    let out_recv = resampler.create_output();
+
+    // The ratio is fractional and the buffers don't split into whole output
+    // frames, so the position in the input has to be kept between them.
+    let input = sine(440.0, 44100, 4410, 1);
+    for chunk in input.chunks(441) {
+        let _ = in_send.send(RawAudioBuffer::from_normalized_samples(
+            chunk,
+            RawAudioFormat::F32LE,
+            1,
+            44100,
+        ));
+        let _ = resampler.update();
+    }
+
+    let output: Vec<f64> = out_recv
+        .iter()
+        .inspect(|audio| assert_eq!(audio.sample_rate(), 48000))
+        .flat_map(|audio| audio.to_normalized_samples())
+        .collect();
+
+    let mut whole_resampler = SincResampler::new(1, 48000.0 / 44100.0);
+    let whole_input =
+        RawAudioBuffer::from_normalized_samples(&input, RawAudioFormat::F32LE, 1, 44100);
+    let whole_output = whole_resampler.process(&whole_input.to_normalized_samples());
+
+    assert_eq!(output.len(), whole_output.len());
+    for (resampled, whole) in output.iter().zip(whole_output.iter()) {
+        assert!((resampled - whole).abs() < 1e-6);
+    }
+}
@@ -1,6 +1,9 @@
 #[cfg(test)]
 mod tests;
 
+use super::resampler::SincResampler;
+use super::time_stretcher::TimeStretcher;
+
 use crate::audio_system::audio::*;
 use crate::audio_system::element::*;
 
@@ -14,12 +17,35 @@ use std::ptr;
 use mueue::*;
 use smallvec::SmallVec;
 
-const TEMP_SAMPLE_BUFFER_LEN: usize = 4;
+const TEMP_FRAME_BUFFER_LEN: usize = 4;
+const MAX_INLINE_CHANNELS: usize = 8;
+
+/// Larger changes of the duration are made by discarding the audio or adding silence.
+const MIN_CONTINUOUS_RESIZE_RATIO: f64 = 3.0 / 4.0;
+const MAX_CONTINUOUS_RESIZE_RATIO: f64 = 4.0 / 3.0;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ResizeMode {
+    /// Averages, duplicates, discards or pads the samples.
+    #[default]
+    Interpolate,
+    /// Changes the duration with the band-limited resampler, which also changes
+    /// the pitch. The resampler delays the audio by its latency.
+    Resample,
+    /// Changes the duration without changing the pitch.
+    TimeStretch,
+}
 
 pub struct AudioResizer {
     send: MessageSender<AudioSystemElementMessage>,
     input: Option<MessageReceiver<ResizableRawAudioBuffer>>,
     output: Option<MessageSender<RawAudioBuffer>>,
+
+    mode: ResizeMode,
+
+    stream_info: Option<RawAudioInfo>,
+    resampler: Option<SincResampler>,
+    time_stretcher: Option<TimeStretcher>,
 }
 
 impl AudioResizer {
@@ -28,34 +54,129 @@ impl AudioResizer {
             send,
             input: None,
             output: None,
+
+            mode: ResizeMode::default(),
+
+            stream_info: None,
+            resampler: None,
+            time_stretcher: None,
+        }
+    }
+
+    pub fn mode(&self) -> ResizeMode {
+        self.mode
+    }
+
+    pub fn set_mode(&mut self, mode: ResizeMode) {
+        self.mode = mode;
+
+        self.reset_state();
+    }
+
+    fn resize(&mut self, audio: ResizableRawAudioBuffer) -> Option<RawAudioBuffer> {
+        let no_samples = audio.no_samples();
+        let desired_no_samples = audio.desired_no_samples();
+        let raw_audio = audio.into_raw();
+
+        //dbg!(no_samples, desired_no_samples);
+
+        if no_samples == 0 || desired_no_samples == 0 {
+            return None;
+        }
+
+        match self.mode {
+            ResizeMode::Interpolate => choose_resize_function(no_samples, desired_no_samples)
+                .map(|f| f(raw_audio, desired_no_samples)),
+            ResizeMode::Resample | ResizeMode::TimeStretch => {
+                Some(self.resize_continuously(raw_audio, desired_no_samples))
+            }
+        }
+    }
+
+    // The resampler and the time stretcher keep the state between buffers, so the
+    // audio always goes through them to stay continuous.
+    fn resize_continuously(
+        &mut self,
+        audio: RawAudioBuffer,
+        desired_no_samples: usize,
+    ) -> RawAudioBuffer {
+        let info = audio.info();
+        if self.stream_info != Some(info) {
+            self.reset_state();
+            self.stream_info = Some(info);
+        }
+
+        let ratio = desired_no_samples as f64 / audio.no_samples() as f64;
+        let is_continuous =
+            (MIN_CONTINUOUS_RESIZE_RATIO..=MAX_CONTINUOUS_RESIZE_RATIO).contains(&ratio);
+        let ratio = if is_continuous { ratio } else { 1.0 };
+
+        let input = audio.to_normalized_samples();
+        let samples = match self.mode {
+            ResizeMode::Resample => {
+                let resampler = self
+                    .resampler
+                    .get_or_insert_with(|| SincResampler::new(info.channels, ratio));
+                resampler.set_ratio(ratio);
+
+                resampler.process(&input)
+            }
+            ResizeMode::TimeStretch => self
+                .time_stretcher
+                .get_or_insert_with(|| TimeStretcher::new(info.channels, info.sample_rate))
+                .process(&input, 1.0 / ratio),
+            ResizeMode::Interpolate => unreachable!("Interpolation doesn't keep any state"),
+        };
+        let resized_audio = RawAudioBuffer::from_normalized_samples(
+            &samples,
+            info.format,
+            info.channels,
+            info.sample_rate,
+        )
+        .with_channel_layout(info.channel_layout);
+
+        if is_continuous {
+            return resized_audio;
+        }
+
+        match choose_resize_function(resized_audio.no_samples(), desired_no_samples) {
+            Some(f) => f(resized_audio, desired_no_samples),
+            None => resized_audio,
         }
     }
+
+    fn reset_state(&mut self) {
+        self.stream_info = None;
+        self.resampler = None;
+        self.time_stretcher = None;
+    }
 }
 
 impl Runnable for AudioResizer {
     fn update(&mut self) -> error::Result<()> {
-        let Some(input) = self.input.as_ref() else {
+        let Some(input) = self.input.clone() else {
             return Ok(());
         };
 
         for audio in input.iter() {
-            let no_samples = audio.no_samples();
-            let desired_no_samples = audio.desired_no_samples();
-            let raw_audio = audio.into_raw();
-
-            //dbg!(no_samples, desired_no_samples);
-
-            let Some(f) = choose_resize_function(no_samples, desired_no_samples) else {
+            let Some(audio) = self.resize(audio) else {
                 continue;
             };
+            if audio.no_samples() == 0 {
+                continue;
+            }
 
             if let Some(output) = self.output.as_ref() {
-                let _ = output.send(f(raw_audio, desired_no_samples));
+                let _ = output.send(audio);
             }
         }
 
         Ok(())
     }
+
+    fn on_stop(&mut self) {
+        self.reset_state();
+    }
 }
 
 fn choose_resize_function(
@@ -91,9 +212,9 @@ fn discard(mut audio: RawAudioBuffer, desired_no_samples: usize) -> RawAudioBuff
             than the current one when performing discarding"
     );
 
-    let no_bytes = audio.format().no_bytes();
+    let frame_size = audio.frame_size();
     let samples_truncated = no_samples - desired_no_samples;
-    vec_truncate_front(audio.as_vec_mut(), samples_truncated * no_bytes);
+    vec_truncate_front(audio.as_vec_mut(), samples_truncated * frame_size);
 
     audio
 }
@@ -110,30 +231,26 @@ fn downsample(mut audio: RawAudioBuffer, mut desired_no_samples: usize) -> RawAu
             than the current one when performing downsampling"
     );
 
-    let no_bytes = audio.format().no_bytes();
-    let mut next_sample_ptr = audio.as_slice_mut().as_mut_ptr();
-    let mut temp_samples = SmallVec::<[Sample; TEMP_SAMPLE_BUFFER_LEN]>::new();
+    let frame_size = audio.frame_size();
+    let mut next_frame_ptr = audio.as_slice_mut().as_mut_ptr();
+    let mut temp_frames = SmallVec::<[Frame; TEMP_FRAME_BUFFER_LEN]>::new();
 
-    for sample in SampleIter::new(&audio) {
+    for frame in FrameIter::new(&audio) {
         let min_rate = no_samples / desired_no_samples;
         if min_rate > 1 {
-            temp_samples.push(sample);
+            temp_frames.push(frame);
 
-            if temp_samples.len() == min_rate {
-                let average_sample = take_average_sample(temp_samples.drain(..));
+            if temp_frames.len() == min_rate {
+                let average_frame = take_average_frame(temp_frames.drain(..));
                 unsafe {
-                    ptr::copy_nonoverlapping(
-                        average_sample.to_bytes().as_ptr(),
-                        next_sample_ptr,
-                        no_bytes,
-                    );
+                    average_frame.copy_to_ptr(next_frame_ptr);
                 }
             }
         }
 
-        if temp_samples.is_empty() {
+        if temp_frames.is_empty() {
             unsafe {
-                next_sample_ptr = next_sample_ptr.add(no_bytes);
+                next_frame_ptr = next_frame_ptr.add(frame_size);
             }
 
             no_samples -= min_rate;
@@ -141,33 +258,29 @@ fn downsample(mut audio: RawAudioBuffer, mut desired_no_samples: usize) -> RawAu
         }
     }
 
-    if !temp_samples.is_empty() {
-        let average_sample = take_average_sample(temp_samples.drain(..));
+    if !temp_frames.is_empty() {
+        let average_frame = take_average_frame(temp_frames.drain(..));
         unsafe {
-            ptr::copy_nonoverlapping(
-                average_sample.to_bytes().as_ptr(),
-                next_sample_ptr,
-                no_bytes,
-            );
-            next_sample_ptr = next_sample_ptr.add(no_bytes);
+            average_frame.copy_to_ptr(next_frame_ptr);
+            next_frame_ptr = next_frame_ptr.add(frame_size);
         }
     }
 
-    let final_len = next_sample_ptr as usize - audio.as_slice().as_ptr() as usize;
+    let final_len = next_frame_ptr as usize - audio.as_slice().as_ptr() as usize;
     audio.as_vec_mut().truncate(final_len);
 
     audio
 }
 
-fn take_average_sample<I>(sample_iter: I) -> Sample
+fn take_average_frame<I>(frame_iter: I) -> Frame
 where
-    I: Iterator<Item = Sample> + ExactSizeIterator,
+    I: Iterator<Item = Frame> + ExactSizeIterator,
 {
-    let no_sample = sample_iter.len();
-    let avg_sample = sample_iter
-        .map(|s| s / no_sample).sum::<Sample>();
+    let no_frames = frame_iter.len();
+    let avg_frame = frame_iter
+        .map(|f| f / no_frames).sum::<Frame>();
 
-    avg_sample
+    avg_frame
 }
 
 fn add_silence(mut audio: RawAudioBuffer, desired_no_samples: usize) -> RawAudioBuffer {
@@ -182,8 +295,8 @@ fn add_silence(mut audio: RawAudioBuffer, desired_no_samples: usize) -> RawAudio
             than the current one when adding silence"
     );
 
-    let no_bytes = audio.format().no_bytes();
-    let silence_bytes = (desired_no_samples - no_samples) * no_bytes;
+    let frame_size = audio.frame_size();
+    let silence_bytes = (desired_no_samples - no_samples) * frame_size;
 
     audio
         .as_vec_mut()
@@ -205,42 +318,50 @@ fn upsample(audio: RawAudioBuffer, mut desired_no_samples: usize) -> RawAudioBuf
             than the current one when performing downsampling"
     );
 
-    let no_bytes = audio.format().no_bytes();
-    let mut new_audio_bytes = Vec::with_capacity(desired_no_samples * no_bytes);
+    let frame_size = audio.frame_size();
+    let mut new_audio_bytes = Vec::with_capacity(desired_no_samples * frame_size);
 
     no_samples -= 1;
     desired_no_samples -= 1;
 
-    for samples_pair in SamplesPairIter::new(&audio) {
+    for [first_frame, second_frame] in FramesPairIter::new(&audio) {
         let additional_samples = desired_no_samples / no_samples - 1;
 
-        let first_sample_bytes = &samples_pair[0].to_bytes()[..no_bytes];
-        new_audio_bytes.extend_from_slice(first_sample_bytes);
-        interpolate_samples(samples_pair, additional_samples, &mut new_audio_bytes);
+        first_frame.write_bytes(&mut new_audio_bytes);
+        interpolate_frames(
+            [first_frame, second_frame],
+            additional_samples,
+            &mut new_audio_bytes,
+        );
 
         no_samples -= 1;
         desired_no_samples -= additional_samples + 1;
     }
 
-    let last_sample_start = audio.len() - no_bytes;
-    let last_sample_bytes = &audio.as_slice()[last_sample_start..];
-    let last_sample = Sample::from_bytes(last_sample_bytes, audio.format());
-    new_audio_bytes.extend(&last_sample.to_bytes()[..no_bytes]);
-
-    RawAudioBuffer::new(new_audio_bytes, audio.format(), audio.sample_rate())
+    let last_frame_start = audio.len() - frame_size;
+    let last_frame_bytes = &audio.as_slice()[last_frame_start..];
+    let last_frame = Frame::from_bytes(last_frame_bytes, audio.format(), audio.channels());
+    last_frame.write_bytes(&mut new_audio_bytes);
+
+    RawAudioBuffer::new(
+        new_audio_bytes,
+        audio.format(),
+        audio.channels(),
+        audio.sample_rate(),
+    )
+    .with_channel_layout(audio.channel_layout())
 }
 
-fn interpolate_samples(
-    [first_sample, second_sample]: [Sample; 2],
+fn interpolate_frames(
+    [first_frame, second_frame]: [Frame; 2],
     additional_samples: usize,
-    samples_dst: &mut Vec<u8>,
+    frames_dst: &mut Vec<u8>,
 ) {
-    let no_bytes = first_sample.no_bytes();
     let denom = additional_samples + 1;
     for num in 1..denom {
-        let interpolated_sample = (first_sample + second_sample) * num / denom;
-        let interpolated_sample_bytes = &interpolated_sample.to_bytes()[..no_bytes];
-        samples_dst.extend_from_slice(interpolated_sample_bytes);
+        let interpolated_frame =
+            first_frame.clone().scale(denom - num, denom) + second_frame.clone().scale(num, denom);
+        interpolated_frame.write_bytes(frames_dst);
     }
 }
 
@@ -338,13 +459,13 @@ impl Sample {
             }
             R::S24LE => {
                 let mut bytes = [0; 4];
-                bytes[0..3].clone_from_slice(&buf[..format.no_bytes()]);
-                S::S24LE(i32::from_le_bytes(bytes))
+                bytes[1..4].clone_from_slice(&buf[..format.no_bytes()]);
+                S::S24LE(i32::from_le_bytes(bytes) >> 8)
             }
             R::S24BE => {
                 let mut bytes = [0; 4];
-                bytes[1..4].clone_from_slice(&buf[..format.no_bytes()]);
-                S::S24BE(i32::from_be_bytes(bytes))
+                bytes[0..3].clone_from_slice(&buf[..format.no_bytes()]);
+                S::S24BE(i32::from_be_bytes(bytes) >> 8)
             }
             R::S32LE => {
                 let bytes = buf[0..format.no_bytes()]
@@ -393,6 +514,65 @@ impl Sample {
 
         bytes
     }
+
+    const fn format(&self) -> RawAudioFormat {
+        use RawAudioFormat as R;
+        use Sample as S;
+
+        match self {
+            S::U8(_) => R::U8,
+            S::S16LE(_) => R::S16LE,
+            S::S16BE(_) => R::S16BE,
+            S::S24LE(_) => R::S24LE,
+            S::S24BE(_) => R::S24BE,
+            S::S32LE(_) => R::S32LE,
+            S::S32BE(_) => R::S32BE,
+            S::F32LE(_) => R::F32LE,
+            S::F32BE(_) => R::F32BE,
+            S::Unspecified => R::Unspecified,
+        }
+    }
+
+    fn to_format(self, format: RawAudioFormat) -> Self {
+        if self.format() == format {
+            return self;
+        }
+
+        let mut bytes = [0; 4];
+        let sample = self.format().read_normalized_sample(&self.to_bytes());
+        format.write_normalized_sample(sample, &mut bytes);
+
+        Self::from_bytes(&bytes, format)
+    }
+
+    /// Multiplies the sample by `num / denom` without overflowing in between.
+    fn scale(self, num: usize, denom: usize) -> Self {
+        use Sample as S;
+
+        let scale_int =
+            |a: i64, min: i64, max: i64| (a * num as i64 / denom as i64).clamp(min, max);
+        let scale_float = |a: f32| (f64::from(a) * num as f64 / denom as f64) as f32;
+
+        match self {
+            S::U8(a) => S::U8(scale_int(a.into(), 0, u8::MAX.into()) as u8),
+            S::S16LE(a) => S::S16LE(scale_int(a.into(), i16::MIN.into(), i16::MAX.into()) as i16),
+            S::S16BE(a) => S::S16BE(scale_int(a.into(), i16::MIN.into(), i16::MAX.into()) as i16),
+            S::S24LE(a) => S::S24LE(scale_int(a.into(), S24_MIN, S24_MAX) as i32),
+            S::S24BE(a) => S::S24BE(scale_int(a.into(), S24_MIN, S24_MAX) as i32),
+            S::S32LE(a) => S::S32LE(scale_int(a.into(), i32::MIN.into(), i32::MAX.into()) as i32),
+            S::S32BE(a) => S::S32BE(scale_int(a.into(), i32::MIN.into(), i32::MAX.into()) as i32),
+            S::F32LE(a) => S::F32LE(scale_float(a)),
+            S::F32BE(a) => S::F32BE(scale_float(a)),
+            S::Unspecified => S::Unspecified,
+        }
+    }
+}
+
+const S24_MIN: i64 = -(1 << 23);
+const S24_MAX: i64 = (1 << 23) - 1;
+
+fn saturating_add_s24(a: i32, b: i32) -> i32 {
+    (i64::from(a) + i64::from(b)).clamp(S24_MIN, S24_MAX) as i32
 }
 
 impl ops::Add for Sample {
@@ -402,136 +582,163 @@ impl ops::Add for Sample {
         use Sample as S;
 
         match (self, rhs) {
-            (S::U8(a), S::U8(b)) => S::U8(a + b),
+            (S::U8(a), S::U8(b)) => S::U8(a.saturating_add(b)),
 
-            (S::S16BE(a), S::S16BE(b)) => S::S16BE(a + b),
             (S::S16LE(a), S::S16LE(b)) => S::S16LE(a.saturating_add(b)),
+            (S::S16BE(a), S::S16BE(b)) => S::S16BE(a.saturating_add(b)),
 
-            (S::S24LE(a), S::S24LE(b)) => S::S24LE(a + b),
-            (S::S24BE(a), S::S24BE(b)) => S::S24BE(a + b),
+            (S::S24LE(a), S::S24LE(b)) => S::S24LE(saturating_add_s24(a, b)),
+            (S::S24BE(a), S::S24BE(b)) => S::S24BE(saturating_add_s24(a, b)),
 
-            (S::S32LE(a), S::S32LE(b)) => S::S32LE(a + b),
-            (S::S32BE(a), S::S32BE(b)) => S::S32BE(a + b),
+            (S::S32LE(a), S::S32LE(b)) => S::S32LE(a.saturating_add(b)),
+            (S::S32BE(a), S::S32BE(b)) => S::S32BE(a.saturating_add(b)),
 
             (S::F32LE(a), S::F32LE(b)) => S::F32LE(a + b),
             (S::F32BE(a), S::F32BE(b)) => S::F32BE(a + b),
 
-            _ => unimplemented!("Adding different sample variats is not supported"),
+            (S::Unspecified, sample) | (sample, S::Unspecified) => sample,
+
+            (a, b) => a + b.to_format(a.format()),
         }
     }
 }
 
-impl ops::Mul<usize> for Sample {
+impl ops::Div<usize> for Sample {
     type Output = Self;
 
-    fn mul(self, rhs: usize) -> Self::Output {
-        use Sample as S;
+    fn div(self, rhs: usize) -> Self::Output {
+        self.scale(1, rhs)
+    }
+}
 
-        match self {
-            S::U8(a) => S::U8(a * rhs as u8),
-            S::S16LE(a) => S::S16LE(a * rhs as i16),
-            S::S16BE(a) => S::S16BE(a * rhs as i16),
-            S::S24LE(a) => S::S24LE(a * rhs as i32),
-            S::S24BE(a) => S::S24BE(a * rhs as i32),
-            S::S32LE(a) => S::S32LE(a * rhs as i32),
-            S::S32BE(a) => S::S32BE(a * rhs as i32),
-            S::F32LE(a) => S::F32LE(a * rhs as f32),
-            S::F32BE(a) => S::F32BE(a * rhs as f32),
-            S::Unspecified => S::Unspecified,
+impl iter::Sum<Sample> for Sample {
+    fn sum<I: Iterator<Item = Sample>>(iter: I) -> Self {
+        iter.reduce(|a, b| a + b).expect("No samples were supplied")
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Frame(SmallVec<[Sample; MAX_INLINE_CHANNELS]>);
+
+impl Frame {
+    fn from_bytes(buf: &[u8], format: RawAudioFormat, channels: u8) -> Self {
+        let samples = buf
+            .chunks_exact(format.no_bytes())
+            .take(channels as usize)
+            .map(|bytes| Sample::from_bytes(bytes, format))
+            .collect();
+
+        Self(samples)
+    }
+
+    fn write_bytes(&self, dst: &mut Vec<u8>) {
+        for sample in self.0.iter() {
+            dst.extend_from_slice(&sample.to_bytes()[..sample.no_bytes()]);
+        }
+    }
+
+    fn scale(self, num: usize, denom: usize) -> Self {
+        Self(self.0.into_iter().map(|s| s.scale(num, denom)).collect())
+    }
+
+    unsafe fn copy_to_ptr(&self, mut dst: *mut u8) {
+        for sample in self.0.iter() {
+            let no_bytes = sample.no_bytes();
+
+            ptr::copy_nonoverlapping(sample.to_bytes().as_ptr(), dst, no_bytes);
+            dst = dst.add(no_bytes);
         }
     }
 }
 
-impl ops::Div<usize> for Sample {
+impl ops::Add for Frame {
     type Output = Self;
 
-    fn div(self, rhs: usize) -> Self::Output {
-        use Sample as S;
+    fn add(self, rhs: Self) -> Self::Output {
+        Self(self.0.into_iter().zip(rhs.0).map(|(a, b)| a + b).collect())
+    }
+}
 
-        match self {
-            S::U8(a) => S::U8(a / rhs as u8),
-            S::S16LE(a) => S::S16LE(a / rhs as i16),
-            S::S16BE(a) => S::S16BE(a / rhs as i16),
-            S::S24LE(a) => S::S24LE(a / rhs as i32),
-            S::S24BE(a) => S::S24BE(a / rhs as i32),
-            S::S32LE(a) => S::S32LE(a / rhs as i32),
-            S::S32BE(a) => S::S32BE(a / rhs as i32),
-            S::F32LE(a) => S::F32LE(a / rhs as f32),
-            S::F32BE(a) => S::F32BE(a / rhs as f32),
-            S::Unspecified => S::Unspecified,
-        }
+impl ops::Div<usize> for Frame {
+    type Output = Self;
+
+    fn div(self, rhs: usize) -> Self::Output {
+        Self(self.0.into_iter().map(|s| s / rhs).collect())
     }
 }
 
-impl iter::Sum<Sample> for Sample {
-    fn sum<I: Iterator<Item = Sample>>(iter: I) -> Self {
-        iter.reduce(|a, b| a + b).expect("No samples were supplied")
+impl iter::Sum<Frame> for Frame {
+    fn sum<I: Iterator<Item = Frame>>(iter: I) -> Self {
+        iter.reduce(|a, b| a + b).expect("No frames were supplied")
     }
 }
 
-struct SampleIter<'b> {
+struct FrameIter<'b> {
     buffer: &'b RawAudioBuffer,
     offset: usize,
 }
 
-impl<'b> SampleIter<'b> {
+impl<'b> FrameIter<'b> {
     fn new(buffer: &'b RawAudioBuffer) -> Self {
         Self { buffer, offset: 0 }
     }
 }
 
-impl iter::Iterator for SampleIter<'_> {
-    type Item = Sample;
+impl iter::Iterator for FrameIter<'_> {
+    type Item = Frame;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let no_bytes = self.buffer.format().no_bytes();
-        if self.offset + no_bytes > self.buffer.as_slice().len() {
+        let frame_size = self.buffer.frame_size();
+        if frame_size == 0 || self.offset + frame_size > self.buffer.as_slice().len() {
             return None;
         }
 
-        let bytes = &self.buffer.as_slice()[self.offset..self.offset + no_bytes];
-        let sample = Sample::from_bytes(bytes, self.buffer.format());
+        let bytes = &self.buffer.as_slice()[self.offset..self.offset + frame_size];
+        let frame = Frame::from_bytes(bytes, self.buffer.format(), self.buffer.channels());
 
-        self.offset += no_bytes;
+        self.offset += frame_size;
 
-        Some(sample)
+        Some(frame)
     }
 }
 
-struct SamplesPairIter<'b> {
+struct FramesPairIter<'b> {
     buffer: &'b RawAudioBuffer,
     offset: usize,
 }
 
-impl<'b> SamplesPairIter<'b> {
+impl<'b> FramesPairIter<'b> {
     fn new(buffer: &'b RawAudioBuffer) -> Self {
         Self { buffer, offset: 0 }
     }
 }
 
-impl iter::Iterator for SamplesPairIter<'_> {
-    type Item = [Sample; 2];
+impl iter::Iterator for FramesPairIter<'_> {
+    type Item = [Frame; 2];
 
     fn next(&mut self) -> Option<Self::Item> {
-        let no_bytes = self.buffer.format().no_bytes();
-        if self.offset + no_bytes * 2 > self.buffer.as_slice().len() {
+        let frame_size = self.buffer.frame_size();
+        if frame_size == 0 || self.offset + frame_size * 2 > self.buffer.as_slice().len() {
             return None;
         }
 
-        let first_sample_start = self.offset;
-        let first_sample_end = self.offset + no_bytes;
+        let format = self.buffer.format();
+        let channels = self.buffer.channels();
+
+        let first_frame_start = self.offset;
+        let first_frame_end = self.offset + frame_size;
 
-        let first_bytes = &self.buffer.as_slice()[first_sample_start..first_sample_end];
-        let first_sample = Sample::from_bytes(first_bytes, self.buffer.format());
+        let first_bytes = &self.buffer.as_slice()[first_frame_start..first_frame_end];
+        let first_frame = Frame::from_bytes(first_bytes, format, channels);
 
-        let second_sample_start = self.offset + no_bytes;
-        let second_sample_end = self.offset + no_bytes * 2;
+        let second_frame_start = self.offset + frame_size;
+        let second_frame_end = self.offset + frame_size * 2;
 
-        let second_bytes = &self.buffer.as_slice()[second_sample_start..second_sample_end];
-        let second_sample = Sample::from_bytes(second_bytes, self.buffer.format());
+        let second_bytes = &self.buffer.as_slice()[second_frame_start..second_frame_end];
+        let second_frame = Frame::from_bytes(second_bytes, format, channels);
 
-        self.offset += no_bytes;
+        self.offset += frame_size;
 
-        Some([first_sample, second_sample])
+        Some([first_frame, second_frame])
     }
 }
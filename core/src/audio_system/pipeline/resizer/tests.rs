@@ -3,7 +3,7 @@ use super::*;
 #[test]
 fn test_discard() {
     let data = vec![1, 2, 3, 4, 5, 6, 1, 2, 3, 4, 5, 6, 1, 2, 3];
-    let audio = RawAudioBuffer::new(data.clone(), RawAudioFormat::S24BE, 0);
+    let audio = RawAudioBuffer::new(data.clone(), RawAudioFormat::S24BE, 1, 0);
     let discraded_audio = vec![4, 5, 6, 1, 2, 3];
 
     assert_eq!(discard(audio, 2).as_slice(), discraded_audio);
@@ -13,7 +13,7 @@ fn test_discard() {
 fn test_downsample_int_rate() {
     let data = vec![1, 2, 3, 4, 5, 6, 1, 2, 3, 4, 5, 6, 1, 2, 3];
 
-    let audio = RawAudioBuffer::new(data.clone(), RawAudioFormat::S24BE, 0);
+    let audio = RawAudioBuffer::new(data.clone(), RawAudioFormat::S24BE, 1, 0);
     let downsampled_audio = {
         let first = i32::from_be_bytes([0, 1, 2, 3]);
         let second = i32::from_be_bytes([0, 4, 5, 6]);
@@ -33,7 +33,7 @@ fn test_downsample_int_rate() {
         downsampled_audio
     );
 
-    let audio = RawAudioBuffer::new(data, RawAudioFormat::S24LE, 0);
+    let audio = RawAudioBuffer::new(data, RawAudioFormat::S24LE, 1, 0);
     let downsampled_audio = {
         let first = i32::from_le_bytes([1, 2, 3, 0]);
         let second = i32::from_le_bytes([4, 5, 6, 0]);
@@ -55,7 +55,7 @@ fn test_downsample_int_rate() {
 
     let data = vec![1, 2, 3, 4, 5, 6, 1, 2, 3, 4, 5, 6];
 
-    let audio = RawAudioBuffer::new(data.clone(), RawAudioFormat::S24BE, 0);
+    let audio = RawAudioBuffer::new(data.clone(), RawAudioFormat::S24BE, 1, 0);
     let downsampled_audio = {
         let first = i32::from_be_bytes([0, 1, 2, 3]);
         let second = i32::from_be_bytes([0, 4, 5, 6]);
@@ -72,7 +72,7 @@ fn test_downsample_int_rate() {
         downsampled_audio
     );
 
-    let audio = RawAudioBuffer::new(data, RawAudioFormat::S24LE, 0);
+    let audio = RawAudioBuffer::new(data, RawAudioFormat::S24LE, 1, 0);
     let downsampled_audio = {
         let first = i32::from_le_bytes([1, 2, 3, 0]);
         let second = i32::from_le_bytes([4, 5, 6, 0]);
@@ -94,7 +94,7 @@ fn test_downsample_int_rate() {
 fn test_downsample_real_rate() {
     let data = vec![1, 2, 3, 4, 5, 6, 1, 2, 3, 4, 5, 6, 1, 2, 3];
 
-    let audio = RawAudioBuffer::new(data.clone(), RawAudioFormat::S24BE, 0);
+    let audio = RawAudioBuffer::new(data.clone(), RawAudioFormat::S24BE, 1, 0);
     let downsampled_audio = {
         let first = i32::from_be_bytes([0, 1, 2, 3]);
         let second = i32::from_be_bytes([0, 4, 5, 6]);
@@ -112,7 +112,7 @@ fn test_downsample_real_rate() {
         downsampled_audio
     );
 
-    let audio = RawAudioBuffer::new(data, RawAudioFormat::S24LE, 0);
+    let audio = RawAudioBuffer::new(data, RawAudioFormat::S24LE, 1, 0);
     let downsampled_audio = {
         let first = i32::from_le_bytes([1, 2, 3, 0]);
         let second = i32::from_le_bytes([4, 5, 6, 0]);
@@ -132,7 +132,7 @@ fn test_downsample_real_rate() {
 
     let data = vec![1, 2, 3, 4, 5, 6, 1, 2, 3, 4, 5, 6, 1, 2, 3, 4, 5, 6];
 
-    let audio = RawAudioBuffer::new(data.clone(), RawAudioFormat::S24BE, 0);
+    let audio = RawAudioBuffer::new(data.clone(), RawAudioFormat::S24BE, 1, 0);
     let downsampled_audio = {
         let first = i32::from_be_bytes([0, 1, 2, 3]);
         let second = i32::from_be_bytes([0, 4, 5, 6]);
@@ -150,7 +150,7 @@ fn test_downsample_real_rate() {
         downsampled_audio
     );
 
-    let audio = RawAudioBuffer::new(data, RawAudioFormat::S24LE, 0);
+    let audio = RawAudioBuffer::new(data, RawAudioFormat::S24LE, 1, 0);
     let downsampled_audio = {
         let first = i32::from_le_bytes([1, 2, 3, 0]);
         let second = i32::from_le_bytes([4, 5, 6, 0]);
@@ -172,9 +172,111 @@ fn test_downsample_real_rate() {
 #[test]
 fn test_upsample_int_rate() {
     let data = vec![1, 2, 3, 4, 5, 6, 1, 2, 3, 4, 5, 6, 1, 2, 3, 4, 5, 6];
-    let audio = RawAudioBuffer::new(data.clone(), RawAudioFormat::S24BE, 0);
+    let audio = RawAudioBuffer::new(data.clone(), RawAudioFormat::S24BE, 1, 0);
     let desired_no_samples = audio.no_samples() * 3 / 2;
 
     let new_audio = upsample(audio, desired_no_samples);
     println!("{}: {:?}", new_audio.no_samples(), new_audio);
 }
+
+#[test]
+fn test_downsample_stereo() {
+    let left = [10i16, 20, 30, 40];
+    let right = [-10i16, -20, -30, -40];
+
+    let data = left
+        .iter()
+        .zip(right.iter())
+        .flat_map(|(l, r)| [l.to_le_bytes(), r.to_le_bytes()])
+        .flatten()
+        .collect::<Vec<u8>>();
+    let audio = RawAudioBuffer::new(data, RawAudioFormat::S16LE, 2, 0);
+    assert_eq!(audio.no_samples(), 4);
+
+    let downsampled_audio = [15i16, -15, 35, -35]
+        .iter()
+        .flat_map(|s| s.to_le_bytes())
+        .collect::<Vec<u8>>();
+
+    let audio = downsample(audio, 2);
+    assert_eq!(audio.no_samples(), 2);
+    assert_eq!(audio.as_slice(), downsampled_audio);
+}
+
+#[test]
+fn test_resample_mode() {
+    let (send, _) = unidirectional_queue();
+    let mut resizer = AudioResizer::new(send);
+    assert_eq!(resizer.mode(), ResizeMode::Interpolate);
+    resizer.set_mode(ResizeMode::Resample);
+
+    let in_send = resizer.create_input();
+    let out_recv = resizer.create_output();
+
+    let audio = RawAudioBuffer::new(vec![0; 480 * 4], RawAudioFormat::S16LE, 2, 48000);
+    for _ in 0..20 {
+        let _ = in_send.send(ResizableRawAudioBuffer::new(audio.clone(), 490));
+    }
+    let _ = resizer.update();
+
+    let no_samples: usize = out_recv.iter().map(|audio| audio.no_samples()).sum();
+    assert!(no_samples.abs_diff(490 * 20) <= 64);
+
+    // Too large changes of the duration aren't made by resampling.
+    let _ = in_send.send(ResizableRawAudioBuffer::new(audio, 1000));
+    let _ = resizer.update();
+    assert_eq!(out_recv.recv().unwrap().no_samples(), 1000);
+}
+
+#[test]
+fn test_time_stretch_mode() {
+    let (send, _) = unidirectional_queue();
+    let mut resizer = AudioResizer::new(send);
+    resizer.set_mode(ResizeMode::TimeStretch);
+
+    let in_send = resizer.create_input();
+    let out_recv = resizer.create_output();
+
+    let audio = RawAudioBuffer::new(vec![0; 480 * 4], RawAudioFormat::S16LE, 2, 48000);
+    for _ in 0..50 {
+        let _ = in_send.send(ResizableRawAudioBuffer::new(audio.clone(), 500));
+    }
+    let _ = resizer.update();
+
+    // The time stretcher holds back a part of the audio.
+    let no_samples: usize = out_recv.iter().map(|audio| audio.no_samples()).sum();
+    assert!(no_samples.abs_diff(500 * 50) <= 2400);
+}
+
+#[test]
+fn test_saturate_loud_samples() {
+    for format in [
+        RawAudioFormat::U8,
+        RawAudioFormat::S16BE,
+        RawAudioFormat::S24LE,
+        RawAudioFormat::S32BE,
+    ] {
+        let samples = [1.0, 1.0, -1.0, -1.0];
+        let audio = RawAudioBuffer::from_normalized_samples(&samples, format, 1, 0);
+
+        let upsampled = upsample(audio.clone(), 10).to_normalized_samples();
+        assert!(upsampled.iter().all(|sample| sample.abs() <= 1.0));
+        assert_eq!(upsampled[0], audio.to_normalized_samples()[0]);
+
+        let downsampled = downsample(audio, 2).to_normalized_samples();
+        assert_eq!(downsampled.len(), 2);
+        assert!(downsampled[0] > 0.9 && downsampled[1] < -0.9);
+    }
+}
+
+#[test]
+fn test_add_different_samples() {
+    let sum = Sample::S16LE(i16::MAX) + Sample::S32BE(i32::MAX);
+    assert!(matches!(sum, Sample::S16LE(i16::MAX)));
+
+    let sum = Sample::F32LE(0.25) + Sample::S16BE(1 << 13);
+    assert!(matches!(sum, Sample::F32LE(sample) if sample == 0.5));
+
+    let sum = Sample::Unspecified + Sample::S24LE(-1);
+    assert!(matches!(sum, Sample::S24LE(-1)));
+}
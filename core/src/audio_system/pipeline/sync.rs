@@ -6,17 +6,23 @@ use crate::util::{
     Clock, ClockTime, Element, Runnable, SlaveClock, Timer, OBSERVATIONS_INTERVAL,
 };
 
-use crate::audio_system::audio::{ResizableRawAudioBuffer, TimestampedRawAudioBuffer};
+use crate::audio_system::audio::{
+    RawAudioBuffer, RawAudioInfo, ResizableRawAudioBuffer, TimestampedRawAudioBuffer,
+};
 use crate::audio_system::element::{
     AudioFilter, AudioSink, AudioSource, AudioSystemElementMessage,
 };
 
+use std::cell::Cell;
 use std::collections::VecDeque;
 use std::rc::Rc;
 use std::sync::Arc;
 
 use mueue::*;
 
+/// Shorter intentional gaps in the audio aren't filled with silence.
+const MIN_SILENCE_DURATION: ClockTime = ClockTime::from_millis(10);
+
 pub struct Synchronizer {
     send: MessageSender<AudioSystemElementMessage>,
     input: Option<MessageReceiver<TimestampedRawAudioBuffer>>,
@@ -25,10 +31,13 @@ pub struct Synchronizer {
     sys_clock: Arc<dyn Clock>,
     virtual_mic_clock: Option<Rc<dyn SlaveClock>>,
     virtual_mic_clock_update_timer: Timer,
+    noise_gate_closed: Option<Rc<Cell<bool>>>,
+    transmission_paused: Option<Rc<Cell<bool>>>,
 
     first_buf_arrival_ts: Option<ClockTime>,
     first_buf_start_ts: Option<ClockTime>,
     buffer_expected_ts: ClockTime,
+    stream_info: Option<RawAudioInfo>,
 
     queue: VecDeque<TimestampedRawAudioBuffer>,
 
@@ -45,10 +54,13 @@ impl Synchronizer {
             sys_clock,
             virtual_mic_clock: None,
             virtual_mic_clock_update_timer: Timer::new(OBSERVATIONS_INTERVAL),
+            noise_gate_closed: None,
+            transmission_paused: None,
 
             first_buf_arrival_ts: None,
             first_buf_start_ts: None,
             buffer_expected_ts: ClockTime::ZERO,
+            stream_info: None,
 
             queue: VecDeque::new(),
 
@@ -67,6 +79,26 @@ impl Synchronizer {
         self.virtual_mic_clock = None;
     }
 
+    /// The audio missing while the noise gate is closed is replaced with silence
+    /// instead of being treated as an underrun.
+    pub fn set_noise_gate_state(&mut self, noise_gate_closed: Option<Rc<Cell<bool>>>) {
+        self.noise_gate_closed = noise_gate_closed;
+    }
+
+    pub fn unset_noise_gate_state(&mut self) {
+        self.noise_gate_closed = None;
+    }
+
+    /// Likewise, the audio missing while the sender pauses the transmission,
+    /// e.g. with DTX, is replaced with silence.
+    pub fn set_transmission_state(&mut self, transmission_paused: Option<Rc<Cell<bool>>>) {
+        self.transmission_paused = transmission_paused;
+    }
+
+    pub fn unset_transmission_state(&mut self) {
+        self.transmission_paused = None;
+    }
+
     fn collect_audio_buffers(&mut self) {
         if let Some(input) = self.input.as_ref() {
             self.queue.extend(input.iter());
@@ -132,6 +164,7 @@ impl Synchronizer {
                 } */
 
                 let desired_no_samples = real_duration.to_no_samples(ts_buf.sample_rate());
+                self.stream_info = Some(ts_buf.info());
                 let buf = ResizableRawAudioBuffer::new(ts_buf.into_raw(), desired_no_samples);
 
                 if let Some(output) = self.output.as_ref() {
@@ -147,10 +180,53 @@ impl Synchronizer {
         }
     }
 
+    // The audio doesn't arrive at all while the noise gate is closed or the
+    // sender pauses the transmission, so the virtual microphone is kept fed
+    // with silence.
+    fn fill_intentional_silence(&mut self) {
+        let is_gate_closed = self.noise_gate_closed.as_deref().is_some_and(Cell::get);
+        let is_transmission_paused = self.transmission_paused.as_deref().is_some_and(Cell::get);
+        if !(is_gate_closed || is_transmission_paused) || !self.queue.is_empty() {
+            return;
+        }
+
+        let (Some(first_buf_arrival_ts), Some(first_buf_start_ts), Some(stream_info)) = (
+            self.first_buf_arrival_ts,
+            self.first_buf_start_ts,
+            self.stream_info,
+        ) else {
+            return;
+        };
+
+        let elapsed = self.sys_clock.get_time();
+        let current_ts = (elapsed + first_buf_start_ts).saturating_sub(first_buf_arrival_ts);
+        let silence_duration = current_ts.saturating_sub(self.buffer_expected_ts);
+        if silence_duration < MIN_SILENCE_DURATION {
+            return;
+        }
+
+        let no_samples = silence_duration.to_no_samples(stream_info.sample_rate);
+        let silence = vec![0.0; no_samples * usize::from(stream_info.channels)];
+        let raw = RawAudioBuffer::from_normalized_samples(
+            &silence,
+            stream_info.format,
+            stream_info.channels,
+            stream_info.sample_rate,
+        )
+        .with_channel_layout(stream_info.channel_layout);
+
+        if let Some(output) = self.output.as_ref() {
+            let _ = output.send(ResizableRawAudioBuffer::new(raw, no_samples));
+        }
+
+        self.buffer_expected_ts += ClockTime::from_no_samples(no_samples, stream_info.sample_rate);
+    }
+
     fn on_eos(&mut self) {
         self.first_buf_arrival_ts = None;
         self.first_buf_start_ts = None;
         self.buffer_expected_ts = ClockTime::ZERO;
+        self.stream_info = None;
     }
 }
 
@@ -167,6 +243,7 @@ impl Runnable for Synchronizer {
 
         self.collect_audio_buffers();
         self.process_audio_buffers();
+        self.fill_intentional_silence();
 
         Ok(())
     }
@@ -4,11 +4,13 @@ use crate::audio_system::audio::{RawAudioBuffer, RawAudioFormat};
 use crate::util::{ClockInfo, ClockTime};
 
 use std::cell::Cell;
+use std::rc::Rc;
 use std::sync::Arc;
 
 use mueue::*;
 
 const RAW_AUDIO_FORMAT: RawAudioFormat = RawAudioFormat::U8;
+const CHANNELS: u8 = 1;
 const SAMPLE_RATE: u32 = 8000;
 
 struct FakeSystemClock(Cell<ClockTime>);
@@ -48,6 +50,7 @@ fn test_buffer_early_arrival() {
         RawAudioBuffer::new(
             vec![42; SAMPLE_RATE as usize],
             RAW_AUDIO_FORMAT,
+            CHANNELS,
             SAMPLE_RATE,
         ),
         Some(ClockTime::ZERO),
@@ -60,6 +63,7 @@ fn test_buffer_early_arrival() {
         RawAudioBuffer::new(
             vec![42; SAMPLE_RATE as usize],
             RAW_AUDIO_FORMAT,
+            CHANNELS,
             SAMPLE_RATE,
         ),
         Some(ClockTime::from_secs(1)),
@@ -87,6 +91,7 @@ fn test_buffer_arrival_in_time() {
         RawAudioBuffer::new(
             vec![42; SAMPLE_RATE as usize],
             RAW_AUDIO_FORMAT,
+            CHANNELS,
             SAMPLE_RATE,
         ),
         Some(ClockTime::ZERO),
@@ -99,6 +104,7 @@ fn test_buffer_arrival_in_time() {
         RawAudioBuffer::new(
             vec![42; SAMPLE_RATE as usize],
             RAW_AUDIO_FORMAT,
+            CHANNELS,
             SAMPLE_RATE,
         ),
         Some(ClockTime::from_secs(1)),
@@ -124,6 +130,7 @@ fn test_buffer_late_arrival() {
         RawAudioBuffer::new(
             vec![42; SAMPLE_RATE as usize],
             RAW_AUDIO_FORMAT,
+            CHANNELS,
             SAMPLE_RATE,
         ),
         SAMPLE_RATE as usize - 250 * SAMPLE_RATE as usize / ClockTime::MILLIS_IN_SEC as usize,
@@ -133,6 +140,7 @@ fn test_buffer_late_arrival() {
         RawAudioBuffer::new(
             vec![42; SAMPLE_RATE as usize],
             RAW_AUDIO_FORMAT,
+            CHANNELS,
             SAMPLE_RATE,
         ),
         Some(ClockTime::ZERO),
@@ -145,6 +153,7 @@ fn test_buffer_late_arrival() {
         RawAudioBuffer::new(
             vec![42; SAMPLE_RATE as usize],
             RAW_AUDIO_FORMAT,
+            CHANNELS,
             SAMPLE_RATE,
         ),
         Some(ClockTime::from_secs(1)),
@@ -168,6 +177,7 @@ fn test_non_monotonous_timestamp() {
         RawAudioBuffer::new(
             vec![42; SAMPLE_RATE as usize / 2],
             RAW_AUDIO_FORMAT,
+            CHANNELS,
             SAMPLE_RATE,
         ),
         SAMPLE_RATE as usize - 500 * SAMPLE_RATE as usize / ClockTime::MILLIS_IN_SEC as usize,
@@ -177,6 +187,7 @@ fn test_non_monotonous_timestamp() {
         RawAudioBuffer::new(
             vec![42; SAMPLE_RATE as usize],
             RAW_AUDIO_FORMAT,
+            CHANNELS,
             SAMPLE_RATE,
         ),
         Some(ClockTime::ZERO),
@@ -189,6 +200,7 @@ fn test_non_monotonous_timestamp() {
         RawAudioBuffer::new(
             vec![42; SAMPLE_RATE as usize],
             RAW_AUDIO_FORMAT,
+            CHANNELS,
             SAMPLE_RATE,
         ),
         Some(ClockTime::from_millis(500)),
@@ -202,3 +214,47 @@ fn test_non_monotonous_timestamp() {
     let _ = sync.update();
     assert_eq!(out_recv.recv(), Some(reference_buffer));
 }
+
+#[test]
+fn test_fill_gated_silence() {
+    let (send, _) = unidirectional_queue();
+    let sys_clock = Arc::new(FakeSystemClock::new());
+    let mut sync = Synchronizer::new(send, sys_clock.clone());
+    let gate_closed = Rc::new(Cell::new(false));
+    sync.set_noise_gate_state(Some(gate_closed.clone()));
+
+    let in_send = sync.create_input();
+    let out_recv = sync.create_output();
+
+    let buf = TimestampedRawAudioBuffer::new(
+        RawAudioBuffer::new(
+            vec![42; SAMPLE_RATE as usize / 10],
+            RAW_AUDIO_FORMAT,
+            CHANNELS,
+            SAMPLE_RATE,
+        ),
+        Some(ClockTime::ZERO),
+    );
+    let _ = in_send.send(buf);
+    let _ = sync.update();
+    assert!(out_recv.recv().is_some());
+
+    // The missing audio is an underrun while the gate is open.
+    sys_clock.move_forward(ClockTime::from_millis(300));
+    let _ = sync.update();
+    assert!(out_recv.recv().is_none());
+
+    gate_closed.set(true);
+    let _ = sync.update();
+    let silence = out_recv.recv().unwrap();
+    assert_eq!(silence.no_samples(), SAMPLE_RATE as usize / 5);
+    assert!(silence
+        .into_raw()
+        .to_normalized_samples()
+        .iter()
+        .all(|sample| *sample == 0.0));
+
+    // The gap is filled only once.
+    let _ = sync.update();
+    assert!(out_recv.recv().is_none());
+}
@@ -0,0 +1,106 @@
+#[cfg(test)]
+mod tests;
+
+use crate::error;
+use crate::util::{Element, Runnable};
+
+use crate::audio_system::element::{
+    AudioFilter, AudioSink, AudioSource, AudioSystemElementMessage,
+};
+
+use mueue::*;
+
+/// Passes the buffers to the output and duplicates them to the branches. The
+/// branch is removed when its sink is gone.
+pub struct AudioTee<T: Message + Clone> {
+    send: MessageSender<AudioSystemElementMessage>,
+    input: Option<MessageReceiver<T>>,
+    output: Option<MessageSender<T>>,
+
+    branches: Vec<MessageSender<T>>,
+}
+
+impl<T: Message + Clone> AudioTee<T> {
+    pub fn new(send: MessageSender<AudioSystemElementMessage>) -> Self {
+        Self {
+            send,
+            input: None,
+            output: None,
+
+            branches: Vec::new(),
+        }
+    }
+
+    pub fn add_branch(&mut self, sink: &mut dyn AudioSink<T>) {
+        let (output, input) = unidirectional_queue();
+
+        self.branches.push(output);
+        sink.set_input(input);
+    }
+
+    pub fn no_branches(&self) -> usize {
+        self.branches.len()
+    }
+}
+
+impl<T: Message + Clone> Runnable for AudioTee<T> {
+    fn update(&mut self) -> error::Result<()> {
+        let Some(input) = self.input.clone() else {
+            return Ok(());
+        };
+
+        for buf in input.iter() {
+            self.branches.retain(|branch| {
+                !matches!(branch.send(buf.clone()), Err(MessagingError::QueueClosed))
+            });
+
+            if let Some(output) = self.output.as_ref() {
+                let _ = output.send(buf);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<T: Message + Clone> Element for AudioTee<T> {
+    type Message = AudioSystemElementMessage;
+
+    fn sender(&self) -> MessageSender<Self::Message> {
+        self.send.clone()
+    }
+
+    fn connect(&mut self, send: MessageSender<Self::Message>) {
+        self.send = send;
+    }
+}
+
+impl<T: Message + Clone> AudioSink<T> for AudioTee<T> {
+    fn input(&self) -> Option<MessageReceiver<T>> {
+        self.input.clone()
+    }
+
+    fn set_input(&mut self, input: MessageReceiver<T>) {
+        self.input = Some(input);
+    }
+
+    fn unset_input(&mut self) {
+        self.input = None;
+    }
+}
+
+impl<T: Message + Clone> AudioSource<T> for AudioTee<T> {
+    fn output(&self) -> Option<MessageSender<T>> {
+        self.output.clone()
+    }
+
+    fn set_output(&mut self, output: MessageSender<T>) {
+        self.output = Some(output);
+    }
+
+    fn unset_output(&mut self) {
+        self.output = None;
+    }
+}
+
+impl<T: Message + Clone> AudioFilter<T, T> for AudioTee<T> {}
@@ -0,0 +1,59 @@
+use super::*;
+
+use crate::audio_system::audio::{RawAudioBuffer, RawAudioFormat};
+
+fn buffer(sample: u8) -> RawAudioBuffer {
+    RawAudioBuffer::new(vec![sample], RawAudioFormat::U8, 1, 8000)
+}
+
+fn samples(recv: &MessageReceiver<RawAudioBuffer>) -> Vec<u8> {
+    recv.iter().map(|audio| audio.as_slice()[0]).collect()
+}
+
+#[test]
+fn test_duplicate_to_branches() {
+    let (send, _) = unidirectional_queue();
+    let mut tee = AudioTee::new(send.clone());
+    let in_send = tee.create_input();
+    let out_recv = tee.create_output();
+
+    let mut branches = [AudioTee::new(send.clone()), AudioTee::new(send)];
+    let branch_recvs = branches
+        .iter_mut()
+        .map(|branch| {
+            tee.add_branch(branch);
+            branch.create_output()
+        })
+        .collect::<Vec<_>>();
+
+    let _ = in_send.send(buffer(1));
+    let _ = in_send.send(buffer(2));
+    let _ = tee.update();
+    branches.iter_mut().for_each(|branch| {
+        let _ = branch.update();
+    });
+
+    assert_eq!(samples(&out_recv), vec![1, 2]);
+    for branch_recv in branch_recvs.iter() {
+        assert_eq!(samples(branch_recv), vec![1, 2]);
+    }
+}
+
+#[test]
+fn test_remove_closed_branch() {
+    let (send, _) = unidirectional_queue();
+    let mut tee = AudioTee::new(send.clone());
+    let in_send = tee.create_input();
+    let out_recv = tee.create_output();
+
+    let mut branch = AudioTee::new(send);
+    tee.add_branch(&mut branch);
+    assert_eq!(tee.no_branches(), 1);
+    drop(branch);
+
+    let _ = in_send.send(buffer(1));
+    let _ = tee.update();
+
+    assert_eq!(tee.no_branches(), 0);
+    assert_eq!(samples(&out_recv), vec![1]);
+}
@@ -0,0 +1,268 @@
+use super::*;
+
+use crate::audio_system::audio::{AudioCodec, RawAudioFormat};
+use crate::test_util::{encoded_audio, temp_path};
+use crate::util::{Clock, ClockTime, ManualClock};
+
+use loss_concealer::LossConcealer;
+
+use resampler::SincResampler;
+use resizer::ResizeMode;
+use virtual_microphone::wav::WavVirtualMicrophone;
+
+use std::iter;
+use std::sync::Arc;
+
+use mueue::unidirectional_queue;
+
+const SAMPLE_RATE: u32 = 8000;
+const BUFFER_DURATION: ClockTime = ClockTime::from_millis(20);
+const NO_BUFFERS: u64 = 5;
+const SAMPLE: i16 = 1000;
+
+fn pcm_audio(no_buffer: u64) -> EncodedAudioBuffer {
+    pcm_audio_with_sample(no_buffer, SAMPLE)
+}
+
+fn pcm_audio_with_sample(no_buffer: u64, sample: i16) -> EncodedAudioBuffer {
+    let no_samples = BUFFER_DURATION.to_no_samples(SAMPLE_RATE);
+
+    let mut audio = encoded_audio(
+        AudioCodec::PcmS16LE,
+        1,
+        sample.to_le_bytes().repeat(no_samples),
+    );
+    audio.header.sample_rate = SAMPLE_RATE;
+    audio.sequence_number = Some(no_buffer as u32);
+    audio.start_ts = Some(BUFFER_DURATION * no_buffer);
+
+    audio
+}
+
+fn new_pipeline(sys_clock: Arc<dyn Clock>, mic: Box<dyn VirtualMicrophone>) -> AudioPipeline {
+    let (send, _) = unidirectional_queue();
+    let mut pipeline = AudioPipeline::new();
+    pipeline.set_audio_demuxer(AudioDemuxer::new(send.clone()));
+    pipeline.set_received_audio_tee(AudioTee::new(send.clone()));
+    pipeline.set_encoded_filter_chain(AudioFilterChain::new(send.clone()));
+    pipeline.set_jitter_buffer(JitterBuffer::new(send.clone(), sys_clock.clone()));
+    pipeline.set_pcm_decoder(PcmDecoder::new(send.clone()));
+    let mut decoded_filters = AudioFilterChain::new(send.clone());
+    decoded_filters.push(Box::new(LossConcealer::new(send.clone())));
+    decoded_filters.push(Box::new(NoiseGate::new(send.clone())));
+    pipeline.set_decoded_filter_chain(decoded_filters);
+    pipeline.set_synchronizer(Synchronizer::new(send.clone(), sys_clock));
+    pipeline.set_resizer(AudioResizer::new(send.clone()));
+    pipeline.set_float_converter(AudioConverter::new(send.clone()));
+    pipeline.set_resampler(AudioResampler::new(send.clone()));
+    pipeline.set_raw_filter_chain(AudioFilterChain::new(send.clone()));
+    pipeline.set_converter(AudioConverter::new(send.clone()));
+    pipeline.set_played_audio_tee(AudioTee::new(send));
+    pipeline.set_virtual_microphone(mic);
+
+    pipeline
+}
+
+fn read_wav_samples(path: &std::path::Path) -> Vec<i16> {
+    let file = std::fs::read(path).unwrap();
+    let _ = std::fs::remove_file(path);
+
+    file[44..]
+        .chunks_exact(2)
+        .map(|sample| i16::from_le_bytes([sample[0], sample[1]]))
+        .collect()
+}
+
+// Plays the PCM through the whole pipeline into a WAV file, so it doesn't need
+// a sound server.
+#[test]
+fn test_play_pcm_into_wav_file() {
+    const STEP: ClockTime = ClockTime::from_millis(1);
+
+    let path = temp_path("test_play_pcm_into_wav_file.wav");
+    let (send, _) = unidirectional_queue();
+    let sys_clock = Arc::new(ManualClock::new());
+
+    let mic = WavVirtualMicrophone::new(send, &path, Some(RawAudioFormat::S16LE));
+    let mic_clock = mic.provide_clock().unwrap();
+    let mut pipeline = new_pipeline(sys_clock.clone(), Box::new(mic));
+
+    pipeline.on_start();
+    for no_buffer in 0..NO_BUFFERS {
+        pipeline
+            .audio_demuxer_mut()
+            .unwrap()
+            .push(pcm_audio(no_buffer).to_muxed().unwrap());
+    }
+
+    let no_steps = (BUFFER_DURATION * NO_BUFFERS).as_nanos() * 2 / STEP.as_nanos();
+    for _ in 0..no_steps {
+        let _ = pipeline.update();
+        sys_clock.advance(STEP);
+    }
+    pipeline.on_stop();
+
+    let samples = read_wav_samples(&path);
+
+    let expected_no_samples = (BUFFER_DURATION * NO_BUFFERS).to_no_samples(SAMPLE_RATE);
+    assert_eq!(samples.len(), expected_no_samples);
+    // The chain has no filters, so the audio isn't converted to float.
+    assert!(samples.iter().all(|sample| *sample == SAMPLE));
+
+    assert_eq!(
+        mic_clock.get_time(),
+        ClockTime::from_no_samples(expected_no_samples, SAMPLE_RATE)
+    );
+}
+
+// The packets are played in order and in full, since the manual clock lets
+// every one of them reach the synchronizer before it's due.
+#[test]
+fn test_reorder_pcm() {
+    const STEP: ClockTime = ClockTime::from_millis(1);
+    const NO_PACKETS: u64 = 6;
+
+    let path = temp_path("test_reorder_pcm.wav");
+    let (send, _) = unidirectional_queue();
+    let sys_clock = Arc::new(ManualClock::new());
+
+    let mic = WavVirtualMicrophone::new(send, &path, Some(RawAudioFormat::S16LE));
+    let mut pipeline = new_pipeline(sys_clock.clone(), Box::new(mic));
+    pipeline
+        .resizer_mut()
+        .unwrap()
+        .set_mode(ResizeMode::Interpolate);
+
+    pipeline.on_start();
+    for no_packet in [1, 0, 3, 2, 5, 4] {
+        let sample = SAMPLE * (no_packet as i16 + 1);
+        pipeline
+            .audio_demuxer_mut()
+            .unwrap()
+            .push(pcm_audio_with_sample(no_packet, sample).to_muxed().unwrap());
+    }
+
+    let no_steps = (BUFFER_DURATION * NO_PACKETS).as_nanos() * 2 / STEP.as_nanos();
+    for _ in 0..no_steps {
+        let _ = pipeline.update();
+        sys_clock.advance(STEP);
+    }
+    pipeline.on_stop();
+
+    let samples = read_wav_samples(&path);
+    let no_samples = BUFFER_DURATION.to_no_samples(SAMPLE_RATE);
+    let expected_samples = (0..NO_PACKETS as i16)
+        .flat_map(|no_packet| iter::repeat_n(SAMPLE * (no_packet + 1), no_samples))
+        .collect::<Vec<_>>();
+    assert_eq!(samples, expected_samples);
+}
+
+// The microphone plays at a fixed sample rate, so the audio is resampled to it
+// on the way.
+#[test]
+fn test_resample_to_microphone_sample_rate() {
+    const STEP: ClockTime = ClockTime::from_millis(1);
+    const MIC_SAMPLE_RATE: u32 = 16000;
+
+    let path = temp_path("test_resample_to_microphone_sample_rate.wav");
+    let (send, _) = unidirectional_queue();
+    let sys_clock = Arc::new(ManualClock::new());
+
+    let mic = WavVirtualMicrophone::new(send, &path, Some(RawAudioFormat::S16LE))
+        .with_sample_rate(Some(MIC_SAMPLE_RATE));
+    let mut pipeline = new_pipeline(sys_clock.clone(), Box::new(mic));
+
+    pipeline.on_start();
+    for no_buffer in 0..NO_BUFFERS {
+        pipeline
+            .audio_demuxer_mut()
+            .unwrap()
+            .push(pcm_audio(no_buffer).to_muxed().unwrap());
+    }
+
+    let no_steps = (BUFFER_DURATION * NO_BUFFERS).as_nanos() * 2 / STEP.as_nanos();
+    for _ in 0..no_steps {
+        let _ = pipeline.update();
+        sys_clock.advance(STEP);
+    }
+    pipeline.on_stop();
+
+    let file = std::fs::read(&path).unwrap();
+    let sample_rate = u32::from_le_bytes(file[24..28].try_into().unwrap());
+    assert_eq!(sample_rate, MIC_SAMPLE_RATE);
+
+    let samples = read_wav_samples(&path);
+    // The resampler holds back the audio which it needs to interpolate the
+    // last samples.
+    let expected_no_samples = (BUFFER_DURATION * NO_BUFFERS).to_no_samples(MIC_SAMPLE_RATE);
+    let latency = 2 * SincResampler::new(1, 2.0).latency();
+    assert!(samples.len() <= expected_no_samples);
+    assert!(samples.len() + latency >= expected_no_samples);
+    assert!(samples[latency..]
+        .iter()
+        .all(|sample| sample.abs_diff(SAMPLE) <= SAMPLE as u16 / 50));
+}
+
+// The sender stops sending the audio after it marks it as DTX, so the gap is
+// filled with silence instead of being an underrun.
+#[test]
+fn test_fill_dtx_gap_with_silence() {
+    const STEP: ClockTime = ClockTime::from_millis(1);
+    const DTX_PACKET: u64 = 2;
+    const RESUMED_PACKETS: [u64; 2] = [10, 11];
+
+    let path = temp_path("test_fill_dtx_gap_with_silence.wav");
+    let (send, _) = unidirectional_queue();
+    let sys_clock = Arc::new(ManualClock::new());
+
+    let mic = WavVirtualMicrophone::new(send, &path, Some(RawAudioFormat::S16LE));
+    let mut pipeline = new_pipeline(sys_clock.clone(), Box::new(mic));
+
+    let mut packets = (0..=DTX_PACKET)
+        .chain(RESUMED_PACKETS)
+        .map(|no_packet| {
+            let mut audio = pcm_audio(no_packet);
+            audio.flags.dtx = no_packet == DTX_PACKET;
+            audio
+        })
+        .peekable();
+
+    pipeline.on_start();
+    let end = BUFFER_DURATION * (RESUMED_PACKETS[1] + 2);
+    while sys_clock.get_time() < end {
+        // The packets are sent in real time, so that the synchronizer runs out
+        // of the audio during the gap.
+        while let Some(audio) =
+            packets.next_if(|audio| audio.start_ts <= Some(sys_clock.get_time()))
+        {
+            pipeline
+                .audio_demuxer_mut()
+                .unwrap()
+                .push(audio.to_muxed().unwrap());
+        }
+
+        let _ = pipeline.update();
+        sys_clock.advance(STEP);
+    }
+    pipeline.on_stop();
+
+    let samples = read_wav_samples(&path);
+    let no_samples = BUFFER_DURATION.to_no_samples(SAMPLE_RATE);
+    let no_sent_samples = no_samples * (DTX_PACKET as usize + 1);
+    let no_resumed_samples = no_samples * RESUMED_PACKETS.len();
+    assert!(samples[..no_sent_samples]
+        .iter()
+        .all(|sample| *sample == SAMPLE));
+    assert!(samples[samples.len() - no_resumed_samples..]
+        .iter()
+        .all(|sample| *sample == SAMPLE));
+
+    let silence = &samples[no_sent_samples..samples.len() - no_resumed_samples];
+    let gap = BUFFER_DURATION * (RESUMED_PACKETS[0] - DTX_PACKET - 1);
+    assert!(silence.iter().all(|sample| *sample == 0));
+    // The silence is filled in chunks, so the end of the gap may stay unfilled
+    // until the audio resumes.
+    let max_unfilled = ClockTime::from_millis(10).to_no_samples(SAMPLE_RATE);
+    assert!(silence.len() <= gap.to_no_samples(SAMPLE_RATE));
+    assert!(silence.len() + max_unfilled >= gap.to_no_samples(SAMPLE_RATE));
+}
@@ -0,0 +1,195 @@
+#[cfg(test)]
+mod tests;
+
+use crate::util::ClockTime;
+
+use std::f64::consts::PI;
+
+const SEGMENT_DURATION: ClockTime = ClockTime::from_millis(20);
+/// How far a segment may be shifted from its nominal position to match the
+/// previous one.
+const SEARCH_DURATION: ClockTime = ClockTime::from_millis(7);
+
+/// The offsets are searched coarsely first and then refined around the best one.
+const COARSE_SEARCH_STEP: usize = 4;
+const CORRELATION_STEP: usize = 2;
+
+/// Changes the duration of interleaved audio without changing its pitch by
+/// overlapping the similar segments of the input (WSOLA).
+#[derive(Debug, Clone)]
+pub struct TimeStretcher {
+    channels: usize,
+    segment_len: usize,
+    hop: usize,
+    tolerance: usize,
+    window: Vec<f64>,
+
+    input: Vec<f64>,
+    // The position of the next segment if there were no need to match the previous one.
+    nominal_pos: f64,
+    // The position of the input which naturally continues the previous segment.
+    natural_pos: Option<usize>,
+    overlap: Vec<f64>,
+}
+
+impl TimeStretcher {
+    pub fn new(channels: u8, sample_rate: u32) -> Self {
+        let channels = usize::from(channels).max(1);
+        let hop = (SEGMENT_DURATION.to_no_samples(sample_rate) / 2).max(1);
+        let segment_len = hop * 2;
+
+        // The periodic Hann window sums to one when the segments overlap by half.
+        let window = (0..segment_len)
+            .map(|n| 0.5 - 0.5 * (2.0 * PI * n as f64 / segment_len as f64).cos())
+            .collect();
+
+        Self {
+            channels,
+            segment_len,
+            hop,
+            tolerance: SEARCH_DURATION.to_no_samples(sample_rate),
+            window,
+
+            input: Vec::new(),
+            nominal_pos: 0.0,
+            natural_pos: None,
+            overlap: vec![0.0; hop * channels],
+        }
+    }
+
+    /// The number of input frames which are held back to compute the next output.
+    pub fn latency(&self) -> usize {
+        self.segment_len + self.tolerance
+    }
+
+    pub fn reset(&mut self) {
+        self.input.clear();
+        self.nominal_pos = 0.0;
+        self.natural_pos = None;
+        self.overlap.fill(0.0);
+    }
+
+    /// Stretches interleaved samples, continuing the previously processed ones. The
+    /// output is `speed` times shorter than the input on average.
+    pub fn process(&mut self, samples: &[f64], speed: f64) -> Vec<f64> {
+        assert!(
+            speed.is_finite() && speed > 0.0,
+            "The speed of the audio should be positive"
+        );
+
+        let channels = self.channels;
+        self.input.extend_from_slice(samples);
+
+        let mut output = Vec::new();
+        loop {
+            let no_frames = self.input.len() / channels;
+
+            let nominal_pos = self.nominal_pos.round() as usize;
+            let natural_pos = self.natural_pos;
+
+            let needed_frames = (nominal_pos + self.tolerance + self.segment_len)
+                .max(natural_pos.map_or(0, |natural_pos| natural_pos + self.hop));
+            if needed_frames > no_frames {
+                break;
+            }
+
+            let pos = match natural_pos {
+                Some(natural_pos) => self.find_similar_segment(nominal_pos, natural_pos),
+                None => nominal_pos,
+            };
+            self.overlap_add(pos, &mut output);
+
+            self.natural_pos = Some(pos + self.hop);
+            self.nominal_pos += self.hop as f64 * speed;
+
+            self.drop_consumed_input();
+        }
+
+        output
+    }
+
+    // Finds the segment near the nominal position, which continues the previous
+    // segment the most naturally.
+    fn find_similar_segment(&self, nominal_pos: usize, natural_pos: usize) -> usize {
+        let first_pos = nominal_pos.saturating_sub(self.tolerance);
+        let last_pos = nominal_pos + self.tolerance;
+
+        let coarse_pos = self.most_similar_segment(
+            (first_pos..=last_pos).step_by(COARSE_SEARCH_STEP),
+            natural_pos,
+        );
+
+        let refined_first_pos = coarse_pos
+            .saturating_sub(COARSE_SEARCH_STEP - 1)
+            .max(first_pos);
+        let refined_last_pos = (coarse_pos + COARSE_SEARCH_STEP - 1).min(last_pos);
+        self.most_similar_segment(refined_first_pos..=refined_last_pos, natural_pos)
+    }
+
+    fn most_similar_segment<I>(&self, positions: I, natural_pos: usize) -> usize
+    where
+        I: Iterator<Item = usize>,
+    {
+        positions
+            .map(|pos| (pos, self.similarity(pos, natural_pos)))
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(pos, _)| pos)
+            .expect("No segments were searched")
+    }
+
+    fn similarity(&self, pos: usize, natural_pos: usize) -> f64 {
+        let mut cross = 0.0;
+        let mut energy = 0.0;
+        for offset in (0..self.hop).step_by(CORRELATION_STEP) {
+            let candidate = self.mono_sample(pos + offset);
+            let natural = self.mono_sample(natural_pos + offset);
+
+            cross += candidate * natural;
+            energy += candidate * candidate;
+        }
+
+        if energy == 0.0 {
+            return 0.0;
+        }
+
+        cross / energy.sqrt()
+    }
+
+    fn mono_sample(&self, frame: usize) -> f64 {
+        let frame = &self.input[frame * self.channels..(frame + 1) * self.channels];
+
+        frame.iter().sum::<f64>() / self.channels as f64
+    }
+
+    fn overlap_add(&mut self, pos: usize, output: &mut Vec<f64>) {
+        let channels = self.channels;
+
+        for frame in 0..self.hop {
+            for channel in 0..channels {
+                let sample = self.input[(pos + frame) * channels + channel] * self.window[frame];
+                output.push(self.overlap[frame * channels + channel] + sample);
+            }
+        }
+
+        for frame in 0..self.hop {
+            for channel in 0..channels {
+                let input_frame = pos + self.hop + frame;
+                self.overlap[frame * channels + channel] =
+                    self.input[input_frame * channels + channel] * self.window[self.hop + frame];
+            }
+        }
+    }
+
+    fn drop_consumed_input(&mut self) {
+        let next_first_pos = (self.nominal_pos.round() as usize).saturating_sub(self.tolerance);
+        let consumed_frames = self.natural_pos.map_or(next_first_pos, |natural_pos| {
+            natural_pos.min(next_first_pos)
+        });
+
+        self.input.drain(..consumed_frames * self.channels);
+        self.nominal_pos -= consumed_frames as f64;
+        self.natural_pos = self
+            .natural_pos
+            .map(|natural_pos| natural_pos - consumed_frames);
+    }
+}
@@ -0,0 +1,86 @@
+use super::*;
+
+const SAMPLE_RATE: u32 = 48000;
+const FREQUENCY: f64 = 440.0;
+const BUFFER_LEN: usize = 480;
+
+fn tone(no_frames: usize, channels: usize) -> Vec<f64> {
+    (0..no_frames)
+        .flat_map(|n| {
+            let sample = 0.5 * (2.0 * PI * FREQUENCY * n as f64 / f64::from(SAMPLE_RATE)).sin();
+            std::iter::repeat_n(sample, channels)
+        })
+        .collect()
+}
+
+// Measures the frequency by the distance between the first and the last rising
+// zero crossings of the first channel.
+fn fundamental_frequency(samples: &[f64], channels: usize) -> f64 {
+    let mono: Vec<f64> = samples.iter().step_by(channels).copied().collect();
+    let crossings: Vec<f64> = mono
+        .windows(2)
+        .enumerate()
+        .filter(|(_, pair)| pair[0] < 0.0 && pair[1] >= 0.0)
+        .map(|(n, pair)| n as f64 + pair[0] / (pair[0] - pair[1]))
+        .collect();
+
+    let first = crossings.first().unwrap();
+    let last = crossings.last().unwrap();
+
+    (crossings.len() - 1) as f64 * f64::from(SAMPLE_RATE) / (last - first)
+}
+
+fn stretch(speed: f64, channels: usize) -> (Vec<f64>, usize) {
+    let mut time_stretcher = TimeStretcher::new(channels as u8, SAMPLE_RATE);
+
+    let input = tone(BUFFER_LEN * 200, channels);
+    let output = input
+        .chunks(BUFFER_LEN * channels)
+        .flat_map(|chunk| time_stretcher.process(chunk, speed))
+        .collect();
+
+    (output, time_stretcher.latency())
+}
+
+#[test]
+fn test_keep_pitch() {
+    let input = tone(BUFFER_LEN * 200, 1);
+    assert!((fundamental_frequency(&input, 1) - FREQUENCY).abs() < 0.1);
+
+    for speed in [0.8, 0.95, 1.0, 1.05, 1.25] {
+        let (output, latency) = stretch(speed, 1);
+
+        let frequency = fundamental_frequency(&output[latency..], 1);
+        assert!(
+            (frequency - FREQUENCY).abs() < FREQUENCY * 0.01,
+            "The frequency changed to {frequency} Hz at the speed {speed}"
+        );
+    }
+}
+
+#[test]
+fn test_change_duration() {
+    for speed in [0.8, 1.25] {
+        let (output, latency) = stretch(speed, 2);
+
+        let expected_no_frames = (BUFFER_LEN * 200) as f64 / speed;
+        let no_frames = (output.len() / 2) as f64;
+        assert!((no_frames - expected_no_frames).abs() < latency as f64 * 2.0);
+    }
+}
+
+#[test]
+fn test_keep_channels_apart() {
+    let mut time_stretcher = TimeStretcher::new(2, SAMPLE_RATE);
+
+    let input: Vec<f64> = tone(BUFFER_LEN * 20, 1)
+        .into_iter()
+        .flat_map(|sample| [sample, -sample])
+        .collect();
+    let output = time_stretcher.process(&input, 1.1);
+
+    assert!(!output.is_empty());
+    for frame in output.chunks_exact(2) {
+        assert!((frame[0] + frame[1]).abs() < 1e-12);
+    }
+}
@@ -1,5 +1,9 @@
+pub mod null;
+pub mod wav;
+
 use crate::audio_system::audio::*;
 use crate::audio_system::element::*;
+use crate::error;
 use crate::util::RunnableStateMachine;
 use crate::util::SlaveClock;
 
@@ -15,9 +19,50 @@ pub struct VirtualMicrophoneInfo {
 pub trait VirtualMicrophone: AudioSink<RawAudioBuffer> {
     fn info(&self) -> VirtualMicrophoneInfo;
 
+    /// The sample format which the virtual microphone negotiated with the sound
+    /// system. The audio is converted to it before reaching the microphone.
+    fn format(&self) -> Option<RawAudioFormat> {
+        None
+    }
+
+    /// The sample rate which the virtual microphone plays at. The audio is
+    /// resampled to it before reaching the microphone.
+    fn sample_rate(&self) -> Option<u32> {
+        None
+    }
+
     fn provide_clock(&self) -> Option<Rc<dyn SlaveClock>> {
         None
     }
+
+    /// The linear volume which the sound system applies to the microphone.
+    fn volume(&self) -> Option<f64> {
+        None
+    }
+
+    fn set_volume(&mut self, _volume: f64) -> error::Result<()> {
+        Ok(())
+    }
+
+    fn muted(&self) -> Option<bool> {
+        None
+    }
+
+    fn set_muted(&mut self, _muted: bool) -> error::Result<()> {
+        Ok(())
+    }
+
+    /// Whether any application records from the microphone.
+    fn in_use(&self) -> Option<bool> {
+        None
+    }
+
+    /// Makes the microphone report the changes of its volume and of whether
+    /// it's in use with the `VirtualMicrophone*Changed` messages, starting with
+    /// the current state.
+    fn subscribe(&mut self) -> error::Result<()> {
+        Ok(())
+    }
 }
 
 crate::trait_alias!(pub VirtualMicrophoneBuilder:
@@ -0,0 +1,112 @@
+#[cfg(test)]
+mod tests;
+
+use crate::error;
+use crate::util::{Element, ElementBuilder, Runnable, SampleClock, SlaveClock, SlavedClock};
+
+use crate::audio_system::audio::RawAudioBuffer;
+use crate::audio_system::element::{AudioSink, AudioSystemElementMessage};
+
+use super::{VirtualMicrophone, VirtualMicrophoneInfo};
+
+use std::rc::Rc;
+
+use mueue::*;
+
+/// Discards the audio. The clock still advances as if the audio was played.
+pub struct NullVirtualMicrophone {
+    send: MessageSender<AudioSystemElementMessage>,
+    input: Option<MessageReceiver<RawAudioBuffer>>,
+
+    clock: SampleClock,
+}
+
+impl NullVirtualMicrophone {
+    pub fn new(send: MessageSender<AudioSystemElementMessage>) -> Self {
+        Self {
+            send,
+            input: None,
+
+            clock: SampleClock::new(),
+        }
+    }
+}
+
+impl Runnable for NullVirtualMicrophone {
+    fn update(&mut self) -> error::Result<()> {
+        let Some(input) = self.input.as_ref() else {
+            return Ok(());
+        };
+
+        for audio in input.iter() {
+            if audio.sample_rate() != 0 {
+                self.clock.advance(audio.duration());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Element for NullVirtualMicrophone {
+    type Message = AudioSystemElementMessage;
+
+    fn sender(&self) -> MessageSender<Self::Message> {
+        self.send.clone()
+    }
+
+    fn connect(&mut self, send: MessageSender<Self::Message>) {
+        self.send = send;
+    }
+}
+
+impl AudioSink<RawAudioBuffer> for NullVirtualMicrophone {
+    fn input(&self) -> Option<MessageReceiver<RawAudioBuffer>> {
+        self.input.clone()
+    }
+
+    fn set_input(&mut self, input: MessageReceiver<RawAudioBuffer>) {
+        self.input = Some(input);
+    }
+
+    fn unset_input(&mut self) {
+        self.input = None;
+    }
+}
+
+impl VirtualMicrophone for NullVirtualMicrophone {
+    fn info(&self) -> VirtualMicrophoneInfo {
+        VirtualMicrophoneInfo {
+            name: "Null Virtual Microphone".to_string(),
+        }
+    }
+
+    fn provide_clock(&self) -> Option<Rc<dyn SlaveClock>> {
+        Some(Rc::new(SlavedClock::new(self.clock.clone())))
+    }
+}
+
+#[derive(Default)]
+pub struct NullVirtualMicrophoneBuilder {
+    send: Option<MessageSender<AudioSystemElementMessage>>,
+}
+
+impl NullVirtualMicrophoneBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl ElementBuilder for NullVirtualMicrophoneBuilder {
+    type Element = dyn VirtualMicrophone;
+
+    fn set_sender(&mut self, send: MessageSender<AudioSystemElementMessage>) {
+        self.send = Some(send);
+    }
+
+    fn build(self: Box<Self>) -> error::Result<Box<Self::Element>> {
+        let send = self.send.expect("A sender wasn't provided");
+
+        Ok(Box::new(NullVirtualMicrophone::new(send)))
+    }
+}
@@ -0,0 +1,29 @@
+use super::*;
+
+use crate::audio_system::audio::RawAudioFormat;
+use crate::util::ClockTime;
+
+#[test]
+fn test_clock_counts_consumed_audio() {
+    let (send, _) = unidirectional_queue();
+    let mut mic = NullVirtualMicrophone::new(send);
+    let in_send = mic.create_input();
+    let clock = mic.provide_clock().unwrap();
+
+    let _ = in_send.send(RawAudioBuffer::new(
+        vec![0; 480 * 2],
+        RawAudioFormat::S16LE,
+        1,
+        48000,
+    ));
+    let _ = in_send.send(RawAudioBuffer::new(
+        vec![0; 441 * 8],
+        RawAudioFormat::F32LE,
+        2,
+        44100,
+    ));
+    assert_eq!(clock.get_time(), ClockTime::ZERO);
+
+    let _ = mic.update();
+    assert_eq!(clock.get_time(), ClockTime::from_millis(20));
+}
@@ -0,0 +1,193 @@
+#[cfg(test)]
+mod tests;
+
+use crate::error;
+use crate::util::{Element, ElementBuilder, Runnable, SampleClock, SlaveClock, SlavedClock};
+
+use crate::audio_system::audio::{RawAudioBuffer, RawAudioFormat};
+use crate::audio_system::element::{AudioSink, AudioSystemElementMessage};
+use crate::audio_system::pipeline::file_writer::WavFileWriter;
+
+use super::{VirtualMicrophone, VirtualMicrophoneInfo};
+
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use mueue::*;
+
+/// Plays the audio into a WAV file, so that the pipeline runs without a sound
+/// server. The file is written anew every time the microphone is started.
+pub struct WavVirtualMicrophone {
+    send: MessageSender<AudioSystemElementMessage>,
+    input: Option<MessageReceiver<RawAudioBuffer>>,
+
+    path: PathBuf,
+    format: Option<RawAudioFormat>,
+    sample_rate: Option<u32>,
+
+    writer: Option<WavFileWriter>,
+    clock: SampleClock,
+}
+
+impl WavVirtualMicrophone {
+    pub fn new(
+        send: MessageSender<AudioSystemElementMessage>,
+        path: impl Into<PathBuf>,
+        format: Option<RawAudioFormat>,
+    ) -> Self {
+        Self {
+            send,
+            input: None,
+
+            path: path.into(),
+            format,
+            sample_rate: None,
+
+            writer: None,
+            clock: SampleClock::new(),
+        }
+    }
+
+    /// The audio is resampled to the sample rate before it's written.
+    pub fn with_sample_rate(mut self, sample_rate: Option<u32>) -> Self {
+        self.sample_rate = sample_rate;
+        self
+    }
+}
+
+impl Runnable for WavVirtualMicrophone {
+    fn update(&mut self) -> error::Result<()> {
+        let Some(input) = self.input.clone() else {
+            return Ok(());
+        };
+
+        for audio in input.iter() {
+            if audio.sample_rate() != 0 {
+                self.clock.advance(audio.duration());
+            }
+
+            let Some(writer) = self.writer.as_mut() else {
+                continue;
+            };
+            if let Err(err) = writer.write(audio) {
+                self.writer = None;
+                let _ = self.send.send(AudioSystemElementMessage::Error(err));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn on_start(&mut self) {
+        match WavFileWriter::create(self.send.clone(), &self.path) {
+            Ok(writer) => self.writer = Some(writer),
+            Err(err) => {
+                let _ = self.send.send(AudioSystemElementMessage::Error(err));
+            }
+        }
+    }
+
+    fn on_stop(&mut self) {
+        if let Some(Err(err)) = self.writer.take().as_mut().map(WavFileWriter::finish) {
+            let _ = self.send.send(AudioSystemElementMessage::Error(err));
+        }
+    }
+}
+
+impl Element for WavVirtualMicrophone {
+    type Message = AudioSystemElementMessage;
+
+    fn sender(&self) -> MessageSender<Self::Message> {
+        self.send.clone()
+    }
+
+    fn connect(&mut self, send: MessageSender<Self::Message>) {
+        self.send = send;
+    }
+}
+
+impl AudioSink<RawAudioBuffer> for WavVirtualMicrophone {
+    fn input(&self) -> Option<MessageReceiver<RawAudioBuffer>> {
+        self.input.clone()
+    }
+
+    fn set_input(&mut self, input: MessageReceiver<RawAudioBuffer>) {
+        self.input = Some(input);
+    }
+
+    fn unset_input(&mut self) {
+        self.input = None;
+    }
+}
+
+impl VirtualMicrophone for WavVirtualMicrophone {
+    fn info(&self) -> VirtualMicrophoneInfo {
+        VirtualMicrophoneInfo {
+            name: "WAV File Virtual Microphone".to_string(),
+        }
+    }
+
+    fn format(&self) -> Option<RawAudioFormat> {
+        self.format
+    }
+
+    fn sample_rate(&self) -> Option<u32> {
+        self.sample_rate
+    }
+
+    // The clock counts the written samples, so it doesn't depend on how fast
+    // the file is written.
+    fn provide_clock(&self) -> Option<Rc<dyn SlaveClock>> {
+        Some(Rc::new(SlavedClock::new(self.clock.clone())))
+    }
+}
+
+pub struct WavVirtualMicrophoneBuilder {
+    send: Option<MessageSender<AudioSystemElementMessage>>,
+
+    path: PathBuf,
+    format: Option<RawAudioFormat>,
+    sample_rate: Option<u32>,
+}
+
+impl WavVirtualMicrophoneBuilder {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            send: None,
+
+            path: path.into(),
+            format: Some(RawAudioFormat::S16LE),
+            sample_rate: None,
+        }
+    }
+
+    /// The audio is written in the format it comes in if it's `None`, but then
+    /// only the first format ends up in the file.
+    pub fn set_format(mut self, format: Option<RawAudioFormat>) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// The audio is written in the sample rate it comes in if it's `None`.
+    pub fn set_sample_rate(mut self, sample_rate: Option<u32>) -> Self {
+        self.sample_rate = sample_rate;
+        self
+    }
+}
+
+impl ElementBuilder for WavVirtualMicrophoneBuilder {
+    type Element = dyn VirtualMicrophone;
+
+    fn set_sender(&mut self, send: MessageSender<AudioSystemElementMessage>) {
+        self.send = Some(send);
+    }
+
+    fn build(self: Box<Self>) -> error::Result<Box<Self::Element>> {
+        let send = self.send.expect("A sender wasn't provided");
+
+        Ok(Box::new(
+            WavVirtualMicrophone::new(send, self.path, self.format)
+                .with_sample_rate(self.sample_rate),
+        ))
+    }
+}
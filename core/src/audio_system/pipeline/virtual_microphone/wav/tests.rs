@@ -0,0 +1,38 @@
+use super::*;
+
+use crate::test_util::temp_path;
+use crate::util::ClockTime;
+
+fn silence(no_samples: usize) -> RawAudioBuffer {
+    RawAudioBuffer::new(vec![0; no_samples * 2], RawAudioFormat::S16LE, 1, 8000)
+}
+
+#[test]
+fn test_write_played_audio() {
+    let path = temp_path("test_write_played_audio.wav");
+    let (send, _) = unidirectional_queue();
+    let mut mic = WavVirtualMicrophone::new(send, &path, Some(RawAudioFormat::S16LE));
+    let in_send = mic.create_input();
+    let clock = mic.provide_clock().unwrap();
+
+    mic.on_start();
+    let _ = in_send.send(silence(80));
+    let _ = in_send.send(silence(160));
+    let _ = mic.update();
+    mic.on_stop();
+
+    assert_eq!(clock.get_time(), ClockTime::from_millis(30));
+    assert_eq!(std::fs::read(&path).unwrap().len(), 44 + 240 * 2);
+
+    // The file is replaced when the microphone is started again.
+    mic.on_start();
+    let _ = in_send.send(silence(8));
+    let _ = mic.update();
+    mic.on_stop();
+
+    let file = std::fs::read(&path).unwrap();
+    let _ = std::fs::remove_file(&path);
+
+    assert_eq!(clock.get_time(), ClockTime::from_millis(31));
+    assert_eq!(file.len(), 44 + 8 * 2);
+}
@@ -1,4 +1,4 @@
-use super::audio::{RawAudioBuffer, RawAudioFormat};
+use super::audio::{ChannelLayout, RawAudioBuffer, RawAudioFormat};
 
 use std::collections::VecDeque;
 
@@ -19,6 +19,14 @@ impl RawAudioQueue {
         self.buffers.front().map(RawAudioBuffer::format)
     }
 
+    pub fn front_buffer_channels(&self) -> Option<u8> {
+        self.buffers.front().map(RawAudioBuffer::channels)
+    }
+
+    pub fn front_buffer_channel_layout(&self) -> Option<ChannelLayout> {
+        self.buffers.front().map(RawAudioBuffer::channel_layout)
+    }
+
     pub fn front_buffer_sample_rate(&self) -> Option<u32> {
         self.buffers.front().map(RawAudioBuffer::sample_rate)
     }
@@ -43,10 +51,14 @@ impl RawAudioQueue {
         self.buffers.push_back(buffer);
     }
 
-    pub fn pop_bytes(&mut self, desired: usize) -> Option<(Vec<u8>, RawAudioFormat, u32)> {
+    pub fn pop_bytes(&mut self, desired: usize) -> Option<(Vec<u8>, RawAudioFormat, u8, u32)> {
         let res = self.buffers.front().map(|front_buffer| {
             let available = front_buffer.len() - self.front_buffer_offset;
 
+            // Only whole frames are popped so that channels don't get shifted.
+            let frame_size = front_buffer.frame_size().max(1);
+            let desired = desired - desired % frame_size;
+
             let start = self.front_buffer_offset;
             let end = self.front_buffer_offset + desired.min(available);
             self.front_buffer_offset = end;
@@ -54,6 +66,7 @@ impl RawAudioQueue {
             (
                 front_buffer.as_slice()[start..end].to_vec(),
                 front_buffer.format(),
+                front_buffer.channels(),
                 front_buffer.sample_rate(),
             )
         });
@@ -74,17 +87,22 @@ impl RawAudioQueue {
         &mut self,
         desired: usize,
         format: RawAudioFormat,
+        channels: u8,
         sample_rate: u32,
     ) -> Option<Vec<u8>> {
         let Some(front_buffer_format) = self.front_buffer_format() else {
             return None;
         };
+        let front_buffer_channels = self.front_buffer_channels()?;
         let Some(front_buffer_sample_rate) = self.front_buffer_sample_rate() else {
             return None;
         };
 
-        if front_buffer_format == format && front_buffer_sample_rate == sample_rate {
-            return self.pop_bytes(desired).map(|(bytes, _, _)| bytes);
+        if front_buffer_format == format
+            && front_buffer_channels == channels
+            && front_buffer_sample_rate == sample_rate
+        {
+            return self.pop_bytes(desired).map(|(bytes, _, _, _)| bytes);
         }
 
         None
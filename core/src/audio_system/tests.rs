@@ -0,0 +1,422 @@
+use super::*;
+
+use audio::{
+    AudioCodec, EncodedAudioBuffer, RawAudioBuffer, RawAudioFormat, TimestampedRawAudioBuffer,
+};
+use pipeline::virtual_microphone::{null::NullVirtualMicrophoneBuilder, wav::*};
+
+use crate::test_util::{encoded_audio, temp_path};
+use crate::util::ManualClock;
+
+use mueue::bidirectional_queue;
+
+const SAMPLE_RATE: u32 = 8000;
+const BUFFER_DURATION: ClockTime = ClockTime::from_millis(20);
+const NO_BUFFERS: u64 = 5;
+const SAMPLE: i16 = 1000;
+const STEP: ClockTime = ClockTime::from_millis(1);
+
+// The tests play only PCM, which doesn't pass through the audio decoder.
+struct UnusedAudioDecoder {
+    send: MessageSender<AudioSystemElementMessage>,
+    input: Option<MessageReceiver<EncodedAudioBuffer>>,
+    output: Option<MessageSender<TimestampedRawAudioBuffer>>,
+}
+
+impl Runnable for UnusedAudioDecoder {
+    fn update(&mut self) -> error::Result<()> {
+        Ok(())
+    }
+}
+
+impl Element for UnusedAudioDecoder {
+    type Message = AudioSystemElementMessage;
+
+    fn sender(&self) -> MessageSender<Self::Message> {
+        self.send.clone()
+    }
+
+    fn connect(&mut self, send: MessageSender<Self::Message>) {
+        self.send = send;
+    }
+}
+
+impl AudioSink<EncodedAudioBuffer> for UnusedAudioDecoder {
+    fn input(&self) -> Option<MessageReceiver<EncodedAudioBuffer>> {
+        self.input.clone()
+    }
+
+    fn set_input(&mut self, input: MessageReceiver<EncodedAudioBuffer>) {
+        self.input = Some(input);
+    }
+
+    fn unset_input(&mut self) {
+        self.input = None;
+    }
+}
+
+impl AudioSource<TimestampedRawAudioBuffer> for UnusedAudioDecoder {
+    fn output(&self) -> Option<MessageSender<TimestampedRawAudioBuffer>> {
+        self.output.clone()
+    }
+
+    fn set_output(&mut self, output: MessageSender<TimestampedRawAudioBuffer>) {
+        self.output = Some(output);
+    }
+
+    fn unset_output(&mut self) {
+        self.output = None;
+    }
+}
+
+impl AudioFilter<EncodedAudioBuffer, TimestampedRawAudioBuffer> for UnusedAudioDecoder {}
+
+impl AudioDecoder for UnusedAudioDecoder {
+    fn info(&self) -> AudioDecoderInfo {
+        AudioDecoderInfo {
+            name: String::from("Unused Audio Decoder"),
+        }
+    }
+}
+
+#[derive(Default)]
+struct UnusedAudioDecoderBuilder {
+    send: Option<MessageSender<AudioSystemElementMessage>>,
+}
+
+impl ElementBuilder for UnusedAudioDecoderBuilder {
+    type Element = dyn AudioDecoder;
+
+    fn set_sender(&mut self, send: MessageSender<AudioSystemElementMessage>) {
+        self.send = Some(send);
+    }
+
+    fn build(self: Box<Self>) -> error::Result<Box<Self::Element>> {
+        Ok(Box::new(UnusedAudioDecoder {
+            send: self.send.expect("A sender wasn't provided"),
+            input: None,
+            output: None,
+        }))
+    }
+}
+
+// The filters are left out, so that the audio is played unchanged.
+fn new_audio_system(
+    virtual_mics_builders: Vec<Box<dyn VirtualMicrophoneBuilder>>,
+    sys_clock: Arc<dyn Clock>,
+) -> (
+    AudioSystem,
+    MessageEndpoint<AudioSystemMessage, AudioSystemControlMessage>,
+) {
+    new_audio_system_with_filters(virtual_mics_builders, Vec::new(), sys_clock)
+}
+
+fn new_audio_system_with_filters(
+    virtual_mics_builders: Vec<Box<dyn VirtualMicrophoneBuilder>>,
+    filters: Vec<AudioFilterInfo>,
+    sys_clock: Arc<dyn Clock>,
+) -> (
+    AudioSystem,
+    MessageEndpoint<AudioSystemMessage, AudioSystemControlMessage>,
+) {
+    let (end, control_end) = bidirectional_queue();
+    let audio_system = AudioSystem::with_sys_clock(
+        end,
+        vec![Box::new(UnusedAudioDecoderBuilder::default())],
+        virtual_mics_builders,
+        AudioFilterBuilders::default(),
+        AudioPipelineConfig {
+            raw_filters: filters,
+            ..Default::default()
+        },
+        sys_clock,
+    );
+
+    (audio_system, control_end)
+}
+
+fn pcm_audio(no_buffer: u64) -> EncodedAudioBuffer {
+    let no_samples = BUFFER_DURATION.to_no_samples(SAMPLE_RATE);
+
+    let mut audio = encoded_audio(
+        AudioCodec::PcmS16LE,
+        1,
+        SAMPLE.to_le_bytes().repeat(no_samples),
+    );
+    audio.header.sample_rate = SAMPLE_RATE;
+    audio.sequence_number = Some(no_buffer as u32);
+    audio.start_ts = Some(BUFFER_DURATION * no_buffer);
+
+    audio
+}
+
+// Plays the buffers while the clock is stepped, so every one of them is due in
+// time.
+fn play_pcm(audio_system: &mut AudioSystem, sys_clock: &ManualClock) {
+    for no_buffer in 0..NO_BUFFERS {
+        audio_system
+            .pipeline
+            .runnable_mut()
+            .audio_demuxer_mut()
+            .unwrap()
+            .push(pcm_audio(no_buffer).to_muxed().unwrap());
+    }
+
+    let no_steps = (BUFFER_DURATION * NO_BUFFERS).as_nanos() * 2 / STEP.as_nanos();
+    for _ in 0..no_steps {
+        let _ = audio_system.update();
+        sys_clock.advance(STEP);
+    }
+}
+
+fn read_wav_data(path: &std::path::Path) -> Vec<u8> {
+    let file = std::fs::read(path).unwrap();
+    let _ = std::fs::remove_file(path);
+
+    file[44..].to_vec()
+}
+
+#[test]
+fn test_play_pcm_into_wav_microphone() {
+    let path = temp_path("test_play_pcm_into_wav_microphone.wav");
+    let sys_clock = Arc::new(ManualClock::new());
+    let (mut audio_system, _) = new_audio_system(
+        vec![Box::new(WavVirtualMicrophoneBuilder::new(&path))],
+        sys_clock.clone(),
+    );
+
+    audio_system.on_start();
+    play_pcm(&mut audio_system, &sys_clock);
+    audio_system.on_stop();
+
+    let samples = read_wav_data(&path)
+        .chunks_exact(2)
+        .map(|sample| i16::from_le_bytes([sample[0], sample[1]]))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        samples.len(),
+        (BUFFER_DURATION * NO_BUFFERS).to_no_samples(SAMPLE_RATE)
+    );
+    // None of the filters is active, so the audio isn't converted to float.
+    assert!(samples.iter().all(|sample| *sample == SAMPLE));
+}
+
+#[test]
+fn test_record_played_audio() {
+    let path = temp_path("test_record_played_audio.wav");
+    let sys_clock = Arc::new(ManualClock::new());
+    let (mut audio_system, control_end) = new_audio_system(
+        vec![Box::new(NullVirtualMicrophoneBuilder::new())],
+        sys_clock.clone(),
+    );
+
+    let _ = control_end.send(AudioSystemControlMessage::StartRecording {
+        received: None,
+        played: Some(path.clone()),
+    });
+    audio_system.on_start();
+    play_pcm(&mut audio_system, &sys_clock);
+    let _ = control_end.send(AudioSystemControlMessage::StopRecording);
+    let _ = audio_system.update();
+    audio_system.on_stop();
+
+    assert!(!control_end
+        .iter()
+        .any(|msg| matches!(msg, AudioSystemMessage::Error(_))));
+
+    // The null microphone doesn't negotiate any format and none of the
+    // filters is active, so the audio isn't converted at all.
+    let samples = read_wav_data(&path)
+        .chunks_exact(2)
+        .map(|sample| i16::from_le_bytes([sample[0], sample[1]]))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        samples,
+        vec![SAMPLE; (BUFFER_DURATION * NO_BUFFERS).to_no_samples(SAMPLE_RATE)]
+    );
+}
+
+// The filters process the audio in float, but the microphone which doesn't
+// negotiate any format gets it back in the decoded one.
+#[test]
+fn test_record_filtered_audio_in_decoded_format() {
+    let path = temp_path("test_record_filtered_audio_in_decoded_format.wav");
+    let sys_clock = Arc::new(ManualClock::new());
+    let (mut audio_system, control_end) = new_audio_system_with_filters(
+        vec![Box::new(NullVirtualMicrophoneBuilder::new())],
+        vec![AudioFilterInfo::new("gain")],
+        sys_clock.clone(),
+    );
+
+    let _ = control_end.send(AudioSystemControlMessage::SetGain(0.5));
+    let _ = control_end.send(AudioSystemControlMessage::StartRecording {
+        received: None,
+        played: Some(path.clone()),
+    });
+    audio_system.on_start();
+    play_pcm(&mut audio_system, &sys_clock);
+    let _ = control_end.send(AudioSystemControlMessage::StopRecording);
+    let _ = audio_system.update();
+    audio_system.on_stop();
+
+    let file = std::fs::read(&path).unwrap();
+    // The format tag of PCM and 16 bits per sample.
+    assert_eq!(u16::from_le_bytes([file[20], file[21]]), 1);
+    assert_eq!(u16::from_le_bytes([file[34], file[35]]), 16);
+
+    let samples = read_wav_data(&path)
+        .chunks_exact(2)
+        .map(|sample| i16::from_le_bytes([sample[0], sample[1]]))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        samples.len(),
+        (BUFFER_DURATION * NO_BUFFERS).to_no_samples(SAMPLE_RATE)
+    );
+    // The gain ramps down at first, and the converter dithers the samples.
+    assert!(samples[samples.len() / 2..]
+        .iter()
+        .all(|sample| sample.abs_diff(SAMPLE / 2) <= 1));
+}
+
+// The noise gate processes the decoded audio, so it can't be linked after the
+// resizer.
+#[test]
+fn test_report_audio_filter_of_other_section() {
+    let (mut audio_system, control_end) = new_audio_system_with_filters(
+        vec![Box::new(NullVirtualMicrophoneBuilder::new())],
+        vec![
+            AudioFilterInfo::new("noise_gate"),
+            AudioFilterInfo::new("gain"),
+        ],
+        Arc::new(ManualClock::new()),
+    );
+    let _ = audio_system.update();
+
+    assert!(control_end.iter().any(|msg| matches!(
+        msg,
+        AudioSystemMessage::Error(error::Error::UnknownAudioFilter(name, "raw"))
+            if name == "noise_gate"
+    )));
+    let pipeline = audio_system.pipeline.runnable_mut();
+    assert_eq!(
+        pipeline.raw_filter_chain().unwrap().infos(),
+        vec![AudioFilterInfo::new("gain")]
+    );
+    assert_eq!(
+        pipeline.decoded_filter_chain().unwrap().infos(),
+        vec![
+            AudioFilterInfo::new("loss_concealer"),
+            AudioFilterInfo::new("noise_gate")
+        ]
+    );
+}
+
+#[test]
+fn test_move_audio_filter_within_its_section() {
+    let (mut audio_system, control_end) = new_audio_system(
+        vec![Box::new(NullVirtualMicrophoneBuilder::new())],
+        Arc::new(ManualClock::new()),
+    );
+
+    let noise_gate = AudioFilterInfo::new("noise_gate");
+    let _ = control_end.send(AudioSystemControlMessage::RemoveAudioFilter(
+        noise_gate.clone(),
+    ));
+    let _ = control_end.send(AudioSystemControlMessage::InsertAudioFilter {
+        index: 0,
+        info: noise_gate.clone(),
+    });
+    let _ = audio_system.update();
+
+    let pipeline = audio_system.pipeline.runnable_mut();
+    assert_eq!(
+        pipeline.decoded_filter_chain().unwrap().infos(),
+        vec![noise_gate, AudioFilterInfo::new("loss_concealer")]
+    );
+    assert!(pipeline.raw_filter_chain().unwrap().is_empty());
+    assert!(pipeline.encoded_filter_chain().unwrap().is_empty());
+}
+
+#[test]
+fn test_choose_virtual_microphone() {
+    let path = temp_path("test_choose_virtual_microphone.wav");
+    let sys_clock = Arc::new(ManualClock::new());
+    let (mut audio_system, _) = new_audio_system(
+        vec![
+            Box::new(NullVirtualMicrophoneBuilder::new()),
+            Box::new(WavVirtualMicrophoneBuilder::new(&path)),
+        ],
+        sys_clock.clone(),
+    );
+
+    let mic_info = |audio_system: &mut AudioSystem| {
+        audio_system
+            .pipeline
+            .runnable_mut()
+            .virtual_microphone_mut()
+            .map(|mic| mic.info().name)
+    };
+
+    let wav_info = VirtualMicrophoneInfo {
+        name: String::from("WAV File Virtual Microphone"),
+    };
+    audio_system.choose_virtual_microphone(wav_info.clone());
+    assert_eq!(mic_info(&mut audio_system), Some(wav_info.name.clone()));
+
+    audio_system.on_start();
+    play_pcm(&mut audio_system, &sys_clock);
+    audio_system.on_stop();
+
+    let no_samples = read_wav_data(&path).len() / 2;
+    assert_eq!(
+        no_samples,
+        (BUFFER_DURATION * NO_BUFFERS).to_no_samples(SAMPLE_RATE)
+    );
+
+    // The microphone which was taken out may be chosen again.
+    let null_info = VirtualMicrophoneInfo {
+        name: String::from("Null Virtual Microphone"),
+    };
+    audio_system.choose_virtual_microphone(null_info.clone());
+    assert_eq!(mic_info(&mut audio_system), Some(null_info.name));
+    assert!(audio_system.virtual_mics[&wav_info].is_some());
+
+    audio_system.choose_virtual_microphone(wav_info.clone());
+    assert_eq!(mic_info(&mut audio_system), Some(wav_info.name));
+}
+
+#[test]
+fn test_stop_recording_writes_queued_audio() {
+    let path = temp_path("test_stop_recording_writes_queued_audio.wav");
+    let (mut audio_system, _) = new_audio_system(
+        vec![Box::new(NullVirtualMicrophoneBuilder::new())],
+        Arc::new(ManualClock::new()),
+    );
+
+    audio_system
+        .start_recording(None, Some(path.clone()))
+        .unwrap();
+    audio_system.on_start();
+
+    // The tee queues the audio for the recorder, but the recorder doesn't run
+    // before the recording is stopped.
+    let played_tee = audio_system
+        .pipeline
+        .runnable_mut()
+        .played_audio_tee_mut()
+        .unwrap();
+    let in_send = played_tee.create_input();
+    let audio = RawAudioBuffer::new(
+        SAMPLE.to_le_bytes().repeat(4),
+        RawAudioFormat::S16LE,
+        1,
+        8000,
+    );
+    let _ = in_send.send(audio);
+    let _ = played_tee.update();
+
+    audio_system.stop_recording();
+    audio_system.on_stop();
+
+    assert_eq!(read_wav_data(&path), SAMPLE.to_le_bytes().repeat(4));
+}
@@ -25,6 +25,12 @@ pub enum Error {
     IntToEnumCastFailed,
     #[error("Failed to parse encoded audio header")]
     EncodedAudioHeaderParseFailed,
+    #[error("Encoded audio doesn't fit into the muxed audio header")]
+    EncodedAudioTooLong,
+    #[error("Failed to decode audio: {0}")]
+    AudioDecodingFailed(String),
+    #[error("The {1} audio section of the pipeline has no filter {0}")]
+    UnknownAudioFilter(String, &'static str),
     #[error("Other error occured: {0}")]
     Other(String),
 }
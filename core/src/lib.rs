@@ -7,3 +7,6 @@ pub mod device;
 pub mod error;
 pub mod util;
 pub mod view;
+
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_util;
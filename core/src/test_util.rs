@@ -0,0 +1,32 @@
+//! Helpers shared by the tests of the core and of the plugins, which enable
+//! them with the `test-util` feature.
+
+use crate::audio_system::audio::{
+    AudioCodec, ChannelLayout, EncodedAudioBuffer, EncodedAudioFlags, EncodedAudioHeader,
+};
+
+use std::path::PathBuf;
+
+/// A path in the temporary directory which doesn't collide with the ones of
+/// other test runs.
+pub fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("ffone_{}_{name}", std::process::id()))
+}
+
+/// Audio at 48 kHz without a sequence number and a timestamp, which the tests
+/// set themselves when they need them.
+pub fn encoded_audio(codec: AudioCodec, channels: u8, data: Vec<u8>) -> EncodedAudioBuffer {
+    EncodedAudioBuffer {
+        header: EncodedAudioHeader {
+            codec,
+            sample_rate: 48000,
+            channels,
+            channel_layout: ChannelLayout::from_no_channels(channels),
+        },
+        sequence_number: None,
+        flags: EncodedAudioFlags::default(),
+        extensions: Vec::new(),
+        start_ts: None,
+        data,
+    }
+}
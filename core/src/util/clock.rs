@@ -1,5 +1,7 @@
 use std::cell::{Cell, UnsafeCell};
 use std::default::Default;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::*;
 use std::{fmt, iter, ops};
@@ -94,14 +96,21 @@ impl ClockTime {
         self.as_nanos() as usize / sample_duration
     }
 
-    pub fn from_no_bytes(no_bytes: usize, sample_rate: u32, format: RawAudioFormat) -> Self {
+    pub fn from_no_bytes(
+        no_bytes: usize,
+        sample_rate: u32,
+        format: RawAudioFormat,
+        channels: u8,
+    ) -> Self {
         let sample_duration = Self::NANOS_IN_SEC as usize / sample_rate as usize;
-        Self::from_nanos((sample_duration * no_bytes / format.no_bytes()) as u64)
+        let frame_size = format.no_bytes() * channels as usize;
+        Self::from_nanos((sample_duration * no_bytes / frame_size) as u64)
     }
 
-    pub fn to_no_bytes(self, sample_rate: u32, format: RawAudioFormat) -> usize {
+    pub fn to_no_bytes(self, sample_rate: u32, format: RawAudioFormat, channels: u8) -> usize {
         let sample_duration = Self::NANOS_IN_SEC as usize / sample_rate as usize;
-        self.as_nanos() as usize / sample_duration * format.no_bytes()
+        let frame_size = format.no_bytes() * channels as usize;
+        self.as_nanos() as usize / sample_duration * frame_size
     }
 
     pub fn saturating_sub(self, rhs: Self) -> Self {
@@ -423,6 +432,61 @@ impl Clock for SystemClock {
     }
 }
 
+/// Advances by the duration of the audio which was consumed, so it runs at the
+/// nominal sample rate no matter how fast the audio is actually consumed. The
+/// clones share the time.
+#[derive(Default, Clone)]
+pub struct SampleClock(Rc<Cell<ClockTime>>);
+
+impl SampleClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn advance(&self, duration: ClockTime) {
+        self.0.set(self.0.get() + duration);
+    }
+}
+
+impl Clock for SampleClock {
+    fn info(&self) -> ClockInfo {
+        ClockInfo {
+            name: String::from("Sample Clock"),
+        }
+    }
+
+    fn get_time(&self) -> ClockTime {
+        self.0.get()
+    }
+}
+
+/// Stands still until it's moved forward, so the time-dependent elements can be
+/// driven step by step.
+#[derive(Default)]
+pub struct ManualClock(AtomicU64);
+
+impl ManualClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn advance(&self, duration: ClockTime) {
+        self.0.fetch_add(duration.as_nanos(), Ordering::Relaxed);
+    }
+}
+
+impl Clock for ManualClock {
+    fn info(&self) -> ClockInfo {
+        ClockInfo {
+            name: String::from("Manual Clock"),
+        }
+    }
+
+    fn get_time(&self) -> ClockTime {
+        ClockTime::from_nanos(self.0.load(Ordering::Relaxed))
+    }
+}
+
 pub struct SlavedClock<B> {
     base: B,
     master: UnsafeCell<Option<Arc<dyn Clock + Send + Sync>>>,
@@ -16,6 +16,7 @@ fn main() -> Result<(), Box<dyn Error>> {
         .with_sys_include("stdint.h")
         .with_sys_include("stddef.h")
         .include_item("RawAudioFormat")
+        .include_item("ChannelLayout")
         .include_item("RawAudioBuffer")
         .generate()?
         .write_to_file("include/audio.h");
@@ -4,6 +4,19 @@
 #include <stdint.h>
 #include <stddef.h>
 
+#define MUXED_AUDIO_VERSION 1
+
+enum ChannelLayout {
+    ChannelLayout_Unspecified,
+    ChannelLayout_Mono,
+    ChannelLayout_Stereo,
+    ChannelLayout_Surround,
+    ChannelLayout_Quad,
+    ChannelLayout_Surround51,
+    ChannelLayout_Surround71,
+};
+typedef uint8_t ChannelLayout;
+
 enum RawAudioFormat {
     RawAudioFormat_U8,
     RawAudioFormat_S16LE,
@@ -28,6 +41,13 @@ uint8_t *ffone_raw_audio_buffer_as_ptr_mut(struct RawAudioBuffer *buffer);
 
 int ffone_raw_audio_buffer_format(const struct RawAudioBuffer *buffer, RawAudioFormat *format);
 
+uint8_t ffone_raw_audio_buffer_channels(const struct RawAudioBuffer *buffer);
+
+int ffone_raw_audio_buffer_channel_layout(const struct RawAudioBuffer *buffer,
+                                          ChannelLayout *channel_layout);
+
+size_t ffone_raw_audio_buffer_frame_size(const struct RawAudioBuffer *buffer);
+
 size_t ffone_raw_audio_buffer_len(const struct RawAudioBuffer *buffer);
 
 size_t ffone_raw_audio_buffer_no_samples(const struct RawAudioBuffer *buffer);
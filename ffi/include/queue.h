@@ -18,24 +18,32 @@ bool ffone_raw_audio_queue_has_buffers(RawAudioQueue *queue);
 
 bool ffone_raw_audio_queue_front_buffer_format(RawAudioQueue *queue, RawAudioFormat *format);
 
+bool ffone_raw_audio_queue_front_buffer_channels(RawAudioQueue *queue, uint8_t *channels);
+
+bool ffone_raw_audio_queue_front_buffer_channel_layout(RawAudioQueue *queue,
+                                                       ChannelLayout *channel_layout);
+
 bool ffone_raw_audio_queue_front_buffer_sample_rate(RawAudioQueue *queue, uint32_t *sample_rate);
 
 void ffone_raw_audio_queue_read_bytes_locked(RawAudioQueue *queue,
                                              uint8_t *bytes,
                                              size_t *nbytes,
                                              RawAudioFormat *format,
+                                             uint8_t *channels,
                                              uint32_t *sample_rate);
 
 void ffone_raw_audio_queue_read_bytes(RawAudioQueue *queue,
                                       uint8_t *bytes,
                                       size_t *nbytes,
                                       RawAudioFormat *format,
+                                      uint8_t *channels,
                                       uint32_t *sample_rate);
 
 void ffone_raw_audio_queue_read_bytes_with_props_locked(RawAudioQueue *queue,
                                                         uint8_t *bytes,
                                                         size_t *nbytes,
                                                         RawAudioFormat format,
+                                                        uint8_t channels,
                                                         uint32_t sample_rate,
                                                         bool *have_same_props);
 
@@ -43,6 +51,7 @@ void ffone_raw_audio_queue_read_bytes_with_props(RawAudioQueue *queue,
                                                  uint8_t *bytes,
                                                  size_t *nbytes,
                                                  RawAudioFormat format,
+                                                 uint8_t channels,
                                                  uint32_t sample_rate,
                                                  bool *have_same_props);
 
@@ -1,5 +1,5 @@
 pub use core::audio_system::audio::RawAudioBuffer;
-use core::audio_system::audio::RawAudioFormat;
+use core::audio_system::audio::{ChannelLayout, RawAudioFormat};
 
 use std::ptr;
 
@@ -43,6 +43,39 @@ pub unsafe extern "C" fn ffone_raw_audio_buffer_format(
     1
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn ffone_raw_audio_buffer_channels(buffer: *const RawAudioBuffer) -> u8 {
+    if buffer.is_null() {
+        return 0;
+    }
+
+    (*buffer).channels()
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn ffone_raw_audio_buffer_channel_layout(
+    buffer: *const RawAudioBuffer,
+    channel_layout: *mut ChannelLayout,
+) -> libc::c_int {
+    if buffer.is_null() {
+        return 0;
+    }
+    *channel_layout = (*buffer).channel_layout();
+
+    1
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn ffone_raw_audio_buffer_frame_size(
+    buffer: *const RawAudioBuffer,
+) -> libc::size_t {
+    if buffer.is_null() {
+        return 0;
+    }
+
+    (*buffer).frame_size() as libc::size_t
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn ffone_raw_audio_buffer_len(buffer: *const RawAudioBuffer) -> libc::size_t {
     if buffer.is_null() {
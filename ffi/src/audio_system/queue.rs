@@ -1,5 +1,5 @@
 use core::audio_system::{
-    audio::{RawAudioBuffer, RawAudioFormat},
+    audio::{ChannelLayout, RawAudioBuffer, RawAudioFormat},
     queue::RawAudioQueue,
 };
 
@@ -94,6 +94,52 @@ pub unsafe extern "C" fn ffone_raw_audio_queue_front_buffer_format(
     false
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn ffone_raw_audio_queue_front_buffer_channels(
+    queue: *mut RawAudioQueue,
+    channels: *mut u8,
+) -> bool {
+    if queue.is_null() || channels.is_null() {
+        return false;
+    }
+
+    ffone_rc_lock(queue.cast());
+
+    if let Some(front_buffer_channels) = (*queue).front_buffer_channels() {
+        channels.write(front_buffer_channels);
+        ffone_rc_unlock(queue.cast());
+
+        return true;
+    }
+
+    ffone_rc_unlock(queue.cast());
+
+    false
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn ffone_raw_audio_queue_front_buffer_channel_layout(
+    queue: *mut RawAudioQueue,
+    channel_layout: *mut ChannelLayout,
+) -> bool {
+    if queue.is_null() || channel_layout.is_null() {
+        return false;
+    }
+
+    ffone_rc_lock(queue.cast());
+
+    if let Some(front_buffer_channel_layout) = (*queue).front_buffer_channel_layout() {
+        channel_layout.write(front_buffer_channel_layout);
+        ffone_rc_unlock(queue.cast());
+
+        return true;
+    }
+
+    ffone_rc_unlock(queue.cast());
+
+    false
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn ffone_raw_audio_queue_front_buffer_sample_rate(
     queue: *mut RawAudioQueue,
@@ -123,6 +169,7 @@ pub unsafe extern "C" fn ffone_raw_audio_queue_read_bytes_locked(
     bytes: *mut u8,
     nbytes: *mut libc::size_t,
     format: *mut RawAudioFormat,
+    channels: *mut u8,
     sample_rate: *mut u32,
 ) {
     if queue.is_null()
@@ -132,7 +179,9 @@ pub unsafe extern "C" fn ffone_raw_audio_queue_read_bytes_locked(
         return;
     }
 
-    let Some((audio, audio_format, audio_sample_rate)) = (*queue).pop_bytes(*nbytes) else {
+    let Some((audio, audio_format, audio_channels, audio_sample_rate)) =
+        (*queue).pop_bytes(*nbytes)
+    else {
         nbytes.write(0);
 
         return;
@@ -142,6 +191,9 @@ pub unsafe extern "C" fn ffone_raw_audio_queue_read_bytes_locked(
     if !format.is_null() {
         format.write(audio_format);
     }
+    if !channels.is_null() {
+        channels.write(audio_channels);
+    }
     if !sample_rate.is_null() {
         sample_rate.write(audio_sample_rate);
     }
@@ -155,6 +207,7 @@ pub unsafe extern "C" fn ffone_raw_audio_queue_read_bytes(
     bytes: *mut u8,
     nbytes: *mut libc::size_t,
     format: *mut RawAudioFormat,
+    channels: *mut u8,
     sample_rate: *mut u32,
 ) {
     if queue.is_null() {
@@ -162,7 +215,7 @@ pub unsafe extern "C" fn ffone_raw_audio_queue_read_bytes(
     }
 
     ffone_rc_lock(queue.cast());
-    ffone_raw_audio_queue_read_bytes_locked(queue, bytes, nbytes, format, sample_rate);
+    ffone_raw_audio_queue_read_bytes_locked(queue, bytes, nbytes, format, channels, sample_rate);
     ffone_rc_unlock(queue.cast());
 }
 
@@ -172,6 +225,7 @@ pub unsafe extern "C" fn ffone_raw_audio_queue_read_bytes_with_props_locked(
     bytes: *mut u8,
     nbytes: *mut libc::size_t,
     format: RawAudioFormat,
+    channels: u8,
     sample_rate: u32,
     have_same_props: *mut bool,
 ) {
@@ -200,6 +254,20 @@ pub unsafe extern "C" fn ffone_raw_audio_queue_read_bytes_with_props_locked(
         return;
     }
 
+    let Some(front_buffer_channels) = (*queue).front_buffer_channels() else {
+        nbytes.write(0);
+
+        return;
+    };
+    if front_buffer_channels != channels {
+        nbytes.write(0);
+        if !have_same_props.is_null() {
+            have_same_props.write(false);
+        }
+
+        return;
+    }
+
     let Some(front_buffer_sample_rate) = (*queue).front_buffer_sample_rate() else {
         nbytes.write(0);
 
@@ -219,6 +287,7 @@ pub unsafe extern "C" fn ffone_raw_audio_queue_read_bytes_with_props_locked(
         bytes,
         nbytes,
         ptr::null_mut(),
+        ptr::null_mut(),
         ptr::null_mut()
     );
 }
@@ -229,6 +298,7 @@ pub unsafe extern "C" fn ffone_raw_audio_queue_read_bytes_with_props(
     bytes: *mut u8,
     nbytes: *mut libc::size_t,
     format: RawAudioFormat,
+    channels: u8,
     sample_rate: u32,
     have_same_props: *mut bool,
 ) {
@@ -242,6 +312,7 @@ pub unsafe extern "C" fn ffone_raw_audio_queue_read_bytes_with_props(
         bytes,
         nbytes,
         format,
+        channels,
         sample_rate,
         have_same_props,
     );
@@ -309,29 +380,33 @@ impl RawAudioQueueRC {
         }
     }
 
-    pub fn read_bytes(&self, bytes: &mut [u8]) -> (usize, Option<RawAudioFormat>, Option<u32>) {
+    pub fn read_bytes(
+        &self,
+        bytes: &mut [u8],
+    ) -> (usize, Option<RawAudioFormat>, Option<u8>, Option<u32>) {
         unsafe { ffone_rc_lock(self.0.cast()); }
 
         let popped_bytes = unsafe { (*self.0).pop_bytes(bytes.len()) };
 
-        if let Some((available_bytes, format, sample_rate)) = popped_bytes {
+        if let Some((available_bytes, format, channels, sample_rate)) = popped_bytes {
             let available_nbytes = available_bytes.len();
             bytes[..available_nbytes].clone_from_slice(&available_bytes);
 
             unsafe { ffone_rc_unlock(self.0.cast()); }
 
-            return (available_nbytes, Some(format), Some(sample_rate));
+            return (available_nbytes, Some(format), Some(channels), Some(sample_rate));
         }
 
         unsafe { ffone_rc_unlock(self.0.cast()); }
 
-        (0, None, None)
+        (0, None, None, None)
     }
 
     pub fn read_bytes_with_props(
         &self,
         bytes: &mut [u8],
         format: RawAudioFormat,
+        channels: u8,
         sample_rate: u32,
     ) -> (usize, bool) {
         unsafe { ffone_rc_lock(self.0.cast()); }
@@ -341,14 +416,22 @@ impl RawAudioQueueRC {
 
             return (0, false);
         };
+        let Some(buffer_channels) = unsafe { &*self.0 }.front_buffer_channels() else {
+            unsafe { ffone_rc_unlock(self.0.cast()); }
+
+            return (0, false);
+        };
         let Some(buffer_sample_rate) = unsafe { &*self.0 }.front_buffer_sample_rate() else {
             unsafe { ffone_rc_unlock(self.0.cast()); }
 
             return (0, false);
         };
 
-        if buffer_format == format && buffer_sample_rate == sample_rate {
-            let (nbytes, _, _) = self.read_bytes(bytes);
+        if buffer_format == format
+            && buffer_channels == channels
+            && buffer_sample_rate == sample_rate
+        {
+            let (nbytes, _, _, _) = self.read_bytes(bytes);
 
             unsafe { ffone_rc_unlock(self.0.cast()); }
 
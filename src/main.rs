@@ -50,6 +50,8 @@ fn main() {
     let header = EncodedAudioHeader {
         codec: AudioCodec::Opus,
         sample_rate: 48000,
+        channels: 1,
+        channel_layout: ChannelLayout::Mono,
     };
 
 
@@ -121,6 +123,9 @@ fn main() {
 
         let encoded_audio = EncodedAudioBuffer {
             header,
+            sequence_number: None,
+            flags: EncodedAudioFlags::default(),
+            extensions: Vec::new(),
             start_ts: None,
             data: buf.map_readable().unwrap().as_slice().to_owned(),
         };
@@ -133,6 +138,9 @@ fn main() {
     for data in opus_buffers {
         let encoded_audio = EncodedAudioBuffer {
             header,
+            sequence_number: None,
+            flags: EncodedAudioFlags::default(),
+            extensions: Vec::new(),
             start_ts: None,
             data,
         };
@@ -160,14 +168,14 @@ fn main() {
             data.extend(bytes);
         }
 
-        let raw = RawAudioBuffer::new(data, RawAudioFormat::S16LE, sample_rate);
+        let raw = RawAudioBuffer::new(data, RawAudioFormat::S16LE, 1, sample_rate);
         let ts_buf = TimestampedRawAudioBuffer::new(raw, None);
 
         let _ = input.send(ts_buf);
     }
 
     for _ in 0..1920 {
-        let raw = RawAudioBuffer::new(vec![0; 200], RawAudioFormat::S16LE, sample_rate);
+        let raw = RawAudioBuffer::new(vec![0; 200], RawAudioFormat::S16LE, 1, sample_rate);
         let ts_buf = TimestampedRawAudioBuffer::new(raw, None);
 
         let _ = input.send(ts_buf);
@@ -187,7 +195,7 @@ fn main() {
             data.extend(bytes);
         }
 
-        let raw = RawAudioBuffer::new(data, RawAudioFormat::S16LE, sample_rate);
+        let raw = RawAudioBuffer::new(data, RawAudioFormat::S16LE, 1, sample_rate);
         let ts_buf = TimestampedRawAudioBuffer::new(raw, None);
 
         let _ = input.send(ts_buf);
@@ -207,7 +215,7 @@ fn main() {
             data.extend(bytes);
         }
 
-        let raw = RawAudioBuffer::new(data, RawAudioFormat::S16BE, sample_rate);
+        let raw = RawAudioBuffer::new(data, RawAudioFormat::S16BE, 1, sample_rate);
         let ts_buf = TimestampedRawAudioBuffer::new(raw, None);
 
         let _ = input.send(ts_buf);
@@ -227,7 +235,7 @@ fn main() {
             data.extend(bytes);
         }
 
-        let raw = RawAudioBuffer::new(data, RawAudioFormat::S16LE, sample_rate);
+        let raw = RawAudioBuffer::new(data, RawAudioFormat::S16LE, 1, sample_rate);
         let ts_buf = TimestampedRawAudioBuffer::new(raw, None);
 
         let _ = input.send(ts_buf); */
@@ -254,6 +262,9 @@ fn main() {
     for data in opus_buffers {
         let encoded_audio = EncodedAudioBuffer {
             header,
+            sequence_number: None,
+            flags: EncodedAudioFlags::default(),
+            extensions: Vec::new(),
             start_ts: Some(ClockTime::from_secs(10)),
             data,
         };
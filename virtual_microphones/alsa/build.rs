@@ -0,0 +1,21 @@
+use std::error::Error;
+
+fn main() -> Result<(), Box<dyn Error>> {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=src/pcm.*");
+
+    let alsa = pkg_config::Config::new()
+        .cargo_metadata(true)
+        .probe("alsa")?;
+
+    cc::Build::new()
+        .flag("-Wall")
+        .flag("-Wextra")
+        .flag("-O2")
+        .include("../../ffi/include")
+        .includes(&alsa.include_paths)
+        .file("src/pcm.c")
+        .compile("ffone_c_alsa");
+
+    Ok(())
+}
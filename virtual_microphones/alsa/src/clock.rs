@@ -0,0 +1,36 @@
+use core::util::{Clock, ClockInfo, ClockTime};
+
+use ffi::rc::ffone_rc_unref;
+
+use super::ffi::ffone_alsa_pcm_get_time;
+use super::ffi::FFoneAlsaPcm;
+
+/// The written frames which the device has played, i.e. which aren't delayed
+/// in its buffer anymore.
+pub struct AlsaClock(*mut FFoneAlsaPcm);
+
+impl AlsaClock {
+    pub unsafe fn new(pcm: *mut FFoneAlsaPcm) -> Self {
+        Self(pcm)
+    }
+}
+
+impl Clock for AlsaClock {
+    fn info(&self) -> ClockInfo {
+        ClockInfo {
+            name: String::from("ALSA PCM Clock"),
+        }
+    }
+
+    fn get_time(&self) -> ClockTime {
+        let nsec = unsafe { ffone_alsa_pcm_get_time(self.0) };
+
+        ClockTime::from_nanos(nsec)
+    }
+}
+
+impl Drop for AlsaClock {
+    fn drop(&mut self) {
+        unsafe { ffone_rc_unref(self.0.cast()) }
+    }
+}
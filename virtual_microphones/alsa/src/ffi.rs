@@ -0,0 +1,27 @@
+use core::audio_system::audio::RawAudioFormat;
+use std::ffi::c_char;
+use std::marker::{PhantomData, PhantomPinned};
+
+#[repr(C)]
+pub struct FFoneAlsaPcm {
+    _data: [u8; 0],
+    _marker: PhantomData<(*mut u8, PhantomPinned)>,
+}
+
+extern "C" {
+    pub(crate) fn ffone_alsa_pcm_new(device: *const c_char) -> *mut FFoneAlsaPcm;
+
+    pub(crate) fn ffone_alsa_pcm_configure(
+        pcm: *mut FFoneAlsaPcm,
+        format: *mut RawAudioFormat,
+        channels: *mut u8,
+        sample_rate: *mut u32,
+    ) -> i32;
+    pub(crate) fn ffone_alsa_pcm_write(
+        pcm: *mut FFoneAlsaPcm,
+        data: *const u8,
+        no_frames: usize,
+    ) -> i64;
+    pub(crate) fn ffone_alsa_pcm_stop(pcm: *mut FFoneAlsaPcm);
+    pub(crate) fn ffone_alsa_pcm_get_time(pcm: *mut FFoneAlsaPcm) -> u64;
+}
@@ -0,0 +1,318 @@
+#[cfg(test)]
+mod tests;
+
+extern crate ffi as ffone_ffi;
+
+mod clock;
+mod ffi;
+
+use self::ffi::*;
+use clock::AlsaClock;
+
+use core::audio_system::audio::{RawAudioBuffer, RawAudioFormat};
+use core::audio_system::element::AudioSink;
+use core::audio_system::element::AudioSystemElementMessage;
+use core::audio_system::pipeline::converter::RawAudioAdapter;
+use core::audio_system::pipeline::virtual_microphone::*;
+use core::error;
+use core::mueue::*;
+use core::util::*;
+
+use ffone_ffi::rc::ffone_rc_ref;
+use ffone_ffi::rc::ffone_rc_unref;
+
+use std::ffi::CString;
+use std::ptr::NonNull;
+use std::rc::Rc;
+
+const DEFAULT_DEVICE: &str = "default";
+/// The audio which the device doesn't take is dropped beyond this duration.
+const MAX_PENDING_DURATION: ClockTime = ClockTime::from_secs(1);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct PcmParams {
+    format: RawAudioFormat,
+    channels: u8,
+    sample_rate: u32,
+}
+
+impl PcmParams {
+    fn of(audio: &RawAudioBuffer) -> Self {
+        Self {
+            format: audio.format(),
+            channels: audio.channels(),
+            sample_rate: audio.sample_rate(),
+        }
+    }
+
+    fn frame_size(&self) -> usize {
+        self.format.no_bytes() * usize::from(self.channels)
+    }
+}
+
+/// Writes the audio into an ALSA PCM, e.g. into the playback side of the
+/// `snd-aloop` loopback, so that it runs without a sound server. The writes
+/// don't block, the audio which the device doesn't take yet waits for the next
+/// update.
+pub struct AlsaVirtualMicrophone {
+    send: MessageSender<AudioSystemElementMessage>,
+    input: Option<MessageReceiver<RawAudioBuffer>>,
+
+    pcm: NonNull<FFoneAlsaPcm>,
+    format: Option<RawAudioFormat>,
+
+    // The parameters of the audio which the PCM was configured for and the ones
+    // which the device negotiated.
+    requested_params: Option<PcmParams>,
+    params: Option<PcmParams>,
+    adapter: Option<RawAudioAdapter>,
+    pending: Vec<u8>,
+}
+
+impl AlsaVirtualMicrophone {
+    pub fn new(
+        send: MessageSender<AudioSystemElementMessage>,
+        device: &str,
+        format: Option<RawAudioFormat>,
+    ) -> Option<Self> {
+        let device = CString::new(device).ok()?;
+        let pcm = unsafe { NonNull::new(ffone_alsa_pcm_new(device.as_ptr())) }?;
+
+        Some(Self {
+            send,
+            input: None,
+
+            pcm,
+            format,
+
+            requested_params: None,
+            params: None,
+            adapter: None,
+            pending: Vec::new(),
+        })
+    }
+
+    fn configure(&mut self, requested_params: PcmParams) -> error::Result<()> {
+        self.requested_params = Some(requested_params);
+        self.params = None;
+        self.adapter = None;
+        self.pending.clear();
+
+        let mut params = requested_params;
+        let ret = unsafe {
+            ffone_alsa_pcm_configure(
+                self.pcm.as_ptr(),
+                &mut params.format,
+                &mut params.channels,
+                &mut params.sample_rate,
+            )
+        };
+        if ret != 0 {
+            return Err(error::Error::Other(format!(
+                "Failed to configure the ALSA PCM for {requested_params:?}"
+            )));
+        }
+
+        self.adapter = Some(RawAudioAdapter::new(
+            params.format,
+            params.channels,
+            params.sample_rate,
+        ));
+        self.params = Some(params);
+
+        Ok(())
+    }
+
+    fn write_pending(&mut self) -> error::Result<()> {
+        let Some(params) = self.params else {
+            return Ok(());
+        };
+        let frame_size = params.frame_size();
+
+        let max_pending_len = MAX_PENDING_DURATION.to_no_samples(params.sample_rate) * frame_size;
+        if self.pending.len() > max_pending_len {
+            let overflow_len = self.pending.len() - max_pending_len;
+            self.pending.drain(..overflow_len);
+        }
+
+        let no_frames = self.pending.len() / frame_size;
+        if no_frames == 0 {
+            return Ok(());
+        }
+
+        let ret =
+            unsafe { ffone_alsa_pcm_write(self.pcm.as_ptr(), self.pending.as_ptr(), no_frames) };
+        if ret < 0 {
+            self.pending.clear();
+            return Err(error::Error::Other(
+                "Failed to write into the ALSA PCM".to_string(),
+            ));
+        }
+        self.pending.drain(..ret as usize * frame_size);
+
+        Ok(())
+    }
+}
+
+impl Runnable for AlsaVirtualMicrophone {
+    fn on_stop(&mut self) {
+        let _ = self.write_pending();
+        self.pending.clear();
+
+        unsafe {
+            ffone_alsa_pcm_stop(self.pcm.as_ptr());
+        }
+
+        if let Some(adapter) = self.adapter.as_mut() {
+            adapter.reset();
+        }
+    }
+
+    fn update(&mut self) -> error::Result<()> {
+        let Some(input) = self.input.clone() else {
+            return Ok(());
+        };
+
+        for audio in input.iter() {
+            if audio.sample_rate() == 0 || audio.channels() == 0 {
+                continue;
+            }
+
+            let requested_params = PcmParams::of(&audio);
+            if self.requested_params != Some(requested_params) {
+                if let Err(err) = self.configure(requested_params) {
+                    let _ = self.send.send(AudioSystemElementMessage::Error(err));
+                }
+            }
+
+            let Some(adapter) = self.adapter.as_mut() else {
+                continue;
+            };
+            let audio = adapter.adapt(audio);
+            self.pending.extend_from_slice(audio.as_slice());
+
+            if let Err(err) = self.write_pending() {
+                let _ = self.send.send(AudioSystemElementMessage::Error(err));
+            }
+        }
+
+        if let Err(err) = self.write_pending() {
+            let _ = self.send.send(AudioSystemElementMessage::Error(err));
+        }
+
+        Ok(())
+    }
+}
+
+impl Element for AlsaVirtualMicrophone {
+    type Message = AudioSystemElementMessage;
+
+    fn sender(&self) -> MessageSender<Self::Message> {
+        self.send.clone()
+    }
+
+    fn connect(&mut self, send: MessageSender<Self::Message>) {
+        self.send = send;
+    }
+}
+
+impl AudioSink<RawAudioBuffer> for AlsaVirtualMicrophone {
+    fn input(&self) -> Option<MessageReceiver<RawAudioBuffer>> {
+        self.input.clone()
+    }
+
+    fn set_input(&mut self, input: MessageReceiver<RawAudioBuffer>) {
+        self.input = Some(input);
+    }
+
+    fn unset_input(&mut self) {
+        self.input = None;
+    }
+}
+
+impl VirtualMicrophone for AlsaVirtualMicrophone {
+    fn info(&self) -> VirtualMicrophoneInfo {
+        VirtualMicrophoneInfo {
+            name: "ALSA Virtual Microphone".to_string(),
+        }
+    }
+
+    fn format(&self) -> Option<RawAudioFormat> {
+        self.format
+    }
+
+    fn provide_clock(&self) -> Option<Rc<dyn SlaveClock>> {
+        unsafe {
+            let pcm = ffone_rc_ref(self.pcm.as_ptr().cast()).cast::<FFoneAlsaPcm>();
+            if pcm.is_null() {
+                None
+            } else {
+                let clock = AlsaClock::new(pcm);
+                Some(Rc::new(SlavedClock::new(clock)))
+            }
+        }
+    }
+}
+
+impl Drop for AlsaVirtualMicrophone {
+    fn drop(&mut self) {
+        unsafe {
+            ffone_rc_unref(self.pcm.as_ptr().cast());
+        }
+    }
+}
+
+pub struct AlsaVirtualMicrophoneBuilder {
+    send: Option<MessageSender<AudioSystemElementMessage>>,
+
+    device: String,
+    format: Option<RawAudioFormat>,
+}
+
+impl AlsaVirtualMicrophoneBuilder {
+    pub fn new() -> Self {
+        Self {
+            send: None,
+
+            device: DEFAULT_DEVICE.to_string(),
+            format: Some(RawAudioFormat::S16LE),
+        }
+    }
+
+    /// The name of the PCM, e.g. `hw:Loopback,0` for the loopback.
+    pub fn set_device(mut self, device: impl Into<String>) -> Self {
+        self.device = device.into();
+        self
+    }
+
+    /// The format which is requested from the device. The device may negotiate
+    /// another one, then the audio is converted once more.
+    pub fn set_format(mut self, format: Option<RawAudioFormat>) -> Self {
+        self.format = format;
+        self
+    }
+}
+
+impl Default for AlsaVirtualMicrophoneBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ElementBuilder for AlsaVirtualMicrophoneBuilder {
+    type Element = dyn VirtualMicrophone;
+
+    fn set_sender(&mut self, send: MessageSender<AudioSystemElementMessage>) {
+        self.send = Some(send);
+    }
+
+    fn build(self: Box<Self>) -> error::Result<Box<Self::Element>> {
+        let send = self.send.expect("A sender wasn't provided");
+
+        let mic = AlsaVirtualMicrophone::new(send, &self.device, self.format).ok_or_else(|| {
+            error::Error::Other(format!("Failed to open the ALSA PCM {}", self.device))
+        })?;
+
+        Ok(Box::new(mic))
+    }
+}
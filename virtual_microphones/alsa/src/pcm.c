@@ -0,0 +1,261 @@
+#include "pcm.h"
+
+#include "error.h"
+
+#include <errno.h>
+#include <stdbool.h>
+#include <stdio.h>
+
+#include <alsa/asoundlib.h>
+
+#define BUFFER_TIME_US 100000
+#define PERIOD_TIME_US 20000
+#define NSEC_PER_SEC 1000000000ULL
+
+struct FFonePcmFormat {
+    RawAudioFormat format;
+    snd_pcm_format_t alsa_format;
+};
+
+/* The formats which are tried when the requested one isn't supported, from the
+ * most precise one. */
+static const struct FFonePcmFormat formats[] = {
+    { RawAudioFormat_F32LE, SND_PCM_FORMAT_FLOAT_LE },
+    { RawAudioFormat_S32LE, SND_PCM_FORMAT_S32_LE },
+    { RawAudioFormat_S24LE, SND_PCM_FORMAT_S24_3LE },
+    { RawAudioFormat_S16LE, SND_PCM_FORMAT_S16_LE },
+    { RawAudioFormat_F32BE, SND_PCM_FORMAT_FLOAT_BE },
+    { RawAudioFormat_S32BE, SND_PCM_FORMAT_S32_BE },
+    { RawAudioFormat_S24BE, SND_PCM_FORMAT_S24_3BE },
+    { RawAudioFormat_S16BE, SND_PCM_FORMAT_S16_BE },
+    { RawAudioFormat_U8, SND_PCM_FORMAT_U8 },
+};
+
+#define NO_FORMATS (sizeof(formats) / sizeof(formats[0]))
+
+struct FFoneAlsaPcm {
+    snd_pcm_t *pcm; /* const, nonnull */
+    bool configured;
+
+    uint32_t sample_rate;
+
+    uint64_t no_written_frames;
+    /* The duration of the frames played with the previous parameters. */
+    uint64_t time_base;
+};
+
+static void pcm_dtor(void *opaque);
+
+ffone_rc(FFoneAlsaPcm) ffone_alsa_pcm_new(const char *device) {
+    FFONE_RETURN_VAL_ON_FAILURE(device, NULL);
+
+    ffone_rc(FFoneAlsaPcm) pcm = ffone_rc_new0(FFoneAlsaPcm);
+    FFONE_RETURN_VAL_ON_FAILURE(pcm, NULL);
+
+    int ret;
+    FFONE_GOTO_ON_FAILURE(
+        (ret = snd_pcm_open(&pcm->pcm, device, SND_PCM_STREAM_PLAYBACK, SND_PCM_NONBLOCK)) == 0,
+        pcm_open_error
+    );
+
+    ffone_rc_set_dtor(pcm, pcm_dtor);
+
+    return pcm;
+pcm_open_error:
+    fprintf(stderr, "Failed to open the ALSA PCM %s: %s\n", device, snd_strerror(ret));
+
+    if (pcm) ffone_rc_unref(pcm);
+
+    return NULL;
+}
+
+static void pcm_dtor(void *opaque) {
+    FFoneAlsaPcm *pcm = opaque;
+    FFONE_RETURN_ON_FAILURE(pcm);
+
+    if (pcm->pcm) {
+        snd_pcm_drop(pcm->pcm);
+        snd_pcm_close(pcm->pcm);
+    }
+    pcm->pcm = NULL;
+}
+
+static const struct FFonePcmFormat *negotiate_format(
+    snd_pcm_t *pcm,
+    snd_pcm_hw_params_t *hw_params,
+    RawAudioFormat format
+) {
+    for (size_t i = 0; i < NO_FORMATS; i++) {
+        if (formats[i].format == format &&
+            snd_pcm_hw_params_test_format(pcm, hw_params, formats[i].alsa_format) == 0)
+        {
+            return &formats[i];
+        }
+    }
+
+    for (size_t i = 0; i < NO_FORMATS; i++) {
+        if (snd_pcm_hw_params_test_format(pcm, hw_params, formats[i].alsa_format) == 0) {
+            return &formats[i];
+        }
+    }
+
+    return NULL;
+}
+
+static uint64_t frames_to_nanos(uint64_t no_frames, uint32_t sample_rate) {
+    FFONE_RETURN_VAL_ON_FAILURE(sample_rate > 0, 0);
+
+    /* The frames are split, so that they don't overflow when multiplied. */
+    return no_frames / sample_rate * NSEC_PER_SEC +
+        no_frames % sample_rate * NSEC_PER_SEC / sample_rate;
+}
+
+int ffone_alsa_pcm_configure(
+    FFoneAlsaPcm *pcm,
+    RawAudioFormat *format,
+    uint8_t *channels,
+    uint32_t *sample_rate
+) {
+    FFONE_RETURN_VAL_ON_FAILURE(
+        pcm && format && channels && sample_rate,
+        FFONE_ERROR_INVALID_ARG
+    );
+
+    ffone_alsa_pcm_stop(pcm);
+    pcm->configured = false;
+
+    int ret;
+    snd_pcm_hw_params_t *hw_params;
+    snd_pcm_hw_params_alloca(&hw_params);
+
+    FFONE_RETURN_VAL_ON_FAILURE(
+        (ret = snd_pcm_hw_params_any(pcm->pcm, hw_params)) >= 0,
+        FFONE_ERROR(ret)
+    );
+    FFONE_RETURN_VAL_ON_FAILURE(
+        (ret = snd_pcm_hw_params_set_access(
+            pcm->pcm,
+            hw_params,
+            SND_PCM_ACCESS_RW_INTERLEAVED
+        )) == 0,
+        FFONE_ERROR(ret)
+    );
+
+    const struct FFonePcmFormat *pcm_format = negotiate_format(pcm->pcm, hw_params, *format);
+    FFONE_RETURN_VAL_ON_FAILURE(pcm_format, FFONE_ERROR_CUSTOM);
+    FFONE_RETURN_VAL_ON_FAILURE(
+        (ret = snd_pcm_hw_params_set_format(pcm->pcm, hw_params, pcm_format->alsa_format)) == 0,
+        FFONE_ERROR(ret)
+    );
+
+    unsigned int no_channels = *channels;
+    FFONE_RETURN_VAL_ON_FAILURE(
+        (ret = snd_pcm_hw_params_set_channels_near(pcm->pcm, hw_params, &no_channels)) == 0,
+        FFONE_ERROR(ret)
+    );
+    FFONE_RETURN_VAL_ON_FAILURE(no_channels > 0 && no_channels <= UINT8_MAX, FFONE_ERROR_CUSTOM);
+
+    unsigned int rate = *sample_rate;
+    FFONE_RETURN_VAL_ON_FAILURE(
+        (ret = snd_pcm_hw_params_set_rate_near(pcm->pcm, hw_params, &rate, NULL)) == 0,
+        FFONE_ERROR(ret)
+    );
+
+    /* The device may not support the requested sizes, then it uses its own. */
+    unsigned int buffer_time = BUFFER_TIME_US;
+    snd_pcm_hw_params_set_buffer_time_near(pcm->pcm, hw_params, &buffer_time, NULL);
+    unsigned int period_time = PERIOD_TIME_US;
+    snd_pcm_hw_params_set_period_time_near(pcm->pcm, hw_params, &period_time, NULL);
+
+    FFONE_RETURN_VAL_ON_FAILURE(
+        (ret = snd_pcm_hw_params(pcm->pcm, hw_params)) == 0,
+        FFONE_ERROR(ret)
+    );
+
+    snd_pcm_uframes_t buffer_size;
+    snd_pcm_uframes_t period_size;
+    snd_pcm_hw_params_get_buffer_size(hw_params, &buffer_size);
+    snd_pcm_hw_params_get_period_size(hw_params, &period_size, NULL);
+
+    snd_pcm_sw_params_t *sw_params;
+    snd_pcm_sw_params_alloca(&sw_params);
+
+    /* The playback starts once half of the buffer is filled, so that it doesn't
+     * underrun right away. */
+    FFONE_RETURN_VAL_ON_FAILURE(
+        (ret = snd_pcm_sw_params_current(pcm->pcm, sw_params)) == 0 &&
+        (ret = snd_pcm_sw_params_set_start_threshold(pcm->pcm, sw_params, buffer_size / 2)) == 0 &&
+        (ret = snd_pcm_sw_params_set_avail_min(pcm->pcm, sw_params, period_size)) == 0 &&
+        (ret = snd_pcm_sw_params(pcm->pcm, sw_params)) == 0,
+        FFONE_ERROR(ret)
+    );
+
+    pcm->configured = true;
+    pcm->sample_rate = rate;
+
+    *format = pcm_format->format;
+    *channels = no_channels;
+    *sample_rate = rate;
+
+    return FFONE_SUCCESS;
+}
+
+int64_t ffone_alsa_pcm_write(FFoneAlsaPcm *pcm, const uint8_t *data, size_t no_frames) {
+    FFONE_RETURN_VAL_ON_FAILURE(pcm && data, FFONE_ERROR_INVALID_ARG);
+    FFONE_RETURN_VAL_ON_FAILURE(pcm->configured, FFONE_ERROR_BAD_STATE);
+
+    snd_pcm_sframes_t ret = snd_pcm_writei(pcm->pcm, data, no_frames);
+    if (ret == -EAGAIN) {
+        return 0;
+    }
+
+    /* The underruns and the suspends are recovered, then the frames are written
+     * again next time. */
+    if (ret < 0) {
+        int err = snd_pcm_recover(pcm->pcm, ret, 1);
+        FFONE_RETURN_VAL_ON_FAILURE(err == 0, FFONE_ERROR(err));
+
+        return 0;
+    }
+
+    pcm->no_written_frames += ret;
+
+    return ret;
+}
+
+static uint64_t get_no_played_frames(FFoneAlsaPcm *pcm) {
+    snd_pcm_sframes_t delay;
+
+    /* The delay isn't available during an underrun, when everything has been
+     * played. */
+    if (snd_pcm_delay(pcm->pcm, &delay) != 0 || delay < 0) {
+        delay = 0;
+    }
+
+    if ((uint64_t)delay > pcm->no_written_frames) {
+        return 0;
+    }
+
+    return pcm->no_written_frames - delay;
+}
+
+void ffone_alsa_pcm_stop(FFoneAlsaPcm *pcm) {
+    FFONE_RETURN_ON_FAILURE(pcm && pcm->configured);
+
+    /* The drain blocks only for the duration of the buffer. */
+    snd_pcm_nonblock(pcm->pcm, 0);
+    snd_pcm_drain(pcm->pcm);
+    snd_pcm_nonblock(pcm->pcm, 1);
+
+    pcm->time_base += frames_to_nanos(pcm->no_written_frames, pcm->sample_rate);
+    pcm->no_written_frames = 0;
+
+    snd_pcm_prepare(pcm->pcm);
+}
+
+uint64_t ffone_alsa_pcm_get_time(FFoneAlsaPcm *pcm) {
+    FFONE_RETURN_VAL_ON_FAILURE(pcm, 0);
+    FFONE_RETURN_VAL_ON_FAILURE(pcm->configured, pcm->time_base);
+
+    return pcm->time_base + frames_to_nanos(get_no_played_frames(pcm), pcm->sample_rate);
+}
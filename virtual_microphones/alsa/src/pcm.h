@@ -0,0 +1,34 @@
+#ifndef _FFONE_ALSA_PCM_H
+#define _FFONE_ALSA_PCM_H
+
+#include <stdint.h>
+#include <stddef.h>
+
+#include "audio.h"
+#include "rc.h"
+
+typedef struct FFoneAlsaPcm FFoneAlsaPcm;
+
+ffone_rc(FFoneAlsaPcm) ffone_alsa_pcm_new(const char *device);
+
+/* Negotiates the hardware parameters closest to the requested ones and stores
+ * the negotiated ones back. The audio written with the previous parameters is
+ * played out first. */
+int ffone_alsa_pcm_configure(
+    FFoneAlsaPcm *pcm,
+    RawAudioFormat *format,
+    uint8_t *channels,
+    uint32_t *sample_rate
+);
+
+/* Writes the interleaved frames without blocking and returns the number of the
+ * written ones, or a negative error if the PCM can't be recovered. */
+int64_t ffone_alsa_pcm_write(FFoneAlsaPcm *pcm, const uint8_t *data, size_t no_frames);
+
+/* Plays out the written frames and stops the PCM. */
+void ffone_alsa_pcm_stop(FFoneAlsaPcm *pcm);
+
+/* The duration of the played frames in nanoseconds. */
+uint64_t ffone_alsa_pcm_get_time(FFoneAlsaPcm *pcm);
+
+#endif /* _FFONE_ALSA_PCM_H */
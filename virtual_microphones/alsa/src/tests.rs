@@ -0,0 +1,79 @@
+use super::*;
+
+use core::test_util::temp_path;
+
+use std::time::{Duration, Instant};
+
+const SAMPLE_RATE: u32 = 8000;
+const BUFFER_DURATION: ClockTime = ClockTime::from_millis(20);
+const NO_BUFFERS: u64 = 5;
+
+fn audio(no_buffer: u64) -> RawAudioBuffer {
+    let no_samples = BUFFER_DURATION.to_no_samples(SAMPLE_RATE);
+    let data = (0..no_samples)
+        .flat_map(|i| ((no_buffer as usize * no_samples + i) as i16).to_le_bytes())
+        .collect();
+
+    RawAudioBuffer::new(data, RawAudioFormat::S16LE, 1, SAMPLE_RATE)
+}
+
+// The samples are adapted the way the microphone adapts them to the negotiated
+// parameters. They are exact in F32LE, so that they can be compared.
+fn map_channels(samples: &[f64], channels: u8, target_channels: u8) -> Vec<f64> {
+    let audio = RawAudioBuffer::from_normalized_samples(
+        samples,
+        RawAudioFormat::F32LE,
+        channels,
+        SAMPLE_RATE,
+    );
+
+    RawAudioAdapter::new(RawAudioFormat::F32LE, target_channels, SAMPLE_RATE)
+        .adapt_normalized(&audio)
+}
+
+#[test]
+fn test_map_channels() {
+    assert_eq!(map_channels(&[0.5, 0.25], 1, 2), vec![0.5, 0.5, 0.25, 0.25]);
+    assert_eq!(
+        map_channels(&[0.5, 0.25, 0.125, -0.5], 2, 3),
+        vec![0.5, 0.25, 0.0, 0.125, -0.5, 0.0]
+    );
+    assert_eq!(
+        map_channels(&[0.5, 0.25, 0.75, 0.125, -0.5, 0.375], 3, 1),
+        vec![0.5, 0.0]
+    );
+}
+
+// The file plugin writes whatever is played into a file, and its null slave
+// doesn't need any hardware.
+#[test]
+fn test_play_into_file_pcm() {
+    let path = temp_path("test_play_into_file_pcm.raw");
+    let device = format!("file:FILE={},FORMAT=raw", path.display());
+
+    let (send, _) = unidirectional_queue();
+    let mut mic = AlsaVirtualMicrophone::new(send, &device, Some(RawAudioFormat::S16LE)).unwrap();
+    let in_send = mic.create_input();
+    let clock = mic.provide_clock().unwrap();
+
+    mic.on_start();
+    let mut expected = Vec::new();
+    for no_buffer in 0..NO_BUFFERS {
+        let audio = audio(no_buffer);
+        expected.extend_from_slice(audio.as_slice());
+        let _ = in_send.send(audio);
+    }
+
+    let deadline = Instant::now() + (BUFFER_DURATION * NO_BUFFERS).as_dur() * 2;
+    while Instant::now() < deadline {
+        let _ = mic.update();
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    mic.on_stop();
+
+    let file = std::fs::read(&path).unwrap();
+    let _ = std::fs::remove_file(&path);
+
+    assert_eq!(file, expected);
+    assert_eq!(clock.get_time(), BUFFER_DURATION * NO_BUFFERS);
+}
@@ -0,0 +1,48 @@
+use core::util::{Clock, ClockInfo, ClockTime};
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+/// Counts the bytes written into the pipe, so it advances as fast as the reader
+/// reads them.
+#[derive(Clone)]
+pub struct ByteClock {
+    no_bytes: Rc<Cell<u64>>,
+    byte_rate: u64,
+}
+
+impl ByteClock {
+    pub fn new(byte_rate: u64) -> Self {
+        Self {
+            no_bytes: Rc::new(Cell::new(0)),
+            byte_rate,
+        }
+    }
+
+    pub fn no_bytes(&self) -> u64 {
+        self.no_bytes.get()
+    }
+
+    pub fn advance(&self, no_bytes: usize) {
+        self.no_bytes.set(self.no_bytes.get() + no_bytes as u64);
+    }
+}
+
+impl Clock for ByteClock {
+    fn info(&self) -> ClockInfo {
+        ClockInfo {
+            name: String::from("FIFO Byte Clock"),
+        }
+    }
+
+    fn get_time(&self) -> ClockTime {
+        if self.byte_rate == 0 {
+            return ClockTime::default();
+        }
+
+        let nanos = u128::from(self.no_bytes.get()) * u128::from(ClockTime::NANOS_IN_SEC)
+            / u128::from(self.byte_rate);
+
+        ClockTime::from_nanos(nanos as u64)
+    }
+}
@@ -0,0 +1,384 @@
+#[cfg(test)]
+mod tests;
+
+mod clock;
+
+use clock::ByteClock;
+
+use core::audio_system::audio::{RawAudioBuffer, RawAudioFormat};
+use core::audio_system::element::AudioSink;
+use core::audio_system::element::AudioSystemElementMessage;
+use core::audio_system::pipeline::converter::RawAudioAdapter;
+use core::audio_system::pipeline::virtual_microphone::*;
+use core::error;
+use core::mueue::*;
+use core::util::*;
+
+use std::ffi::CString;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::{FileTypeExt, OpenOptionsExt};
+use std::path::PathBuf;
+use std::rc::Rc;
+
+const DEFAULT_FORMAT: RawAudioFormat = RawAudioFormat::S16LE;
+const DEFAULT_SAMPLE_RATE: u32 = 48000;
+const DEFAULT_CHANNELS: u8 = 2;
+
+/// What happens to the audio which doesn't fit into the pipe, e.g. when nobody
+/// reads it or the reader is slow.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum FifoOverflowPolicy {
+    /// The audio is dropped, so the reader gets the latest audio.
+    #[default]
+    Drop,
+    /// The audio waits for the reader, but the oldest audio beyond the duration
+    /// is dropped.
+    Buffer(ClockTime),
+}
+
+/// Writes the raw audio into a named pipe, e.g. for `module-pipe-source` of
+/// PulseAudio or for `ffmpeg` and `sox`. The reader has to know the format, the
+/// sample rate and the number of channels, since nothing but the samples is
+/// written. The pipe is created if it doesn't exist, then removed on drop.
+pub struct FifoVirtualMicrophone {
+    send: MessageSender<AudioSystemElementMessage>,
+    input: Option<MessageReceiver<RawAudioBuffer>>,
+
+    path: PathBuf,
+    format: RawAudioFormat,
+    sample_rate: u32,
+    channels: u8,
+    overflow_policy: FifoOverflowPolicy,
+
+    fifo: Option<File>,
+    created_fifo: bool,
+
+    // The audio in the other formats is converted, since the reader can't know
+    // about them.
+    adapter: RawAudioAdapter,
+    pending: Vec<u8>,
+    clock: ByteClock,
+}
+
+impl FifoVirtualMicrophone {
+    pub fn new(
+        send: MessageSender<AudioSystemElementMessage>,
+        path: impl Into<PathBuf>,
+        format: RawAudioFormat,
+        sample_rate: u32,
+        channels: u8,
+        overflow_policy: FifoOverflowPolicy,
+    ) -> Self {
+        let byte_rate = format.no_bytes() as u64 * u64::from(channels) * u64::from(sample_rate);
+
+        Self {
+            send,
+            input: None,
+
+            path: path.into(),
+            format,
+            sample_rate,
+            channels,
+            overflow_policy,
+
+            fifo: None,
+            created_fifo: false,
+
+            adapter: RawAudioAdapter::new(format, channels, sample_rate),
+            pending: Vec::new(),
+            clock: ByteClock::new(byte_rate),
+        }
+    }
+
+    fn frame_size(&self) -> usize {
+        self.format.no_bytes() * usize::from(self.channels)
+    }
+
+    fn create_fifo(&mut self) -> error::Result<()> {
+        match std::fs::metadata(&self.path) {
+            Ok(metadata) if metadata.file_type().is_fifo() => return Ok(()),
+            Ok(_) => {
+                return Err(error::Error::Other(format!(
+                    "{} isn't a FIFO",
+                    self.path.display()
+                )))
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+            Err(err) => return Err(err.into()),
+        }
+
+        let path = CString::new(self.path.as_os_str().as_bytes())
+            .map_err(|_| error::Error::Other("The FIFO path contains a NUL byte".to_string()))?;
+        if unsafe { libc::mkfifo(path.as_ptr(), 0o644) } != 0 {
+            return Err(io::Error::last_os_error().into());
+        }
+        self.created_fifo = true;
+
+        Ok(())
+    }
+
+    // The pipe can't be opened for writing without blocking until somebody
+    // opens it for reading, so it is retried on every update.
+    fn open_fifo(&mut self) -> error::Result<()> {
+        let open_result = OpenOptions::new()
+            .write(true)
+            .custom_flags(libc::O_NONBLOCK)
+            .open(&self.path);
+
+        match open_result {
+            Ok(fifo) => self.fifo = Some(fifo),
+            Err(err) if err.raw_os_error() == Some(libc::ENXIO) => {}
+            Err(err) => return Err(err.into()),
+        }
+
+        Ok(())
+    }
+
+    fn write_pending(&mut self) -> error::Result<()> {
+        while !self.pending.is_empty() {
+            let Some(fifo) = self.fifo.as_mut() else {
+                break;
+            };
+
+            match fifo.write(&self.pending) {
+                Ok(0) => break,
+                Ok(no_bytes) => {
+                    self.pending.drain(..no_bytes);
+                    self.clock.advance(no_bytes);
+                }
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => break,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                // The reader has gone, the next one may come later.
+                Err(err) if err.kind() == io::ErrorKind::BrokenPipe => self.close_fifo(),
+                Err(err) => {
+                    self.close_fifo();
+                    return Err(err.into());
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn close_fifo(&mut self) {
+        self.fifo = None;
+
+        // The next reader starts with a whole frame, as if the rest of the frame
+        // written in part was read.
+        let partial_len = self.partial_frame_len();
+        self.pending.drain(..partial_len.min(self.pending.len()));
+        self.clock.advance(partial_len);
+    }
+
+    /// The number of the bytes which are left of the frame written in part.
+    fn partial_frame_len(&self) -> usize {
+        let frame_size = self.frame_size() as u64;
+        if frame_size == 0 {
+            return 0;
+        }
+
+        ((frame_size - self.clock.no_bytes() % frame_size) % frame_size) as usize
+    }
+
+    fn apply_overflow_policy(&mut self) {
+        let max_pending_len = match self.overflow_policy {
+            // Only the rest of the frame written in part is kept.
+            FifoOverflowPolicy::Drop if self.fifo.is_some() => self.partial_frame_len(),
+            FifoOverflowPolicy::Drop => 0,
+            FifoOverflowPolicy::Buffer(duration) => {
+                duration.to_no_bytes(self.sample_rate, self.format, self.channels)
+            }
+        };
+
+        if self.pending.len() > max_pending_len {
+            match self.overflow_policy {
+                FifoOverflowPolicy::Drop => self.pending.truncate(max_pending_len),
+                FifoOverflowPolicy::Buffer(_) => {
+                    let overflow_len = self.pending.len() - max_pending_len;
+                    let frame_size = self.frame_size().max(1);
+                    let overflow_len = overflow_len.div_ceil(frame_size) * frame_size;
+                    // The frame written in part is never dropped.
+                    let start = self.partial_frame_len().min(self.pending.len());
+
+                    let end = (start + overflow_len).min(self.pending.len());
+                    self.pending.drain(start..end);
+                }
+            }
+        }
+    }
+}
+
+impl Runnable for FifoVirtualMicrophone {
+    fn on_start(&mut self) {
+        if let Err(err) = self.create_fifo() {
+            let _ = self.send.send(AudioSystemElementMessage::Error(err));
+        }
+    }
+
+    fn on_stop(&mut self) {
+        let _ = self.write_pending();
+
+        // The reader gets the end of the file.
+        self.close_fifo();
+        self.pending.clear();
+        self.adapter.reset();
+    }
+
+    fn update(&mut self) -> error::Result<()> {
+        let Some(input) = self.input.clone() else {
+            return Ok(());
+        };
+
+        if self.fifo.is_none() {
+            if let Err(err) = self.open_fifo() {
+                let _ = self.send.send(AudioSystemElementMessage::Error(err));
+            }
+        }
+
+        for audio in input.iter() {
+            if audio.sample_rate() == 0 || audio.channels() == 0 {
+                continue;
+            }
+
+            let audio = self.adapter.adapt(audio);
+            self.pending.extend_from_slice(audio.as_slice());
+        }
+
+        if let Err(err) = self.write_pending() {
+            let _ = self.send.send(AudioSystemElementMessage::Error(err));
+        }
+        self.apply_overflow_policy();
+
+        Ok(())
+    }
+}
+
+impl Element for FifoVirtualMicrophone {
+    type Message = AudioSystemElementMessage;
+
+    fn sender(&self) -> MessageSender<Self::Message> {
+        self.send.clone()
+    }
+
+    fn connect(&mut self, send: MessageSender<Self::Message>) {
+        self.send = send;
+    }
+}
+
+impl AudioSink<RawAudioBuffer> for FifoVirtualMicrophone {
+    fn input(&self) -> Option<MessageReceiver<RawAudioBuffer>> {
+        self.input.clone()
+    }
+
+    fn set_input(&mut self, input: MessageReceiver<RawAudioBuffer>) {
+        self.input = Some(input);
+    }
+
+    fn unset_input(&mut self) {
+        self.input = None;
+    }
+}
+
+impl VirtualMicrophone for FifoVirtualMicrophone {
+    fn info(&self) -> VirtualMicrophoneInfo {
+        VirtualMicrophoneInfo {
+            name: "FIFO Virtual Microphone".to_string(),
+        }
+    }
+
+    fn format(&self) -> Option<RawAudioFormat> {
+        Some(self.format)
+    }
+
+    fn sample_rate(&self) -> Option<u32> {
+        Some(self.sample_rate)
+    }
+
+    fn provide_clock(&self) -> Option<Rc<dyn SlaveClock>> {
+        Some(Rc::new(SlavedClock::new(self.clock.clone())))
+    }
+}
+
+impl Drop for FifoVirtualMicrophone {
+    fn drop(&mut self) {
+        self.fifo = None;
+
+        if self.created_fifo {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+}
+
+pub struct FifoVirtualMicrophoneBuilder {
+    send: Option<MessageSender<AudioSystemElementMessage>>,
+
+    path: PathBuf,
+    format: RawAudioFormat,
+    sample_rate: u32,
+    channels: u8,
+    overflow_policy: FifoOverflowPolicy,
+}
+
+impl FifoVirtualMicrophoneBuilder {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            send: None,
+
+            path: path.into(),
+            format: DEFAULT_FORMAT,
+            sample_rate: DEFAULT_SAMPLE_RATE,
+            channels: DEFAULT_CHANNELS,
+            overflow_policy: FifoOverflowPolicy::default(),
+        }
+    }
+
+    pub fn set_format(mut self, format: RawAudioFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn set_sample_rate(mut self, sample_rate: u32) -> Self {
+        self.sample_rate = sample_rate;
+        self
+    }
+
+    pub fn set_channels(mut self, channels: u8) -> Self {
+        self.channels = channels;
+        self
+    }
+
+    pub fn set_overflow_policy(mut self, overflow_policy: FifoOverflowPolicy) -> Self {
+        self.overflow_policy = overflow_policy;
+        self
+    }
+}
+
+impl ElementBuilder for FifoVirtualMicrophoneBuilder {
+    type Element = dyn VirtualMicrophone;
+
+    fn set_sender(&mut self, send: MessageSender<AudioSystemElementMessage>) {
+        self.send = Some(send);
+    }
+
+    fn build(self: Box<Self>) -> error::Result<Box<Self::Element>> {
+        let send = self.send.expect("A sender wasn't provided");
+
+        if self.format == RawAudioFormat::Unspecified || self.sample_rate == 0 || self.channels == 0
+        {
+            return Err(error::Error::Other(
+                "The FIFO needs a format, a sample rate and channels".to_string(),
+            ));
+        }
+
+        Ok(Box::new(FifoVirtualMicrophone::new(
+            send,
+            self.path,
+            self.format,
+            self.sample_rate,
+            self.channels,
+            self.overflow_policy,
+        )))
+    }
+}
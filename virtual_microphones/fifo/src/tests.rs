@@ -0,0 +1,175 @@
+use super::*;
+
+use core::test_util::temp_path;
+
+use std::path::Path;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+const SAMPLE_RATE: u32 = 48000;
+const CHANNELS: u8 = 2;
+const BUFFER_DURATION: ClockTime = ClockTime::from_millis(20);
+
+fn audio(no_buffers: usize) -> Vec<RawAudioBuffer> {
+    let no_samples = BUFFER_DURATION.to_no_samples(SAMPLE_RATE) * usize::from(CHANNELS);
+
+    (0..no_buffers)
+        .map(|no_buffer| {
+            let data = (0..no_samples)
+                .flat_map(|i| ((no_buffer * no_samples + i) as i16).to_le_bytes())
+                .collect();
+
+            RawAudioBuffer::new(data, RawAudioFormat::S16LE, CHANNELS, SAMPLE_RATE)
+        })
+        .collect()
+}
+
+fn create_mic(path: &Path, overflow_policy: FifoOverflowPolicy) -> FifoVirtualMicrophone {
+    let (send, _) = unidirectional_queue();
+
+    FifoVirtualMicrophone::new(
+        send,
+        path,
+        RawAudioFormat::S16LE,
+        SAMPLE_RATE,
+        CHANNELS,
+        overflow_policy,
+    )
+}
+
+// Opens the pipe for reading, which blocks until the microphone opens it for
+// writing. The reading waits for the start, so that the pipe may overflow.
+fn spawn_reader(path: &Path, start: Duration) -> JoinHandle<Vec<u8>> {
+    let path = path.to_path_buf();
+
+    std::thread::spawn(move || {
+        let mut fifo = File::open(path).unwrap();
+        std::thread::sleep(start);
+
+        let mut data = Vec::new();
+        io::Read::read_to_end(&mut fifo, &mut data).unwrap();
+
+        data
+    })
+}
+
+fn wait_for_reader(mic: &mut FifoVirtualMicrophone) {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while mic.fifo.is_none() && Instant::now() < deadline {
+        let _ = mic.update();
+        std::thread::sleep(Duration::from_millis(1));
+    }
+
+    assert!(mic.fifo.is_some());
+}
+
+#[test]
+fn test_write_to_reader() {
+    let path = temp_path("test_write_to_reader.fifo");
+    let mut mic = create_mic(&path, FifoOverflowPolicy::Drop);
+    let in_send = mic.create_input();
+    let clock = mic.provide_clock().unwrap();
+
+    mic.on_start();
+    // Nobody reads yet, so the audio is dropped.
+    let _ = in_send.send(audio(1).remove(0));
+    let _ = mic.update();
+    assert_eq!(clock.get_time(), ClockTime::ZERO);
+
+    let reader = spawn_reader(&path, Duration::ZERO);
+    wait_for_reader(&mut mic);
+
+    let audio = audio(5);
+    for audio in audio.iter().cloned() {
+        let _ = in_send.send(audio);
+        let _ = mic.update();
+    }
+    mic.on_stop();
+
+    let data = reader.join().unwrap();
+    drop(mic);
+
+    assert_eq!(
+        data,
+        audio
+            .iter()
+            .flat_map(|audio| audio.as_slice())
+            .copied()
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(clock.get_time(), BUFFER_DURATION * 5);
+    assert!(!path.exists());
+}
+
+#[test]
+fn test_drop_on_overflow() {
+    let path = temp_path("test_drop_on_overflow.fifo");
+    let mut mic = create_mic(&path, FifoOverflowPolicy::Drop);
+    let in_send = mic.create_input();
+    let clock = mic.provide_clock().unwrap();
+
+    mic.on_start();
+    let reader = spawn_reader(&path, Duration::from_millis(100));
+    wait_for_reader(&mut mic);
+
+    // Two seconds of audio don't fit into the pipe.
+    let audio = audio(100);
+    for audio in audio.iter().cloned() {
+        let _ = in_send.send(audio);
+    }
+    let _ = mic.update();
+    assert!(mic.pending.len() < mic.frame_size());
+    mic.on_stop();
+
+    let data = reader.join().unwrap();
+    let expected = audio
+        .iter()
+        .flat_map(|audio| audio.as_slice())
+        .copied()
+        .collect::<Vec<_>>();
+
+    assert!(!data.is_empty() && data.len() < expected.len());
+    assert_eq!(data, expected[..data.len()]);
+    assert!(clock.get_time() < BUFFER_DURATION * 100);
+}
+
+#[test]
+fn test_buffer_on_overflow() {
+    let path = temp_path("test_buffer_on_overflow.fifo");
+    let mut mic = create_mic(&path, FifoOverflowPolicy::Buffer(ClockTime::from_secs(1)));
+    let in_send = mic.create_input();
+
+    mic.on_start();
+    let reader = spawn_reader(&path, Duration::from_millis(100));
+    wait_for_reader(&mut mic);
+
+    // The second of audio waits for the reader behind the filled pipe, the rest
+    // of it is dropped.
+    let audio = audio(100);
+    for audio in audio.iter().cloned() {
+        let _ = in_send.send(audio);
+    }
+    let _ = mic.update();
+    let pipe_len = mic.clock.no_bytes() as usize;
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !mic.pending.is_empty() && Instant::now() < deadline {
+        let _ = mic.update();
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    mic.on_stop();
+
+    let data = reader.join().unwrap();
+    let expected = audio
+        .iter()
+        .flat_map(|audio| audio.as_slice())
+        .copied()
+        .collect::<Vec<_>>();
+    let buffered_len =
+        ClockTime::from_secs(1).to_no_bytes(SAMPLE_RATE, RawAudioFormat::S16LE, CHANNELS);
+
+    assert_eq!(data.len(), pipe_len + buffered_len);
+    assert_eq!(data[..pipe_len], expected[..pipe_len]);
+    assert_eq!(data[pipe_len..], expected[expected.len() - buffered_len..]);
+    assert_eq!(mic.clock.no_bytes(), data.len() as u64);
+}
@@ -0,0 +1,22 @@
+use std::error::Error;
+
+fn main() -> Result<(), Box<dyn Error>> {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=src/client.*");
+
+    let jack = pkg_config::Config::new()
+        .cargo_metadata(true)
+        .probe("jack")?;
+
+    cc::Build::new()
+        .flag("-Wall")
+        .flag("-Wextra")
+        .flag("-pedantic")
+        .flag("-O2")
+        .include("../../ffi/include")
+        .includes(&jack.include_paths)
+        .file("src/client.c")
+        .compile("ffone_c_jack");
+
+    Ok(())
+}
@@ -0,0 +1,201 @@
+#include "client.h"
+
+#include "error.h"
+
+#include <stdatomic.h>
+#include <stdio.h>
+#include <string.h>
+
+#include <jack/jack.h>
+#include <jack/ringbuffer.h>
+
+#define PORT_NAME_SIZE 16
+#define QUEUE_DURATION_SECS 1
+
+struct FFoneJackClient {
+    jack_client_t *client; /* const, nonnull */
+    jack_port_t *ports[FFONE_JACK_MAX_PORTS]; /* const */
+    uint8_t no_ports; /* const */
+
+    /* Written by the owner of the client and read by the process callback, so
+     * neither of them waits for the other. */
+    jack_ringbuffer_t *queue; /* const, nonnull */
+
+    atomic_bool running;
+    bool active;
+};
+
+static void client_dtor(void *opaque);
+
+static int process_cb(jack_nframes_t nframes, void *arg);
+static void shutdown_cb(void *arg);
+
+ffone_rc(FFoneJackClient) ffone_jack_client_new(uint8_t no_ports) {
+    FFONE_RETURN_VAL_ON_FAILURE(no_ports > 0 && no_ports <= FFONE_JACK_MAX_PORTS, NULL);
+
+    ffone_rc(FFoneJackClient) client = ffone_rc_new0(FFoneJackClient);
+    FFONE_RETURN_VAL_ON_FAILURE(client, NULL);
+
+    client->no_ports = no_ports;
+    atomic_init(&client->running, true);
+
+    jack_status_t status;
+    FFONE_GOTO_ON_FAILURE(
+        client->client = jack_client_open(FFONE_JACK_CLIENT_NAME, JackNoStartServer, &status),
+        client_open_error
+    );
+
+    for (uint8_t i = 0; i < no_ports; i++) {
+        char port_name[PORT_NAME_SIZE];
+        snprintf(port_name, sizeof(port_name), "output_%u", (unsigned)i + 1);
+
+        FFONE_GOTO_ON_FAILURE(
+            client->ports[i] = jack_port_register(
+                client->client,
+                port_name,
+                JACK_DEFAULT_AUDIO_TYPE,
+                JackPortIsOutput | JackPortIsTerminal,
+                0
+            ),
+            port_register_error
+        );
+    }
+
+    size_t queue_size = (size_t)jack_get_sample_rate(client->client) * QUEUE_DURATION_SECS *
+        no_ports * sizeof(float);
+    FFONE_GOTO_ON_FAILURE(
+        client->queue = jack_ringbuffer_create(queue_size),
+        port_register_error
+    );
+
+    FFONE_GOTO_ON_FAILURE(
+        jack_set_process_callback(client->client, process_cb, client) == 0,
+        set_callback_error
+    );
+    jack_on_shutdown(client->client, shutdown_cb, client);
+
+    ffone_rc_set_dtor(client, client_dtor);
+
+    return client;
+set_callback_error:
+    jack_ringbuffer_free(client->queue);
+port_register_error:
+    jack_client_close(client->client);
+client_open_error:
+    if (client) ffone_rc_unref(client);
+
+    return NULL;
+}
+
+static void client_dtor(void *opaque) {
+    FFoneJackClient *client = opaque;
+    FFONE_RETURN_ON_FAILURE(client);
+
+    if (client->client) {
+        if (client->active) jack_deactivate(client->client);
+        jack_client_close(client->client);
+    }
+    client->client = NULL;
+
+    if (client->queue) jack_ringbuffer_free(client->queue);
+    client->queue = NULL;
+}
+
+int ffone_jack_client_activate(FFoneJackClient *client) {
+    FFONE_RETURN_VAL_ON_FAILURE(client, FFONE_ERROR_INVALID_ARG);
+    FFONE_RETURN_VAL_ON_FAILURE(!client->active, FFONE_SUCCESS);
+
+    int ret;
+    FFONE_RETURN_VAL_ON_FAILURE(
+        (ret = jack_activate(client->client)) == 0,
+        FFONE_ERROR(ret)
+    );
+    client->active = true;
+
+    return FFONE_SUCCESS;
+}
+
+int ffone_jack_client_deactivate(FFoneJackClient *client) {
+    FFONE_RETURN_VAL_ON_FAILURE(client, FFONE_ERROR_INVALID_ARG);
+    FFONE_RETURN_VAL_ON_FAILURE(client->active, FFONE_SUCCESS);
+
+    int ret;
+    FFONE_RETURN_VAL_ON_FAILURE(
+        (ret = jack_deactivate(client->client)) == 0,
+        FFONE_ERROR(ret)
+    );
+    client->active = false;
+
+    /* The process callback doesn't run anymore, so the stale audio can be
+     * dropped. */
+    jack_ringbuffer_reset(client->queue);
+
+    return FFONE_SUCCESS;
+}
+
+bool ffone_jack_client_is_running(FFoneJackClient *client) {
+    FFONE_RETURN_VAL_ON_FAILURE(client, false);
+
+    return atomic_load(&client->running);
+}
+
+uint32_t ffone_jack_client_get_sample_rate(FFoneJackClient *client) {
+    FFONE_RETURN_VAL_ON_FAILURE(client, 0);
+
+    return jack_get_sample_rate(client->client);
+}
+
+uint32_t ffone_jack_client_get_frame_time(FFoneJackClient *client) {
+    FFONE_RETURN_VAL_ON_FAILURE(client, 0);
+
+    return jack_frame_time(client->client);
+}
+
+size_t ffone_jack_client_write(FFoneJackClient *client, const float *frames, size_t no_frames) {
+    FFONE_RETURN_VAL_ON_FAILURE(client && frames, 0);
+
+    size_t frame_size = client->no_ports * sizeof(float);
+    size_t free_frames = jack_ringbuffer_write_space(client->queue) / frame_size;
+    if (no_frames > free_frames) {
+        no_frames = free_frames;
+    }
+
+    /* Only the whole frames are written, so the reader stays aligned. */
+    jack_ringbuffer_write(client->queue, (const char *)frames, no_frames * frame_size);
+
+    return no_frames;
+}
+
+static int process_cb(jack_nframes_t nframes, void *arg) {
+    FFoneJackClient *client = arg;
+
+    float *buffers[FFONE_JACK_MAX_PORTS];
+    for (uint8_t i = 0; i < client->no_ports; i++) {
+        buffers[i] = jack_port_get_buffer(client->ports[i], nframes);
+    }
+
+    size_t frame_size = client->no_ports * sizeof(float);
+    size_t available_frames = jack_ringbuffer_read_space(client->queue) / frame_size;
+
+    jack_nframes_t no_frames = 0;
+    for (; no_frames < nframes && no_frames < available_frames; no_frames++) {
+        float frame[FFONE_JACK_MAX_PORTS];
+        jack_ringbuffer_read(client->queue, (char *)frame, frame_size);
+
+        for (uint8_t i = 0; i < client->no_ports; i++) {
+            buffers[i][no_frames] = frame[i];
+        }
+    }
+
+    for (uint8_t i = 0; i < client->no_ports; i++) {
+        memset(buffers[i] + no_frames, 0, (nframes - no_frames) * sizeof(float));
+    }
+
+    return 0;
+}
+
+static void shutdown_cb(void *arg) {
+    FFoneJackClient *client = arg;
+
+    atomic_store(&client->running, false);
+}
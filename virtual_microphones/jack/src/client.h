@@ -0,0 +1,31 @@
+#ifndef _FFONE_JACK_CLIENT_H
+#define _FFONE_JACK_CLIENT_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#include "rc.h"
+
+#define FFONE_JACK_CLIENT_NAME "ffone"
+#define FFONE_JACK_MAX_PORTS 8
+
+typedef struct FFoneJackClient FFoneJackClient;
+
+ffone_rc(FFoneJackClient) ffone_jack_client_new(uint8_t no_ports);
+
+int ffone_jack_client_activate(FFoneJackClient *client);
+int ffone_jack_client_deactivate(FFoneJackClient *client);
+
+/* False after the server shut the client down. */
+bool ffone_jack_client_is_running(FFoneJackClient *client);
+
+uint32_t ffone_jack_client_get_sample_rate(FFoneJackClient *client);
+/* The estimated current time in frames, which wraps around. */
+uint32_t ffone_jack_client_get_frame_time(FFoneJackClient *client);
+
+/* Writes the interleaved frames without blocking and returns the number of the
+ * written ones, the rest doesn't fit into the queue. */
+size_t ffone_jack_client_write(FFoneJackClient *client, const float *frames, size_t no_frames);
+
+#endif /* _FFONE_JACK_CLIENT_H */
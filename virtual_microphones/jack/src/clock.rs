@@ -0,0 +1,69 @@
+use core::util::{Clock, ClockInfo, ClockTime};
+
+use ffi::rc::ffone_rc_unref;
+
+use super::ffi::FFoneJackClient;
+use super::ffi::{ffone_jack_client_get_frame_time, ffone_jack_client_get_sample_rate};
+
+use std::cell::Cell;
+
+/// The frame time of the JACK server, which advances with the cycles of its
+/// driver.
+pub struct JackClock {
+    client: *mut FFoneJackClient,
+
+    // The frame time wraps around after a day at 48 kHz, so it is extended.
+    last_frame_time: Cell<u32>,
+    no_frames: Cell<u64>,
+}
+
+impl JackClock {
+    pub unsafe fn new(client: *mut FFoneJackClient) -> Self {
+        Self {
+            client,
+
+            last_frame_time: Cell::new(ffone_jack_client_get_frame_time(client)),
+            no_frames: Cell::new(0),
+        }
+    }
+}
+
+impl Clock for JackClock {
+    fn info(&self) -> ClockInfo {
+        ClockInfo {
+            name: String::from("JACK Frame Time Clock"),
+        }
+    }
+
+    fn get_time(&self) -> ClockTime {
+        let (frame_time, sample_rate) = unsafe {
+            (
+                ffone_jack_client_get_frame_time(self.client),
+                ffone_jack_client_get_sample_rate(self.client),
+            )
+        };
+
+        let elapsed = frame_time.wrapping_sub(self.last_frame_time.get());
+        // The estimate may go back a little when a cycle starts late.
+        if elapsed < u32::MAX / 2 {
+            self.no_frames
+                .set(self.no_frames.get() + u64::from(elapsed));
+            self.last_frame_time.set(frame_time);
+        }
+
+        if sample_rate == 0 {
+            return ClockTime::default();
+        }
+
+        let nanos = u128::from(self.no_frames.get()) * u128::from(ClockTime::NANOS_IN_SEC)
+            / u128::from(sample_rate);
+
+        ClockTime::from_nanos(nanos as u64)
+    }
+}
+
+impl Drop for JackClock {
+    fn drop(&mut self) {
+        unsafe { ffone_rc_unref(self.client.cast()) }
+    }
+}
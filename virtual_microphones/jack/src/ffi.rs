@@ -0,0 +1,24 @@
+use std::marker::{PhantomData, PhantomPinned};
+
+#[repr(C)]
+pub struct FFoneJackClient {
+    _data: [u8; 0],
+    _marker: PhantomData<(*mut u8, PhantomPinned)>,
+}
+
+extern "C" {
+    pub(crate) fn ffone_jack_client_new(no_ports: u8) -> *mut FFoneJackClient;
+
+    pub(crate) fn ffone_jack_client_activate(client: *mut FFoneJackClient) -> i32;
+    pub(crate) fn ffone_jack_client_deactivate(client: *mut FFoneJackClient) -> i32;
+    pub(crate) fn ffone_jack_client_is_running(client: *mut FFoneJackClient) -> bool;
+
+    pub(crate) fn ffone_jack_client_get_sample_rate(client: *mut FFoneJackClient) -> u32;
+    pub(crate) fn ffone_jack_client_get_frame_time(client: *mut FFoneJackClient) -> u32;
+
+    pub(crate) fn ffone_jack_client_write(
+        client: *mut FFoneJackClient,
+        frames: *const f32,
+        no_frames: usize,
+    ) -> usize;
+}
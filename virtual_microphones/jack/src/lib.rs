@@ -0,0 +1,236 @@
+#[cfg(test)]
+mod tests;
+
+extern crate ffi as ffone_ffi;
+
+mod clock;
+mod ffi;
+
+use self::ffi::*;
+use clock::JackClock;
+
+use core::audio_system::audio::{RawAudioBuffer, RawAudioFormat};
+use core::audio_system::element::AudioSink;
+use core::audio_system::element::AudioSystemElementMessage;
+use core::audio_system::pipeline::converter::RawAudioAdapter;
+use core::audio_system::pipeline::virtual_microphone::*;
+use core::error;
+use core::mueue::*;
+use core::util::*;
+
+use ffone_ffi::rc::ffone_rc_ref;
+use ffone_ffi::rc::ffone_rc_unref;
+
+use std::ptr::NonNull;
+use std::rc::Rc;
+
+const DEFAULT_NO_PORTS: u8 = 2;
+
+/// Registers the output ports of a JACK client, which the applications connect
+/// their inputs to. The ports are fed through a lock-free queue, so the
+/// real-time thread of JACK never waits for the audio system.
+pub struct JackVirtualMicrophone {
+    send: MessageSender<AudioSystemElementMessage>,
+    input: Option<MessageReceiver<RawAudioBuffer>>,
+
+    client: NonNull<FFoneJackClient>,
+    no_ports: u8,
+
+    // JACK runs at one sample rate, so the audio in the other ones is resampled.
+    adapter: RawAudioAdapter,
+    shut_down: bool,
+}
+
+impl JackVirtualMicrophone {
+    pub fn new(send: MessageSender<AudioSystemElementMessage>, no_ports: u8) -> Option<Self> {
+        let client = unsafe { NonNull::new(ffone_jack_client_new(no_ports)) }?;
+        let sample_rate = unsafe { ffone_jack_client_get_sample_rate(client.as_ptr()) };
+
+        Some(Self {
+            send,
+            input: None,
+
+            client,
+            no_ports,
+
+            adapter: RawAudioAdapter::new(RawAudioFormat::F32LE, no_ports, sample_rate),
+            shut_down: false,
+        })
+    }
+
+    fn write(&mut self, audio: RawAudioBuffer) {
+        let frames = self
+            .adapter
+            .adapt_normalized(&audio)
+            .into_iter()
+            .map(|sample| sample as f32)
+            .collect::<Vec<_>>();
+        let no_frames = frames.len() / usize::from(self.no_ports);
+
+        // The frames which don't fit are dropped, the synchronizer catches up
+        // with the clock afterwards.
+        unsafe {
+            ffone_jack_client_write(self.client.as_ptr(), frames.as_ptr(), no_frames);
+        }
+    }
+}
+
+impl Runnable for JackVirtualMicrophone {
+    fn on_start(&mut self) {
+        let ret = unsafe { ffone_jack_client_activate(self.client.as_ptr()) };
+        if ret != 0 {
+            let _ = self
+                .send
+                .send(AudioSystemElementMessage::Error(error::Error::Other(
+                    "Failed to activate the JACK client".to_string(),
+                )));
+        }
+    }
+
+    fn on_stop(&mut self) {
+        unsafe {
+            ffone_jack_client_deactivate(self.client.as_ptr());
+        }
+
+        self.adapter.reset();
+    }
+
+    fn update(&mut self) -> error::Result<()> {
+        let Some(input) = self.input.clone() else {
+            return Ok(());
+        };
+
+        let running = unsafe { ffone_jack_client_is_running(self.client.as_ptr()) };
+        if !running {
+            if !self.shut_down {
+                self.shut_down = true;
+                let _ = self
+                    .send
+                    .send(AudioSystemElementMessage::Error(error::Error::Other(
+                        "The JACK server shut the client down".to_string(),
+                    )));
+            }
+
+            input.iter().for_each(drop);
+            return Ok(());
+        }
+
+        for audio in input.iter() {
+            if audio.sample_rate() == 0 || audio.channels() == 0 {
+                continue;
+            }
+
+            self.write(audio);
+        }
+
+        Ok(())
+    }
+}
+
+impl Element for JackVirtualMicrophone {
+    type Message = AudioSystemElementMessage;
+
+    fn sender(&self) -> MessageSender<Self::Message> {
+        self.send.clone()
+    }
+
+    fn connect(&mut self, send: MessageSender<Self::Message>) {
+        self.send = send;
+    }
+}
+
+impl AudioSink<RawAudioBuffer> for JackVirtualMicrophone {
+    fn input(&self) -> Option<MessageReceiver<RawAudioBuffer>> {
+        self.input.clone()
+    }
+
+    fn set_input(&mut self, input: MessageReceiver<RawAudioBuffer>) {
+        self.input = Some(input);
+    }
+
+    fn unset_input(&mut self) {
+        self.input = None;
+    }
+}
+
+impl VirtualMicrophone for JackVirtualMicrophone {
+    fn info(&self) -> VirtualMicrophoneInfo {
+        VirtualMicrophoneInfo {
+            name: "JACK Virtual Microphone".to_string(),
+        }
+    }
+
+    // JACK plays 32-bit floats, so they are read without losing any precision.
+    fn format(&self) -> Option<RawAudioFormat> {
+        Some(RawAudioFormat::F32LE)
+    }
+
+    fn sample_rate(&self) -> Option<u32> {
+        Some(unsafe { ffone_jack_client_get_sample_rate(self.client.as_ptr()) })
+    }
+
+    fn provide_clock(&self) -> Option<Rc<dyn SlaveClock>> {
+        unsafe {
+            let client = ffone_rc_ref(self.client.as_ptr().cast()).cast::<FFoneJackClient>();
+            if client.is_null() {
+                None
+            } else {
+                let clock = JackClock::new(client);
+                Some(Rc::new(SlavedClock::new(clock)))
+            }
+        }
+    }
+}
+
+impl Drop for JackVirtualMicrophone {
+    fn drop(&mut self) {
+        unsafe {
+            ffone_rc_unref(self.client.as_ptr().cast());
+        }
+    }
+}
+
+pub struct JackVirtualMicrophoneBuilder {
+    send: Option<MessageSender<AudioSystemElementMessage>>,
+
+    no_ports: u8,
+}
+
+impl JackVirtualMicrophoneBuilder {
+    pub fn new() -> Self {
+        Self {
+            send: None,
+
+            no_ports: DEFAULT_NO_PORTS,
+        }
+    }
+
+    /// At most 8 ports are registered.
+    pub fn set_no_ports(mut self, no_ports: u8) -> Self {
+        self.no_ports = no_ports;
+        self
+    }
+}
+
+impl Default for JackVirtualMicrophoneBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ElementBuilder for JackVirtualMicrophoneBuilder {
+    type Element = dyn VirtualMicrophone;
+
+    fn set_sender(&mut self, send: MessageSender<AudioSystemElementMessage>) {
+        self.send = Some(send);
+    }
+
+    fn build(self: Box<Self>) -> error::Result<Box<Self::Element>> {
+        let send = self.send.expect("A sender wasn't provided");
+
+        let mic = JackVirtualMicrophone::new(send, self.no_ports)
+            .ok_or_else(|| error::Error::Other("Failed to open a JACK client".to_string()))?;
+
+        Ok(Box::new(mic))
+    }
+}
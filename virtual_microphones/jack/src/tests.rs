@@ -0,0 +1,77 @@
+use super::*;
+
+use std::time::{Duration, Instant};
+
+const BUFFER_DURATION: ClockTime = ClockTime::from_millis(20);
+const NO_BUFFERS: u64 = 25;
+
+// The samples are adapted the way the microphone adapts them for its ports.
+// They are exact in F32LE, so that they can be compared.
+fn map_channels(samples: &[f64], channels: u8, no_ports: u8) -> Vec<f64> {
+    let audio =
+        RawAudioBuffer::from_normalized_samples(samples, RawAudioFormat::F32LE, channels, 48000);
+
+    RawAudioAdapter::new(RawAudioFormat::F32LE, no_ports, 48000).adapt_normalized(&audio)
+}
+
+#[test]
+fn test_map_channels() {
+    assert_eq!(map_channels(&[0.5, 0.25], 1, 2), vec![0.5, 0.5, 0.25, 0.25]);
+    assert_eq!(
+        map_channels(&[0.5, 0.25, 0.125, -0.5], 2, 2),
+        vec![0.5, 0.25, 0.125, -0.5]
+    );
+    assert_eq!(
+        map_channels(&[0.5, 0.25, 0.125, -0.5], 2, 3),
+        vec![0.5, 0.25, 0.0, 0.125, -0.5, 0.0]
+    );
+    assert_eq!(
+        map_channels(&[0.5, 0.25, 0.125, -0.5, 0.75, -0.25], 3, 2),
+        vec![0.5, 0.25, -0.5, 0.75]
+    );
+}
+
+// The server may run without any hardware with `jackd -d dummy`.
+#[test]
+#[ignore = "needs a running JACK server"]
+fn test_play_tone() {
+    let (send, _) = unidirectional_queue();
+    let mut mic = JackVirtualMicrophone::new(send, 2).unwrap();
+    let in_send = mic.create_input();
+
+    mic.on_start();
+    let clock = mic.provide_clock().unwrap();
+
+    // The audio is resampled to the rate of the server.
+    let sample_rate = 44100;
+    let no_samples = BUFFER_DURATION.to_no_samples(sample_rate);
+    for no_buffer in 0..NO_BUFFERS as usize {
+        let data = (0..no_samples)
+            .map(|i| {
+                let t = (no_buffer * no_samples + i) as f32 / sample_rate as f32;
+                (t * 440.0 * std::f32::consts::TAU).sin() * 0.1
+            })
+            .flat_map(f32::to_le_bytes)
+            .collect();
+        let _ = in_send.send(RawAudioBuffer::new(
+            data,
+            RawAudioFormat::F32LE,
+            1,
+            sample_rate,
+        ));
+    }
+
+    let start_time = clock.get_time();
+    let deadline = Instant::now() + (BUFFER_DURATION * NO_BUFFERS).as_dur();
+    while Instant::now() < deadline {
+        let _ = mic.update();
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    let elapsed = clock.get_time() - start_time;
+
+    mic.on_stop();
+
+    // The frame time follows the wall clock.
+    let expected = BUFFER_DURATION * NO_BUFFERS;
+    assert!(elapsed > expected / 2 && elapsed < expected * 2);
+}
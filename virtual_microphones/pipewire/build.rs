@@ -0,0 +1,25 @@
+use std::error::Error;
+
+fn main() -> Result<(), Box<dyn Error>> {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=src/core.*");
+    println!("cargo:rerun-if-changed=src/stream.*");
+
+    // The headers of PipeWire and SPA aren't in the default include paths.
+    let pipewire = pkg_config::Config::new()
+        .cargo_metadata(true)
+        .probe("libpipewire-0.3")?;
+
+    cc::Build::new()
+        .flag("-Wall")
+        .flag("-Wextra")
+        .flag("-O2")
+        .flag("-lpthread")
+        .include("../../ffi/include")
+        .includes(&pipewire.include_paths)
+        .file("src/core.c")
+        .file("src/stream.c")
+        .compile("ffone_c_pw");
+
+    Ok(())
+}
@@ -0,0 +1,35 @@
+use core::util::{Clock, ClockInfo, ClockTime};
+
+use ffi::rc::ffone_rc_unref;
+
+use super::ffi::ffone_pw_stream_get_time;
+use super::ffi::FFonePWStream;
+
+/// The clock of the graph driver which the stream is scheduled by.
+pub struct PWClock(*mut FFonePWStream);
+
+impl PWClock {
+    pub unsafe fn new(stream: *mut FFonePWStream) -> Self {
+        Self(stream)
+    }
+}
+
+impl Clock for PWClock {
+    fn info(&self) -> ClockInfo {
+        ClockInfo {
+            name: String::from("PipeWire Graph Clock"),
+        }
+    }
+
+    fn get_time(&self) -> ClockTime {
+        let nsec = unsafe { ffone_pw_stream_get_time(self.0) };
+
+        ClockTime::from_nanos(nsec)
+    }
+}
+
+impl Drop for PWClock {
+    fn drop(&mut self) {
+        unsafe { ffone_rc_unref(self.0.cast()) }
+    }
+}
@@ -0,0 +1,114 @@
+#include "core.h"
+
+#include "error.h"
+
+#include <stdio.h>
+#include <stdlib.h>
+
+struct FFonePWCore {
+    struct pw_thread_loop *loop; /* const */
+    struct pw_context *context; /* const */
+    struct pw_core *core; /* const */
+};
+
+static void ffone_pw_core_dtor(void *opaque);
+
+ffone_rc(FFonePWCore) ffone_pw_core_new(void) {
+    pw_init(NULL, NULL);
+
+    ffone_rc(FFonePWCore) core = ffone_rc_new0(FFonePWCore);
+    FFONE_RETURN_VAL_ON_FAILURE(core, NULL);
+
+    FFONE_GOTO_ON_FAILURE(
+        core->loop = pw_thread_loop_new("ffone-pw-loop", NULL),
+        thread_loop_new_error
+    );
+
+    FFONE_GOTO_ON_FAILURE(
+        core->context = pw_context_new(pw_thread_loop_get_loop(core->loop), NULL, 0),
+        context_new_error
+    );
+
+    FFONE_GOTO_ON_FAILURE(pw_thread_loop_start(core->loop) == 0, thread_loop_start_error);
+
+    pw_thread_loop_lock(core->loop);
+
+    FFONE_GOTO_ON_FAILURE(
+        core->core = pw_context_connect(core->context, NULL, 0),
+        context_connect_error
+    );
+
+    ffone_rc_set_dtor(core, ffone_pw_core_dtor);
+
+    pw_thread_loop_unlock(core->loop);
+
+    return core;
+context_connect_error:
+    pw_thread_loop_unlock(core->loop);
+    pw_thread_loop_stop(core->loop);
+thread_loop_start_error:
+    pw_context_destroy(core->context);
+context_new_error:
+    pw_thread_loop_destroy(core->loop);
+thread_loop_new_error:
+    if (core) ffone_rc_unref(core);
+
+    return NULL;
+}
+
+static void ffone_pw_core_dtor(void *opaque) {
+    FFonePWCore *core = opaque;
+    FFONE_RETURN_ON_FAILURE(core);
+
+    pw_thread_loop_lock(core->loop);
+
+    if (core->core) {
+        pw_core_disconnect(core->core);
+        core->core = NULL;
+    }
+
+    pw_thread_loop_unlock(core->loop);
+    pw_thread_loop_stop(core->loop);
+
+    pw_context_destroy(core->context);
+    core->context = NULL;
+
+    pw_thread_loop_destroy(core->loop);
+    core->loop = NULL;
+}
+
+struct pw_core *ffone_pw_core_get_core(FFonePWCore *core) {
+    FFONE_RETURN_VAL_ON_FAILURE(core, NULL);
+
+    return core->core;
+}
+
+struct pw_thread_loop *ffone_pw_core_get_loop(FFonePWCore *core) {
+    FFONE_RETURN_VAL_ON_FAILURE(core, NULL);
+
+    return core->loop;
+}
+
+void ffone_pw_core_loop_lock(FFonePWCore *core) {
+    FFONE_RETURN_ON_FAILURE(core);
+
+    pw_thread_loop_lock(ffone_pw_core_get_loop(core));
+}
+
+void ffone_pw_core_loop_unlock(FFonePWCore *core) {
+    FFONE_RETURN_ON_FAILURE(core);
+
+    pw_thread_loop_unlock(ffone_pw_core_get_loop(core));
+}
+
+void ffone_pw_core_loop_signal(FFonePWCore *core) {
+    FFONE_RETURN_ON_FAILURE(core);
+
+    pw_thread_loop_signal(ffone_pw_core_get_loop(core), false);
+}
+
+void ffone_pw_core_loop_wait(FFonePWCore *core) {
+    FFONE_RETURN_ON_FAILURE(core);
+
+    pw_thread_loop_wait(ffone_pw_core_get_loop(core));
+}
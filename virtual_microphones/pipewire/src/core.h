@@ -0,0 +1,20 @@
+#ifndef _FFONE_PW_CORE_H
+#define _FFONE_PW_CORE_H
+
+#include "rc.h"
+
+#include <pipewire/pipewire.h>
+
+typedef struct FFonePWCore FFonePWCore;
+
+ffone_rc(FFonePWCore) ffone_pw_core_new(void);
+
+struct pw_core *ffone_pw_core_get_core(FFonePWCore *core);
+
+struct pw_thread_loop *ffone_pw_core_get_loop(FFonePWCore *core);
+void ffone_pw_core_loop_lock(FFonePWCore *core);
+void ffone_pw_core_loop_unlock(FFonePWCore *core);
+void ffone_pw_core_loop_signal(FFonePWCore *core);
+void ffone_pw_core_loop_wait(FFonePWCore *core);
+
+#endif /* _FFONE_PW_CORE_H */
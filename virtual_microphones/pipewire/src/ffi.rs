@@ -0,0 +1,34 @@
+use core::audio_system::audio::{ChannelLayout, RawAudioFormat};
+use core::audio_system::queue::RawAudioQueue;
+use std::marker::{PhantomData, PhantomPinned};
+
+#[repr(C)]
+pub struct FFonePWCore {
+    _data: [u8; 0],
+    _marker: PhantomData<(*mut u8, PhantomPinned)>,
+}
+
+#[repr(C)]
+pub struct FFonePWStream {
+    _data: [u8; 0],
+    _marker: PhantomData<(*mut u8, PhantomPinned)>,
+}
+
+extern "C" {
+    pub(crate) fn ffone_pw_core_new() -> *mut FFonePWCore;
+
+    #[allow(improper_ctypes)]
+    pub(crate) fn ffone_pw_stream_new(
+        core: *mut FFonePWCore,
+        queue: *mut RawAudioQueue,
+    ) -> *mut FFonePWStream;
+    pub(crate) fn ffone_pw_stream_play(stream: *mut FFonePWStream);
+    pub(crate) fn ffone_pw_stream_get_negotiated_format(
+        stream: *mut FFonePWStream,
+        format: *mut RawAudioFormat,
+        channels: *mut u8,
+        channel_layout: *mut ChannelLayout,
+        sample_rate: *mut u32,
+    ) -> bool;
+    pub(crate) fn ffone_pw_stream_get_time(stream: *mut FFonePWStream) -> u64;
+}
@@ -0,0 +1,217 @@
+#[cfg(test)]
+mod tests;
+
+extern crate ffi as ffone_ffi;
+
+mod clock;
+mod ffi;
+
+use self::ffi::*;
+use clock::PWClock;
+
+use core::audio_system::audio::{ChannelLayout, RawAudioBuffer, RawAudioFormat, RawAudioInfo};
+use core::audio_system::element::AudioSink;
+use core::audio_system::element::AudioSystemElementMessage;
+use core::audio_system::pipeline::virtual_microphone::*;
+use core::error;
+use core::mueue::*;
+use core::util::*;
+
+use ffone_ffi::audio_system::queue::RawAudioQueueRC;
+use ffone_ffi::rc::ffone_rc_ref;
+use ffone_ffi::rc::ffone_rc_unref;
+
+use std::ptr::NonNull;
+use std::rc::Rc;
+
+/// Exports an `Audio/Source` node into the PipeWire graph, so that the
+/// applications can record from it like from a microphone.
+pub struct PWVirtualMicrophone {
+    send: MessageSender<AudioSystemElementMessage>,
+    input: Option<MessageReceiver<RawAudioBuffer>>,
+
+    queue: RawAudioQueueRC,
+
+    pw_core: NonNull<FFonePWCore>,
+    pw_stream: *mut FFonePWStream,
+
+    playing: bool,
+}
+
+impl PWVirtualMicrophone {
+    pub fn new(send: MessageSender<AudioSystemElementMessage>) -> Option<Self> {
+        let queue = RawAudioQueueRC::new()?;
+        let pw_core = unsafe { NonNull::new(ffone_pw_core_new()) }?;
+
+        Some(Self {
+            send,
+            input: None,
+
+            queue,
+
+            pw_core,
+            pw_stream: std::ptr::null_mut(),
+
+            playing: false,
+        })
+    }
+
+    /// The format which the graph has accepted, once the node is linked.
+    pub fn negotiated_info(&self) -> Option<RawAudioInfo> {
+        if self.pw_stream.is_null() {
+            return None;
+        }
+
+        let mut info = RawAudioInfo {
+            format: RawAudioFormat::Unspecified,
+            channels: 0,
+            channel_layout: ChannelLayout::Unspecified,
+            sample_rate: 0,
+        };
+        let negotiated = unsafe {
+            ffone_pw_stream_get_negotiated_format(
+                self.pw_stream,
+                &mut info.format,
+                &mut info.channels,
+                &mut info.channel_layout,
+                &mut info.sample_rate,
+            )
+        };
+
+        negotiated.then_some(info)
+    }
+
+    fn disconnect(&mut self) {
+        if !self.pw_stream.is_null() {
+            unsafe {
+                ffone_rc_unref(self.pw_stream.cast());
+            }
+        }
+
+        self.pw_stream = std::ptr::null_mut();
+        self.playing = false;
+    }
+}
+
+impl Runnable for PWVirtualMicrophone {
+    fn on_start(&mut self) {
+        self.disconnect();
+
+        self.pw_stream =
+            unsafe { ffone_pw_stream_new(self.pw_core.as_ptr().cast(), self.queue.as_raw()) };
+    }
+
+    fn on_stop(&mut self) {
+        self.disconnect();
+    }
+
+    fn update(&mut self) -> error::Result<()> {
+        let Some(input) = self.input.as_ref() else {
+            return Ok(());
+        };
+
+        for audio in input.iter() {
+            self.queue.push_buffer(audio);
+
+            if !self.playing && !self.pw_stream.is_null() {
+                unsafe {
+                    ffone_pw_stream_play(self.pw_stream);
+                }
+
+                self.playing = true;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Element for PWVirtualMicrophone {
+    type Message = AudioSystemElementMessage;
+
+    fn sender(&self) -> MessageSender<Self::Message> {
+        self.send.clone()
+    }
+
+    fn connect(&mut self, send: MessageSender<Self::Message>) {
+        self.send = send;
+    }
+}
+
+impl AudioSink<RawAudioBuffer> for PWVirtualMicrophone {
+    fn input(&self) -> Option<MessageReceiver<RawAudioBuffer>> {
+        self.input.clone()
+    }
+
+    fn set_input(&mut self, input: MessageReceiver<RawAudioBuffer>) {
+        self.input = Some(input);
+    }
+
+    fn unset_input(&mut self) {
+        self.input = None;
+    }
+}
+
+impl VirtualMicrophone for PWVirtualMicrophone {
+    fn info(&self) -> VirtualMicrophoneInfo {
+        VirtualMicrophoneInfo {
+            name: "PipeWire Virtual Microphone".to_string(),
+        }
+    }
+
+    // The format is renegotiated whenever it changes, which may glitch the
+    // recording applications, so all of the audio is played in one format.
+    fn format(&self) -> Option<RawAudioFormat> {
+        Some(RawAudioFormat::F32LE)
+    }
+
+    fn provide_clock(&self) -> Option<Rc<dyn SlaveClock>> {
+        unsafe {
+            let stream = ffone_rc_ref(self.pw_stream.cast()).cast::<FFonePWStream>();
+            if stream.is_null() {
+                None
+            } else {
+                let clock = PWClock::new(stream);
+                Some(Rc::new(SlavedClock::new(clock)))
+            }
+        }
+    }
+}
+
+impl Drop for PWVirtualMicrophone {
+    fn drop(&mut self) {
+        self.disconnect();
+
+        unsafe {
+            ffone_rc_unref(self.pw_core.as_ptr().cast());
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct PWVirtualMicrophoneBuilder {
+    send: Option<MessageSender<AudioSystemElementMessage>>,
+}
+
+impl PWVirtualMicrophoneBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl ElementBuilder for PWVirtualMicrophoneBuilder {
+    type Element = dyn VirtualMicrophone;
+
+    fn set_sender(&mut self, send: MessageSender<AudioSystemElementMessage>) {
+        self.send = Some(send);
+    }
+
+    fn build(self: Box<Self>) -> error::Result<Box<Self::Element>> {
+        let send = self.send.expect("A sender wasn't provided");
+
+        let mic = PWVirtualMicrophone::new(send)
+            .ok_or_else(|| error::Error::Other("Failed to connect to PipeWire".to_string()))?;
+
+        Ok(Box::new(mic))
+    }
+}
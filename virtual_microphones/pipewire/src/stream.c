@@ -0,0 +1,555 @@
+#include "stream.h"
+
+#include "error.h"
+
+#include <stdio.h>
+#include <string.h>
+#include <time.h>
+
+#include <spa/param/audio/format-utils.h>
+#include <spa/param/format-utils.h>
+#include <spa/pod/builder.h>
+
+#define FORMAT_POD_BUFFER_SIZE 1024
+
+struct FFonePWStream {
+    ffone_rc(FFonePWCore) core; /* const, nonnull */
+    ffone_rc(RawAudioQueue) queue; /* const, nonnull */
+
+    struct pw_stream *stream;
+    struct spa_hook listener;
+    StreamFlags flags;
+
+    uint32_t sample_rate;
+    RawAudioFormat format;
+    uint8_t channels;
+    ChannelLayout channel_layout;
+
+    /* The format which the graph has accepted, valid with
+     * FFONE_STREAM_FLAG_FORMAT_NEGOTIATED. */
+    uint32_t negotiated_sample_rate;
+    RawAudioFormat negotiated_format;
+    uint8_t negotiated_channels;
+    ChannelLayout negotiated_channel_layout;
+
+    /* The graph clock at the start of the last cycle and the monotonic time
+     * when it was read, so that the clock can be interpolated. */
+    uint64_t graph_time;
+    uint64_t graph_time_read_at;
+};
+
+static void stream_dtor(void *opaque);
+
+static int connect_pw_stream(FFonePWStream *stream);
+static void update_format_locked(FFonePWStream *stream);
+
+static const enum spa_audio_format raw_audio_format_cast[] = {
+    [RawAudioFormat_U8] = SPA_AUDIO_FORMAT_U8,
+    [RawAudioFormat_S16LE] = SPA_AUDIO_FORMAT_S16_LE,
+    [RawAudioFormat_S16BE] = SPA_AUDIO_FORMAT_S16_BE,
+    [RawAudioFormat_S24LE] = SPA_AUDIO_FORMAT_S24_LE,
+    [RawAudioFormat_S24BE] = SPA_AUDIO_FORMAT_S24_BE,
+    [RawAudioFormat_S32LE] = SPA_AUDIO_FORMAT_S32_LE,
+    [RawAudioFormat_S32BE] = SPA_AUDIO_FORMAT_S32_BE,
+    [RawAudioFormat_F32LE] = SPA_AUDIO_FORMAT_F32_LE,
+    [RawAudioFormat_F32BE] = SPA_AUDIO_FORMAT_F32_BE,
+};
+
+static uint32_t raw_audio_format_sample_size(RawAudioFormat format) {
+    static const uint32_t sample_sizes[] = {
+        [RawAudioFormat_U8] = 1,
+        [RawAudioFormat_S16LE] = 2,
+        [RawAudioFormat_S16BE] = 2,
+        [RawAudioFormat_S24LE] = 3,
+        [RawAudioFormat_S24BE] = 3,
+        [RawAudioFormat_S32LE] = 4,
+        [RawAudioFormat_S32BE] = 4,
+        [RawAudioFormat_F32LE] = 4,
+        [RawAudioFormat_F32BE] = 4,
+    };
+
+    FFONE_RETURN_VAL_ON_FAILURE(format < RawAudioFormat_Unspecified, 0);
+
+    return sample_sizes[format];
+}
+
+static void on_state_changed(
+    void *userdata,
+    enum pw_stream_state old,
+    enum pw_stream_state state,
+    const char *error
+);
+static void on_param_changed(void *userdata, uint32_t id, const struct spa_pod *param);
+static void on_process(void *userdata);
+
+static const struct pw_stream_events stream_events = {
+    PW_VERSION_STREAM_EVENTS,
+    .state_changed = on_state_changed,
+    .param_changed = on_param_changed,
+    .process = on_process,
+};
+
+ffone_rc(FFonePWStream) ffone_pw_stream_new(
+    FFonePWCore *core,
+    RawAudioQueue *queue
+) {
+    FFONE_RETURN_VAL_ON_FAILURE(core && queue, NULL);
+
+    ffone_rc(FFonePWStream) stream = ffone_rc_new0(FFonePWStream);
+    FFONE_RETURN_VAL_ON_FAILURE(stream, NULL);
+
+    FFONE_GOTO_ON_FAILURE(stream->core = ffone_rc_ref(core), rc_ref_error);
+    FFONE_GOTO_ON_FAILURE(stream->queue = ffone_rc_ref(queue), rc_ref_error);
+
+    stream->sample_rate = FFONE_DEFAULT_SAMPLE_RATE;
+    stream->format = FFONE_DEFAULT_AUDIO_FORMAT;
+    stream->channels = FFONE_DEFAULT_CHANNELS;
+    stream->channel_layout = FFONE_DEFAULT_CHANNEL_LAYOUT;
+
+    ffone_pw_core_loop_lock(stream->core);
+
+    FFONE_GOTO_ON_FAILURE(
+        stream->stream = pw_stream_new(
+            ffone_pw_core_get_core(core),
+            FFONE_PW_NODE_NAME,
+            pw_properties_new(
+                PW_KEY_MEDIA_TYPE, "Audio",
+                PW_KEY_MEDIA_CATEGORY, "Duplex",
+                PW_KEY_MEDIA_CLASS, "Audio/Source",
+                PW_KEY_NODE_NAME, FFONE_PW_NODE_NAME,
+                PW_KEY_NODE_DESCRIPTION, FFONE_PW_NODE_DESCRIPTION,
+                NULL
+            )
+        ),
+        pw_stream_new_error
+    );
+
+    pw_stream_add_listener(stream->stream, &stream->listener, &stream_events, stream);
+
+    FFONE_GOTO_ON_FAILURE(connect_pw_stream(stream) == 0, connect_pw_stream_error);
+
+    ffone_rc_set_dtor(stream, stream_dtor);
+
+    ffone_pw_core_loop_unlock(stream->core);
+
+    return stream;
+connect_pw_stream_error:
+    pw_stream_destroy(stream->stream);
+pw_stream_new_error:
+    ffone_pw_core_loop_unlock(stream->core);
+rc_ref_error:
+    if (stream->queue) ffone_rc_unref(stream->queue);
+    if (stream->core) ffone_rc_unref(stream->core);
+
+    if (stream) ffone_rc_unref(stream);
+
+    return NULL;
+}
+
+static void stream_dtor(void *opaque) {
+    FFonePWStream *stream = opaque;
+    FFONE_RETURN_ON_FAILURE(stream);
+
+    if (stream->stream) {
+        ffone_pw_core_loop_lock(stream->core);
+
+        pw_stream_disconnect(stream->stream);
+        pw_stream_destroy(stream->stream);
+
+        ffone_pw_core_loop_unlock(stream->core);
+    }
+    stream->stream = NULL;
+
+    if (stream->queue) ffone_rc_unref(stream->queue);
+    stream->queue = NULL;
+
+    if (stream->core) ffone_rc_unref(stream->core);
+    stream->core = NULL;
+}
+
+static uint32_t init_channel_positions(
+    uint32_t *positions,
+    uint8_t channels,
+    ChannelLayout channel_layout
+) {
+    static const uint32_t surround71[] = {
+        SPA_AUDIO_CHANNEL_FL, SPA_AUDIO_CHANNEL_FR, SPA_AUDIO_CHANNEL_FC,
+        SPA_AUDIO_CHANNEL_LFE, SPA_AUDIO_CHANNEL_RL, SPA_AUDIO_CHANNEL_RR,
+        SPA_AUDIO_CHANNEL_SL, SPA_AUDIO_CHANNEL_SR,
+    };
+    static const uint32_t quad[] = {
+        SPA_AUDIO_CHANNEL_FL, SPA_AUDIO_CHANNEL_FR,
+        SPA_AUDIO_CHANNEL_RL, SPA_AUDIO_CHANNEL_RR,
+    };
+
+    const uint32_t *layout_positions = NULL;
+    uint8_t layout_channels = 0;
+    switch (channel_layout) {
+        case ChannelLayout_Mono:
+            positions[0] = SPA_AUDIO_CHANNEL_MONO;
+            return channels == 1;
+        case ChannelLayout_Stereo:
+            layout_positions = surround71;
+            layout_channels = 2;
+            break;
+        case ChannelLayout_Surround:
+            layout_positions = surround71;
+            layout_channels = 3;
+            break;
+        case ChannelLayout_Quad:
+            layout_positions = quad;
+            layout_channels = 4;
+            break;
+        case ChannelLayout_Surround51:
+            layout_positions = surround71;
+            layout_channels = 6;
+            break;
+        case ChannelLayout_Surround71:
+            layout_positions = surround71;
+            layout_channels = 8;
+            break;
+        default:
+            break;
+    }
+
+    if (layout_positions && layout_channels == channels) {
+        memcpy(positions, layout_positions, channels * sizeof(*positions));
+    } else {
+        for (uint8_t i = 0; i < channels; i++) {
+            positions[i] = SPA_AUDIO_CHANNEL_AUX0 + i;
+        }
+    }
+
+    return channels;
+}
+
+static const struct spa_pod *build_format(
+    struct spa_pod_builder *builder,
+    uint32_t sample_rate,
+    RawAudioFormat format,
+    uint8_t channels,
+    ChannelLayout channel_layout
+) {
+    FFONE_RETURN_VAL_ON_FAILURE(format < RawAudioFormat_Unspecified, NULL);
+    FFONE_RETURN_VAL_ON_FAILURE(channels > 0 && channels <= SPA_AUDIO_MAX_CHANNELS, NULL);
+
+    struct spa_audio_info_raw info = {
+        .format = raw_audio_format_cast[format],
+        .rate = sample_rate,
+        .channels = channels,
+    };
+    if (init_channel_positions(info.position, channels, channel_layout) != channels) {
+        info.flags |= SPA_AUDIO_FLAG_UNPOSITIONED;
+    }
+
+    return spa_format_audio_raw_build(builder, SPA_PARAM_EnumFormat, &info);
+}
+
+static void on_state_changed(
+    void *userdata,
+    enum pw_stream_state old,
+    enum pw_stream_state state,
+    const char *error
+) {
+    FFonePWStream *stream = userdata;
+    FFONE_RETURN_ON_FAILURE(stream);
+
+    if (state == PW_STREAM_STATE_ERROR) {
+        fprintf(stderr, "PipeWire stream failed: %s\n", error ? error : "unknown error");
+    }
+
+    ffone_pw_core_loop_signal(stream->core);
+
+    (void)old;
+}
+
+static void on_param_changed(void *userdata, uint32_t id, const struct spa_pod *param) {
+    FFonePWStream *stream = userdata;
+    FFONE_RETURN_ON_FAILURE(stream);
+
+    if (id != SPA_PARAM_Format) {
+        return;
+    }
+
+    /* The format is cleared when the stream is unlinked. */
+    stream->flags &= ~FFONE_STREAM_FLAG_FORMAT_NEGOTIATED;
+    if (!param) {
+        return;
+    }
+
+    uint32_t media_type, media_subtype;
+    FFONE_RETURN_ON_FAILURE(
+        spa_format_parse(param, &media_type, &media_subtype) >= 0 &&
+        media_type == SPA_MEDIA_TYPE_audio &&
+        media_subtype == SPA_MEDIA_SUBTYPE_raw
+    );
+
+    struct spa_audio_info_raw info;
+    spa_zero(info);
+    FFONE_RETURN_ON_FAILURE(spa_format_audio_raw_parse(param, &info) >= 0);
+
+    RawAudioFormat format = RawAudioFormat_Unspecified;
+    for (RawAudioFormat i = 0; i < RawAudioFormat_Unspecified; i++) {
+        if (raw_audio_format_cast[i] == info.format) {
+            format = i;
+
+            break;
+        }
+    }
+    FFONE_RETURN_ON_FAILURE(format != RawAudioFormat_Unspecified);
+    FFONE_RETURN_ON_FAILURE(info.channels > 0 && info.channels <= UINT8_MAX);
+
+    stream->negotiated_sample_rate = info.rate;
+    stream->negotiated_format = format;
+    stream->negotiated_channels = info.channels;
+    stream->negotiated_channel_layout = info.channels == stream->channels ?
+        stream->channel_layout :
+        ChannelLayout_Unspecified;
+    stream->flags |= FFONE_STREAM_FLAG_FORMAT_NEGOTIATED;
+}
+
+static int connect_pw_stream(FFonePWStream *stream) {
+    FFONE_RETURN_VAL_ON_FAILURE(stream && stream->stream, FFONE_ERROR_INVALID_ARG);
+
+    int ret;
+
+    uint8_t buffer[FORMAT_POD_BUFFER_SIZE];
+    struct spa_pod_builder builder = SPA_POD_BUILDER_INIT(buffer, sizeof(buffer));
+    const struct spa_pod *params[1];
+    FFONE_RETURN_VAL_ON_FAILURE(
+        params[0] = build_format(
+            &builder,
+            stream->sample_rate,
+            stream->format,
+            stream->channels,
+            stream->channel_layout
+        ),
+        FFONE_ERROR_INVALID_ARG
+    );
+
+    /* The node is created inactive and isn't linked to anything, the
+     * applications choose it as a microphone. */
+    FFONE_RETURN_VAL_ON_FAILURE(
+        (ret = pw_stream_connect(
+            stream->stream,
+            PW_DIRECTION_OUTPUT,
+            PW_ID_ANY,
+            PW_STREAM_FLAG_MAP_BUFFERS | PW_STREAM_FLAG_INACTIVE,
+            params,
+            1
+        )) == 0,
+        FFONE_ERROR(ret)
+    );
+
+    enum pw_stream_state state;
+    while ((state = pw_stream_get_state(stream->stream, NULL)) != PW_STREAM_STATE_PAUSED &&
+        state != PW_STREAM_STATE_STREAMING)
+    {
+        if (state == PW_STREAM_STATE_ERROR || state == PW_STREAM_STATE_UNCONNECTED) {
+            return FFONE_ERROR_CUSTOM;
+        }
+
+        ffone_pw_core_loop_wait(stream->core);
+    }
+
+    return FFONE_SUCCESS;
+}
+
+static uint64_t get_monotonic_time(void) {
+    struct timespec ts;
+    clock_gettime(CLOCK_MONOTONIC, &ts);
+
+    return (uint64_t)ts.tv_sec * SPA_NSEC_PER_SEC + (uint64_t)ts.tv_nsec;
+}
+
+static void update_graph_time_locked(FFonePWStream *stream) {
+    struct pw_time time;
+    FFONE_RETURN_ON_FAILURE(
+        pw_stream_get_time_n(stream->stream, &time, sizeof(time)) == 0 &&
+        time.rate.denom != 0
+    );
+
+    /* The ticks are split, so that they don't overflow when multiplied. */
+    uint64_t secs = time.ticks * time.rate.num / time.rate.denom;
+    uint64_t rem = time.ticks * time.rate.num % time.rate.denom;
+
+    stream->graph_time = secs * SPA_NSEC_PER_SEC + rem * SPA_NSEC_PER_SEC / time.rate.denom;
+    stream->graph_time_read_at = time.now > 0 ? (uint64_t)time.now : get_monotonic_time();
+}
+
+static void on_process(void *userdata) {
+    FFonePWStream *stream = userdata;
+    FFONE_RETURN_ON_FAILURE(stream && stream->stream);
+
+    update_graph_time_locked(stream);
+
+    struct pw_buffer *pw_buffer = pw_stream_dequeue_buffer(stream->stream);
+    FFONE_RETURN_ON_FAILURE(pw_buffer);
+
+    struct spa_data *data = &pw_buffer->buffer->datas[0];
+    uint8_t *write_buffer = data->data;
+    FFONE_GOTO_ON_FAILURE(write_buffer, queue_buffer);
+
+    uint32_t frame_size = raw_audio_format_sample_size(stream->format) * stream->channels;
+    FFONE_GOTO_ON_FAILURE(frame_size > 0, queue_buffer);
+
+    size_t write_buffer_size = data->maxsize / frame_size * frame_size;
+    if (pw_buffer->requested > 0 && pw_buffer->requested * frame_size < write_buffer_size) {
+        write_buffer_size = pw_buffer->requested * frame_size;
+    }
+    uint8_t *write_buffer_cursor = write_buffer;
+    uint8_t *write_buffer_end = write_buffer + write_buffer_size;
+
+    RawAudioQueue *queue = stream->queue;
+
+    ffone_rc_lock(queue);
+    while (write_buffer_cursor < write_buffer_end &&
+        ffone_raw_audio_queue_has_bytes_locked(queue))
+    {
+        size_t read_size = write_buffer_end - write_buffer_cursor;
+        bool have_same_props = false;
+        ffone_raw_audio_queue_read_bytes_with_props_locked(
+            queue,
+            write_buffer_cursor,
+            &read_size,
+            stream->format,
+            stream->channels,
+            stream->sample_rate,
+            &have_same_props
+        );
+
+        if (read_size == 0) {
+            if (!have_same_props) {
+                stream->flags |= FFONE_STREAM_FLAG_OUTDATED_PROPS;
+            }
+
+            break;
+        }
+
+        write_buffer_cursor += read_size;
+    }
+    ffone_rc_unlock(queue);
+
+    if (write_buffer_end - write_buffer_cursor > 0) {
+        memset(write_buffer_cursor, 0, write_buffer_end - write_buffer_cursor);
+    }
+
+    data->chunk->offset = 0;
+    data->chunk->stride = frame_size;
+    data->chunk->size = write_buffer_size;
+queue_buffer:
+    pw_stream_queue_buffer(stream->stream, pw_buffer);
+
+    if (stream->flags & FFONE_STREAM_FLAG_OUTDATED_PROPS) {
+        update_format_locked(stream);
+    }
+}
+
+/* The format is renegotiated in place, so the node stays the same for the
+ * applications which record from it. */
+static void update_format_locked(FFonePWStream *stream) {
+    FFONE_RETURN_ON_FAILURE(stream);
+
+    RawAudioQueue *queue = stream->queue;
+    bool can_update = true;
+
+    RawAudioFormat new_format;
+    can_update &= ffone_raw_audio_queue_front_buffer_format(queue, &new_format);
+
+    uint8_t new_channels;
+    can_update &= ffone_raw_audio_queue_front_buffer_channels(queue, &new_channels);
+
+    ChannelLayout new_channel_layout;
+    can_update &= ffone_raw_audio_queue_front_buffer_channel_layout(
+        queue,
+        &new_channel_layout
+    );
+
+    uint32_t new_sample_rate;
+    can_update &= ffone_raw_audio_queue_front_buffer_sample_rate(queue, &new_sample_rate);
+
+    FFONE_RETURN_ON_FAILURE(can_update);
+
+    uint8_t buffer[FORMAT_POD_BUFFER_SIZE];
+    struct spa_pod_builder builder = SPA_POD_BUILDER_INIT(buffer, sizeof(buffer));
+    const struct spa_pod *params[1];
+    FFONE_RETURN_ON_FAILURE(params[0] = build_format(
+        &builder,
+        new_sample_rate,
+        new_format,
+        new_channels,
+        new_channel_layout
+    ));
+
+    FFONE_RETURN_ON_FAILURE(pw_stream_update_params(stream->stream, params, 1) == 0);
+
+    stream->sample_rate = new_sample_rate;
+    stream->format = new_format;
+    stream->channels = new_channels;
+    stream->channel_layout = new_channel_layout;
+    stream->flags &= ~FFONE_STREAM_FLAG_OUTDATED_PROPS;
+}
+
+void ffone_pw_stream_play(FFonePWStream *stream) {
+    FFONE_RETURN_ON_FAILURE(stream);
+
+    ffone_pw_core_loop_lock(stream->core);
+
+    if (stream->stream && !(stream->flags & FFONE_STREAM_FLAG_PLAYING)) {
+        if (pw_stream_set_active(stream->stream, true) == 0) {
+            stream->flags |= FFONE_STREAM_FLAG_PLAYING;
+        }
+    }
+
+    ffone_pw_core_loop_unlock(stream->core);
+}
+
+bool ffone_pw_stream_get_negotiated_format(
+    FFonePWStream *stream,
+    RawAudioFormat *format,
+    uint8_t *channels,
+    ChannelLayout *channel_layout,
+    uint32_t *sample_rate
+) {
+    FFONE_RETURN_VAL_ON_FAILURE(
+        stream && format && channels && channel_layout && sample_rate,
+        false
+    );
+
+    ffone_pw_core_loop_lock(stream->core);
+
+    bool negotiated = stream->flags & FFONE_STREAM_FLAG_FORMAT_NEGOTIATED;
+    if (negotiated) {
+        *format = stream->negotiated_format;
+        *channels = stream->negotiated_channels;
+        *channel_layout = stream->negotiated_channel_layout;
+        *sample_rate = stream->negotiated_sample_rate;
+    }
+
+    ffone_pw_core_loop_unlock(stream->core);
+
+    return negotiated;
+}
+
+uint64_t ffone_pw_stream_get_time(FFonePWStream *stream) {
+    FFONE_RETURN_VAL_ON_FAILURE(stream, 0);
+
+    ffone_pw_core_loop_lock(stream->core);
+
+    uint64_t time = stream->graph_time;
+
+    /* The graph clock advances only once per cycle, so it is interpolated with
+     * the monotonic clock between the cycles. */
+    if (stream->stream && stream->graph_time_read_at > 0 &&
+        pw_stream_get_state(stream->stream, NULL) == PW_STREAM_STATE_STREAMING)
+    {
+        uint64_t now = get_monotonic_time();
+        if (now > stream->graph_time_read_at) {
+            time += now - stream->graph_time_read_at;
+        }
+    }
+
+    ffone_pw_core_loop_unlock(stream->core);
+
+    return time;
+}
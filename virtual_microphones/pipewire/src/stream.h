@@ -0,0 +1,50 @@
+#ifndef _FFONE_PW_STREAM_H
+#define _FFONE_PW_STREAM_H
+
+#include "core.h"
+
+#include <stdbool.h>
+#include <stdint.h>
+
+#include "audio.h"
+#include "queue.h"
+#include "rc.h"
+
+#define FFONE_PW_NODE_NAME "ffone-virtual-microphone"
+#define FFONE_PW_NODE_DESCRIPTION "FFone Virtual Microphone"
+
+#define FFONE_DEFAULT_SAMPLE_RATE 48000
+#define FFONE_DEFAULT_AUDIO_FORMAT RawAudioFormat_F32LE
+#define FFONE_DEFAULT_CHANNELS 1
+#define FFONE_DEFAULT_CHANNEL_LAYOUT ChannelLayout_Mono
+
+typedef uint32_t StreamFlags;
+
+#define FFONE_STREAM_FLAG_NONE 0
+#define FFONE_STREAM_FLAG_PLAYING (1U << 1)
+#define FFONE_STREAM_FLAG_OUTDATED_PROPS (1U << 2)
+#define FFONE_STREAM_FLAG_FORMAT_NEGOTIATED (1U << 3)
+
+typedef struct FFonePWStream FFonePWStream;
+
+ffone_rc(FFonePWStream) ffone_pw_stream_new(
+    FFonePWCore *core,
+    RawAudioQueue *queue
+);
+
+void ffone_pw_stream_play(FFonePWStream *stream);
+
+/* The format which the graph has accepted for the stream, false until the
+ * stream is linked. */
+bool ffone_pw_stream_get_negotiated_format(
+    FFonePWStream *stream,
+    RawAudioFormat *format,
+    uint8_t *channels,
+    ChannelLayout *channel_layout,
+    uint32_t *sample_rate
+);
+
+/* The time of the graph clock in nanoseconds. */
+uint64_t ffone_pw_stream_get_time(FFonePWStream *stream);
+
+#endif /* _FFONE_PW_STREAM_H */
@@ -0,0 +1,138 @@
+use super::*;
+
+use core::audio_system::audio::ChannelLayout;
+use core::test_util::temp_path;
+
+use std::path::Path;
+use std::process::{Child, Command};
+use std::time::{Duration, Instant};
+
+const SAMPLE_RATE: u32 = 48000;
+const BUFFER_DURATION: ClockTime = ClockTime::from_millis(20);
+const NO_BUFFERS: u64 = 25;
+
+fn tone_samples(no_buffer: u64) -> Vec<f32> {
+    let no_samples = BUFFER_DURATION.to_no_samples(SAMPLE_RATE);
+
+    (0..no_samples)
+        .map(|i| {
+            let t = (no_buffer as usize * no_samples + i) as f32 / SAMPLE_RATE as f32;
+            (t * 440.0 * std::f32::consts::TAU).sin() * 0.1
+        })
+        .collect()
+}
+
+fn tone(no_buffer: u64) -> RawAudioBuffer {
+    let data = tone_samples(no_buffer)
+        .into_iter()
+        .flat_map(f32::to_le_bytes)
+        .collect();
+
+    RawAudioBuffer::new(data, RawAudioFormat::F32LE, 1, SAMPLE_RATE)
+}
+
+// Records the node in the format it's played in, so that the samples arrive
+// unconverted.
+fn spawn_recorder(path: &Path) -> Child {
+    Command::new("pw-record")
+        .args(["--target", "ffone-virtual-microphone"])
+        .args(["--format", "f32", "--rate", "48000", "--channels", "1"])
+        .arg(path)
+        .spawn()
+        .expect("Failed to spawn pw-record")
+}
+
+// The recorder finishes the file only when it's interrupted.
+fn stop_recorder(mut recorder: Child) {
+    unsafe {
+        libc::kill(recorder.id() as libc::pid_t, libc::SIGINT);
+    }
+    let _ = recorder.wait();
+}
+
+fn read_wav_f32_samples(path: &Path) -> Vec<f32> {
+    let file = std::fs::read(path).unwrap();
+    let _ = std::fs::remove_file(path);
+
+    let mut chunks = &file[12..];
+    while chunks.len() >= 8 {
+        let id = &chunks[..4];
+        let size = u32::from_le_bytes(chunks[4..8].try_into().unwrap()) as usize;
+        let size = size.min(chunks.len() - 8);
+        if id == b"data" {
+            return chunks[8..8 + size]
+                .chunks_exact(4)
+                .map(|sample| f32::from_le_bytes(sample.try_into().unwrap()))
+                .collect();
+        }
+
+        // The chunks are padded to an even size.
+        chunks = &chunks[(8 + size + size % 2).min(chunks.len())..];
+    }
+
+    panic!("The recording has no data chunk");
+}
+
+// The daemon may be started without any hardware, e.g. with
+// `pipewire -c pipewire.conf` and `wireplumber`, where the configuration
+// contains a `support.null-audio-sink` node that drives the graph. pw-record
+// links the node to itself, like an application recording from the microphone.
+#[test]
+#[ignore = "needs a running PipeWire daemon and pw-record"]
+fn test_play_tone() {
+    let path = temp_path("test_play_tone.wav");
+
+    let (send, _) = unidirectional_queue();
+    let mut mic = PWVirtualMicrophone::new(send).unwrap();
+    let in_send = mic.create_input();
+
+    mic.on_start();
+    let clock = mic.provide_clock().unwrap();
+
+    let recorder = spawn_recorder(&path);
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while mic.negotiated_info().is_none() && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    assert_eq!(
+        mic.negotiated_info(),
+        Some(RawAudioInfo {
+            format: RawAudioFormat::F32LE,
+            channels: 1,
+            channel_layout: ChannelLayout::Mono,
+            sample_rate: SAMPLE_RATE,
+        })
+    );
+
+    let mut expected = Vec::new();
+    for no_buffer in 0..NO_BUFFERS {
+        expected.extend(tone_samples(no_buffer));
+        let _ = in_send.send(tone(no_buffer));
+    }
+
+    let start_time = clock.get_time();
+    let deadline = Instant::now() + (BUFFER_DURATION * NO_BUFFERS).as_dur() * 2;
+    while Instant::now() < deadline {
+        let _ = mic.update();
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    assert!(clock.get_time() > start_time);
+
+    stop_recorder(recorder);
+    mic.on_stop();
+
+    // The node plays silence until the tone is queued, and the tone itself
+    // starts with a zero sample.
+    let samples = read_wav_f32_samples(&path);
+    let tone_start = samples
+        .iter()
+        .position(|sample| *sample != 0.0)
+        .expect("No audio was recorded");
+    let recorded = &samples[tone_start..];
+    let expected = &expected[1..];
+    assert!(recorded.len() >= expected.len());
+    assert!(recorded
+        .iter()
+        .zip(expected)
+        .all(|(sample, expected)| (sample - expected).abs() < 1e-6));
+}
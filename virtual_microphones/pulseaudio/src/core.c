@@ -3,12 +3,16 @@
 
 #include "error.h"
 
+#include <stdbool.h>
 #include <stdio.h>
 #include <stdlib.h>
+#include <string.h>
 
 struct FFonePACore {
     pa_threaded_mainloop *loop; /* const */
     pa_context *context; /* const */
+
+    int sources_changed;
 };
 
 static void ffone_pa_core_dtor(void *opaque);
@@ -191,6 +195,282 @@ operation_error:
     return FFONE_ERROR_BAD_ALLOC;
 }
 
+typedef struct FFonePAVirtualDeviceQuery {
+    FFonePACore *core;
+    uint32_t idx;
+    pa_proplist *proplist;
+} FFonePAVirtualDeviceQuery;
+
+static bool is_virtual_device(pa_proplist *proplist) {
+    const char *marker = pa_proplist_gets(proplist, FFONE_PA_PROP_VIRTUAL_DEVICE);
+
+    return marker && strcmp(marker, "1") == 0;
+}
+
+static void sink_info_cb(pa_context *c, const pa_sink_info *info, int eol, void *userdata) {
+    FFonePAVirtualDeviceQuery *query = userdata;
+
+    if (eol == 0 && info && is_virtual_device(info->proplist)) {
+        query->idx = info->owner_module;
+        query->proplist = pa_proplist_copy(info->proplist);
+    }
+
+    ffone_pa_core_loop_signal(query->core, 0);
+
+    (void)c;
+}
+
+static void source_info_cb(pa_context *c, const pa_source_info *info, int eol, void *userdata) {
+    FFonePAVirtualDeviceQuery *query = userdata;
+
+    if (eol == 0 && info && is_virtual_device(info->proplist)) {
+        query->idx = info->owner_module;
+        query->proplist = pa_proplist_copy(info->proplist);
+    }
+
+    ffone_pa_core_loop_signal(query->core, 0);
+
+    (void)c;
+}
+
+int ffone_pa_core_find_virtual_device(
+    FFonePACore *core,
+    pa_subscription_mask_t type,
+    const char *name,
+    uint32_t *idx,
+    pa_proplist **proplist
+) {
+    FFONE_RETURN_VAL_ON_FAILURE(core && name && idx && proplist, FFONE_ERROR_INVALID_ARG);
+    FFONE_RETURN_VAL_ON_FAILURE(
+        type == PA_SUBSCRIPTION_MASK_SINK || type == PA_SUBSCRIPTION_MASK_SOURCE,
+        FFONE_ERROR_INVALID_ARG
+    );
+
+    FFonePAVirtualDeviceQuery query = {
+        .core = core,
+        .idx = PA_INVALID_INDEX,
+        .proplist = NULL,
+    };
+
+    pa_threaded_mainloop_lock(core->loop);
+
+    pa_operation *o = type == PA_SUBSCRIPTION_MASK_SINK
+        ? pa_context_get_sink_info_by_name(core->context, name, sink_info_cb, &query)
+        : pa_context_get_source_info_by_name(core->context, name, source_info_cb, &query);
+    FFONE_GOTO_ON_FAILURE(o, operation_error);
+
+    int ret = ffone_pa_core_execute_operation(core, o);
+
+    pa_threaded_mainloop_unlock(core->loop);
+
+    FFONE_GOTO_ON_FAILURE(ret == FFONE_SUCCESS, query_error);
+    ret = FFONE_ERROR_CUSTOM;
+    FFONE_GOTO_ON_FAILURE(query.idx != PA_INVALID_INDEX, query_error);
+    ret = FFONE_ERROR_BAD_ALLOC;
+    FFONE_GOTO_ON_FAILURE(query.proplist, query_error);
+
+    *idx = query.idx;
+    *proplist = query.proplist;
+
+    return FFONE_SUCCESS;
+query_error:
+    if (query.proplist) pa_proplist_free(query.proplist);
+
+    return ret;
+operation_error:
+    pa_threaded_mainloop_unlock(core->loop);
+
+    return FFONE_ERROR_BAD_ALLOC;
+}
+
+typedef struct FFonePASourceStateQuery {
+    FFonePACore *core;
+    FFonePASourceState *state;
+    int found;
+} FFonePASourceStateQuery;
+
+static void source_state_cb(pa_context *c, const pa_source_info *info, int eol, void *userdata) {
+    FFonePASourceStateQuery *query = userdata;
+
+    if (eol == 0 && info) {
+        query->state->volume = pa_sw_volume_to_linear(pa_cvolume_max(&info->volume));
+        query->state->muted = info->mute;
+        query->state->in_use = info->state == PA_SOURCE_RUNNING;
+        query->found = 1;
+    }
+
+    ffone_pa_core_loop_signal(query->core, 0);
+
+    (void)c;
+}
+
+int ffone_pa_core_get_source_state(
+    FFonePACore *core,
+    const char *name,
+    FFonePASourceState *state
+) {
+    FFONE_RETURN_VAL_ON_FAILURE(core && name && state, FFONE_ERROR_INVALID_ARG);
+
+    FFonePASourceStateQuery query = {
+        .core = core,
+        .state = state,
+        .found = 0,
+    };
+
+    pa_threaded_mainloop_lock(core->loop);
+
+    pa_operation *o = pa_context_get_source_info_by_name(
+        core->context,
+        name,
+        source_state_cb,
+        &query
+    );
+    FFONE_GOTO_ON_FAILURE(o, operation_error);
+
+    int ret = ffone_pa_core_execute_operation(core, o);
+
+    pa_threaded_mainloop_unlock(core->loop);
+
+    FFONE_RETURN_VAL_ON_FAILURE(ret == FFONE_SUCCESS, ret);
+    FFONE_RETURN_VAL_ON_FAILURE(query.found, FFONE_ERROR_CUSTOM);
+
+    return FFONE_SUCCESS;
+operation_error:
+    pa_threaded_mainloop_unlock(core->loop);
+
+    return FFONE_ERROR_BAD_ALLOC;
+}
+
+typedef struct FFonePASuccessQuery {
+    FFonePACore *core;
+    int success;
+} FFonePASuccessQuery;
+
+static void success_cb(pa_context *c, int success, void *userdata) {
+    FFonePASuccessQuery *query = userdata;
+
+    query->success = success;
+
+    ffone_pa_core_loop_signal(query->core, 0);
+
+    (void)c;
+}
+
+/* Expects the loop to be locked. */
+static int execute_success_operation(FFonePASuccessQuery *query, pa_operation *o) {
+    FFONE_RETURN_VAL_ON_FAILURE(o, FFONE_ERROR_BAD_ALLOC);
+
+    int ret = ffone_pa_core_execute_operation(query->core, o);
+    FFONE_RETURN_VAL_ON_FAILURE(ret == FFONE_SUCCESS, ret);
+    FFONE_RETURN_VAL_ON_FAILURE(query->success, FFONE_ERROR_CUSTOM);
+
+    return FFONE_SUCCESS;
+}
+
+int ffone_pa_core_set_source_volume(FFonePACore *core, const char *name, double volume) {
+    FFONE_RETURN_VAL_ON_FAILURE(core && name && volume >= 0.0, FFONE_ERROR_INVALID_ARG);
+
+    pa_cvolume cvolume;
+    pa_cvolume_set(&cvolume, FFONE_PA_DEFAULT_CHANNELS, pa_sw_volume_from_linear(volume));
+
+    FFonePASuccessQuery query = {
+        .core = core,
+        .success = 0,
+    };
+
+    pa_threaded_mainloop_lock(core->loop);
+
+    int ret = execute_success_operation(&query, pa_context_set_source_volume_by_name(
+        core->context,
+        name,
+        &cvolume,
+        success_cb,
+        &query
+    ));
+
+    pa_threaded_mainloop_unlock(core->loop);
+
+    return ret;
+}
+
+int ffone_pa_core_set_source_mute(FFonePACore *core, const char *name, int muted) {
+    FFONE_RETURN_VAL_ON_FAILURE(core && name, FFONE_ERROR_INVALID_ARG);
+
+    FFonePASuccessQuery query = {
+        .core = core,
+        .success = 0,
+    };
+
+    pa_threaded_mainloop_lock(core->loop);
+
+    int ret = execute_success_operation(&query, pa_context_set_source_mute_by_name(
+        core->context,
+        name,
+        muted,
+        success_cb,
+        &query
+    ));
+
+    pa_threaded_mainloop_unlock(core->loop);
+
+    return ret;
+}
+
+/* Runs in the loop thread, so the flag is guarded by the loop lock. */
+static void subscribe_cb(
+    pa_context *c,
+    pa_subscription_event_type_t type,
+    uint32_t idx,
+    void *userdata
+) {
+    FFonePACore *core = userdata;
+
+    core->sources_changed = 1;
+
+    (void)c;
+    (void)type;
+    (void)idx;
+}
+
+int ffone_pa_core_subscribe_sources(FFonePACore *core) {
+    FFONE_RETURN_VAL_ON_FAILURE(core, FFONE_ERROR_INVALID_ARG);
+
+    FFonePASuccessQuery query = {
+        .core = core,
+        .success = 0,
+    };
+
+    pa_threaded_mainloop_lock(core->loop);
+
+    pa_context_set_subscribe_callback(core->context, subscribe_cb, core);
+    /* The initial state is reported as well. */
+    core->sources_changed = 1;
+
+    int ret = execute_success_operation(&query, pa_context_subscribe(
+        core->context,
+        PA_SUBSCRIPTION_MASK_SOURCE | PA_SUBSCRIPTION_MASK_SOURCE_OUTPUT,
+        success_cb,
+        &query
+    ));
+
+    pa_threaded_mainloop_unlock(core->loop);
+
+    return ret;
+}
+
+int ffone_pa_core_take_sources_changed(FFonePACore *core) {
+    FFONE_RETURN_VAL_ON_FAILURE(core, 0);
+
+    pa_threaded_mainloop_lock(core->loop);
+
+    int changed = core->sources_changed;
+    core->sources_changed = 0;
+
+    pa_threaded_mainloop_unlock(core->loop);
+
+    return changed;
+}
+
 int ffone_pa_core_unload_virtual_device(
     FFonePACore *core,
     uint32_t idx,
@@ -7,6 +7,12 @@
 
 typedef struct FFonePACore FFonePACore;
 
+typedef struct FFonePASourceState {
+    double volume; /* linear */
+    int muted;
+    int in_use; /* some application records from the source */
+} FFonePASourceState;
+
 ffone_rc(FFonePACore) ffone_pa_core_new(void);
 
 pa_context *ffone_pa_core_get_context(FFonePACore *core);
@@ -26,6 +32,29 @@ int ffone_pa_core_load_virtual_device(
     pa_context_index_cb_t cb,
     void *userdata
 );
+/* Finds the module which owns the sink or the source with the name, if ffone
+ * loaded it, and copies the properties of the device. The caller frees them. */
+int ffone_pa_core_find_virtual_device(
+    FFonePACore *core,
+    pa_subscription_mask_t type,
+    const char *name,
+    uint32_t *idx,
+    pa_proplist **proplist
+);
+/* The source is looked up by its name, so it may be reloaded in between. */
+int ffone_pa_core_get_source_state(
+    FFonePACore *core,
+    const char *name,
+    FFonePASourceState *state
+);
+int ffone_pa_core_set_source_volume(FFonePACore *core, const char *name, double volume);
+int ffone_pa_core_set_source_mute(FFonePACore *core, const char *name, int muted);
+
+/* Watches the sources and the recording streams. */
+int ffone_pa_core_subscribe_sources(FFonePACore *core);
+/* Returns 1 if the sources changed since the previous call. */
+int ffone_pa_core_take_sources_changed(FFonePACore *core);
+
 int ffone_pa_core_unload_virtual_device(
     FFonePACore *core,
     uint32_t idx,
@@ -1,4 +1,5 @@
 use core::audio_system::queue::RawAudioQueue;
+use std::ffi::c_char;
 use std::marker::{PhantomData, PhantomPinned};
 
 #[repr(C)]
@@ -7,6 +8,20 @@ pub struct FFonePACore {
     _marker: PhantomData<(*mut u8, PhantomPinned)>,
 }
 
+#[repr(C)]
+pub struct FFonePAVirtualDeviceProps {
+    _data: [u8; 0],
+    _marker: PhantomData<(*mut u8, PhantomPinned)>,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FFonePASourceState {
+    pub volume: f64,
+    pub muted: i32,
+    pub in_use: i32,
+}
+
 #[repr(C)]
 pub struct FFonePAStream {
     _data: [u8; 0],
@@ -15,11 +30,41 @@ pub struct FFonePAStream {
 
 extern "C" {
     pub(crate) fn ffone_pa_core_new() -> *mut FFonePACore;
+    pub(crate) fn ffone_pa_core_get_source_state(
+        core: *mut FFonePACore,
+        name: *const c_char,
+        state: *mut FFonePASourceState,
+    ) -> i32;
+    pub(crate) fn ffone_pa_core_set_source_volume(
+        core: *mut FFonePACore,
+        name: *const c_char,
+        volume: f64,
+    ) -> i32;
+    pub(crate) fn ffone_pa_core_set_source_mute(
+        core: *mut FFonePACore,
+        name: *const c_char,
+        muted: i32,
+    ) -> i32;
+    pub(crate) fn ffone_pa_core_subscribe_sources(core: *mut FFonePACore) -> i32;
+    pub(crate) fn ffone_pa_core_take_sources_changed(core: *mut FFonePACore) -> i32;
+
+    pub(crate) fn ffone_pa_virtual_device_props_new(
+        name: *const c_char,
+    ) -> *mut FFonePAVirtualDeviceProps;
+    pub(crate) fn ffone_pa_virtual_device_props_get_name(
+        props: *mut FFonePAVirtualDeviceProps,
+    ) -> *const c_char;
+    pub(crate) fn ffone_pa_virtual_device_props_set(
+        props: *mut FFonePAVirtualDeviceProps,
+        key: *const c_char,
+        value: *const c_char,
+    ) -> i32;
 
     #[allow(improper_ctypes)]
     pub(crate) fn ffone_pa_stream_new(
         core: *mut FFonePACore,
         queue: *mut RawAudioQueue,
+        props: *mut FFonePAVirtualDeviceProps,
     ) -> *mut FFonePAStream;
     pub(crate) fn ffone_pa_stream_play(stream: *mut FFonePAStream);
     pub(crate) fn ffone_pa_stream_get_time(stream: *mut FFonePAStream) -> u64;
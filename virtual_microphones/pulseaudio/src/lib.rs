@@ -6,7 +6,7 @@ mod ffi;
 use self::ffi::*;
 use clock::PAClock;
 
-use core::audio_system::audio::RawAudioBuffer;
+use core::audio_system::audio::{RawAudioBuffer, RawAudioFormat};
 use core::audio_system::element::AudioSink;
 use core::audio_system::element::AudioSystemElementMessage;
 use core::audio_system::pipeline::virtual_microphone::*;
@@ -18,11 +18,19 @@ use ffone_ffi::audio_system::queue::RawAudioQueueRC;
 use ffone_ffi::rc::ffone_rc_ref;
 use ffone_ffi::rc::ffone_rc_unref;
 
+use std::ffi::{c_char, CString};
 use std::ptr::NonNull;
 use std::rc::Rc;
 
 const MAX_PREBUF: usize = 0;
 
+const DEFAULT_NAME: &str = "ffone_virtual_microphone";
+const DEFAULT_DESCRIPTION: &str = "FFone Virtual Microphone";
+const DEFAULT_ICON_NAME: &str = "audio-input-microphone";
+
+const PROP_DEVICE_DESCRIPTION: &str = "device.description";
+const PROP_DEVICE_ICON_NAME: &str = "device.icon_name";
+
 pub struct PAVirtualMicrophone {
     send: MessageSender<AudioSystemElementMessage>,
     input: Option<MessageReceiver<RawAudioBuffer>>,
@@ -30,16 +38,36 @@ pub struct PAVirtualMicrophone {
     queue: RawAudioQueueRC,
 
     pa_core: NonNull<FFonePACore>,
+    pa_props: NonNull<FFonePAVirtualDeviceProps>,
     pa_stream: *mut FFonePAStream,
 
     prebuf: usize,
     playing: bool,
+
+    subscribed: bool,
+    source_state: Option<FFonePASourceState>,
 }
 
 impl PAVirtualMicrophone {
+    /// The microphone with the default name and properties.
     pub fn new(send: MessageSender<AudioSystemElementMessage>) -> Option<Self> {
-        let queue = RawAudioQueueRC::new()?;
-        let pa_core = unsafe { NonNull::new(ffone_pa_core_new()) }?;
+        let pa_props = PAVirtualMicrophoneBuilder::new().create_props()?;
+
+        Self::with_props(send, pa_props)
+    }
+
+    fn with_props(
+        send: MessageSender<AudioSystemElementMessage>,
+        pa_props: NonNull<FFonePAVirtualDeviceProps>,
+    ) -> Option<Self> {
+        let queue = RawAudioQueueRC::new();
+        let pa_core = queue
+            .as_ref()
+            .and_then(|_| unsafe { NonNull::new(ffone_pa_core_new()) });
+        let (Some(queue), Some(pa_core)) = (queue, pa_core) else {
+            unsafe { ffone_rc_unref(pa_props.as_ptr().cast()) };
+            return None;
+        };
 
         Some(Self {
             send,
@@ -48,12 +76,59 @@ impl PAVirtualMicrophone {
             queue,
 
             pa_core,
+            pa_props,
             pa_stream: std::ptr::null_mut(),
 
             prebuf: 0,
             playing: false,
+
+            subscribed: false,
+            source_state: None,
         })
     }
+
+    fn source_name(&self) -> *const c_char {
+        unsafe { ffone_pa_virtual_device_props_get_name(self.pa_props.as_ptr()) }
+    }
+
+    // The source exists only while the microphone runs.
+    fn source_state(&self) -> error::Result<FFonePASourceState> {
+        let mut state = FFonePASourceState::default();
+        let ret = unsafe {
+            ffone_pa_core_get_source_state(self.pa_core.as_ptr(), self.source_name(), &mut state)
+        };
+
+        if ret == 0 {
+            Ok(state)
+        } else {
+            Err(error::Error::Other(format!(
+                "Failed to get the state of the PulseAudio source: {ret}"
+            )))
+        }
+    }
+
+    fn report_source_state(&mut self) {
+        let Ok(state) = self.source_state() else {
+            return;
+        };
+        let old_state = self.source_state.replace(state);
+
+        if old_state.map(|old| (old.volume, old.muted)) != Some((state.volume, state.muted)) {
+            let _ = self
+                .send
+                .send(AudioSystemElementMessage::VirtualMicrophoneVolumeChanged {
+                    volume: state.volume,
+                    muted: state.muted != 0,
+                });
+        }
+        if old_state.map(|old| old.in_use) != Some(state.in_use) {
+            let _ = self
+                .send
+                .send(AudioSystemElementMessage::VirtualMicrophoneInUseChanged {
+                    in_use: state.in_use != 0,
+                });
+        }
+    }
 }
 
 impl Runnable for PAVirtualMicrophone {
@@ -65,7 +140,11 @@ impl Runnable for PAVirtualMicrophone {
         }
 
         self.pa_stream = unsafe {
-            ffone_pa_stream_new(self.pa_core.as_ptr().cast(), self.queue.as_raw())
+            ffone_pa_stream_new(
+                self.pa_core.as_ptr().cast(),
+                self.queue.as_raw(),
+                self.pa_props.as_ptr(),
+            )
         };
     }
 
@@ -76,6 +155,12 @@ impl Runnable for PAVirtualMicrophone {
     }
 
     fn update(&mut self) -> error::Result<()> {
+        if self.subscribed
+            && unsafe { ffone_pa_core_take_sources_changed(self.pa_core.as_ptr()) } != 0
+        {
+            self.report_source_state();
+        }
+
         let Some(input) = self.input.as_ref() else {
             return Ok(());
         };
@@ -131,6 +216,12 @@ impl VirtualMicrophone for PAVirtualMicrophone {
         }
     }
 
+    // The stream has to be reconnected whenever its format changes, so all of
+    // the audio is converted to the format which keeps the most precision.
+    fn format(&self) -> Option<RawAudioFormat> {
+        Some(RawAudioFormat::F32LE)
+    }
+
     fn provide_clock(&self) -> Option<Rc<dyn SlaveClock>> {
         let clock: Option<Rc<dyn SlaveClock>> = unsafe {
             let stream = ffone_rc_ref(self.pa_stream.cast()).cast::<FFonePAStream>();
@@ -144,12 +235,184 @@ impl VirtualMicrophone for PAVirtualMicrophone {
 
         clock
     }
+
+    fn volume(&self) -> Option<f64> {
+        self.source_state().ok().map(|state| state.volume)
+    }
+
+    fn set_volume(&mut self, volume: f64) -> error::Result<()> {
+        let ret = unsafe {
+            ffone_pa_core_set_source_volume(self.pa_core.as_ptr(), self.source_name(), volume)
+        };
+
+        if ret == 0 {
+            Ok(())
+        } else {
+            Err(error::Error::Other(format!(
+                "Failed to set the volume of the PulseAudio source: {ret}"
+            )))
+        }
+    }
+
+    fn muted(&self) -> Option<bool> {
+        self.source_state().ok().map(|state| state.muted != 0)
+    }
+
+    fn set_muted(&mut self, muted: bool) -> error::Result<()> {
+        let ret = unsafe {
+            ffone_pa_core_set_source_mute(self.pa_core.as_ptr(), self.source_name(), muted.into())
+        };
+
+        if ret == 0 {
+            Ok(())
+        } else {
+            Err(error::Error::Other(format!(
+                "Failed to mute the PulseAudio source: {ret}"
+            )))
+        }
+    }
+
+    // The source is running only while some application records from it.
+    fn in_use(&self) -> Option<bool> {
+        self.source_state().ok().map(|state| state.in_use != 0)
+    }
+
+    fn subscribe(&mut self) -> error::Result<()> {
+        let ret = unsafe { ffone_pa_core_subscribe_sources(self.pa_core.as_ptr()) };
+        if ret != 0 {
+            return Err(error::Error::Other(format!(
+                "Failed to subscribe to the PulseAudio sources: {ret}"
+            )));
+        }
+
+        self.subscribed = true;
+        self.source_state = None;
+
+        Ok(())
+    }
 }
 
 impl Drop for PAVirtualMicrophone {
     fn drop(&mut self) {
         unsafe {
+            ffone_rc_unref(self.pa_props.as_ptr().cast());
             ffone_rc_unref(self.pa_core.as_ptr().cast());
         }
     }
 }
+
+/// The microphone keeps its name across the runs, so the applications remember
+/// it. A device which ffone left over after a crash is reused, so the
+/// applications stay connected to it, unless its description or properties
+/// changed.
+pub struct PAVirtualMicrophoneBuilder {
+    send: Option<MessageSender<AudioSystemElementMessage>>,
+
+    name: String,
+    description: String,
+    icon_name: Option<String>,
+    properties: Vec<(String, String)>,
+}
+
+impl PAVirtualMicrophoneBuilder {
+    pub fn new() -> Self {
+        Self {
+            send: None,
+
+            name: DEFAULT_NAME.to_string(),
+            description: DEFAULT_DESCRIPTION.to_string(),
+            icon_name: Some(DEFAULT_ICON_NAME.to_string()),
+            properties: Vec::new(),
+        }
+    }
+
+    /// The name of the source may contain only the ASCII letters, the digits,
+    /// `_`, `-` and `.`.
+    pub fn set_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// The name which the applications show in their lists of microphones.
+    pub fn set_description(mut self, description: impl Into<String>) -> Self {
+        self.description = description.into();
+        self
+    }
+
+    pub fn set_icon_name(mut self, icon_name: Option<String>) -> Self {
+        self.icon_name = icon_name;
+        self
+    }
+
+    /// Any property of the `pa_proplist` of the source, which overrides the
+    /// description and the icon.
+    pub fn set_property(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.properties.push((key.into(), value.into()));
+        self
+    }
+
+    fn create_props(&self) -> Option<NonNull<FFonePAVirtualDeviceProps>> {
+        let name = CString::new(self.name.as_str()).ok()?;
+        let props = unsafe { NonNull::new(ffone_pa_virtual_device_props_new(name.as_ptr())) }?;
+
+        let properties = [
+            Some((PROP_DEVICE_DESCRIPTION, self.description.as_str())),
+            self.icon_name
+                .as_deref()
+                .map(|icon_name| (PROP_DEVICE_ICON_NAME, icon_name)),
+        ]
+        .into_iter()
+        .flatten()
+        .chain(
+            self.properties
+                .iter()
+                .map(|(key, value)| (key.as_str(), value.as_str())),
+        );
+
+        for (key, value) in properties {
+            let is_set = CString::new(key)
+                .ok()
+                .zip(CString::new(value).ok())
+                .is_some_and(|(key, value)| unsafe {
+                    ffone_pa_virtual_device_props_set(props.as_ptr(), key.as_ptr(), value.as_ptr())
+                        == 0
+                });
+
+            if !is_set {
+                unsafe { ffone_rc_unref(props.as_ptr().cast()) };
+                return None;
+            }
+        }
+
+        Some(props)
+    }
+}
+
+impl Default for PAVirtualMicrophoneBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ElementBuilder for PAVirtualMicrophoneBuilder {
+    type Element = dyn VirtualMicrophone;
+
+    fn set_sender(&mut self, send: MessageSender<AudioSystemElementMessage>) {
+        self.send = Some(send);
+    }
+
+    fn build(self: Box<Self>) -> error::Result<Box<Self::Element>> {
+        let send = self.send.clone().expect("A sender wasn't provided");
+
+        let pa_props = self.create_props().ok_or_else(|| {
+            error::Error::Other(format!(
+                "Invalid properties of the PulseAudio device {}",
+                self.name
+            ))
+        })?;
+        let mic = PAVirtualMicrophone::with_props(send, pa_props)
+            .ok_or_else(|| error::Error::Other("Failed to connect to PulseAudio".to_string()))?;
+
+        Ok(Box::new(mic))
+    }
+}
@@ -26,7 +26,7 @@ fn main() {
             data.push(wave);
         }
 
-        let _ = in_send.send(RawAudioBuffer::new(data, RawAudioFormat::U8, 8000));
+        let _ = in_send.send(RawAudioBuffer::new(data, RawAudioFormat::U8, 1, 8000));
     }
 
     /* for _ in 0..8 * 3 {
@@ -43,7 +43,7 @@ fn main() {
             data.extend(bytes);
         }
 
-        let _ = in_send.send(RawAudioBuffer::new(data, RawAudioFormat::S16LE, 8000));
+        let _ = in_send.send(RawAudioBuffer::new(data, RawAudioFormat::S16LE, 1, 8000));
     }
 
     for _ in 0..44 * 3 {
@@ -58,7 +58,7 @@ fn main() {
             data.push(wave);
         }
 
-        let _ = in_send.send(RawAudioBuffer::new(data, RawAudioFormat::U8, 44100));
+        let _ = in_send.send(RawAudioBuffer::new(data, RawAudioFormat::U8, 1, 44100));
     }
 
     for _ in 0..48 * 3 {
@@ -73,7 +73,7 @@ fn main() {
             data.push(wave);
         }
 
-        let _ = in_send.send(RawAudioBuffer::new(data, RawAudioFormat::U8, 48000));
+        let _ = in_send.send(RawAudioBuffer::new(data, RawAudioFormat::U8, 1, 48000));
     } */
 
     loop {
@@ -28,6 +28,8 @@ struct FFonePAStream {
 
     uint32_t sample_rate;
     RawAudioFormat format;
+    uint8_t channels;
+    ChannelLayout channel_layout;
 
     uint64_t time_base;
 
@@ -40,7 +42,9 @@ static void stream_dtor(void *opaque);
 static pa_stream *new_pa_stream(
     FFonePACore *core,
     uint32_t sample_rate,
-    RawAudioFormat format
+    RawAudioFormat format,
+    uint8_t channels,
+    ChannelLayout channel_layout
 );
 static int connect_pa_stream(pa_stream *stream, FFonePACore *core, FFonePAStream *s);
 
@@ -48,9 +52,10 @@ static void *stream_update_thread(void *userdata);
 
 ffone_rc(FFonePAStream) ffone_pa_stream_new(
     FFonePACore *core,
-    RawAudioQueue *queue
+    RawAudioQueue *queue,
+    FFonePAVirtualDeviceProps *props
 ) {
-    FFONE_RETURN_VAL_ON_FAILURE(core && queue, NULL);
+    FFONE_RETURN_VAL_ON_FAILURE(core && queue && props, NULL);
 
     ffone_rc(FFonePAStream) stream = ffone_rc_new0(FFonePAStream);
     FFONE_RETURN_VAL_ON_FAILURE(stream, NULL);
@@ -58,9 +63,9 @@ ffone_rc(FFonePAStream) ffone_pa_stream_new(
     FFONE_GOTO_ON_FAILURE(stream->core = ffone_rc_ref(core), rc_ref_error);
     FFONE_GOTO_ON_FAILURE(stream->queue = ffone_rc_ref(queue), rc_ref_error);
 
-    FFONE_GOTO_ON_FAILURE(stream->sink = ffone_pa_virtual_sink_new(core), rc_ref_error);
+    FFONE_GOTO_ON_FAILURE(stream->sink = ffone_pa_virtual_sink_new(core, props), rc_ref_error);
     FFONE_GOTO_ON_FAILURE(
-        stream->source = ffone_pa_virtual_source_new(core, stream->sink),
+        stream->source = ffone_pa_virtual_source_new(core, stream->sink, props),
         virtual_source_new_error
     );
 
@@ -76,6 +81,8 @@ ffone_rc(FFonePAStream) ffone_pa_stream_new(
 
     stream->sample_rate = FFONE_DEFAULT_SAMPLE_RATE;
     stream->format = FFONE_DEFAULT_AUDIO_FORMAT;
+    stream->channels = FFONE_DEFAULT_CHANNELS;
+    stream->channel_layout = FFONE_DEFAULT_CHANNEL_LAYOUT;
 
     stream->time_base = 0;
     
@@ -83,7 +90,13 @@ ffone_rc(FFonePAStream) ffone_pa_stream_new(
     ffone_pa_core_loop_lock(stream->core);
 
     FFONE_GOTO_ON_FAILURE(
-        stream->stream = new_pa_stream(core, stream->sample_rate, stream->format),
+        stream->stream = new_pa_stream(
+            core,
+            stream->sample_rate,
+            stream->format,
+            stream->channels,
+            stream->channel_layout
+        ),
         new_pa_stream_error
     );
 
@@ -159,10 +172,38 @@ static void stream_dtor(void *opaque) {
     stream->core = NULL;
 }
 
+static pa_channel_map *init_channel_map(
+    pa_channel_map *map,
+    uint8_t channels,
+    ChannelLayout channel_layout
+) {
+    switch (channel_layout) {
+        case ChannelLayout_Mono:
+            return pa_channel_map_init_mono(map);
+        case ChannelLayout_Stereo:
+            return pa_channel_map_init_stereo(map);
+        case ChannelLayout_Surround:
+            return pa_channel_map_parse(map, "front-left,front-right,front-center");
+        case ChannelLayout_Quad:
+            return pa_channel_map_parse(map, "front-left,front-right,rear-left,rear-right");
+        case ChannelLayout_Surround51:
+            return pa_channel_map_parse(map,
+                "front-left,front-right,front-center,lfe,rear-left,rear-right");
+        case ChannelLayout_Surround71:
+            return pa_channel_map_parse(map,
+                "front-left,front-right,front-center,lfe,rear-left,rear-right,"
+                "side-left,side-right");
+        default:
+            return pa_channel_map_init_extend(map, channels, PA_CHANNEL_MAP_DEFAULT);
+    }
+}
+
 static pa_stream *new_pa_stream(
     FFonePACore *core,
     uint32_t sample_rate,
-    RawAudioFormat format
+    RawAudioFormat format,
+    uint8_t channels,
+    ChannelLayout channel_layout
 ) {
     static pa_sample_format_t raw_audio_format_cast[] = {
         [RawAudioFormat_U8] = PA_SAMPLE_U8,
@@ -182,10 +223,13 @@ static pa_stream *new_pa_stream(
     const pa_sample_spec sample_spec = {
         .format = raw_audio_format_cast[format],
         .rate = sample_rate,
-        .channels = 1,
+        .channels = channels,
     };
+    FFONE_RETURN_VAL_ON_FAILURE(pa_sample_spec_valid(&sample_spec), NULL);
+
     pa_channel_map map;
-    pa_channel_map_init_mono(&map);
+    FFONE_RETURN_VAL_ON_FAILURE(init_channel_map(&map, channels, channel_layout), NULL);
+    FFONE_RETURN_VAL_ON_FAILURE(pa_channel_map_compatible(&map, &sample_spec), NULL);
 
     pa_stream *stream = pa_stream_new(
         context,
@@ -254,8 +298,8 @@ static int connect_pa_stream(
     pa_stream_set_state_callback(stream, stream_state_cb, ffone_pa_core_get_loop(core));
 
     FFONE_RETURN_VAL_ON_FAILURE(
-        (ret = pa_stream_connect_playback(stream, 
-            /* sink->base.name */ NULL, &buf_attr, flags, NULL, NULL)) == 0,
+        (ret = pa_stream_connect_playback(stream,
+            ffone_pa_virtual_sink_get_name(s->sink), &buf_attr, flags, NULL, NULL)) == 0,
         FFONE_ERROR(ret)
     );
 
@@ -331,6 +375,7 @@ static void try_write_locked(FFonePAStream *stream) {
             write_buffer_cursor,
             &read_size,
             stream->format,
+            stream->channels,
             stream->sample_rate,
             &have_same_props
         );
@@ -396,7 +441,9 @@ static void ffone_pa_stream_play_locked(FFonePAStream *stream);
 static void update_pa_stream_locked(
     FFonePAStream *stream,
     uint32_t sample_rate,
-    RawAudioFormat format
+    RawAudioFormat format,
+    uint8_t channels,
+    ChannelLayout channel_layout
 ) {
     FFONE_RETURN_ON_FAILURE(stream);
 
@@ -412,7 +459,7 @@ static void update_pa_stream_locked(
     }
 
     FFONE_RETURN_ON_FAILURE(stream->stream = new_pa_stream(
-        stream->core, sample_rate, format));
+        stream->core, sample_rate, format, channels, channel_layout));
     FFONE_GOTO_ON_FAILURE(
         connect_pa_stream(stream->stream, stream->core, stream) == 0,
         connect_pa_stream_error
@@ -424,6 +471,8 @@ static void update_pa_stream_locked(
 
     stream->sample_rate = sample_rate;
     stream->format = format;
+    stream->channels = channels;
+    stream->channel_layout = channel_layout;
 
     return;
 connect_pa_stream_error:
@@ -434,12 +483,16 @@ connect_pa_stream_error:
 static void update_props_locked(
     FFonePAStream *stream,
     uint32_t sample_rate,
-    RawAudioFormat format
+    RawAudioFormat format,
+    uint8_t channels,
+    ChannelLayout channel_layout
 ) {
     FFONE_RETURN_ON_FAILURE(stream);
 
-    if (stream->format != format) {
-        update_pa_stream_locked(stream, sample_rate, format);
+    if (stream->format != format || stream->channels != channels ||
+        stream->channel_layout != channel_layout)
+    {
+        update_pa_stream_locked(stream, sample_rate, format, channels, channel_layout);
     } else if (stream->sample_rate != sample_rate) {
         update_sample_rate_locked(stream, sample_rate);
     }
@@ -455,11 +508,26 @@ static void fix_outdated_props_locked(FFonePAStream *stream) {
         RawAudioFormat new_format;
         can_update &= ffone_raw_audio_queue_front_buffer_format(queue, &new_format);
 
+        uint8_t new_channels;
+        can_update &= ffone_raw_audio_queue_front_buffer_channels(queue, &new_channels);
+
+        ChannelLayout new_channel_layout;
+        can_update &= ffone_raw_audio_queue_front_buffer_channel_layout(
+            queue,
+            &new_channel_layout
+        );
+
         uint32_t new_sample_rate;
         can_update &= ffone_raw_audio_queue_front_buffer_sample_rate(queue, &new_sample_rate);
 
         if (can_update) {
-            update_props_locked(stream, new_sample_rate, new_format);
+            update_props_locked(
+                stream,
+                new_sample_rate,
+                new_format,
+                new_channels,
+                new_channel_layout
+            );
             stream->flags &= ~FFONE_STREAM_FLAG_OUTDATED_PROPS;
         }
     }
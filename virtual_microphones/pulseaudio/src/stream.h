@@ -11,6 +11,8 @@
 
 #define FFONE_DEFAULT_SAMPLE_RATE 8000
 #define FFONE_DEFAULT_AUDIO_FORMAT RawAudioFormat_U8
+#define FFONE_DEFAULT_CHANNELS 1
+#define FFONE_DEFAULT_CHANNEL_LAYOUT ChannelLayout_Mono
 
 typedef uint32_t StreamFlags;
 
@@ -23,7 +25,8 @@ typedef struct FFonePAStream FFonePAStream;
 
 ffone_rc(FFonePAStream) ffone_pa_stream_new(
     FFonePACore *core,
-    RawAudioQueue *queue
+    RawAudioQueue *queue,
+    FFonePAVirtualDeviceProps *props
 );
 
 void ffone_pa_stream_play(FFonePAStream *stream);
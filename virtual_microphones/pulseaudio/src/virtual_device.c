@@ -1,5 +1,7 @@
 #include "virtual_device.h"
 
+#include <ctype.h>
+#include <stdbool.h>
 #include <stdio.h>
 #include <stdlib.h>
 #include <string.h>
@@ -7,7 +9,86 @@
 #include "util.h"
 #include "error.h"
 
-static const char *ffone_pa_virtual_sink_get_name(FFonePAVirtualSink *sink);
+#define SINK_NAME_SUFFIX ".output"
+#define SINK_DESCRIPTION_SUFFIX " Output"
+
+struct FFonePAVirtualDeviceProps {
+    char *name; /* const, nonnull */
+    pa_proplist *proplist; /* nonnull */
+};
+
+static void virtual_device_props_dtor(void *opaque);
+
+static bool is_valid_name(const char *name) {
+    if (!*name) {
+        return false;
+    }
+
+    for (const char *c = name; *c; c++) {
+        if (!isalnum((unsigned char)*c) && !strchr("_-.", *c)) {
+            return false;
+        }
+    }
+
+    return true;
+}
+
+ffone_rc(FFonePAVirtualDeviceProps) ffone_pa_virtual_device_props_new(const char *name) {
+    FFONE_RETURN_VAL_ON_FAILURE(name && is_valid_name(name), NULL);
+
+    ffone_rc(FFonePAVirtualDeviceProps) props = ffone_rc_new0(FFonePAVirtualDeviceProps);
+    FFONE_RETURN_VAL_ON_FAILURE(props, NULL);
+
+    FFONE_GOTO_ON_FAILURE(props->name = strdup(name), error);
+    FFONE_GOTO_ON_FAILURE(props->proplist = pa_proplist_new(), error);
+
+    ffone_rc_set_dtor(props, virtual_device_props_dtor);
+
+    return props;
+error:
+    if (props->name) free(props->name);
+    ffone_rc_unref(props);
+
+    return NULL;
+}
+
+static void virtual_device_props_dtor(void *opaque) {
+    FFonePAVirtualDeviceProps *props = opaque;
+    FFONE_RETURN_ON_FAILURE(props);
+
+    if (props->proplist) pa_proplist_free(props->proplist);
+    props->proplist = NULL;
+
+    if (props->name) free(props->name);
+    props->name = NULL;
+}
+
+const char *ffone_pa_virtual_device_props_get_name(FFonePAVirtualDeviceProps *props) {
+    FFONE_RETURN_VAL_ON_FAILURE(props, NULL);
+
+    return props->name;
+}
+
+int ffone_pa_virtual_device_props_set(
+    FFonePAVirtualDeviceProps *props,
+    const char *key,
+    const char *value
+) {
+    FFONE_RETURN_VAL_ON_FAILURE(props && key && value, FFONE_ERROR_INVALID_ARG);
+    /* The properties are passed to the modules in single quotes. */
+    FFONE_RETURN_VAL_ON_FAILURE(
+        !strchr(key, '\'') && !strchr(value, '\''),
+        FFONE_ERROR_INVALID_ARG
+    );
+
+    int ret;
+    FFONE_RETURN_VAL_ON_FAILURE(
+        (ret = pa_proplist_sets(props->proplist, key, value)) == 0,
+        FFONE_ERROR(ret)
+    );
+
+    return FFONE_SUCCESS;
+}
 
 typedef struct FFonePAVirtualDevice {
     ffone_rc(FFonePACore) core; /* const */
@@ -15,16 +96,17 @@ typedef struct FFonePAVirtualDevice {
 
     uint32_t idx;
     char *name; /* const */
-    char *descr; /* const */
+    pa_proplist *proplist; /* const */
+    char *properties; /* const */
 } FFonePAVirtualDevice;
 
 static int virtual_device_new(
     FFonePAVirtualDevice *device,
     FFonePACore *core,
     const char *name,
-    const char *descr
+    pa_proplist *proplist
 ) {
-    FFONE_RETURN_VAL_ON_FAILURE(device && core && name && descr, FFONE_ERROR_INVALID_ARG);
+    FFONE_RETURN_VAL_ON_FAILURE(device && core && name && proplist, FFONE_ERROR_INVALID_ARG);
 
     int ret;
 
@@ -36,25 +118,27 @@ static int virtual_device_new(
     device->flags = FFONE_PA_VIRTUAL_DEVICE_FLAGS_CREATED;
     device->idx = FFONE_PA_VIRTUAL_DEVICE_INDEX_NONE;
     device->name = NULL;
-    device->descr = NULL;
-
-    int did = rand();
-    int pid = ffone_get_pid();
-    size_t device_name_len = snprintf(NULL, 0, "%d-%s-%d", did, name, pid);
+    device->proplist = NULL;
+    device->properties = NULL;
 
     ret = FFONE_ERROR_BAD_ALLOC;
-    FFONE_GOTO_ON_FAILURE(device->name = malloc(device_name_len + 1), error);
-    sprintf(device->name, "%d-%s-%d", did, name, pid);
+    FFONE_GOTO_ON_FAILURE(device->name = strdup(name), error);
 
-    printf("NAME: %s\n", device->name);
-
-    ret = FFONE_ERROR_BAD_ALLOC;
-    FFONE_GOTO_ON_FAILURE(device->descr = malloc(strlen(descr) + 1), error);
-    strcpy(device->descr, descr);
+    FFONE_GOTO_ON_FAILURE(device->proplist = pa_proplist_copy(proplist), error);
+    FFONE_GOTO_ON_FAILURE(
+        pa_proplist_sets(device->proplist, FFONE_PA_PROP_VIRTUAL_DEVICE, "1") == 0,
+        error
+    );
+    char *properties = pa_proplist_to_string_sep(device->proplist, " ");
+    FFONE_GOTO_ON_FAILURE(properties, error);
+    device->properties = strdup(properties);
+    pa_xfree(properties);
+    FFONE_GOTO_ON_FAILURE(device->properties, error);
 
     return FFONE_SUCCESS;
 error:
-    if (device->descr) free(device->descr);
+    if (device->properties) free(device->properties);
+    if (device->proplist) pa_proplist_free(device->proplist);
     if (device->name) free(device->name);
     if (device->core) ffone_rc_unref(device->core);
 
@@ -64,8 +148,11 @@ error:
 static void virtual_device_delete(FFonePAVirtualDevice *device) {
     FFONE_RETURN_ON_FAILURE(device);
 
-    if (device->descr) free(device->descr);
-    device->descr = NULL;
+    if (device->properties) free(device->properties);
+    device->properties = NULL;
+
+    if (device->proplist) pa_proplist_free(device->proplist);
+    device->proplist = NULL;
 
     if (device->name) free(device->name);
     device->name = NULL;
@@ -77,9 +164,65 @@ static void virtual_device_delete(FFonePAVirtualDevice *device) {
     device->core = NULL;
 }
 
+static void virtual_device_unloaded(pa_context *c, int success, void *userdata);
+
+/* The server adds its own properties, so only the ones which the device is
+ * loaded with are compared. */
+static bool virtual_device_matches(FFonePAVirtualDevice *device, pa_proplist *proplist) {
+    void *state = NULL;
+    const char *key;
+    while ((key = pa_proplist_iterate(device->proplist, &state))) {
+        const char *value = pa_proplist_gets(proplist, key);
+        if (!value || strcmp(value, pa_proplist_gets(device->proplist, key)) != 0) {
+            return false;
+        }
+    }
+
+    return true;
+}
+
+/* The device which is left over from a run which didn't unload it, e.g. after a
+ * crash, is reused, so that the streams of the applications stay connected to
+ * it. It's only loaded again if its description or properties changed. The
+ * devices without the ffone marker aren't touched, loading over somebody
+ * else's device fails. */
+static int virtual_device_reuse_leftover(
+    FFonePAVirtualDevice *device,
+    pa_subscription_mask_t type,
+    bool *reused
+) {
+    *reused = false;
+
+    uint32_t idx;
+    pa_proplist *proplist;
+    FFONE_RETURN_VAL_ON_FAILURE(
+        ffone_pa_core_find_virtual_device(device->core, type, device->name, &idx, &proplist) == 0,
+        FFONE_SUCCESS
+    );
+
+    bool matches = virtual_device_matches(device, proplist);
+    pa_proplist_free(proplist);
+
+    if (matches) {
+        device->flags |= FFONE_PA_VIRTUAL_DEVICE_FLAGS_LOADED;
+        device->idx = idx;
+        *reused = true;
+
+        return FFONE_SUCCESS;
+    }
+
+    int ret = ffone_pa_core_unload_virtual_device(
+        device->core,
+        idx,
+        virtual_device_unloaded,
+        device
+    );
+
+    return FFONE_ERROR(ret);
+}
+
 static void virtual_device_loaded(pa_context *c, uint32_t idx, void *userdata) {
     FFonePAVirtualDevice *device = (FFonePAVirtualDevice *)userdata;
-    printf("Virtual Device Index: %u\n", idx);
 
     if (device) {
         device->flags |= FFONE_PA_VIRTUAL_DEVICE_FLAGS_LOADED;
@@ -93,7 +236,6 @@ static void virtual_device_loaded(pa_context *c, uint32_t idx, void *userdata) {
 
 static void virtual_device_unloaded(pa_context *c, int success, void *userdata) {
     FFonePAVirtualDevice *device = (FFonePAVirtualDevice *)userdata;
-    printf("Virtual Device Unloaded: %d\n", success);
 
     if (success && device) {
         device->flags &= ~FFONE_PA_VIRTUAL_DEVICE_FLAGS_LOADED;
@@ -118,9 +260,10 @@ static int ffone_pa_virtual_source_unload(FFonePAVirtualSource *src);
 
 ffone_rc(FFonePAVirtualSource) ffone_pa_virtual_source_new(
     FFonePACore *core,
-    FFonePAVirtualSink *master)
+    FFonePAVirtualSink *master,
+    FFonePAVirtualDeviceProps *props)
 {
-    FFONE_RETURN_VAL_ON_FAILURE(core && master, NULL);
+    FFONE_RETURN_VAL_ON_FAILURE(core && master && props, NULL);
 
     ffone_rc(FFonePAVirtualSource) src = ffone_rc_new0(FFonePAVirtualSource);
     FFONE_RETURN_VAL_ON_FAILURE(src, NULL);
@@ -129,8 +272,8 @@ ffone_rc(FFonePAVirtualSource) ffone_pa_virtual_source_new(
     FFONE_GOTO_ON_FAILURE(virtual_device_new(
         &src->base,
         core,
-        "ffone_pa_virtual_source",
-        "FFone_Virtual_Microphone"
+        props->name,
+        props->proplist
     ) == 0, error_virtual_device_new);
     FFONE_GOTO_ON_FAILURE(ffone_pa_virtual_source_load(src) == 0, error_virtual_source_load);
 
@@ -156,8 +299,6 @@ static void ffone_pa_virtual_source_dtor(void *opaque) {
     ffone_rc_unref(src->master);
     src->master = NULL;
     virtual_device_delete(&src->base);
-
-    puts("FFonePAVirtualSource dtor");
 }
 
 static int ffone_pa_virtual_source_load(FFonePAVirtualSource *src) {
@@ -170,20 +311,25 @@ static int ffone_pa_virtual_source_load(FFonePAVirtualSource *src) {
         FFONE_ERROR_BAD_STATE
     );
 
+    bool reused;
+    int ret = virtual_device_reuse_leftover(&src->base, PA_SUBSCRIPTION_MASK_SOURCE, &reused);
+    FFONE_RETURN_VAL_ON_FAILURE(ret == 0, ret);
+    FFONE_RETURN_VAL_ON_FAILURE(!reused, FFONE_SUCCESS);
+
     char *args = ffone_format_str(
-        "source_name=%s source_properties=device.description=%s master=%s.monitor "
+        "source_name=%s source_properties='%s' master=%s.monitor "
         "master_channel_map=%s rate=%d channels=%d channel_map=%s",
         src->base.name,
-        src->base.descr,
+        src->base.properties,
         ffone_pa_virtual_sink_get_name(src->master),
-        "mono",
+        FFONE_PA_DEFAULT_CHANNEL_MAP,
         FFONE_PA_DEFAULT_SAMPLE_RATE,
-        1,
-        "mono"
+        FFONE_PA_DEFAULT_CHANNELS,
+        FFONE_PA_DEFAULT_CHANNEL_MAP
     );
     FFONE_RETURN_VAL_ON_FAILURE(args, FFONE_ERROR_BAD_ALLOC);
 
-    int ret = ffone_pa_core_load_virtual_device(
+    ret = ffone_pa_core_load_virtual_device(
         src->base.core,
         "module-remap-source",
         args,
@@ -220,27 +366,67 @@ static void ffone_pa_virtual_sink_dtor(void *opaque);
 static int ffone_pa_virtual_sink_load(FFonePAVirtualSink *sink);
 static int ffone_pa_virtual_sink_unload(FFonePAVirtualSink *sink);
 
-ffone_rc(FFonePAVirtualSink) ffone_pa_virtual_sink_new(FFonePACore *core) {
-    FFONE_RETURN_VAL_ON_FAILURE(core, NULL);
+/* The sink is named after the source, so that a leftover pair is found
+ * together. */
+static pa_proplist *sink_proplist_new(FFonePAVirtualDeviceProps *props) {
+    pa_proplist *proplist = pa_proplist_new();
+    FFONE_RETURN_VAL_ON_FAILURE(proplist, NULL);
+
+    const char *description = pa_proplist_gets(props->proplist, PA_PROP_DEVICE_DESCRIPTION);
+    char *sink_description = ffone_format_str(
+        "%s" SINK_DESCRIPTION_SUFFIX,
+        description ? description : props->name
+    );
+    FFONE_GOTO_ON_FAILURE(sink_description, error);
+
+    int ret = pa_proplist_sets(proplist, PA_PROP_DEVICE_DESCRIPTION, sink_description);
+    free(sink_description);
+    FFONE_GOTO_ON_FAILURE(ret == 0, error);
+
+    return proplist;
+error:
+    pa_proplist_free(proplist);
+
+    return NULL;
+}
+
+ffone_rc(FFonePAVirtualSink) ffone_pa_virtual_sink_new(
+    FFonePACore *core,
+    FFonePAVirtualDeviceProps *props
+) {
+    FFONE_RETURN_VAL_ON_FAILURE(core && props, NULL);
 
     ffone_rc(FFonePAVirtualSink) sink = ffone_rc_new0(FFonePAVirtualSink);
     FFONE_RETURN_VAL_ON_FAILURE(sink, NULL);
 
+    char *name = NULL;
+    pa_proplist *proplist = NULL;
+    FFONE_GOTO_ON_FAILURE(
+        name = ffone_format_str("%s" SINK_NAME_SUFFIX, props->name),
+        error_virtual_device_new
+    );
+    FFONE_GOTO_ON_FAILURE(proplist = sink_proplist_new(props), error_virtual_device_new);
+
     FFONE_GOTO_ON_FAILURE(virtual_device_new(
         &sink->base,
         core,
-        "ffone_pa_virtual_sink",
-        "FFone_Output"
+        name,
+        proplist
     ) == 0, error_virtual_device_new);
     FFONE_GOTO_ON_FAILURE(ffone_pa_virtual_sink_load(sink) == 0, error_virtual_sink_load);
 
     ffone_rc_set_dtor(sink, ffone_pa_virtual_sink_dtor);
 
+    pa_proplist_free(proplist);
+    free(name);
+
     return sink;
 
 error_virtual_sink_load:
     virtual_device_delete(&sink->base);
 error_virtual_device_new:
+    if (proplist) pa_proplist_free(proplist);
+    if (name) free(name);
     ffone_rc_unref(sink);
 
     return NULL;
@@ -252,8 +438,6 @@ static void ffone_pa_virtual_sink_dtor(void *opaque) {
 
     ffone_pa_virtual_sink_unload(sink);
     virtual_device_delete(&sink->base);
-
-    puts("FFonePAVirtualSink dtor");
 }
 
 static int ffone_pa_virtual_sink_load(FFonePAVirtualSink *sink) {
@@ -265,18 +449,23 @@ static int ffone_pa_virtual_sink_load(FFonePAVirtualSink *sink) {
         FFONE_ERROR_BAD_STATE
     );
 
+    bool reused;
+    int ret = virtual_device_reuse_leftover(&sink->base, PA_SUBSCRIPTION_MASK_SINK, &reused);
+    FFONE_RETURN_VAL_ON_FAILURE(ret == 0, ret);
+    FFONE_RETURN_VAL_ON_FAILURE(!reused, FFONE_SUCCESS);
+
     char *args = ffone_format_str(
-        "sink_name=%s sink_properties=device.description=%s "
+        "sink_name=%s sink_properties='%s' "
         "rate=%d channels=%d channel_map=%s",
         sink->base.name,
-        sink->base.descr,
+        sink->base.properties,
         FFONE_PA_DEFAULT_SAMPLE_RATE,
-        1,
-        "mono"
+        FFONE_PA_DEFAULT_CHANNELS,
+        FFONE_PA_DEFAULT_CHANNEL_MAP
     );
     FFONE_RETURN_VAL_ON_FAILURE(args, FFONE_ERROR_BAD_ALLOC);
 
-    int ret = ffone_pa_core_load_virtual_device(
+    ret = ffone_pa_core_load_virtual_device(
         sink->base.core,
         "module-null-sink",
         args,
@@ -308,7 +497,7 @@ static int ffone_pa_virtual_sink_unload(FFonePAVirtualSink *sink) {
     return FFONE_ERROR(ret);
 }
 
-static const char *ffone_pa_virtual_sink_get_name(FFonePAVirtualSink *sink) {
+const char *ffone_pa_virtual_sink_get_name(FFonePAVirtualSink *sink) {
     FFONE_RETURN_VAL_ON_FAILURE(sink, NULL);
 
     return sink->base.name;
@@ -11,8 +11,13 @@
 #include <pulse/pulseaudio.h>
 
 #define FFONE_PA_DEFAULT_SAMPLE_RATE 48000
+#define FFONE_PA_DEFAULT_CHANNELS 2
+#define FFONE_PA_DEFAULT_CHANNEL_MAP "stereo"
 #define FFONE_PA_VIRTUAL_DEVICE_INDEX_NONE UINT32_MAX
+/* Marks the devices which ffone loaded, so that only they are reused. */
+#define FFONE_PA_PROP_VIRTUAL_DEVICE "ffone.virtual_device"
 
+typedef struct FFonePAVirtualDeviceProps FFonePAVirtualDeviceProps;
 typedef struct FFonePAVirtualSource FFonePAVirtualSource;
 typedef struct FFonePAVirtualSink FFonePAVirtualSink;
 
@@ -22,11 +27,27 @@ typedef uint32_t FFonePAVirtualDeviceFlags;
 #define FFONE_PA_VIRTUAL_DEVICE_FLAGS_CREATED (1U << 0)
 #define FFONE_PA_VIRTUAL_DEVICE_FLAGS_LOADED (1U << 1)
 
+/* The name stays the same across the runs, so the applications remember the
+ * microphone. */
+ffone_rc(FFonePAVirtualDeviceProps) ffone_pa_virtual_device_props_new(const char *name);
+/* The name of the source, the sink is named after it. */
+const char *ffone_pa_virtual_device_props_get_name(FFonePAVirtualDeviceProps *props);
+int ffone_pa_virtual_device_props_set(
+    FFonePAVirtualDeviceProps *props,
+    const char *key,
+    const char *value
+);
+
 ffone_rc(FFonePAVirtualSource) ffone_pa_virtual_source_new(
     FFonePACore *core,
-    FFonePAVirtualSink *master
+    FFonePAVirtualSink *master,
+    FFonePAVirtualDeviceProps *props
 );
 
-ffone_rc(FFonePAVirtualSink) ffone_pa_virtual_sink_new(FFonePACore *core);
+ffone_rc(FFonePAVirtualSink) ffone_pa_virtual_sink_new(
+    FFonePACore *core,
+    FFonePAVirtualDeviceProps *props
+);
+const char *ffone_pa_virtual_sink_get_name(FFonePAVirtualSink *sink);
 
 #endif /* _FFONE_VIRTUAL_DEVICE_H */
\ No newline at end of file